@@ -0,0 +1,247 @@
+//! Comparing the numbers of two fuzzing runs or coverage measurements for
+//! `cargo fuzz compare`, to tell whether a change to a fuzz target helped
+
+use crate::llvm_cov::CoverageTotals;
+use crate::sessions::RunRecord;
+use crate::stats::RunStats;
+
+/// The artifact categories that count as failures of a run.
+const FAILURES: &[&str] = &["crash", "leak", "oom", "timeout"];
+
+/// The numbers of a run or of a coverage measurement. Each kind of snapshot
+/// only has some of them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Snapshot {
+    pub execs_per_sec: Option<u64>,
+    pub cov: Option<u64>,
+    pub features: Option<u64>,
+    pub corpus_inputs: Option<u64>,
+    pub corpus_bytes: Option<u64>,
+    pub failures: Option<u64>,
+    pub line_coverage: Option<f64>,
+    pub region_coverage: Option<f64>,
+}
+
+impl Snapshot {
+    pub fn from_run(run: &RunRecord) -> Self {
+        let execs_per_sec = run.execs_per_sec.or_else(|| {
+            let elapsed = run.elapsed_secs.filter(|&secs| secs > 0)?;
+            Some(run.execs? / elapsed)
+        });
+        let failures = run
+            .artifacts
+            .iter()
+            .filter(|artifact| FAILURES.contains(&artifact.category.as_str()))
+            .count();
+        Snapshot {
+            execs_per_sec,
+            cov: run.cov,
+            features: run.features,
+            corpus_inputs: run.corpus_inputs,
+            corpus_bytes: run.corpus_bytes,
+            failures: Some(failures as u64),
+            ..Snapshot::default()
+        }
+    }
+
+    /// The stats files of runs don't know about their artifacts.
+    pub fn from_stats(stats: &RunStats) -> Self {
+        Snapshot {
+            execs_per_sec: (stats.elapsed_secs > 0).then(|| stats.execs / stats.elapsed_secs),
+            cov: stats.cov,
+            features: stats.features,
+            corpus_inputs: stats.corpus_inputs,
+            corpus_bytes: stats.corpus_bytes,
+            ..Snapshot::default()
+        }
+    }
+
+    pub fn from_coverage(totals: CoverageTotals) -> Self {
+        Snapshot {
+            line_coverage: Some(totals.lines),
+            region_coverage: Some(totals.regions),
+            ..Snapshot::default()
+        }
+    }
+}
+
+/// Which way a number should go.
+#[derive(Clone, Copy)]
+enum Better {
+    Higher,
+    /// Neither, like the size of the corpus, which minimizing shrinks.
+    Either,
+}
+
+/// How a number changed between two snapshots.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Delta {
+    pub metric: &'static str,
+    pub base: String,
+    pub new: String,
+    pub change: String,
+    pub regression: bool,
+    pub improvement: bool,
+}
+
+/// Compares the numbers that both snapshots have. A number that gets worse
+/// by more than `threshold` percent is a regression, except for failures,
+/// where any new one is.
+pub fn compare(base: &Snapshot, new: &Snapshot, threshold: f64) -> Vec<Delta> {
+    let counts = [
+        (
+            "exec/s",
+            base.execs_per_sec,
+            new.execs_per_sec,
+            Better::Higher,
+        ),
+        ("coverage", base.cov, new.cov, Better::Higher),
+        ("features", base.features, new.features, Better::Higher),
+        (
+            "corpus inputs",
+            base.corpus_inputs,
+            new.corpus_inputs,
+            Better::Either,
+        ),
+        (
+            "corpus bytes",
+            base.corpus_bytes,
+            new.corpus_bytes,
+            Better::Either,
+        ),
+    ];
+    let mut deltas: Vec<_> = counts
+        .into_iter()
+        .filter_map(|(metric, base, new, better)| {
+            let (base, new) = (base?, new?);
+            let percent = percent_change(base as f64, new as f64);
+            let change = if percent.is_finite() {
+                format!("{:+.1}%", percent)
+            } else {
+                format!("+{}", new)
+            };
+            let percent = match better {
+                Better::Higher => percent,
+                Better::Either => 0.0,
+            };
+            Some(Delta {
+                metric,
+                base: base.to_string(),
+                new: new.to_string(),
+                change,
+                regression: -percent > threshold,
+                improvement: percent > threshold,
+            })
+        })
+        .collect();
+
+    if let (Some(base), Some(new)) = (base.failures, new.failures) {
+        deltas.push(Delta {
+            metric: "failures",
+            base: base.to_string(),
+            new: new.to_string(),
+            change: format!("{:+}", new as i64 - base as i64),
+            regression: new > base,
+            improvement: new < base,
+        });
+    }
+
+    // Coverage percentages are shown changing by points, but are judged by
+    // percent like the rest.
+    let percentages = [
+        ("line coverage", base.line_coverage, new.line_coverage),
+        ("region coverage", base.region_coverage, new.region_coverage),
+    ];
+    for (metric, base, new) in percentages {
+        let (Some(base), Some(new)) = (base, new) else {
+            continue;
+        };
+        let percent = percent_change(base, new);
+        deltas.push(Delta {
+            metric,
+            base: format!("{:.1}%", base),
+            new: format!("{:.1}%", new),
+            change: format!("{:+.1} points", new - base),
+            regression: -percent > threshold,
+            improvement: percent > threshold,
+        });
+    }
+    deltas
+}
+
+/// How much `new` changed from `base`, in percent, infinite when it grew
+/// from 0.
+fn percent_change(base: f64, new: f64) -> f64 {
+    if base == 0.0 {
+        if new == 0.0 {
+            0.0
+        } else {
+            f64::INFINITY
+        }
+    } else {
+        (new - base) / base * 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(execs_per_sec: u64, cov: u64, corpus_inputs: u64, failures: u64) -> Snapshot {
+        Snapshot {
+            execs_per_sec: Some(execs_per_sec),
+            cov: Some(cov),
+            corpus_inputs: Some(corpus_inputs),
+            failures: Some(failures),
+            ..Snapshot::default()
+        }
+    }
+
+    #[test]
+    fn compares_runs() {
+        let deltas = compare(&run(5120, 97, 41, 0), &run(2560, 99, 20, 1), 5.0);
+        let summary: Vec<_> = deltas
+            .iter()
+            .map(|delta| (delta.metric, delta.change.as_str(), delta.regression))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("exec/s", "-50.0%", true),
+                ("coverage", "+2.1%", false),
+                ("corpus inputs", "-51.2%", false),
+                ("failures", "+1", true),
+            ]
+        );
+        assert!(!deltas[1].improvement);
+
+        // Within the threshold, nothing changed.
+        let deltas = compare(&run(5120, 97, 41, 0), &run(5000, 90, 41, 0), 10.0);
+        assert!(deltas
+            .iter()
+            .all(|delta| !delta.regression && !delta.improvement));
+    }
+
+    #[test]
+    fn compares_coverage() {
+        let base = Snapshot::from_coverage(CoverageTotals {
+            lines: 40.0,
+            regions: 50.0,
+        });
+        let new = Snapshot::from_coverage(CoverageTotals {
+            lines: 48.0,
+            regions: 45.0,
+        });
+        let deltas = compare(&base, &new, 5.0);
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[0].change, "+8.0 points");
+        assert!(deltas[0].improvement);
+        assert_eq!(
+            (deltas[1].new.as_str(), deltas[1].regression),
+            ("45.0%", true)
+        );
+
+        // Runs and coverage have nothing to compare.
+        assert!(compare(&run(1, 1, 1, 0), &new, 5.0).is_empty());
+    }
+}
@@ -0,0 +1,658 @@
+//! Project defaults for command line options, read from `fuzz/fuzz.toml` or
+//! from the `[package.metadata.cargo-fuzz]` table of the fuzz manifest
+
+use crate::project;
+use anyhow::{bail, Context, Result};
+use clap::{parser::ValueSource, ArgMatches, CommandFactory};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const CONFIG_FILE: &str = "fuzz.toml";
+
+/// Defaults for all fuzz targets, or overrides of them for a single one.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Defaults {
+    // Build options, for every command that builds fuzz targets.
+    pub sanitizer: Option<String>,
+    pub features: Option<String>,
+    pub no_default_features: Option<bool>,
+    pub all_features: Option<bool>,
+    pub dev: Option<bool>,
+    pub release: Option<bool>,
+    pub debug_assertions: Option<bool>,
+    pub build_std: Option<bool>,
+    pub careful: Option<bool>,
+    pub no_trace_compares: Option<bool>,
+    pub no_leak_detection: Option<bool>,
+
+    // libFuzzer options, for `cargo fuzz run`, `tmin` and `cmin`.
+    /// Relative to the fuzz directory
+    pub dict: Option<PathBuf>,
+    // Also under libFuzzer's own name.
+    #[serde(alias = "max_len")]
+    pub max_len: Option<u64>,
+    pub timeout: Option<u64>,
+
+    // Options of `cargo fuzz run` only.
+    pub jobs: Option<u16>,
+    /// Sets of features to fuzz with in turn, as with `--feature-set`
+    #[serde(default)]
+    pub feature_sets: Vec<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    #[serde(default)]
+    pub hooks: Hooks,
+
+    /// Stack hashes, as printed by `cargo fuzz triage`, of known crashes that
+    /// don't fail `cargo fuzz ci`
+    #[serde(default)]
+    pub suppress: Vec<String>,
+}
+
+/// Executables that `cargo fuzz run` invokes around fuzzing, relative to the
+/// fuzz directory.
+///
+/// They get the fuzz target in `CARGO_FUZZ_TARGET`, the fuzz directory in
+/// `CARGO_FUZZ_DIR`, and after fuzzing the exit code of the fuzz target in
+/// `CARGO_FUZZ_EXIT_STATUS`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Hooks {
+    /// Runs before fuzzing, which doesn't start if it fails. Background
+    /// processes it starts, such as servers, keep running.
+    pub pre_run: Option<PathBuf>,
+    /// Runs after fuzzing, whether the fuzz target crashed or not
+    pub post_run: Option<PathBuf>,
+    /// Runs for every new artifact, with its path in `CARGO_FUZZ_ARTIFACT`
+    pub on_crash: Option<PathBuf>,
+}
+
+impl Defaults {
+    /// Overrides these defaults with the ones of a single fuzz target. The
+    /// libFuzzer arguments and suppressions of both are kept, the target's
+    /// last.
+    fn merge(&self, target: &Defaults) -> Defaults {
+        Defaults {
+            sanitizer: target.sanitizer.clone().or_else(|| self.sanitizer.clone()),
+            features: target.features.clone().or_else(|| self.features.clone()),
+            no_default_features: target.no_default_features.or(self.no_default_features),
+            all_features: target.all_features.or(self.all_features),
+            dev: target.dev.or(self.dev),
+            release: target.release.or(self.release),
+            debug_assertions: target.debug_assertions.or(self.debug_assertions),
+            build_std: target.build_std.or(self.build_std),
+            careful: target.careful.or(self.careful),
+            no_trace_compares: target.no_trace_compares.or(self.no_trace_compares),
+            no_leak_detection: target.no_leak_detection.or(self.no_leak_detection),
+            jobs: target.jobs.or(self.jobs),
+            feature_sets: if target.feature_sets.is_empty() {
+                self.feature_sets.clone()
+            } else {
+                target.feature_sets.clone()
+            },
+            dict: target.dict.clone().or_else(|| self.dict.clone()),
+            max_len: target.max_len.or(self.max_len),
+            timeout: target.timeout.or(self.timeout),
+            args: self.args.iter().chain(&target.args).cloned().collect(),
+            hooks: Hooks {
+                pre_run: target
+                    .hooks
+                    .pre_run
+                    .clone()
+                    .or_else(|| self.hooks.pre_run.clone()),
+                post_run: target
+                    .hooks
+                    .post_run
+                    .clone()
+                    .or_else(|| self.hooks.post_run.clone()),
+                on_crash: target
+                    .hooks
+                    .on_crash
+                    .clone()
+                    .or_else(|| self.hooks.on_crash.clone()),
+            },
+            suppress: self
+                .suppress
+                .iter()
+                .chain(&target.suppress)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// The options these defaults set, by the id of their argument. Flags
+    /// have no value.
+    fn options(&self) -> Vec<(&'static str, Option<String>)> {
+        let flags = [
+            ("no_default_features", self.no_default_features),
+            ("all_features", self.all_features),
+            ("dev", self.dev),
+            ("release", self.release),
+            ("debug_assertions", self.debug_assertions),
+            ("build_std", self.build_std),
+            ("careful_mode", self.careful),
+            ("no_trace_compares", self.no_trace_compares),
+            ("no_leak_detection", self.no_leak_detection),
+        ];
+        let mut options: Vec<_> = flags
+            .into_iter()
+            .filter(|(_, set)| *set == Some(true))
+            .map(|(id, _)| (id, None))
+            .collect();
+        if let Some(sanitizer) = &self.sanitizer {
+            options.push(("sanitizer", Some(sanitizer.clone())));
+        }
+        if let Some(features) = &self.features {
+            options.push(("features", Some(features.clone())));
+        }
+        if let Some(jobs) = self.jobs {
+            options.push(("jobs", Some(jobs.to_string())));
+        }
+        for features in &self.feature_sets {
+            options.push(("feature_sets", Some(features.clone())));
+        }
+        options
+    }
+
+    /// The libFuzzer arguments these defaults set for `command`.
+    pub fn libfuzzer_args(&self, command: &str, fuzz_dir: &Path) -> Vec<String> {
+        if !matches!(command, "run" | "tmin" | "cmin") {
+            return Vec::new();
+        }
+
+        let mut args = Vec::new();
+        if let Some(dict) = &self.dict {
+            args.push(format!("-dict={}", fuzz_dir.join(dict).display()));
+        }
+        if let Some(max_len) = self.max_len {
+            args.push(format!("-max_len={}", max_len));
+        }
+        if let Some(timeout) = self.timeout {
+            args.push(format!("-timeout={}", timeout));
+        }
+        // Arbitrary arguments, like `-runs=`, could break minimization.
+        if command == "run" {
+            args.extend(self.args.iter().cloned());
+        }
+        args
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    pub defaults: Defaults,
+    pub targets: BTreeMap<String, Defaults>,
+    /// Named sets of options, selected with `--with`
+    pub profiles: BTreeMap<String, Defaults>,
+}
+
+impl Config {
+    /// Reads the configuration of the fuzz project in `fuzz_dir`, if it has
+    /// any.
+    pub fn load(fuzz_dir: &Path) -> Result<Option<Config>> {
+        let path = fuzz_dir.join(CONFIG_FILE);
+        if path.exists() {
+            let data =
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            let table = toml::from_slice(&data)
+                .with_context(|| format!("failed to parse {}", path.display()))?;
+            return Config::parse(table)
+                .with_context(|| format!("invalid configuration in {}", path.display()))
+                .map(Some);
+        }
+
+        let manifest_path = fuzz_dir.join("Cargo.toml");
+        let manifest: toml::Value = match fs::read(&manifest_path) {
+            Ok(data) => toml::from_slice(&data)
+                .with_context(|| format!("failed to parse {}", manifest_path.display()))?,
+            Err(_) => return Ok(None),
+        };
+        match manifest
+            .get("package")
+            .and_then(|package| package.get("metadata"))
+            .and_then(|metadata| metadata.get("cargo-fuzz"))
+            .and_then(toml::Value::as_table)
+        {
+            Some(table) => Config::parse(table.clone())
+                .with_context(|| {
+                    format!(
+                        "invalid `[package.metadata.cargo-fuzz]` in {}",
+                        manifest_path.display()
+                    )
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses the defaults at the top level of `table`, the overrides for
+    /// single fuzz targets in its `targets` table, and the named profiles in
+    /// its `profiles` table.
+    pub fn parse(mut table: toml::value::Table) -> Result<Config> {
+        let targets = match table.remove("targets") {
+            Some(targets) => targets.try_into()?,
+            None => BTreeMap::new(),
+        };
+        let profiles = match table.remove("profiles") {
+            Some(profiles) => profiles.try_into()?,
+            None => BTreeMap::new(),
+        };
+        Ok(Config {
+            defaults: toml::Value::Table(table).try_into()?,
+            targets,
+            profiles,
+        })
+    }
+
+    /// Returns the defaults of a fuzz target, or of all of them, with the
+    /// options of `profile` on top.
+    pub fn for_target(&self, target: Option<&str>, profile: Option<&str>) -> Result<Defaults> {
+        let mut defaults = match target.and_then(|target| self.targets.get(target)) {
+            Some(overrides) => self.defaults.merge(overrides),
+            None => self.defaults.clone(),
+        };
+        if let Some(profile) = profile {
+            match self.profiles.get(profile) {
+                Some(overrides) => defaults = defaults.merge(overrides),
+                None if self.profiles.is_empty() => {
+                    bail!("no profile named `{}`, none are configured", profile)
+                }
+                None => bail!(
+                    "no profile named `{}`, the configured ones are: {}",
+                    profile,
+                    self.profiles
+                        .keys()
+                        .map(String::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            }
+        }
+        Ok(defaults)
+    }
+}
+
+/// Inserts the defaults from the project configuration into the command line
+/// `args`, for the options that aren't given on the command line already.
+///
+/// Command lines that don't parse, or that aren't run in a fuzz project, are
+/// returned as they are, for clap to report or run them.
+pub fn apply(args: Vec<OsString>) -> Result<Vec<OsString>> {
+    let matches = match crate::Command::command().try_get_matches_from(&args) {
+        Ok(matches) => matches,
+        Err(_) => return Ok(args),
+    };
+    let mut command = crate::Command::command();
+    let mut matches = &matches;
+    while let Some((name, sub_matches)) = matches.subcommand() {
+        command = match command.find_subcommand(name) {
+            Some(subcommand) => subcommand.clone(),
+            None => return Ok(args),
+        };
+        matches = sub_matches;
+    }
+
+    let fuzz_dir = match matches.try_get_one::<PathBuf>("fuzz_dir") {
+        Ok(Some(fuzz_dir)) => fuzz_dir.clone(),
+        Ok(None) => match project::default_fuzz_dir() {
+            Ok(fuzz_dir) => fuzz_dir,
+            Err(_) => return Ok(args),
+        },
+        // Commands without a fuzz project, such as `help`.
+        Err(_) => return Ok(args),
+    };
+    let profile = matches.try_get_one::<String>("profile").ok().flatten();
+    let config = match Config::load(&fuzz_dir)? {
+        Some(config) => config,
+        None if profile.is_some() => bail!(
+            "`--with` needs a profile in {} or in the `[package.metadata.cargo-fuzz]` \
+             table of {}",
+            fuzz_dir.join(CONFIG_FILE).display(),
+            fuzz_dir.join("Cargo.toml").display()
+        ),
+        None => return Ok(args),
+    };
+    let target = matches.try_get_one::<String>("target").ok().flatten();
+    let defaults = config.for_target(target.map(String::as_str), profile.map(String::as_str))?;
+
+    let mut options = Vec::new();
+    for (id, value) in defaults.options() {
+        // Only `cargo fuzz run` takes `--jobs` as the number of fuzzing jobs.
+        if id == "jobs" && command.get_name() != "run" {
+            continue;
+        }
+        let arg = match command.get_arguments().find(|arg| arg.get_id() == id) {
+            Some(arg) => arg,
+            None => continue,
+        };
+        if is_explicit(matches, id) || conflicts_with_explicit(&command, matches, arg) {
+            continue;
+        }
+        let long = arg
+            .get_long()
+            .expect("configurable options have a long name");
+        options.push(match value {
+            Some(value) => OsString::from(format!("--{}={}", long, value)),
+            None => OsString::from(format!("--{}", long)),
+        });
+    }
+
+    // libFuzzer arguments go first, so that the ones on the command line
+    // override them.
+    let libfuzzer_args = defaults.libfuzzer_args(command.get_name(), &fuzz_dir);
+
+    let mut args = args;
+    let separator = match args.iter().position(|arg| arg == "--") {
+        Some(separator) => separator,
+        None if libfuzzer_args.is_empty() => args.len(),
+        None => {
+            args.push("--".into());
+            args.len() - 1
+        }
+    };
+    if !libfuzzer_args.is_empty() {
+        args.splice(
+            separator + 1..separator + 1,
+            libfuzzer_args.into_iter().map(OsString::from),
+        );
+    }
+    args.splice(separator..separator, options);
+    Ok(args)
+}
+
+fn is_explicit(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+/// Whether `arg` conflicts with one that is given on the command line, such
+/// as a configured `--dev` with `--release`.
+fn conflicts_with_explicit(command: &clap::Command, matches: &ArgMatches, arg: &clap::Arg) -> bool {
+    command
+        .get_arguments()
+        .filter(|other| is_explicit(matches, other.get_id().as_str()))
+        .any(|other| {
+            command
+                .get_arg_conflicts_with(arg)
+                .iter()
+                .chain(&command.get_arg_conflicts_with(other))
+                .any(|conflict| {
+                    conflict.get_id() == other.get_id() || conflict.get_id() == arg.get_id()
+                })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> Result<Config> {
+        Config::parse(toml::from_str(toml).unwrap())
+    }
+
+    #[test]
+    fn parses_defaults_and_targets() {
+        let config = parse(
+            r#"
+            sanitizer = "none"
+            max-len = 4096
+            args = ["-use_value_profile=1"]
+
+            [targets.foo]
+            sanitizer = "memory"
+            timeout = 10
+            args = ["-only_ascii=1"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.defaults.sanitizer.as_deref(), Some("none"));
+        assert_eq!(config.targets.len(), 1);
+
+        let foo = config.for_target(Some("foo"), None).unwrap();
+        assert_eq!(foo.sanitizer.as_deref(), Some("memory"));
+        assert_eq!(foo.max_len, Some(4096));
+        assert_eq!(foo.timeout, Some(10));
+        assert_eq!(foo.args, ["-use_value_profile=1", "-only_ascii=1"]);
+        assert_eq!(
+            foo.libfuzzer_args("run", Path::new("fuzz")),
+            [
+                "-max_len=4096",
+                "-timeout=10",
+                "-use_value_profile=1",
+                "-only_ascii=1"
+            ]
+        );
+
+        assert_eq!(
+            foo.libfuzzer_args("tmin", Path::new("fuzz")),
+            ["-max_len=4096", "-timeout=10"]
+        );
+        assert!(foo.libfuzzer_args("build", Path::new("fuzz")).is_empty());
+
+        assert_eq!(
+            config.for_target(Some("bar"), None).unwrap(),
+            config.defaults
+        );
+        assert_eq!(config.for_target(None, None).unwrap(), config.defaults);
+    }
+
+    #[test]
+    fn merges_hooks() {
+        let config = parse(
+            r#"
+            [hooks]
+            pre-run = "start-server.sh"
+            on-crash = "archive.sh"
+
+            [targets.foo.hooks]
+            pre-run = "start-other-server.sh"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.for_target(Some("foo"), None).unwrap().hooks,
+            Hooks {
+                pre_run: Some("start-other-server.sh".into()),
+                post_run: None,
+                on_crash: Some("archive.sh".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn applies_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(CONFIG_FILE),
+            r#"
+            max-len = 64
+
+            [profiles.ci]
+            sanitizer = "none"
+            jobs = 8
+            args = ["-max_total_time=600"]
+
+            [profiles.quick]
+            args = ["-runs=1000"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            apply_in(dir.path(), &["run", "--with", "ci", "foo", "-j2"]),
+            [
+                "--with",
+                "ci",
+                "foo",
+                "-j2",
+                "--sanitizer=none",
+                "--",
+                "-max_len=64",
+                "-max_total_time=600"
+            ]
+        );
+        assert_eq!(
+            apply_in(dir.path(), &["build", "--profile-name=ci"]),
+            ["--profile-name=ci", "--sanitizer=none"]
+        );
+
+        let config = Config::load(dir.path()).unwrap().unwrap();
+        let error = config.for_target(None, Some("deep")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "no profile named `deep`, the configured ones are: ci, quick"
+        );
+    }
+
+    #[test]
+    fn applies_feature_sets() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(CONFIG_FILE),
+            r#"
+            feature-sets = ["", "a,b"]
+
+            [targets.foo]
+            feature-sets = ["c"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            apply_in(dir.path(), &["run", "bar"]),
+            ["bar", "--feature-set=", "--feature-set=a,b"]
+        );
+        assert_eq!(
+            apply_in(dir.path(), &["run", "foo"]),
+            ["foo", "--feature-set=c"]
+        );
+        assert_eq!(
+            apply_in(dir.path(), &["run", "foo", "--feature-set=d"]),
+            ["foo", "--feature-set=d"]
+        );
+        assert!(apply_in(dir.path(), &["build", "foo"]).len() == 1);
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(parse("max-length = 1").is_err());
+        assert!(parse("[targets.foo]\nsanitiser = \"none\"").is_err());
+    }
+
+    fn apply_in(fuzz_dir: &Path, args: &[&str]) -> Vec<String> {
+        let mut command_line = vec![
+            "cargo-fuzz".to_owned(),
+            args[0].to_owned(),
+            format!("--fuzz-dir={}", fuzz_dir.display()),
+        ];
+        command_line.extend(args[1..].iter().map(|arg| arg.to_string()));
+        apply(command_line.into_iter().map(OsString::from).collect())
+            .unwrap()
+            .into_iter()
+            .skip(3)
+            .map(|arg| arg.into_string().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn command_line_overrides_config() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(CONFIG_FILE),
+            "sanitizer = \"none\"\ndev = true\njobs = 4\nmax-len = 64\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            apply_in(dir.path(), &["run", "foo"]),
+            [
+                "foo",
+                "--dev",
+                "--sanitizer=none",
+                "--jobs=4",
+                "--",
+                "-max_len=64"
+            ]
+        );
+        assert_eq!(
+            apply_in(
+                dir.path(),
+                &[
+                    "run",
+                    "--release",
+                    "-s",
+                    "address",
+                    "foo",
+                    "--",
+                    "-max_len=8"
+                ]
+            ),
+            [
+                "--release",
+                "-s",
+                "address",
+                "foo",
+                "--jobs=4",
+                "--",
+                "-max_len=64",
+                "-max_len=8"
+            ]
+        );
+        assert_eq!(
+            apply_in(dir.path(), &["build", "foo"]),
+            ["foo", "--dev", "--sanitizer=none"]
+        );
+    }
+
+    #[test]
+    fn reads_fuzz_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            r#"
+            [package]
+            name = "foo-fuzz"
+
+            [package.metadata.cargo-fuzz.targets.foo]
+            dict = "json.dict"
+            max_len = 4096
+            timeout = 10
+            "#,
+        )
+        .unwrap();
+
+        let dict = format!("-dict={}", dir.path().join("json.dict").display());
+        assert_eq!(
+            apply_in(dir.path(), &["tmin", "foo", "crash-1"]),
+            [
+                "foo",
+                "crash-1",
+                "--",
+                &dict,
+                "-max_len=4096",
+                "-timeout=10"
+            ]
+        );
+        assert_eq!(
+            apply_in(dir.path(), &["cmin", "foo", "--", "-timeout=1"]),
+            [
+                "foo",
+                "--",
+                &dict,
+                "-max_len=4096",
+                "-timeout=10",
+                "-timeout=1"
+            ]
+        );
+        assert_eq!(
+            apply_in(dir.path(), &["tmin", "bar", "crash-1"]),
+            ["bar", "crash-1"]
+        );
+    }
+}
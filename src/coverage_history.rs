@@ -0,0 +1,160 @@
+//! The history of the coverage of each fuzz target,
+//! `coverage/.history/<target>.jsonl` in the fuzz directory: the summary of
+//! every `cargo fuzz coverage` run, one JSON object per line, with the time
+//! and git commit it measured
+
+use crate::llvm_cov::CoverageTotals;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    fs,
+    io::{self, Write as _},
+    path::Path,
+};
+
+/// Coverage that changed by less than this many percentage points is taken
+/// to be the same, as it's usually the rounding of the same line counts.
+const EPSILON: f64 = 0.005;
+
+/// The coverage measured by a `cargo fuzz coverage` run, in percent.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CoverageRecord {
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    pub git_commit: Option<String>,
+    pub totals: CoverageTotals,
+    /// The coverage of each source file, by its path relative to the
+    /// project.
+    pub files: BTreeMap<String, CoverageTotals>,
+}
+
+/// Appends `record` to the history at `path`, creating it if needed.
+pub fn append(path: &Path, record: &CoverageRecord) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create directory {}", dir.display()))?;
+    }
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(&line))
+        .with_context(|| format!("failed to append to {}", path.display()))
+}
+
+/// Reads the history at `path`, oldest first. There is none until the first
+/// `cargo fuzz coverage` run.
+pub fn read(path: &Path) -> Result<Vec<CoverageRecord>> {
+    let history = match fs::read_to_string(path) {
+        Ok(history) => history,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+    };
+    history
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("failed to parse line {} of {}", i + 1, path.display()))
+        })
+        .collect()
+}
+
+/// Lists the files whose line coverage differs between two records, like
+/// the last run and the one before it.
+pub fn render_file_changes(old: &CoverageRecord, new: &CoverageRecord) -> String {
+    let percent = |totals: Option<&CoverageTotals>| {
+        totals.map_or_else(|| "-".to_owned(), |totals| format!("{:.1}%", totals.lines))
+    };
+    let mut out = String::new();
+    let files: BTreeSet<_> = old.files.keys().chain(new.files.keys()).collect();
+    for file in files {
+        let (before, after) = (old.files.get(file), new.files.get(file));
+        let changed = match (before, after) {
+            (Some(before), Some(after)) => (before.lines - after.lines).abs() >= EPSILON,
+            _ => true,
+        };
+        if changed {
+            writeln!(out, "  {}: {} -> {}", file, percent(before), percent(after)).unwrap();
+        }
+    }
+    if out.is_empty() {
+        "The line coverage of every file is unchanged\n".to_owned()
+    } else {
+        format!("Files whose line coverage changed:\n{}", out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(time: u64, files: &[(&str, f64)]) -> CoverageRecord {
+        CoverageRecord {
+            time,
+            git_commit: Some("0123456789abcdef".to_owned()),
+            totals: CoverageTotals {
+                lines: 50.0,
+                regions: 40.0,
+            },
+            files: files
+                .iter()
+                .map(|(file, lines)| {
+                    let totals = CoverageTotals {
+                        lines: *lines,
+                        regions: *lines,
+                    };
+                    (file.to_string(), totals)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn records_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir
+            .path()
+            .join("coverage")
+            .join(".history")
+            .join("parse.jsonl");
+        assert_eq!(read(&path).unwrap(), []);
+
+        let records = [
+            record(1714570620, &[("src/lib.rs", 50.0)]),
+            record(1714574220, &[("src/lib.rs", 60.0)]),
+        ];
+        for record in &records {
+            append(&path, record).unwrap();
+        }
+        assert_eq!(read(&path).unwrap(), records);
+    }
+
+    #[test]
+    fn renders_file_changes() {
+        let old = record(
+            0,
+            &[("src/a.rs", 50.0), ("src/b.rs", 20.0), ("src/c.rs", 10.0)],
+        );
+        let new = record(
+            1,
+            &[("src/a.rs", 50.001), ("src/b.rs", 25.0), ("src/d.rs", 5.0)],
+        );
+        assert_eq!(
+            render_file_changes(&old, &new),
+            "Files whose line coverage changed:\n  \
+             src/b.rs: 20.0% -> 25.0%\n  \
+             src/c.rs: 10.0% -> -\n  \
+             src/d.rs: - -> 5.0%\n"
+        );
+        assert_eq!(
+            render_file_changes(&old, &old),
+            "The line coverage of every file is unchanged\n"
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! Generating libFuzzer dictionaries from the literals of Rust sources and
+//! the frequent words of a corpus
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// The longest dictionary entry that libFuzzer accepts.
+pub const MAX_TOKEN_LEN: usize = 64;
+
+/// The most words a corpus adds to a dictionary.
+const MAX_CORPUS_TOKENS: usize = 256;
+
+/// Returns the string, byte string and character literals of Rust `source`,
+/// with their escapes resolved, so also the patterns of match arms like
+/// `b'{' =>` or `"null" =>`.
+pub fn source_tokens(source: &str) -> Vec<Vec<u8>> {
+    let s = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < s.len() {
+        let literal = match s[i] {
+            b'/' if s.get(i + 1) == Some(&b'/') => {
+                i = s[i..]
+                    .iter()
+                    .position(|&c| c == b'\n')
+                    .map_or(s.len(), |n| i + n);
+                continue;
+            }
+            b'/' if s.get(i + 1) == Some(&b'*') => {
+                i = skip_block_comment(s, i);
+                continue;
+            }
+            b'"' => Some(quoted(s, i + 1)),
+            // A character literal, or a lifetime or a label.
+            b'\'' => char_literal(s, i + 1),
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                let start = i;
+                while i < s.len() && (s[i].is_ascii_alphanumeric() || s[i] == b'_') {
+                    i += 1;
+                }
+                let literal = match (&s[start..i], s.get(i)) {
+                    (b"r" | b"br" | b"cr", Some(b'"' | b'#')) => raw_string(s, i),
+                    (b"b" | b"c", Some(b'"')) => Some(quoted(s, i + 1)),
+                    (b"b", Some(b'\'')) => char_literal(s, i + 1),
+                    _ => None,
+                };
+                if literal.is_none() {
+                    // What comes after the identifier may start a literal.
+                    continue;
+                }
+                literal
+            }
+            _ => None,
+        };
+        match literal {
+            Some((token, end)) => {
+                tokens.push(token);
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+    tokens
+}
+
+/// Returns the index after the block comment that starts at `i`, which may
+/// have comments nested in it.
+fn skip_block_comment(s: &[u8], mut i: usize) -> usize {
+    let mut depth = 0;
+    while i < s.len() {
+        if s[i..].starts_with(b"/*") {
+            depth += 1;
+            i += 2;
+        } else if s[i..].starts_with(b"*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                break;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    i
+}
+
+/// Returns the contents of the quoted literal that starts at `i`, after its
+/// opening quote, and the index after its closing quote.
+fn quoted(s: &[u8], mut i: usize) -> (Vec<u8>, usize) {
+    let mut token = Vec::new();
+    while i < s.len() && s[i] != b'"' {
+        if s[i] == b'\\' {
+            i = unescape(s, i + 1, &mut token);
+        } else {
+            token.push(s[i]);
+            i += 1;
+        }
+    }
+    (token, i + 1)
+}
+
+/// Returns the contents of the character literal that starts at `i`, after
+/// its opening quote, and the index after its closing quote, unless the quote
+/// starts a lifetime or a label.
+fn char_literal(s: &[u8], i: usize) -> Option<(Vec<u8>, usize)> {
+    let mut token = Vec::new();
+    let end = match *s.get(i)? {
+        b'\\' => unescape(s, i + 1, &mut token),
+        first => {
+            let len = match first {
+                0xf0.. => 4,
+                0xe0.. => 3,
+                0x80.. => 2,
+                _ => 1,
+            };
+            token.extend_from_slice(s.get(i..i + len)?);
+            i + len
+        }
+    };
+    (s.get(end) == Some(&b'\'')).then_some((token, end + 1))
+}
+
+/// Returns the contents of the raw string literal whose `#`s or opening quote
+/// start at `i`, and the index after it.
+fn raw_string(s: &[u8], i: usize) -> Option<(Vec<u8>, usize)> {
+    let hashes = s[i..].iter().take_while(|&&c| c == b'#').count();
+    let start = i + hashes + 1;
+    if s.get(start - 1) != Some(&b'"') {
+        // A raw identifier, like `r#type`.
+        return None;
+    }
+    let closing = [&b"\""[..], &b"#".repeat(hashes)].concat();
+    let len = s[start..]
+        .windows(closing.len())
+        .position(|window| window == closing)?;
+    Some((s[start..start + len].to_vec(), start + len + closing.len()))
+}
+
+/// Pushes the value of the escape whose backslash comes before `i` to
+/// `token`, and returns the index after the escape.
+fn unescape(s: &[u8], i: usize, token: &mut Vec<u8>) -> usize {
+    let Some(&c) = s.get(i) else {
+        return i;
+    };
+    match c {
+        b'n' => token.push(b'\n'),
+        b'r' => token.push(b'\r'),
+        b't' => token.push(b'\t'),
+        b'0' => token.push(0),
+        b'x' => {
+            let byte = s
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            if let Some(byte) = byte {
+                token.push(byte);
+                return i + 3;
+            }
+        }
+        b'u' => {
+            let end = s[i..].iter().position(|&c| c == b'}').map_or(i, |n| i + n);
+            let c = s
+                .get(i + 2..end)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .and_then(char::from_u32);
+            if let Some(c) = c {
+                token.extend_from_slice(c.to_string().as_bytes());
+            }
+            return end + 1;
+        }
+        // A line continuation, which skips the whitespace after it.
+        b'\n' | b'\r' => {
+            return i + s[i..]
+                .iter()
+                .take_while(|c| c.is_ascii_whitespace())
+                .count();
+        }
+        c => token.push(c),
+    }
+    i + 1
+}
+
+/// Returns the words, runs of ASCII letters, digits and underscores, that are
+/// in at least `min_inputs` of `inputs`, from the most to the least frequent.
+pub fn corpus_tokens(inputs: &[Vec<u8>], min_inputs: usize) -> Vec<Vec<u8>> {
+    let mut counts: HashMap<&[u8], usize> = HashMap::new();
+    for input in inputs {
+        let words: HashSet<&[u8]> = input
+            .split(|c| !c.is_ascii_alphanumeric() && *c != b'_')
+            .filter(|word| (2..=MAX_TOKEN_LEN).contains(&word.len()))
+            .collect();
+        for word in words {
+            *counts.entry(word).or_default() += 1;
+        }
+    }
+    let mut frequent: Vec<(&[u8], usize)> = counts
+        .into_iter()
+        .filter(|&(_, count)| count >= min_inputs)
+        .collect();
+    frequent.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    frequent
+        .into_iter()
+        .take(MAX_CORPUS_TOKENS)
+        .map(|(word, _)| word.to_vec())
+        .collect()
+}
+
+/// Renders `tokens` as a libFuzzer dictionary, after a `#` comment.
+pub fn render(comment: &str, tokens: &BTreeSet<Vec<u8>>) -> String {
+    let mut dict = format!("# {}\n", comment);
+    for token in tokens {
+        dict.push('"');
+        for &byte in token {
+            match byte {
+                b'"' | b'\\' => {
+                    dict.push('\\');
+                    dict.push(byte as char);
+                }
+                b' '..=b'~' => dict.push(byte as char),
+                _ => dict.push_str(&format!("\\x{:02X}", byte)),
+            }
+        }
+        dict.push_str("\"\n");
+    }
+    dict
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_source_tokens() {
+        let source = r###"
+            // "in a comment"
+            /* "in a /* nested */ comment" */
+            fn parse<'a>(input: &'a [u8]) -> Option<&'static str> {
+                match input {
+                    b"null" => Some("\u{2603}\n"),
+                    [b'{', ..] => Some(r#"a "raw" string"#),
+                    _ if input.starts_with(br"\x") => None,
+                    _ => Some("tab\t\x41 \
+                               continued"),
+                }
+            }
+        "###;
+        let tokens: Vec<_> = source_tokens(source)
+            .into_iter()
+            .map(|t| String::from_utf8(t).unwrap())
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                "null",
+                "\u{2603}\n",
+                "{",
+                "a \"raw\" string",
+                "\\x",
+                "tab\tA continued"
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_corpus_tokens() {
+        let inputs = [
+            b"{\"key\": true}".to_vec(),
+            b"{\"key\": false, \"other\": true}".to_vec(),
+            b"x".to_vec(),
+        ];
+        assert_eq!(
+            corpus_tokens(&inputs, 2),
+            [b"key".to_vec(), b"true".to_vec()]
+        );
+    }
+
+    #[test]
+    fn renders_dictionaries() {
+        let tokens = BTreeSet::from([b"a\"b\\".to_vec(), vec![0, b'x', 0xff]]);
+        assert_eq!(
+            render("comment", &tokens),
+            "# comment\n\"\\x00x\\xFF\"\n\"a\\\"b\\\\\"\n"
+        );
+    }
+}
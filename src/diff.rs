@@ -0,0 +1,102 @@
+//! Line diffs of the `std::fmt::Debug` output of fuzz inputs
+//!
+//! The pretty `Debug` output puts every field and element on a line of its
+//! own, so a diff of lines is a diff of the structure. It is a plain longest
+//! common subsequence, after the common prefix and suffix are set aside, which
+//! is all an input and its minimized form leave to compare.
+
+/// A line of a diff.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Line<'a> {
+    /// A line of both texts
+    Same(&'a str),
+    /// A line of the old text only
+    Removed(&'a str),
+    /// A line of the new text only
+    Added(&'a str),
+}
+
+impl std::fmt::Display for Line<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Line::Same(line) => write!(f, " {}", line),
+            Line::Removed(line) => write!(f, "-{}", line),
+            Line::Added(line) => write!(f, "+{}", line),
+        }
+    }
+}
+
+/// Returns the lines of `old` and `new`, in order, each marked as in both of
+/// them or in only one.
+pub fn lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    // `lcs[i][j]` is the length of the longest common subsequence of `a[i..]`
+    // and `b[j..]`.
+    let mut lcs = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff: Vec<Line> = old[..prefix].iter().map(|l| Line::Same(l)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            diff.push(Line::Same(a[i]));
+            i += 1;
+            j += 1;
+        } else if j == b.len() || (i < a.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push(Line::Removed(a[i]));
+            i += 1;
+        } else {
+            diff.push(Line::Added(b[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old[old.len() - suffix..].iter().map(|l| Line::Same(l)));
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_lines() {
+        let old = "Input {\n    a: 1,\n    b: [\n        2,\n        3,\n    ],\n}\n";
+        let new = "Input {\n    a: 0,\n    b: [\n        3,\n    ],\n}\n";
+        assert_eq!(
+            lines(old, new),
+            [
+                Line::Same("Input {"),
+                Line::Removed("    a: 1,"),
+                Line::Added("    a: 0,"),
+                Line::Same("    b: ["),
+                Line::Removed("        2,"),
+                Line::Same("        3,"),
+                Line::Same("    ],"),
+                Line::Same("}"),
+            ]
+        );
+        assert!(lines(old, old).iter().all(|l| matches!(l, Line::Same(_))));
+        assert_eq!(lines("", "a"), [Line::Added("a")]);
+    }
+}
@@ -0,0 +1,188 @@
+//! `cargo fuzz doctor`: checks of the toolchain and the system that fuzz
+//! targets are built and run with
+
+use crate::exit_code::Failure;
+use crate::options::{self, Sanitizer};
+use crate::project::{
+    find_symbolizer, is_on_path, msvc_asan_dll, msvc_asan_dll_dir, rustlib, sysroot,
+};
+use crate::rustc_version::RustVersion;
+use anyhow::{anyhow, Result};
+use std::{env, fs, path::PathBuf};
+
+/// How a check of `cargo fuzz doctor` turned out.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Health {
+    Ok,
+    /// Something that only some commands or options need is missing.
+    Warn,
+    Fail,
+}
+
+/// Checks the toolchain and the system for what fuzzing with the build
+/// options of `doctor` needs, printing how to fix each problem.
+pub fn exec_doctor(doctor: &options::Doctor) -> Result<()> {
+    let build = &doctor.build;
+    let mut failures = 0;
+    let mut check = |health: Health, what: String, fix: &str| {
+        let label = match health {
+            Health::Ok => "ok",
+            Health::Warn => "warn",
+            Health::Fail => "FAIL",
+        };
+        println!("{:<5} {}", label, what);
+        if health != Health::Ok {
+            println!("      fix: {}", fix);
+        }
+        if health == Health::Fail {
+            failures += 1;
+        }
+    };
+
+    let version = RustVersion::discover()?;
+    let sysroot = PathBuf::from(sysroot()?);
+    // The fixes go to the toolchain that cargo-fuzz runs with.
+    let rustup = if version.nightly {
+        "rustup +nightly"
+    } else {
+        "rustup"
+    };
+    let build_std = build.build_std || build.careful_mode || build.sanitizer == Sanitizer::Memory;
+
+    let needs_nightly = (build.sanitizer != Sanitizer::None && !version.has_sanitizers_on_stable())
+        || build_std
+        || build.sanitizer_recover
+        || !build.unstable_flags.is_empty();
+    if version.nightly {
+        check(Health::Ok, "toolchain: nightly".to_owned(), "");
+    } else if needs_nightly {
+        check(
+            Health::Fail,
+            "toolchain: stable, but these build options need nightly".to_owned(),
+            "rustup toolchain install nightly, and run `cargo +nightly fuzz`",
+        );
+    } else {
+        check(Health::Ok, "toolchain: stable".to_owned(), "");
+    }
+
+    let target_lib = sysroot
+        .join("lib")
+        .join("rustlib")
+        .join(&build.triple)
+        .join("lib");
+    if target_lib.is_dir() {
+        check(
+            Health::Ok,
+            format!("target {} is installed", build.triple),
+            "",
+        );
+    } else {
+        check(
+            Health::Fail,
+            format!("target {} isn't installed", build.triple),
+            &format!("{} target add {}", rustup, build.triple),
+        );
+    }
+
+    let rust_src = sysroot.join("lib").join("rustlib").join("src").join("rust");
+    if rust_src.join("library").is_dir() {
+        check(Health::Ok, "rust-src is installed".to_owned(), "");
+    } else {
+        check(
+            if build_std {
+                Health::Fail
+            } else {
+                Health::Warn
+            },
+            "rust-src isn't installed, which `--build-std`, `--careful` and \
+             `--sanitizer=memory` need"
+                .to_owned(),
+            &format!("{} component add rust-src", rustup),
+        );
+    }
+
+    let rustlib = rustlib()?;
+    let llvm_tools = ["llvm-profdata", "llvm-cov"]
+        .map(|tool| rustlib.join(format!("{}{}", tool, env::consts::EXE_SUFFIX)));
+    if llvm_tools.iter().all(|tool| tool.is_file()) {
+        check(
+            Health::Ok,
+            format!("llvm-profdata and llvm-cov are in {}", rustlib.display()),
+            "",
+        );
+    } else {
+        check(
+            Health::Warn,
+            "llvm-tools aren't installed, which `cargo fuzz coverage` and \
+             `cargo fuzz cov` need"
+                .to_owned(),
+            &format!("{} component add llvm-tools-preview", rustup),
+        );
+    }
+
+    if build.sanitizer != Sanitizer::None {
+        match env::var_os("ASAN_SYMBOLIZER_PATH")
+            .map(PathBuf::from)
+            .or_else(find_symbolizer)
+        {
+            Some(symbolizer) => check(
+                Health::Ok,
+                format!("llvm-symbolizer: {}", symbolizer.display()),
+                "",
+            ),
+            None => check(
+                Health::Warn,
+                "llvm-symbolizer isn't in the Rust sysroot or on the PATH, so sanitizer \
+                 reports won't be symbolized"
+                    .to_owned(),
+                "install LLVM, or set ASAN_SYMBOLIZER_PATH",
+            ),
+        }
+    }
+
+    if let Some((dll, _)) = msvc_asan_dll(build) {
+        if is_on_path(&dll) || msvc_asan_dll_dir(build).is_some() {
+            check(Health::Ok, format!("{} is available", dll), "");
+        } else {
+            check(
+                Health::Fail,
+                format!("{} isn't on the PATH or in the MSVC tools", dll),
+                &format!(
+                    "run cargo fuzz from a Developer Command Prompt for Visual Studio, \
+                     or add the directory of {} to the PATH",
+                    dll
+                ),
+            );
+        }
+    }
+
+    // The leak sanitizer attaches to the fuzz target with ptrace to look for
+    // leaks, which Yama may not allow.
+    if cfg!(target_os = "linux") && matches!(build.sanitizer, Sanitizer::Address | Sanitizer::Leak)
+    {
+        let scope = fs::read_to_string("/proc/sys/kernel/yama/ptrace_scope")
+            .ok()
+            .and_then(|scope| scope.trim().parse::<u32>().ok());
+        match scope {
+            Some(scope) if scope >= 2 => check(
+                Health::Fail,
+                format!(
+                    "kernel.yama.ptrace_scope is {}, so the leak sanitizer can't look for leaks",
+                    scope
+                ),
+                "sudo sysctl kernel.yama.ptrace_scope=1, or fuzz with `-- -detect_leaks=0`",
+            ),
+            Some(scope) => check(
+                Health::Ok,
+                format!("kernel.yama.ptrace_scope is {}", scope),
+                "",
+            ),
+            None => {}
+        }
+    }
+
+    if failures > 0 {
+        return Err(Failure::Environment.wrap(anyhow!("{} check(s) failed", failures)));
+    }
+    Ok(())
+}
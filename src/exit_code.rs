@@ -0,0 +1,130 @@
+//! The exit codes of `cargo fuzz`, which tell scripts and CI pipelines why it
+//! failed: that fuzzing found a crash, say, rather than that the fuzz targets
+//! don't build
+
+use std::{error::Error, fmt};
+
+/// The exit code of errors that aren't a [`Failure`], as well as of anyhow's
+/// errors before there were any.
+pub const OTHER_ERROR: u8 = 1;
+
+/// The exit codes, as the end of `cargo fuzz --help` lists them.
+pub const EXIT_CODES_HELP: &str = "\
+Exit codes:
+   0  Success, for instance fuzzing found no crash
+   1  Any other error
+   2  Invalid command-line arguments
+   3  The fuzz targets failed to build
+   4  A tool that cargo fuzz needs is missing or doesn't work, like rustc,
+      llvm-tools, a debugger or a profiler
+  10  Fuzzing found a crash
+  11  The inputs to reproduce crashed the fuzz target again, whereas
+      `cargo fuzz repro` exits with 0 if its input no longer crashes
+  12  The coverage is below the `--fail-under` threshold\
+";
+
+/// Why `cargo fuzz` failed, as far as scripts that run it care.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Failure {
+    /// cargo failed to build the fuzz targets.
+    Build,
+    /// A tool that cargo fuzz runs is missing or doesn't work.
+    Environment,
+    /// Fuzzing, or replaying a corpus, found a crash.
+    Crash,
+    /// The inputs of `cargo fuzz repro` or `cargo fuzz verify` still crash.
+    Reproduced,
+    /// The coverage is below the threshold of `--fail-under`.
+    CoverageBelowThreshold,
+}
+
+impl Failure {
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Failure::Build => 3,
+            Failure::Environment => 4,
+            Failure::Crash => 10,
+            Failure::Reproduced => 11,
+            Failure::CoverageBelowThreshold => 12,
+        }
+    }
+
+    /// Marks `error` as this failure, leaving its message as it is.
+    pub fn wrap(self, error: impl Into<anyhow::Error>) -> anyhow::Error {
+        anyhow::Error::new(Failed {
+            failure: self,
+            error: error.into(),
+        })
+    }
+
+    /// The failure that `error` was marked as, under any context added
+    /// since.
+    pub fn of(error: &anyhow::Error) -> Option<Failure> {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<Failed>())
+            .map(|failed| failed.failure)
+    }
+}
+
+/// Marks the error of a `Result` as a [`Failure`], the way that
+/// `anyhow::Context` adds context to it.
+pub trait Fail<T> {
+    fn fail(self, failure: Failure) -> anyhow::Result<T>;
+}
+
+impl<T, E: Into<anyhow::Error>> Fail<T> for Result<T, E> {
+    fn fail(self, failure: Failure) -> anyhow::Result<T> {
+        self.map_err(|error| failure.wrap(error))
+    }
+}
+
+/// An error marked as a failure, which displays and chains like the error.
+#[derive(Debug)]
+struct Failed {
+    failure: Failure,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for Failed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl Error for Failed {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// The exit code of `cargo fuzz` when it fails with `error`.
+pub fn exit_code(error: &anyhow::Error) -> u8 {
+    Failure::of(error).map_or(OTHER_ERROR, Failure::exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(exit_code(&anyhow!("no fuzz target named `foo`")), 1);
+
+        let error = Failure::Crash
+            .wrap(anyhow!("Fuzz target exited with signal: 6"))
+            .context("failed to fuzz `foo`");
+        assert_eq!(exit_code(&error), 10);
+        assert_eq!(
+            format!("{:#}", error),
+            "failed to fuzz `foo`: Fuzz target exited with signal: 6"
+        );
+
+        let error = Err::<(), _>(anyhow!("not found").context("failed to run perf"))
+            .fail(Failure::Environment)
+            .unwrap_err();
+        assert_eq!(exit_code(&error), 4);
+        assert_eq!(format!("{:#}", error), "failed to run perf: not found");
+    }
+}
@@ -0,0 +1,117 @@
+//! Driving external test case minimizers for `cargo fuzz tmin --external`
+//!
+//! Minimizers like halfempty shrink an input as long as an "interestingness"
+//! test accepts the result. The test is a shell script that calls back into
+//! `cargo fuzz tmin` to replay an input, and succeeds when it crashes the
+//! fuzz target with the stack hash of the original test case.
+
+use anyhow::{bail, Result};
+use std::{ffi::OsStr, path::Path};
+
+/// The command that `--external halfempty` stands for.
+const HALFEMPTY: &str = "halfempty --output={output} {test} {input}";
+
+/// Returns the command line of an external minimizer from `template`, either
+/// the name of a known minimizer or a command with `{test}`, `{input}` and
+/// `{output}` placeholders.
+pub fn command(template: &str, test: &Path, input: &Path, output: &Path) -> Result<Vec<String>> {
+    let template = match template {
+        "halfempty" => HALFEMPTY,
+        template => template,
+    };
+    if !template.contains("{test}") {
+        bail!(
+            "the `--external` command `{}` doesn't pass `{{test}}`, the script that tells \
+             the minimizer whether an input still crashes",
+            template
+        );
+    }
+    Ok(template
+        .split_whitespace()
+        .map(|arg| {
+            arg.replace("{test}", &test.display().to_string())
+                .replace("{input}", &input.display().to_string())
+                .replace("{output}", &output.display().to_string())
+        })
+        .collect())
+}
+
+/// Returns the interestingness test that runs `check`, the `cargo fuzz tmin`
+/// command line checking a single input, from the directory `cwd`. The input
+/// is passed as a file argument, or on stdin, which is kept in `scratch`
+/// meanwhile.
+pub fn script(check: &[&OsStr], cwd: &Path, scratch: &Path) -> String {
+    let check = check
+        .iter()
+        .map(|arg| shell_quote(&arg.to_string_lossy()))
+        .collect::<Vec<_>>();
+    let (args, libfuzzer_args) = match check.iter().position(|arg| arg == "'--'") {
+        Some(i) => (&check[..i], &check[i..]),
+        None => (&check[..], &[][..]),
+    };
+    format!(
+        "#!/bin/sh\n\
+         # Generated by `cargo fuzz tmin --external`: succeeds when the input,\n\
+         # passed as a file or on stdin, still crashes the same way.\n\
+         if [ \"$#\" -gt 0 ]; then\n\
+         \x20   case \"$1\" in\n\
+         \x20       /*) input=\"$1\" ;;\n\
+         \x20       *) input=\"$PWD/$1\" ;;\n\
+         \x20   esac\n\
+         else\n\
+         \x20   input={scratch}/stdin.$$\n\
+         \x20   trap 'rm -f \"$input\"' EXIT\n\
+         \x20   cat > \"$input\"\n\
+         fi\n\
+         cd {cwd} || exit 1\n\
+         {args} \"$input\" {libfuzzer_args} > /dev/null 2>&1\n",
+        scratch = shell_quote(&scratch.display().to_string()),
+        cwd = shell_quote(&cwd.display().to_string()),
+        args = args.join(" "),
+        libfuzzer_args = libfuzzer_args.join(" "),
+    )
+}
+
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands() {
+        let (test, input, output) = (
+            Path::new("/tmp/test.sh"),
+            Path::new("/tmp/input"),
+            Path::new("/tmp/output"),
+        );
+        assert_eq!(
+            command("halfempty", test, input, output).unwrap(),
+            [
+                "halfempty",
+                "--output=/tmp/output",
+                "/tmp/test.sh",
+                "/tmp/input"
+            ]
+        );
+        assert_eq!(
+            command("shrinkray {test} {input}", test, input, output).unwrap(),
+            ["shrinkray", "/tmp/test.sh", "/tmp/input"]
+        );
+        assert!(command("ddmin {input}", test, input, output).is_err());
+    }
+
+    #[test]
+    fn scripts() {
+        let check = ["cargo-fuzz", "tmin", "it's", "--", "-runs=1"].map(OsStr::new);
+        let script = script(&check, Path::new("/project"), Path::new("/tmp/scratch"));
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("    input='/tmp/scratch'/stdin.$$\n"));
+        assert!(script.contains("cd '/project' || exit 1\n"));
+        assert!(script.ends_with(
+            "'cargo-fuzz' 'tmin' 'it'\\''s' \"$input\" '--' '-runs=1' > /dev/null 2>&1\n"
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+//! JUnit XML reports, for CI systems that display test results
+
+use std::{fmt::Write, time::Duration};
+
+/// One input replayed through a fuzz target.
+pub struct TestCase {
+    pub name: String,
+    pub time: Duration,
+    pub failure: Option<Failure>,
+}
+
+pub struct Failure {
+    /// A one-line summary of the crash
+    pub message: String,
+    /// The backtrace or other output of the crash
+    pub details: String,
+}
+
+/// Renders a report with a single test suite, named after the fuzz target.
+pub fn render(target: &str, cases: &[TestCase]) -> String {
+    render_suites(&[(target, cases)])
+}
+
+/// Renders a report with a test suite per fuzz target.
+pub fn render_suites(suites: &[(&str, &[TestCase])]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+    for (target, cases) in suites {
+        render_suite(&mut xml, target, cases);
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn render_suite(xml: &mut String, target: &str, cases: &[TestCase]) {
+    let failures = cases.iter().filter(|case| case.failure.is_some()).count();
+    let time: Duration = cases.iter().map(|case| case.time).sum();
+
+    writeln!(
+        xml,
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+        escape(target),
+        cases.len(),
+        failures,
+        time.as_secs_f64()
+    )
+    .unwrap();
+    for case in cases {
+        write!(
+            xml,
+            "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+            escape(target),
+            escape(&case.name),
+            case.time.as_secs_f64()
+        )
+        .unwrap();
+        match &case.failure {
+            Some(failure) => {
+                writeln!(
+                    xml,
+                    ">\n      <failure type=\"crash\" message=\"{}\">{}</failure>\n    </testcase>",
+                    escape(&failure.message),
+                    escape(&failure.details)
+                )
+                .unwrap();
+            }
+            None => xml.push_str("/>\n"),
+        }
+    }
+    xml.push_str("  </testsuite>\n");
+}
+
+/// Escapes text for use in XML attributes and content, dropping the control
+/// characters that XML 1.0 cannot represent at all.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_passes_and_failures() {
+        let cases = [
+            TestCase {
+                name: "crash-1".to_owned(),
+                time: Duration::from_millis(250),
+                failure: None,
+            },
+            TestCase {
+                name: "crash-2".to_owned(),
+                time: Duration::from_millis(500),
+                failure: Some(Failure {
+                    message: "panicked at src/lib.rs:1:2".to_owned(),
+                    details: "thread '<unnamed>' panicked\u{1b}[0m".to_owned(),
+                }),
+            },
+        ];
+        assert_eq!(
+            render("foo", &cases),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <testsuites>\n  \
+             <testsuite name=\"foo\" tests=\"2\" failures=\"1\" time=\"0.750\">\n    \
+             <testcase classname=\"foo\" name=\"crash-1\" time=\"0.250\"/>\n    \
+             <testcase classname=\"foo\" name=\"crash-2\" time=\"0.500\">\n      \
+             <failure type=\"crash\" message=\"panicked at src/lib.rs:1:2\">\
+             thread &apos;&lt;unnamed&gt;&apos; panicked[0m</failure>\n    \
+             </testcase>\n  \
+             </testsuite>\n\
+             </testsuites>\n"
+        );
+    }
+}
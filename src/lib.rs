@@ -0,0 +1,371 @@
+// Copyright 2016 rust-fuzz developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! The fuzz projects, build options and operations behind `cargo fuzz`, for
+//! tools that drive fuzzing without going through its command line.
+//!
+//! ```no_run
+//! use cargo_fuzz::{BuildOptions, FuzzProject};
+//! use clap::Parser;
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let project = FuzzProject::new(None)?;
+//! let build = BuildOptions::parse_from(["build"]);
+//! for target in project.targets() {
+//!     let outcome = project.fuzz(&build, target, &["-runs=1000".to_owned()])?;
+//!     println!("{}: {} {:?}", target, outcome.status, outcome.artifacts);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::Result;
+use clap::{CommandFactory, FromArgMatches, Parser};
+
+#[macro_use]
+mod templates;
+mod compare;
+mod config;
+mod coverage_history;
+mod dict;
+mod diff;
+mod doctor;
+mod exit_code;
+mod external;
+mod junit;
+mod lint;
+mod llvm_cov;
+mod logs;
+mod man;
+mod metrics;
+mod notify;
+pub mod options;
+mod output;
+mod profile;
+mod project;
+mod regression_test;
+mod report;
+mod rustc_version;
+mod sarif;
+mod sessions;
+mod shrink;
+mod stacktrace;
+mod stats;
+mod sync;
+mod tui;
+mod upgrade;
+mod utils;
+mod workspace;
+
+pub use exit_code::{exit_code, Failure};
+pub use llvm_cov::CoverageTotals;
+pub use options::{BuildMode, BuildOptions, Sanitizer};
+pub use project::{CoverageOutcome, FuzzOutcome, FuzzProject};
+pub use stats::Status;
+
+static FUZZ_TARGETS_DIR_OLD: &str = "fuzzers";
+static FUZZ_TARGETS_DIR: &str = "fuzz_targets";
+
+// It turns out that `clap`'s `long_about()` makes `cargo fuzz --help`
+// unreadable, and its `before_help()` injects our long about text before the
+// version, so change the default template slightly.
+const LONG_ABOUT_TEMPLATE: &str = "\
+{bin} {version}
+{about}
+
+USAGE:
+    {usage}
+
+{before-help}
+
+{all-args}
+
+{after-help}";
+
+const RUN_BEFORE_HELP: &str = "\
+The fuzz target name is the same as the name of the fuzz target script in
+fuzz/fuzz_targets/, i.e. the name picked when running `cargo fuzz add`.
+
+This will run the script inside the fuzz target with varying inputs until it
+finds a crash, at which point it will save the crash input to the artifact
+directory, print some output, and exit. Unless you configure it otherwise (see
+libFuzzer options below), this will run indefinitely.
+
+By default fuzz targets are built with optimizations equivalent to
+`cargo build --release`, but with debug assertions and overflow checks enabled.
+Address Sanitizer is also enabled by default.";
+
+const RUN_AFTER_HELP: &str = "\
+A full list of libFuzzer options can be found at
+http://llvm.org/docs/LibFuzzer.html#options
+
+You can also get this by running `cargo fuzz run fuzz_target -- -help=1`
+
+Some useful options (to be used as `cargo fuzz run fuzz_target -- <options>`)
+include:
+
+  * `-max_len=<len>`: Will limit the length of the input string to `<len>`
+
+  * `-runs=<number>`: Will limit the number of tries (runs) before it gives up
+
+  * `-max_total_time=<time>`: Will limit the amount of time (seconds) to
+    fuzz before it gives up
+
+  * `-timeout=<time>`: Will limit the amount of time (seconds) for a single
+    run before it considers that run a failure
+
+  * `-only_ascii`: Only provide ASCII input
+
+  * `-dict=<file>`: Use a keyword dictionary from specified file. See
+    http://llvm.org/docs/LibFuzzer.html#dictionaries\
+";
+
+const BUILD_BEFORE_HELP: &str = "\
+By default fuzz targets are built with optimizations equivalent to
+`cargo build --release`, but with debug assertions and overflow checks enabled.
+Address Sanitizer is also enabled by default.";
+
+const BUILD_AFTER_HELP: &str = "\
+Sanitizers perform checks necessary for detecting bugs in unsafe code
+at the cost of some performance. For more information on sanitizers see
+https://doc.rust-lang.org/unstable-book/compiler-flags/sanitizer.html\
+";
+
+/// A trait for running our various commands.
+trait RunCommand {
+    /// Run this command!
+    fn run_command(&mut self) -> Result<()>;
+}
+
+#[derive(Clone, Debug, Parser)]
+#[command(version, about)]
+#[command(after_long_help(exit_code::EXIT_CODES_HELP))]
+#[command(subcommand_required = true)]
+#[command(arg_required_else_help = true)]
+#[command(propagate_version = true)]
+// Cargo passes in the subcommand name to the invoked executable.
+// Use a hidden, optional positional argument to deal with it.
+#[command(
+    arg(clap::Arg::new("dummy")
+        .value_parser(["fuzz"])
+        .required(false)
+        .hide(true))
+)]
+#[command(
+    arg(clap::Arg::new("color")
+        .long("color")
+        .value_name("WHEN")
+        .value_parser(clap::value_parser!(output::ColorChoice))
+        .default_value("auto")
+        .global(true)
+        .help("Whether to color the output: auto, always or never"))
+)]
+enum Command {
+    /// Initialize the fuzz directory
+    Init(options::Init),
+
+    /// Add a new fuzz target
+    Add(options::Add),
+
+    #[command(
+        help_template(LONG_ABOUT_TEMPLATE),
+        before_help(BUILD_BEFORE_HELP),
+        after_help(BUILD_AFTER_HELP)
+    )]
+    /// Build fuzz targets
+    Build(options::Build),
+
+    #[command(help_template(LONG_ABOUT_TEMPLATE))]
+    /// Type-check the fuzz targets
+    Check(options::Check),
+
+    /// Print the `std::fmt::Debug` output for an input
+    Fmt(options::Fmt),
+
+    /// Print mutations that libFuzzer makes of an input, as hexdumps and, for
+    /// fuzz targets that take an `Arbitrary` type, their `std::fmt::Debug`
+    /// output
+    Mutate(options::Mutate),
+
+    /// List all the existing fuzz targets
+    List(options::List),
+
+    /// Check the fuzz targets and the manifest for common mistakes
+    Lint(options::Lint),
+
+    #[command(
+        help_template(LONG_ABOUT_TEMPLATE),
+        before_help(RUN_BEFORE_HELP),
+        after_help(RUN_AFTER_HELP)
+    )]
+    /// Run a fuzz target
+    Run(options::Run),
+
+    /// Minify a corpus
+    Cmin(options::Cmin),
+
+    /// Minify a test case
+    Tmin(options::Tmin),
+
+    /// Run program on the generated corpus and generate coverage information
+    Coverage(options::Coverage),
+
+    /// Inspect the coverage data generated by `cargo fuzz coverage` with llvm-cov
+    Cov(options::Cov),
+
+    /// Manage the corpora of fuzz targets
+    Corpus(options::Corpus),
+
+    /// Replay the corpus of a fuzz target, timing each input, and report the
+    /// slowest ones
+    BenchCorpus(options::BenchCorpus),
+
+    /// Measure the throughput of a fuzz target: replay a fixed corpus, then
+    /// fuzz it, each for a fixed time, and report the executions per second
+    Bench(options::Bench),
+
+    /// Profile a fuzz target with perf or DTrace and draw a flamegraph of
+    /// where it spends its time
+    Profile(options::Profile),
+
+    /// Generate libFuzzer dictionaries for fuzz targets
+    Dict(options::Dict),
+
+    /// Group the crash artifacts of a fuzz target by their stack hash
+    Triage(options::Triage),
+
+    /// Replay the crash artifacts of a fuzz target and report which still crash
+    Verify(options::Verify),
+
+    /// Manage the crash artifacts of fuzz targets
+    Artifacts(options::Artifacts),
+
+    /// Reproduce a crash with a debug build and a full backtrace
+    Repro(options::Repro),
+
+    /// Pack a crash artifact with its metadata, `std::fmt::Debug` output and
+    /// backtrace into an archive, to attach to a bug report
+    Bundle(options::Bundle),
+
+    /// Run a crash artifact under gdb or lldb, stopping where it panics
+    Debug(options::Debug),
+
+    /// List the slow units of a fuzz target, slowest first, and profile the
+    /// slowest one
+    Slow(options::Slow),
+
+    /// Move a crash artifact into the corpus, to keep it as a regression input
+    Promote(options::Promote),
+
+    /// Summarize the corpora, fuzzing runs and crashes of fuzz targets
+    Report(options::Report),
+
+    /// Show the recorded runs of a fuzz target: when and at which commit they
+    /// fuzzed, for how long, and what they found
+    History(options::History),
+
+    /// Compare two recorded runs, stats files or coverage summaries, and
+    /// point out what got worse
+    Compare(options::Compare),
+
+    /// Print an overview of every fuzz target: whether it is built, its corpus,
+    /// its artifacts, when it was last fuzzed and its coverage
+    Status(options::Status),
+
+    /// Replay the corpora and fuzz every fuzz target within a time budget,
+    /// failing only on new crashes that aren't suppressed in the project
+    /// configuration
+    Ci(options::Ci),
+
+    /// Build the fuzz targets and run each one briefly, to check that they
+    /// start, read their corpus and don't crash on the first inputs
+    Smoke(options::Smoke),
+
+    /// Write a `.clusterfuzzlite/` directory that builds the fuzz targets for
+    /// ClusterFuzzLite
+    InitClusterfuzzlite(options::InitClusterfuzzlite),
+
+    /// Write the Dockerfile, build.sh and project.yaml that onboard the package
+    /// to OSS-Fuzz
+    InitOssFuzz(options::InitOssFuzz),
+
+    /// Write a GitHub Actions workflow that runs `cargo fuzz ci` on every fuzz
+    /// target on a schedule
+    InitGithubActions(options::InitGithubActions),
+
+    /// Migrate a fuzz project from the layout of older versions of cargo-fuzz
+    Upgrade(options::Upgrade),
+
+    /// Add `[[bin]]` sections for the fuzz targets in `fuzz_targets/` that the
+    /// manifest is missing, and report those whose file is gone
+    Sync(options::Sync),
+
+    /// Check that the toolchain and the system have what fuzzing with these
+    /// build options needs, and print how to fix what's missing
+    Doctor(options::Doctor),
+
+    /// Print the man page of cargo-fuzz or of one of its subcommands, or write
+    /// them all to a directory
+    Man(options::Man),
+}
+
+impl RunCommand for Command {
+    fn run_command(&mut self) -> Result<()> {
+        match self {
+            Command::Init(x) => x.run_command(),
+            Command::Add(x) => x.run_command(),
+            Command::Build(x) => x.run_command(),
+            Command::Check(x) => x.run_command(),
+            Command::List(x) => x.run_command(),
+            Command::Lint(x) => x.run_command(),
+            Command::Fmt(x) => x.run_command(),
+            Command::Mutate(x) => x.run_command(),
+            Command::Run(x) => x.run_command(),
+            Command::Cmin(x) => x.run_command(),
+            Command::Tmin(x) => x.run_command(),
+            Command::Coverage(x) => x.run_command(),
+            Command::Cov(x) => x.run_command(),
+            Command::Corpus(x) => x.run_command(),
+            Command::BenchCorpus(x) => x.run_command(),
+            Command::Bench(x) => x.run_command(),
+            Command::Profile(x) => x.run_command(),
+            Command::Dict(x) => x.run_command(),
+            Command::Triage(x) => x.run_command(),
+            Command::Verify(x) => x.run_command(),
+            Command::Artifacts(x) => x.run_command(),
+            Command::Repro(x) => x.run_command(),
+            Command::Bundle(x) => x.run_command(),
+            Command::Debug(x) => x.run_command(),
+            Command::Slow(x) => x.run_command(),
+            Command::Promote(x) => x.run_command(),
+            Command::Report(x) => x.run_command(),
+            Command::History(x) => x.run_command(),
+            Command::Compare(x) => x.run_command(),
+            Command::Status(x) => x.run_command(),
+            Command::Ci(x) => x.run_command(),
+            Command::Smoke(x) => x.run_command(),
+            Command::InitClusterfuzzlite(x) => x.run_command(),
+            Command::InitOssFuzz(x) => x.run_command(),
+            Command::InitGithubActions(x) => x.run_command(),
+            Command::Upgrade(x) => x.run_command(),
+            Command::Sync(x) => x.run_command(),
+            Command::Doctor(x) => x.run_command(),
+            Command::Man(x) => x.run_command(),
+        }
+    }
+}
+
+/// Runs the `cargo fuzz` command line of the current process, which is all
+/// that the `cargo-fuzz` binary does.
+pub fn main() -> Result<()> {
+    let args = workspace::select(std::env::args_os().collect())?;
+    let matches = Command::command().get_matches_from(config::apply(args)?);
+    output::init(*matches.get_one("color").expect("`--color` has a default"));
+    Command::from_arg_matches(&matches)
+        .unwrap_or_else(|e| e.exit())
+        .run_command()
+}
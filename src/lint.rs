@@ -0,0 +1,140 @@
+//! Static checks of the fuzz targets and the manifest of a fuzz project
+//!
+//! The manifest is read directly rather than through `cargo metadata`, which
+//! fails on the very mistakes that are checked here, like a `[[bin]]` without
+//! a source file.
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+/// A `[[bin]]` of a fuzz manifest.
+pub struct Bin {
+    pub name: String,
+    /// The source file, relative to the fuzz directory
+    pub path: PathBuf,
+}
+
+/// Returns the `[[bin]]`s of `manifest`, and the problems of their
+/// declarations.
+pub fn check_manifest(manifest: &str) -> Result<(Vec<Bin>, Vec<String>)> {
+    let value: toml::Value = manifest
+        .parse()
+        .context("could not decode the fuzz manifest")?;
+    let mut bins = Vec::new();
+    let mut problems = Vec::new();
+    let tables = value
+        .get("bin")
+        .and_then(toml::Value::as_array)
+        .map_or(&[][..], Vec::as_slice);
+    for table in tables {
+        let Some(name) = table.get("name").and_then(toml::Value::as_str) else {
+            problems.push("a `[[bin]]` has no `name`".to_owned());
+            continue;
+        };
+        let enabled: Vec<_> = ["test", "doc", "bench"]
+            .into_iter()
+            .filter(|key| table.get(key).and_then(toml::Value::as_bool) != Some(false))
+            .map(|key| format!("`{} = false`", key))
+            .collect();
+        if !enabled.is_empty() {
+            problems.push(format!(
+                "fuzz target `{}` is missing {}, so `cargo test` and friends try to \
+                 build it",
+                name,
+                enabled.join(", ")
+            ));
+        }
+        let path = match table.get("path").and_then(toml::Value::as_str) {
+            Some(path) => PathBuf::from(path),
+            None => PathBuf::from("src")
+                .join("bin")
+                .join(format!("{}.rs", name)),
+        };
+        bins.push(Bin {
+            name: name.to_owned(),
+            path,
+        });
+    }
+    Ok((bins, problems))
+}
+
+/// Returns the problems of the source of a fuzz target.
+pub fn check_source(source: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    if !source.contains("#![no_main]") {
+        problems.push(
+            "missing `#![no_main]`, so libFuzzer's `main` clashes with the one of the fuzz \
+             target"
+                .to_owned(),
+        );
+    }
+    if !source.contains("fuzz_target!") {
+        problems.push("no `fuzz_target!` invocation, so there is nothing to fuzz".to_owned());
+    }
+    problems
+}
+
+/// Returns the input type of a typed fuzz target, like `MyInput` in
+/// `fuzz_target!(|input: MyInput| ...)`, or `None` for byte slices.
+pub fn input_type(source: &str) -> Option<String> {
+    let invocation = &source[source.find("fuzz_target!")?..];
+    let closure = &invocation[invocation.find('|')? + 1..];
+    let parameter = &closure[..closure.find('|')?];
+    let ty = parameter.split_once(':')?.1.trim();
+    let ty: String = ty.split_whitespace().collect::<Vec<_>>().join(" ");
+    (ty.replace(' ', "") != "&[u8]").then_some(ty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_manifests() {
+        let manifest = r#"[package]
+name = "a-fuzz"
+
+[[bin]]
+name = "good"
+path = "fuzz_targets/good.rs"
+test = false
+doc = false
+bench = false
+
+[[bin]]
+name = "default_path"
+test = false
+"#;
+        let (bins, problems) = check_manifest(manifest).unwrap();
+        assert_eq!(bins.len(), 2);
+        assert_eq!(bins[0].path, PathBuf::from("fuzz_targets/good.rs"));
+        assert_eq!(bins[1].path, PathBuf::from("src/bin/default_path.rs"));
+        assert_eq!(
+            problems,
+            [
+                "fuzz target `default_path` is missing `doc = false`, `bench = false`, so \
+              `cargo test` and friends try to build it"
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_sources() {
+        let source = "#![no_main]\nuse libfuzzer_sys::fuzz_target;\n\
+                      fuzz_target!(|data: &[u8]| {});\n";
+        assert!(check_source(source).is_empty());
+        assert_eq!(check_source("fn main() {}").len(), 2);
+    }
+
+    #[test]
+    fn finds_input_types() {
+        assert_eq!(input_type("fuzz_target!(|data: &[u8]| {});"), None);
+        assert_eq!(input_type("fuzz_target!(|data: & [u8]| {});"), None);
+        assert_eq!(
+            input_type("fuzz_target!(|input: (u8, Vec<String>)| {});").as_deref(),
+            Some("(u8, Vec<String>)")
+        );
+        assert_eq!(input_type("fuzz_target!(|data| {});"), None);
+        assert_eq!(input_type("fn main() {}"), None);
+    }
+}
@@ -0,0 +1,457 @@
+//! Analysis of the coverage data exported by `llvm-cov export` as JSON
+
+use crate::exit_code::Failure;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Write};
+
+/// The region kind that `llvm-cov` uses for plain code, as opposed to
+/// expansions, skipped code, gaps and branches.
+const CODE_REGION: u64 = 0;
+
+/// The coverage of a single function.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FunctionCoverage {
+    pub regions: usize,
+    pub covered_regions: usize,
+    /// The number of source lines the function spans
+    pub lines: u64,
+    /// Where the function is defined, as `file:line`
+    pub location: Option<String>,
+}
+
+impl FunctionCoverage {
+    fn is_covered(&self) -> bool {
+        self.covered_regions > 0
+    }
+}
+
+/// Coverage per function, keyed by the demangled function name.
+pub type Functions = BTreeMap<String, FunctionCoverage>;
+
+#[derive(Deserialize)]
+struct Export {
+    data: Vec<ExportData>,
+}
+
+#[derive(Deserialize)]
+struct ExportData {
+    functions: Vec<ExportFunction>,
+}
+
+#[derive(Deserialize)]
+struct ExportFunction {
+    name: String,
+    filenames: Vec<String>,
+    /// `[line_start, column_start, line_end, column_end, execution_count,
+    /// file_id, expanded_file_id, kind]`
+    regions: Vec<Vec<u64>>,
+}
+
+/// Parses the output of `llvm-cov export --format=text`.
+pub fn parse(json: &[u8]) -> Result<Functions> {
+    let export: Export =
+        serde_json::from_slice(json).context("failed to parse the output of llvm-cov export")?;
+    let mut functions = Functions::new();
+    for function in export.data.iter().flat_map(|data| &data.functions) {
+        let mut coverage = FunctionCoverage::default();
+        let mut first_line = u64::MAX;
+        let mut last_line = 0;
+        for region in &function.regions {
+            if region.get(7).copied().unwrap_or(CODE_REGION) != CODE_REGION {
+                continue;
+            }
+            coverage.regions += 1;
+            if region.get(4).copied().unwrap_or(0) > 0 {
+                coverage.covered_regions += 1;
+            }
+            // Only regions of the function's own file (rather than of macro
+            // expansions) tell its extent.
+            if region.get(5).copied().unwrap_or(0) == 0 {
+                first_line = first_line.min(region[0]);
+                last_line = last_line.max(region.get(2).copied().unwrap_or(region[0]));
+            }
+        }
+        if last_line >= first_line {
+            coverage.lines = last_line - first_line + 1;
+            coverage.location = function
+                .filenames
+                .first()
+                .map(|file| format!("{}:{}", file, first_line));
+        }
+
+        // Names that demangle to the same path, such as instances of the same
+        // generic function, are merged.
+        let name = format!("{:#}", rustc_demangle::demangle(&function.name));
+        let merged = functions.entry(name).or_default();
+        merged.regions = merged.regions.max(coverage.regions);
+        merged.covered_regions = merged.covered_regions.max(coverage.covered_regions);
+        merged.lines = merged.lines.max(coverage.lines);
+        if merged.location.is_none() {
+            merged.location = coverage.location;
+        }
+    }
+    Ok(functions)
+}
+
+/// Renders the functions and regions that are covered in only one of `old`
+/// and `new`.
+pub fn render_diff(old: &Functions, new: &Functions) -> String {
+    let count = |functions: &Functions| {
+        let covered = functions.values().filter(|f| f.is_covered()).count();
+        let regions = functions.values().map(|f| f.covered_regions).sum::<usize>();
+        (covered, regions)
+    };
+    let (old_functions, old_regions) = count(old);
+    let (new_functions, new_regions) = count(new);
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "Functions covered: {} -> {} ({:+})",
+        old_functions,
+        new_functions,
+        new_functions as i64 - old_functions as i64
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "Regions covered: {} -> {} ({:+})",
+        old_regions,
+        new_regions,
+        new_regions as i64 - old_regions as i64
+    )
+    .unwrap();
+
+    let mut gained = Vec::new();
+    let mut lost = Vec::new();
+    let mut changed = Vec::new();
+    for name in old
+        .keys()
+        .chain(new.keys().filter(|name| !old.contains_key(*name)))
+    {
+        let before = old.get(name).cloned().unwrap_or_default();
+        let after = new.get(name).cloned().unwrap_or_default();
+        match (before.is_covered(), after.is_covered()) {
+            (false, true) => gained.push(name),
+            (true, false) => lost.push(name),
+            (true, true) if before.covered_regions != after.covered_regions => {
+                changed.push((name, before, after))
+            }
+            _ => {}
+        }
+    }
+
+    for (title, names) in [
+        ("Newly covered functions", gained),
+        ("Functions no longer covered", lost),
+    ] {
+        if !names.is_empty() {
+            writeln!(out, "\n{}:", title).unwrap();
+            for name in names {
+                writeln!(out, "  {}", name).unwrap();
+            }
+        }
+    }
+    if !changed.is_empty() {
+        writeln!(out, "\nFunctions with changed region coverage:").unwrap();
+        for (name, before, after) in changed {
+            writeln!(
+                out,
+                "  {}: {}/{} -> {}/{} regions",
+                name, before.covered_regions, before.regions, after.covered_regions, after.regions
+            )
+            .unwrap();
+        }
+    }
+    out
+}
+
+/// Renders the functions that were never executed, largest first, as those
+/// are the ones that most need new seeds or fuzz targets.
+pub fn render_uncovered(functions: &Functions) -> String {
+    let mut uncovered: Vec<_> = functions
+        .iter()
+        .filter(|(_, function)| !function.is_covered())
+        .collect();
+    uncovered.sort_by(|(a_name, a), (b_name, b)| b.lines.cmp(&a.lines).then(a_name.cmp(b_name)));
+
+    let mut out = String::new();
+    if uncovered.is_empty() {
+        writeln!(out, "All {} functions were reached", functions.len()).unwrap();
+        return out;
+    }
+    writeln!(
+        out,
+        "{} of {} functions were never reached, largest first:\n",
+        uncovered.len(),
+        functions.len()
+    )
+    .unwrap();
+    for (name, function) in uncovered {
+        write!(out, "{:>6} lines  {}", function.lines, name).unwrap();
+        if let Some(location) = &function.location {
+            write!(out, "  ({})", location).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    out
+}
+
+#[derive(Deserialize)]
+struct Summary {
+    data: Vec<SummaryData>,
+}
+
+#[derive(Deserialize)]
+struct SummaryData {
+    files: Vec<SummaryFile>,
+    totals: Totals,
+}
+
+#[derive(Deserialize)]
+struct SummaryFile {
+    filename: String,
+    summary: Totals,
+}
+
+#[derive(Deserialize)]
+struct Totals {
+    lines: Total,
+    regions: Total,
+}
+
+#[derive(Deserialize)]
+struct Total {
+    percent: f64,
+}
+
+/// The total coverage in the output of `llvm-cov export --summary-only`, in
+/// percent.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CoverageTotals {
+    pub lines: f64,
+    pub regions: f64,
+}
+
+impl Totals {
+    fn percentages(&self) -> CoverageTotals {
+        CoverageTotals {
+            lines: self.lines.percent,
+            regions: self.regions.percent,
+        }
+    }
+}
+
+/// The output of `llvm-cov export --summary-only`: the total coverage, and
+/// the coverage of each source file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverageSummary {
+    pub totals: CoverageTotals,
+    pub files: BTreeMap<String, CoverageTotals>,
+}
+
+pub fn summary(summary_json: &[u8]) -> Result<CoverageSummary> {
+    let summary: Summary = serde_json::from_slice(summary_json)
+        .context("failed to parse the output of llvm-cov export")?;
+    let data = summary
+        .data
+        .first()
+        .context("llvm-cov exported no coverage data")?;
+    Ok(CoverageSummary {
+        totals: data.totals.percentages(),
+        files: data
+            .files
+            .iter()
+            .map(|file| (file.filename.clone(), file.summary.percentages()))
+            .collect(),
+    })
+}
+
+pub fn totals(summary_json: &[u8]) -> Result<CoverageTotals> {
+    Ok(summary(summary_json)?.totals)
+}
+
+/// Checks the total coverage against the threshold of `--fail-under`.
+pub fn check_fail_under(totals: CoverageTotals, threshold: f64) -> Result<()> {
+    for (kind, percent) in [("line", totals.lines), ("region", totals.regions)] {
+        if percent < threshold {
+            return Err(Failure::CoverageBelowThreshold.wrap(anyhow!(
+                "{} coverage of {:.1}% is below the `--fail-under` threshold of {}%",
+                kind,
+                percent,
+                threshold
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Renders a [shields.io endpoint](https://shields.io/badges/endpoint-badge)
+/// badge with the line coverage.
+pub fn badge(totals: CoverageTotals) -> String {
+    let percent = totals.lines;
+    let color = match percent {
+        p if p >= 90.0 => "brightgreen",
+        p if p >= 75.0 => "green",
+        p if p >= 60.0 => "yellowgreen",
+        p if p >= 40.0 => "yellow",
+        p if p >= 20.0 => "orange",
+        _ => "red",
+    };
+    let badge = serde_json::json!({
+        "schemaVersion": 1,
+        "label": "fuzz coverage",
+        "message": format!("{:.1}%", percent),
+        "color": color,
+    });
+    badge.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(functions: &[(&str, &[[u64; 8]])]) -> Vec<u8> {
+        let functions: Vec<_> = functions
+            .iter()
+            .map(|(name, regions)| {
+                serde_json::json!({ "name": name, "filenames": ["src/lib.rs"], "regions": regions })
+            })
+            .collect();
+        serde_json::to_vec(&serde_json::json!({ "data": [{ "functions": functions }] })).unwrap()
+    }
+
+    #[test]
+    fn parses_export() {
+        let json = export(&[
+            (
+                "_ZN8my_crate5parse17h0123456789abcdefE",
+                &[
+                    [1, 1, 2, 2, 5, 0, 0, 0],
+                    [3, 1, 4, 2, 0, 0, 0, 0],
+                    // A gap region, which doesn't count.
+                    [5, 1, 6, 2, 0, 0, 0, 3],
+                ],
+            ),
+            ("my_crate::unused", &[[1, 1, 2, 2, 0, 0, 0, 0]]),
+        ]);
+        let functions = parse(&json).unwrap();
+        assert_eq!(
+            functions["my_crate::parse"],
+            FunctionCoverage {
+                regions: 2,
+                covered_regions: 1,
+                lines: 4,
+                location: Some("src/lib.rs:1".to_owned()),
+            }
+        );
+        assert!(!functions["my_crate::unused"].is_covered());
+    }
+
+    #[test]
+    fn renders_diff() {
+        let old = parse(&export(&[
+            ("a", &[[1, 1, 2, 2, 1, 0, 0, 0], [3, 1, 4, 2, 0, 0, 0, 0]]),
+            ("b", &[[1, 1, 2, 2, 1, 0, 0, 0]]),
+            ("c", &[[1, 1, 2, 2, 0, 0, 0, 0]]),
+        ]))
+        .unwrap();
+        let new = parse(&export(&[
+            ("a", &[[1, 1, 2, 2, 1, 0, 0, 0], [3, 1, 4, 2, 1, 0, 0, 0]]),
+            ("b", &[[1, 1, 2, 2, 0, 0, 0, 0]]),
+            ("c", &[[1, 1, 2, 2, 3, 0, 0, 0]]),
+            ("d", &[[1, 1, 2, 2, 1, 0, 0, 0]]),
+        ]))
+        .unwrap();
+        assert_eq!(
+            render_diff(&old, &new),
+            "Functions covered: 2 -> 3 (+1)\n\
+             Regions covered: 2 -> 4 (+2)\n\
+             \n\
+             Newly covered functions:\n  \
+             c\n  \
+             d\n\
+             \n\
+             Functions no longer covered:\n  \
+             b\n\
+             \n\
+             Functions with changed region coverage:\n  \
+             a: 1/2 -> 2/2 regions\n"
+        );
+    }
+
+    #[test]
+    fn renders_uncovered() {
+        let functions = parse(&export(&[
+            ("covered", &[[1, 1, 2, 2, 1, 0, 0, 0]]),
+            ("small", &[[10, 1, 11, 2, 0, 0, 0, 0]]),
+            (
+                "large",
+                &[[20, 1, 30, 2, 0, 0, 0, 0], [40, 1, 40, 9, 0, 1, 0, 0]],
+            ),
+        ]))
+        .unwrap();
+        assert_eq!(
+            render_uncovered(&functions),
+            "2 of 3 functions were never reached, largest first:\n\
+             \n    \
+             11 lines  large  (src/lib.rs:20)\n     \
+             2 lines  small  (src/lib.rs:10)\n"
+        );
+    }
+
+    const SUMMARY: &[u8] = br#"{"data": [{"files": [{"filename": "src/lib.rs", "summary": {
+        "lines": {"count": 3, "covered": 2, "percent": 66.666},
+        "regions": {"count": 4, "covered": 1, "notcovered": 3, "percent": 25}
+    }}], "totals": {
+        "lines": {"count": 3, "covered": 2, "percent": 66.666},
+        "regions": {"count": 4, "covered": 1, "notcovered": 3, "percent": 25}
+    }}]}"#;
+
+    #[test]
+    fn parses_summary() {
+        let summary = summary(SUMMARY).unwrap();
+        assert_eq!(
+            summary.totals,
+            CoverageTotals {
+                lines: 66.666,
+                regions: 25.0
+            }
+        );
+        assert_eq!(summary.files["src/lib.rs"], summary.totals);
+    }
+
+    #[test]
+    fn parses_totals() {
+        assert_eq!(
+            totals(SUMMARY).unwrap(),
+            CoverageTotals {
+                lines: 66.666,
+                regions: 25.0
+            }
+        );
+    }
+
+    #[test]
+    fn renders_badge() {
+        assert_eq!(
+            badge(totals(SUMMARY).unwrap()),
+            r#"{"color":"yellowgreen","label":"fuzz coverage","message":"66.7%","schemaVersion":1}"#
+        );
+    }
+
+    #[test]
+    fn fail_under() {
+        let totals = totals(SUMMARY).unwrap();
+        assert!(check_fail_under(totals, 25.0).is_ok());
+        assert_eq!(
+            check_fail_under(totals, 50.0).unwrap_err().to_string(),
+            "region coverage of 25.0% is below the `--fail-under` threshold of 50%"
+        );
+        assert_eq!(
+            check_fail_under(totals, 70.0).unwrap_err().to_string(),
+            "line coverage of 66.7% is below the `--fail-under` threshold of 70%"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Log files of the workers of `cargo fuzz run --worker-logs`, rotated by
+//! size so that long sessions don't fill the disk
+
+use anyhow::{Context, Result};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// How many rotated files are kept next to a log, as `<log>.1` for the most
+/// recent one up to `<log>.4`.
+pub const ROTATIONS: usize = 4;
+
+/// A log file that moves to `<log>.1` once it grows past its maximum size,
+/// shifting the older rotations along.
+pub struct RotatingLog {
+    path: PathBuf,
+    max_size: u64,
+    size: u64,
+    file: fs::File,
+}
+
+impl RotatingLog {
+    /// Creates the log at `path`, replacing the one of a previous session
+    /// along with its rotations.
+    pub fn create(path: &Path, max_size: u64) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create directory {}", dir.display()))?;
+        }
+        for i in 1..=ROTATIONS {
+            let rotated = rotation(path, i);
+            if rotated.exists() {
+                fs::remove_file(&rotated)
+                    .with_context(|| format!("failed to remove {}", rotated.display()))?;
+            }
+        }
+        let file = fs::File::create(path)
+            .with_context(|| format!("failed to create {}", path.display()))?;
+        Ok(RotatingLog {
+            path: path.to_owned(),
+            max_size,
+            size: 0,
+            file,
+        })
+    }
+
+    /// Appends a line, with its newline, rotating the log first if the line
+    /// doesn't fit. A line longer than the maximum size gets a log of its own.
+    pub fn write_line(&mut self, line: &str) -> Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        self.file
+            .write_all(line.as_bytes())
+            .with_context(|| format!("failed to write {}", self.path.display()))?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        for i in (1..ROTATIONS).rev() {
+            let older = rotation(&self.path, i);
+            if older.exists() {
+                fs::rename(&older, rotation(&self.path, i + 1))
+                    .with_context(|| format!("failed to rotate {}", older.display()))?;
+            }
+        }
+        fs::rename(&self.path, rotation(&self.path, 1))
+            .with_context(|| format!("failed to rotate {}", self.path.display()))?;
+        self.file = fs::File::create(&self.path)
+            .with_context(|| format!("failed to create {}", self.path.display()))?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// The path of the `i`th rotation of the log at `path`.
+fn rotation(path: &Path, i: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{}", i));
+    PathBuf::from(rotated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("worker-0.log");
+
+        let mut log = RotatingLog::create(&path, 10).unwrap();
+        for i in 0..=ROTATIONS + 1 {
+            log.write_line(&format!("line {}\n", i)).unwrap();
+        }
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("line {}\n", ROTATIONS + 1)
+        );
+        assert_eq!(
+            fs::read_to_string(rotation(&path, 1)).unwrap(),
+            format!("line {}\n", ROTATIONS)
+        );
+        assert_eq!(
+            fs::read_to_string(rotation(&path, ROTATIONS)).unwrap(),
+            "line 1\n"
+        );
+        assert!(!rotation(&path, ROTATIONS + 1).exists());
+
+        // A new session starts over.
+        let mut log = RotatingLog::create(&path, 10).unwrap();
+        log.write_line("new\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+        assert!(!rotation(&path, 1).exists());
+    }
+}
@@ -0,0 +1,103 @@
+//! Man pages of cargo-fuzz and its subcommands, rendered from the same
+//! definitions as `--help`
+
+use crate::options;
+use anyhow::{bail, Context, Result};
+use std::{fs, io::Write};
+
+/// Returns the man pages of `cmd` and of each of its subcommands, with their
+/// file names, like `cargo-fuzz.1` and `cargo-fuzz-artifacts-list.1`.
+pub fn pages(cmd: &clap::Command) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut pages = Vec::new();
+    add_pages(cmd, "cargo-fuzz", "cargo fuzz", &mut pages)?;
+    Ok(pages)
+}
+
+fn add_pages(
+    cmd: &clap::Command,
+    name: &str,
+    bin_name: &str,
+    pages: &mut Vec<(String, Vec<u8>)>,
+) -> Result<()> {
+    let mut cmd = cmd.clone().display_name(name).bin_name(bin_name);
+    // What `--help` shows before the options, like the explanation of
+    // `cargo fuzz run`, is the description of a man page.
+    if cmd.get_long_about().is_none() {
+        if let Some(before_help) = cmd.get_before_help().cloned() {
+            cmd = cmd.long_about(before_help);
+        }
+    }
+    let mut page = Vec::new();
+    clap_mangen::Man::new(cmd.clone())
+        .source(format!("cargo-fuzz {}", env!("CARGO_PKG_VERSION")))
+        .render(&mut page)
+        .with_context(|| format!("failed to render the man page of `{}`", bin_name))?;
+    pages.push((format!("{}.1", name), page));
+
+    for sub in cmd.get_subcommands() {
+        add_pages(
+            sub,
+            &format!("{}-{}", name, sub.get_name()),
+            &format!("{} {}", bin_name, sub.get_name()),
+            pages,
+        )?;
+    }
+    Ok(())
+}
+
+pub fn exec_man(man: &options::Man) -> Result<()> {
+    use clap::CommandFactory;
+
+    let pages = pages(&crate::Command::command())?;
+    if let Some(dir) = &man.output_dir {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create directory {}", dir.display()))?;
+        for (name, page) in &pages {
+            let path = dir.join(name);
+            fs::write(&path, page)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        eprintln!("Wrote {} man pages to {}", pages.len(), dir.display());
+        return Ok(());
+    }
+
+    let name = format!(
+        "cargo-fuzz{}.1",
+        man.command
+            .iter()
+            .map(|c| format!("-{}", c))
+            .collect::<String>()
+    );
+    let Some((_, page)) = pages.iter().find(|(page_name, _)| *page_name == name) else {
+        bail!("there is no `cargo fuzz {}` command", man.command.join(" "));
+    };
+    std::io::stdout()
+        .write_all(page)
+        .context("failed to write the man page")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn renders_pages() {
+        let pages = pages(&crate::Command::command()).unwrap();
+        let page = |name: &str| {
+            let (_, page) = pages.iter().find(|(n, _)| n == name).unwrap();
+            String::from_utf8(page.clone()).unwrap()
+        };
+
+        assert!(page("cargo-fuzz.1").contains("cargo\\-fuzz\\-run(1)"));
+        let run = page("cargo-fuzz-run.1");
+        assert!(run.contains(".TH cargo-fuzz-run 1"));
+        assert!(run.contains("cargo fuzz run"));
+        // The explanation before the options, and the libFuzzer options after.
+        assert!(run.contains("fuzz/fuzz_targets/"));
+        assert!(run.contains("max_total_time"));
+        assert!(pages
+            .iter()
+            .any(|(name, _)| name == "cargo-fuzz-artifacts-list.1"));
+    }
+}
@@ -0,0 +1,187 @@
+//! Prometheus metrics of `cargo fuzz run --metrics-addr`, served over HTTP
+//! from the status lines of libFuzzer and the artifacts it wrote
+
+use crate::stats::Status;
+use anyhow::{Context, Result};
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+/// The artifact categories that always have a metric, at 0 until libFuzzer
+/// writes one.
+const CATEGORIES: &[&str] = &["crash", "leak", "oom", "timeout", "slow-unit"];
+
+/// Listens on `addr` for Prometheus to scrape.
+pub fn listen(addr: SocketAddr) -> Result<TcpListener> {
+    let listener = TcpListener::bind(addr)
+        .with_context(|| format!("failed to listen for metrics on {}", addr))?;
+    let addr = listener.local_addr().unwrap_or(addr);
+    eprintln!("Serving metrics at http://{}/metrics", addr);
+    Ok(listener)
+}
+
+/// Answers the requests of `listener` with the text of `metrics` until
+/// `stopped` disconnects.
+pub fn serve(listener: TcpListener, stopped: Receiver<()>, metrics: impl Fn() -> String) {
+    // Accepting without blocking lets the loop notice `stopped`.
+    if let Err(e) = listener.set_nonblocking(true) {
+        eprintln!("warning: failed to serve metrics: {}", e);
+        return;
+    }
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(e) = respond(stream, &metrics) {
+                    eprintln!("warning: failed to answer a metrics request: {}", e);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                let poll = Duration::from_millis(100);
+                if stopped.recv_timeout(poll) != Err(RecvTimeoutError::Timeout) {
+                    return;
+                }
+            }
+            Err(e) => eprintln!("warning: failed to accept a metrics request: {}", e),
+        }
+    }
+}
+
+fn respond(mut stream: TcpStream, metrics: impl Fn() -> String) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    while !request.windows(4).any(|end| end == b"\r\n\r\n") && request.len() < 8192 {
+        match stream.read(&mut buf)? {
+            0 => break,
+            n => request.extend_from_slice(&buf[..n]),
+        }
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next().unwrap_or("").split_whitespace();
+    let (status, body) = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", metrics()),
+        _ => ("404 Not Found", "Metrics are at /metrics\n".to_owned()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n\
+         Content-Type: text/plain; version=0.0.4\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\
+         \r\n\
+         {}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+/// Renders the metrics of fuzzing `target` in the Prometheus text format,
+/// from its `status` and the number of `artifacts` of each category.
+pub fn render(target: &str, status: &Status, artifacts: &BTreeMap<&str, usize>) -> String {
+    let target = target.replace('\\', r"\\").replace('"', "\\\"");
+    let mut text = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, values: &[(String, u64)]| {
+        text.push_str(&format!("# HELP cargo_fuzz_{} {}\n", name, help));
+        text.push_str(&format!("# TYPE cargo_fuzz_{} {}\n", name, kind));
+        for (labels, value) in values {
+            text.push_str(&format!(
+                "cargo_fuzz_{}{{target=\"{}\"{}}} {}\n",
+                name, target, labels, value
+            ));
+        }
+    };
+    let gauge = |value: Option<u64>| value.map(|value| (String::new(), value)).into_iter();
+
+    metric(
+        "execs_total",
+        "counter",
+        "Inputs that the fuzz target ran.",
+        &[(String::new(), status.execs)],
+    );
+    metric(
+        "execs_per_second",
+        "gauge",
+        "Inputs that the fuzz target runs per second.",
+        &gauge(status.execs_per_sec).collect::<Vec<_>>(),
+    );
+    metric(
+        "coverage",
+        "gauge",
+        "Code blocks or edges that the corpus covers.",
+        &gauge(status.cov).collect::<Vec<_>>(),
+    );
+    metric(
+        "features",
+        "gauge",
+        "Features, like edges and their counters, that the corpus covers.",
+        &gauge(status.features).collect::<Vec<_>>(),
+    );
+    metric(
+        "corpus_inputs",
+        "gauge",
+        "Inputs in the corpus.",
+        &gauge(status.corpus_inputs).collect::<Vec<_>>(),
+    );
+    metric(
+        "corpus_bytes",
+        "gauge",
+        "Size of the corpus.",
+        &gauge(status.corpus_bytes).collect::<Vec<_>>(),
+    );
+    metric(
+        "rss_bytes",
+        "gauge",
+        "Resident memory of the fuzz target.",
+        &gauge(status.rss_bytes).collect::<Vec<_>>(),
+    );
+
+    let mut counts: BTreeMap<&str, usize> = CATEGORIES.iter().map(|&c| (c, 0)).collect();
+    counts.extend(artifacts);
+    let counts: Vec<_> = counts
+        .into_iter()
+        .map(|(category, count)| (format!(",category=\"{}\"", category), count as u64))
+        .collect();
+    metric(
+        "artifacts_total",
+        "counter",
+        "Artifacts that the fuzz target wrote, by category.",
+        &counts,
+    );
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_metrics() {
+        let status =
+            Status::parse("#1024\tpulse  cov: 12 ft: 13 corp: 5/40b lim: 4 exec/s: 512 rss: 30Mb")
+                .unwrap();
+        let metrics = render("my_target", &status, &BTreeMap::from([("crash", 2)]));
+        assert!(metrics.starts_with(
+            "# HELP cargo_fuzz_execs_total Inputs that the fuzz target ran.\n\
+             # TYPE cargo_fuzz_execs_total counter\n\
+             cargo_fuzz_execs_total{target=\"my_target\"} 1024\n"
+        ));
+        assert!(metrics.contains("cargo_fuzz_execs_per_second{target=\"my_target\"} 512\n"));
+        assert!(metrics.contains("cargo_fuzz_corpus_bytes{target=\"my_target\"} 40\n"));
+        assert!(metrics.contains("cargo_fuzz_rss_bytes{target=\"my_target\"} 31457280\n"));
+        assert!(metrics
+            .contains("cargo_fuzz_artifacts_total{target=\"my_target\",category=\"crash\"} 2\n"));
+        assert!(metrics
+            .contains("cargo_fuzz_artifacts_total{target=\"my_target\",category=\"oom\"} 0\n"));
+
+        // What libFuzzer hasn't reported yet has no sample.
+        let metrics = render("my_target", &Status::default(), &BTreeMap::new());
+        assert!(metrics.contains("# TYPE cargo_fuzz_coverage gauge\n# HELP"));
+        assert!(!metrics.contains("cargo_fuzz_coverage{"));
+    }
+}
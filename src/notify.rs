@@ -0,0 +1,21 @@
+//! Desktop notifications for `cargo fuzz run --notify`, for sessions that run
+//! in a terminal nobody is looking at
+
+/// Shows a desktop notification. Failing to is only worth a warning, since
+/// fuzzing goes on either way.
+#[cfg(feature = "notifications")]
+pub fn send(summary: &str, body: &str) {
+    let result = notify_rust::Notification::new()
+        .appname("cargo-fuzz")
+        .summary(summary)
+        .body(body)
+        .show();
+    if let Err(e) = result {
+        eprintln!("warning: failed to show a desktop notification: {}", e);
+    }
+}
+
+/// Without the `notifications` feature, `--notify` is rejected before there
+/// is anything to notify of.
+#[cfg(not(feature = "notifications"))]
+pub fn send(_summary: &str, _body: &str) {}
@@ -1,17 +1,83 @@
 mod add;
+mod artifacts;
+mod bench;
+mod bench_corpus;
 mod build;
+mod bundle;
 mod check;
+mod ci;
 mod cmin;
+mod compare;
+mod corpus;
+mod cov;
 mod coverage;
+mod debug;
+mod dict;
+mod doctor;
 mod fmt;
+mod history;
 mod init;
+mod init_clusterfuzzlite;
+mod init_github_actions;
+mod init_oss_fuzz;
+mod lint;
 mod list;
+mod man;
+mod mutate;
+mod profile;
+mod promote;
+mod report;
+mod repro;
 mod run;
+mod slow;
+mod smoke;
+mod status;
+mod sync;
 mod tmin;
+mod triage;
+mod upgrade;
+mod verify;
 
 pub use self::{
-    add::Add, build::Build, check::Check, cmin::Cmin, coverage::Coverage, fmt::Fmt, init::Init,
-    list::List, run::Run, tmin::Tmin,
+    add::Add,
+    artifacts::{Artifacts, ArtifactsArchive, ArtifactsList, ArtifactsPrune},
+    bench::Bench,
+    bench_corpus::BenchCorpus,
+    build::Build,
+    bundle::Bundle,
+    check::Check,
+    ci::Ci,
+    cmin::Cmin,
+    compare::Compare,
+    corpus::{Corpus, CrossPollinate, Dedup, ImportAfl},
+    cov::{Cov, CovDiff, CovHistory, LlvmCov},
+    coverage::{Coverage, CoverageFormat},
+    debug::{Debug, Debugger},
+    dict::{Dict, DictGenerate},
+    doctor::Doctor,
+    fmt::{Fmt, FmtOutput},
+    history::History,
+    init::Init,
+    init_clusterfuzzlite::InitClusterfuzzlite,
+    init_github_actions::InitGithubActions,
+    init_oss_fuzz::InitOssFuzz,
+    lint::Lint,
+    list::List,
+    man::Man,
+    mutate::Mutate,
+    profile::{Profile, Profiler},
+    promote::Promote,
+    report::{Report, ReportFormat},
+    repro::Repro,
+    run::Run,
+    slow::Slow,
+    smoke::Smoke,
+    status::Status,
+    sync::Sync,
+    tmin::{Tmin, TminKind},
+    triage::Triage,
+    upgrade::Upgrade,
+    verify::Verify,
 };
 
 use clap::{Parser, ValueEnum};
@@ -78,8 +144,14 @@ pub struct BuildOptions {
     /// Build artifacts with given Cargo feature enabled
     pub features: Option<String>,
 
-    #[arg(short, long, value_enum, default_value = "address")]
-    /// Use a specific sanitizer
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t = crate::utils::default_sanitizer()
+    )]
+    /// Use a specific sanitizer, the address sanitizer by default, or none on
+    /// OpenBSD
     pub sanitizer: Sanitizer,
 
     #[arg(long = "build-std")]
@@ -116,6 +188,17 @@ pub struct BuildOptions {
     /// the coverage option will be disabled).
     pub coverage: bool,
 
+    #[arg(skip = false)]
+    /// Build with the `fuzzing_repro` configuration, so that harnesses can gate
+    /// code that should only run when reproducing a crash. Set automatically by
+    /// `cargo fuzz repro` and, like `coverage`, not exposed as a flag.
+    pub repro: bool,
+
+    #[arg(skip = false)]
+    /// Build with frame pointers, so that profilers can walk the stack. Set
+    /// automatically by `cargo fuzz profile`.
+    pub frame_pointers: bool,
+
     /// Dead code is linked by default to prevent a potential error with some
     /// optimized targets. This flag allows you to opt out of it.
     #[arg(long)]
@@ -179,6 +262,41 @@ pub struct BuildOptions {
     /// the DLL from having an extern `main` reference added to it. (DLLs/shared
     /// libraries should not have any reference to `main`.)
     pub no_include_main_msvc: bool,
+
+    #[arg(long)]
+    /// Build with `-Zsanitizer-recover` and run with `halt_on_error=0`, so
+    /// that the fuzzer keeps going after a sanitizer error instead of stopping
+    /// at the first. `cargo fuzz run` reports the distinct errors at the end.
+    /// Works with the address and memory sanitizers, and requires nightly
+    pub sanitizer_recover: bool,
+
+    #[arg(long)]
+    /// Don't report memory leaks, for fuzz targets that leak on purpose, like
+    /// those with arena allocators. Runs with `detect_leaks=0` in
+    /// `ASAN_OPTIONS` and libFuzzer's `-detect_leaks=0`
+    pub no_leak_detection: bool,
+
+    #[arg(long, value_name = "PATH")]
+    /// Link the fuzz targets against this libFuzzer static library, like the
+    /// `libclang_rt.fuzzer` of a specific LLVM release, instead of the one
+    /// bundled with libfuzzer-sys
+    ///
+    /// This sets `CUSTOM_LIBFUZZER_PATH` for libfuzzer-sys, which is also
+    /// honored when set in the environment. The C++ standard library to link
+    /// with it defaults to the one of the target, and can be chosen with
+    /// `CUSTOM_LIBFUZZER_STD_CXX`.
+    pub libfuzzer: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Print the `RUSTFLAGS`, the environment and the command line that cargo
+    /// is run with, to debug build caching or to replicate a build
+    pub print_rustflags: bool,
+
+    #[arg(long = "with", visible_alias = "profile-name", value_name = "PROFILE")]
+    /// Use the options of a named profile of the project configuration, like
+    /// `[profiles.ci]` in `fuzz/fuzz.toml`. Options on the command line
+    /// override the profile's
+    pub profile: Option<String>,
 }
 
 impl stdfmt::Display for BuildOptions {
@@ -212,8 +330,8 @@ impl stdfmt::Display for BuildOptions {
         }
 
         match self.sanitizer {
+            sanitizer if sanitizer == crate::utils::default_sanitizer() => {}
             Sanitizer::None => write!(f, " --sanitizer=none")?,
-            Sanitizer::Address => {}
             _ => write!(f, " --sanitizer={}", self.sanitizer)?,
         }
 
@@ -233,6 +351,18 @@ impl stdfmt::Display for BuildOptions {
             write!(f, " --coverage")?;
         }
 
+        if self.sanitizer_recover {
+            write!(f, " --sanitizer-recover")?;
+        }
+
+        if self.no_leak_detection {
+            write!(f, " --no-leak-detection")?;
+        }
+
+        if let Some(libfuzzer) = &self.libfuzzer {
+            write!(f, " --libfuzzer={}", libfuzzer.display())?;
+        }
+
         Ok(())
     }
 }
@@ -242,6 +372,12 @@ pub struct FuzzDirWrapper {
     /// The path to the fuzz project directory.
     #[arg(long)]
     pub fuzz_dir: Option<PathBuf>,
+
+    /// Use the fuzz project of this package of the workspace, instead of the
+    /// one in the current directory. Fuzz targets can also be given as
+    /// `<PACKAGE>::<TARGET>`.
+    #[arg(short = 'p', long, value_name = "PACKAGE")]
+    pub package: Option<String>,
 }
 
 impl stdfmt::Display for FuzzDirWrapper {
@@ -254,10 +390,77 @@ impl stdfmt::Display for FuzzDirWrapper {
     }
 }
 
+/// The limits on single runs of a fuzz target, beyond which libFuzzer reports
+/// an input as a timeout or an OOM.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Parser)]
+pub struct Limits {
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    /// Report inputs that run for longer than this as timeouts, libFuzzer's
+    /// `-timeout=`
+    pub timeout: Option<u64>,
+
+    #[arg(long, value_name = "MB")]
+    /// Report inputs that make the fuzz target use more memory than this as
+    /// OOMs, libFuzzer's `-rss_limit_mb=`. 0 disables the limit
+    pub rss_limit: Option<u64>,
+}
+
+impl Limits {
+    /// Returns the libFuzzer arguments `args` with the limits after them, so
+    /// that they take precedence over the ones in `args`, like the `timeout`
+    /// of `fuzz.toml`.
+    pub fn apply(&self, args: &[String]) -> Vec<String> {
+        let mut args = args.to_vec();
+        if let Some(timeout) = self.timeout {
+            args.push(format!("-timeout={}", timeout));
+        }
+        if let Some(rss_limit) = self.rss_limit {
+            args.push(format!("-rss_limit_mb={}", rss_limit));
+        }
+        args
+    }
+}
+
+impl stdfmt::Display for Limits {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        if let Some(timeout) = self.timeout {
+            write!(f, " --timeout={}", timeout)?;
+        }
+
+        if let Some(rss_limit) = self.rss_limit {
+            write!(f, " --rss-limit={}", rss_limit)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn limits() {
+        let limits = Limits {
+            timeout: Some(5),
+            rss_limit: Some(4096),
+        };
+        assert_eq!(limits.to_string(), " --timeout=5 --rss-limit=4096");
+        assert_eq!(
+            limits.apply(&["-timeout=10".to_owned(), "-runs=10".to_owned()]),
+            [
+                "-timeout=10",
+                "-runs=10",
+                "-timeout=5",
+                "-rss_limit_mb=4096"
+            ]
+        );
+        assert_eq!(
+            Limits::default().apply(&["-timeout=10".to_owned()]),
+            ["-timeout=10"]
+        );
+    }
+
     #[test]
     fn display_build_options() {
         let default_opts = BuildOptions {
@@ -275,11 +478,18 @@ mod test {
             unstable_flags: Vec::new(),
             target_dir: None,
             coverage: false,
+            repro: false,
+            frame_pointers: false,
             strip_dead_code: false,
             no_cfg_fuzzing: false,
             no_trace_compares: false,
             disable_branch_folding: None,
             no_include_main_msvc: false,
+            sanitizer_recover: false,
+            no_leak_detection: false,
+            libfuzzer: None,
+            print_rustflags: false,
+            profile: None,
         };
 
         let opts = vec![
@@ -330,6 +540,18 @@ mod test {
             },
             BuildOptions {
                 coverage: false,
+                ..default_opts.clone()
+            },
+            BuildOptions {
+                sanitizer_recover: true,
+                ..default_opts.clone()
+            },
+            BuildOptions {
+                no_leak_detection: true,
+                ..default_opts.clone()
+            },
+            BuildOptions {
+                libfuzzer: Some(PathBuf::from("/usr/lib/libFuzzer.a")),
                 ..default_opts
             },
         ];
@@ -0,0 +1,112 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Artifacts {
+    #[command(subcommand)]
+    pub command: ArtifactsCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum ArtifactsCommand {
+    /// List the artifacts of a fuzz target with their size, category and age
+    List(ArtifactsList),
+
+    /// Delete old artifacts, or artifacts that no longer crash
+    Prune(ArtifactsPrune),
+
+    /// Pack artifacts and their metadata into a `.tar.gz` archive
+    Archive(ArtifactsArchive),
+}
+
+impl RunCommand for Artifacts {
+    fn run_command(&mut self) -> Result<()> {
+        match &mut self.command {
+            ArtifactsCommand::List(x) => x.run_command(),
+            ArtifactsCommand::Prune(x) => x.run_command(),
+            ArtifactsCommand::Archive(x) => x.run_command(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct ArtifactsList {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+}
+
+impl RunCommand for ArtifactsList {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_artifacts_list(self)
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+#[command(group(clap::ArgGroup::new("criteria").required(true).multiple(true)))]
+pub struct ArtifactsPrune {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[arg(long, value_name = "DAYS", group = "criteria")]
+    /// Delete artifacts that were last modified more than this many days ago
+    pub older_than: Option<u64>,
+
+    #[arg(long, group = "criteria")]
+    /// Rebuild the fuzz target and delete artifacts that no longer crash it
+    pub fixed: bool,
+
+    #[arg(long)]
+    /// Only print which artifacts would be deleted
+    pub dry_run: bool,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for ArtifactsPrune {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_artifacts_prune(self)
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct ArtifactsArchive {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    /// Names of the artifacts to archive, all of them if none are given
+    pub artifacts: Vec<String>,
+
+    #[arg(short, long)]
+    /// Path of the archive to write, `<target>-artifacts-<timestamp>.tar.gz`
+    /// by default
+    pub output: Option<PathBuf>,
+}
+
+impl RunCommand for ArtifactsArchive {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_artifacts_archive(self)
+    }
+}
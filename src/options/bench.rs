@@ -0,0 +1,53 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Bench {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    /// The corpus directory to benchmark with, instead of the target's default
+    /// corpus. It is left as it is: fuzzing starts from a copy of it
+    pub corpus: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value = "10",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    /// How long to replay the corpus for, and then how long to fuzz for
+    pub time: u64,
+
+    #[arg(long, default_value = "1")]
+    /// The libFuzzer seed to fuzz with, the same one for every benchmark so
+    /// that they mutate the same way
+    pub seed: u32,
+
+    #[arg(long)]
+    /// Print the results as JSON
+    pub json: bool,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Bench {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_bench(self)
+    }
+}
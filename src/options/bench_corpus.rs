@@ -0,0 +1,44 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct BenchCorpus {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    /// The corpus directory to replay, instead of the target's default corpus
+    pub corpus: Option<PathBuf>,
+
+    #[arg(
+        short = 'n',
+        long,
+        value_name = "INPUTS",
+        default_value = "10",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    /// How many of the slowest inputs to report
+    pub slowest: u32,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for BenchCorpus {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_bench_corpus(self)
+    }
+}
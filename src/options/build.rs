@@ -16,11 +16,19 @@ pub struct Build {
 
     /// Name of the fuzz target to build, or build all targets if not supplied
     pub target: Option<String>,
+
+    #[arg(long, conflicts_with = "target")]
+    /// Build only the dependencies of the fuzz targets, with the flags they
+    /// are fuzzed with, to cache them apart from the fuzz targets
+    pub deps_only: bool,
 }
 
 impl RunCommand for Build {
     fn run_command(&mut self) -> Result<()> {
         let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        if self.deps_only {
+            return project.exec_build_deps(&self.build);
+        }
         project.exec_build(BuildMode::Build, &self.build, self.target.as_deref())
     }
 }
@@ -0,0 +1,39 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Bundle {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    /// Path to the crash artifact to bundle
+    pub artifact: PathBuf,
+
+    #[arg(short, long)]
+    /// Path of the archive to write, `<TARGET>-<ARTIFACT>.tar.gz` by default
+    pub output: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Add a Dockerfile that checks out the fuzzed repository at the current
+    /// commit, builds the fuzz target and replays the artifact
+    pub dockerfile: bool,
+}
+
+impl RunCommand for Bundle {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_bundle(self)
+    }
+}
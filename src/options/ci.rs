@@ -0,0 +1,50 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Ci {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Names of the fuzz targets to run, all of them by default
+    pub targets: Vec<String>,
+
+    #[arg(long, value_name = "SECONDS", default_value = "600")]
+    /// Total time for the whole command, including building; what is left
+    /// after replaying the corpora is split evenly between the fuzz targets
+    pub time_budget: u64,
+
+    #[arg(long, value_name = "REV")]
+    /// Only run the fuzz targets whose source changed since the git revision
+    /// `REV`, or all of them if anything else changed
+    pub changed_since: Option<String>,
+
+    #[arg(long, value_name = "PATH")]
+    /// Write the results as a JUnit XML report, with a test case for replaying
+    /// the corpus and for fuzzing each target
+    pub junit: Option<PathBuf>,
+
+    #[arg(long, value_name = "PATH")]
+    /// Write the crashes as a SARIF report, for code scanning tools
+    pub sarif: Option<PathBuf>,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary when fuzzing
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Ci {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_ci(self)
+    }
+}
@@ -22,6 +22,15 @@ pub struct Cmin {
     /// The corpus directory to minify into
     pub corpus: Option<PathBuf>,
 
+    #[arg(long, conflicts_with = "no_backup")]
+    /// Where to move the original corpus once it has been minified. Defaults to
+    /// `<corpus>.bak-<timestamp>` next to the corpus directory
+    pub backup_dir: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Delete the original corpus after minifying instead of keeping a backup
+    pub no_backup: bool,
+
     #[arg(last(true))]
     /// Additional libFuzzer arguments passed through to the binary
     pub args: Vec<String>,
@@ -0,0 +1,38 @@
+use crate::{options::FuzzDirWrapper, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Compare {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// The run to compare against: the id of a recorded run, as listed by
+    /// `cargo fuzz history`, a stats file of `stats/<TARGET>/`, or a coverage
+    /// summary written by `cargo fuzz coverage --summary-json`
+    pub base: String,
+
+    /// The run to compare with the base one, of the same kind
+    pub new: String,
+
+    #[arg(
+        long,
+        value_name = "PCT",
+        default_value = "5",
+        value_parser = clap::value_parser!(u32).range(0..=100)
+    )]
+    /// How many percent a number has to get worse by to count as a
+    /// regression. Any new failure is one
+    pub threshold: u32,
+
+    #[arg(long)]
+    /// Exit with an error when there's a regression
+    pub fail_on_regression: bool,
+}
+
+impl RunCommand for Compare {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_compare(self)
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Corpus {
+    #[command(subcommand)]
+    pub command: CorpusCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum CorpusCommand {
+    /// Merge the corpus of one fuzz target into the corpus of another, keeping
+    /// only the inputs that add coverage to the destination target
+    CrossPollinate(CrossPollinate),
+
+    /// Remove byte-identical duplicate inputs from a corpus without building
+    /// the fuzz target
+    Dedup(Dedup),
+
+    /// Import the queue, crashes and hangs of an AFL/AFL++ output directory
+    ImportAfl(ImportAfl),
+}
+
+impl RunCommand for Corpus {
+    fn run_command(&mut self) -> Result<()> {
+        match &mut self.command {
+            CorpusCommand::CrossPollinate(x) => x.run_command(),
+            CorpusCommand::Dedup(x) => x.run_command(),
+            CorpusCommand::ImportAfl(x) => x.run_command(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct CrossPollinate {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target whose corpus is read
+    pub from: String,
+
+    /// Name of the fuzz target whose corpus is merged into
+    pub to: String,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for CrossPollinate {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_cross_pollinate(self)
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct Dedup {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[arg()]
+    /// The corpus directory to deduplicate, instead of the target's default corpus
+    pub corpus: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Rename the remaining inputs to the SHA-1 of their contents, like libFuzzer does
+    pub rename: bool,
+}
+
+impl RunCommand for Dedup {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_dedup(self)
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct ImportAfl {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// The AFL output directory, either of a single instance (containing
+    /// `queue/`) or of several parallel instances
+    pub afl_out_dir: PathBuf,
+
+    /// Name of the fuzz target to import into
+    pub target: String,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for ImportAfl {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_import_afl(self)
+    }
+}
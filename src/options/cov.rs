@@ -0,0 +1,147 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Cov {
+    #[command(subcommand)]
+    pub command: CovCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum CovCommand {
+    /// Run `llvm-cov show` on the coverage data of a fuzz target, to see the
+    /// covered lines of the source
+    Show(LlvmCov),
+
+    /// Run `llvm-cov report` on the coverage data of a fuzz target, to see a
+    /// summary per file
+    Report(LlvmCov),
+
+    /// Compare two coverage runs of a fuzz target, listing the functions
+    /// whose coverage was gained or lost
+    Diff(CovDiff),
+
+    /// Print how the coverage of a fuzz target changed over the runs of
+    /// `cargo fuzz coverage`, which records each of them in
+    /// `coverage/.history`
+    ///
+    /// It isn't `cargo fuzz coverage history`, nor kept in `coverage/history`,
+    /// because `cargo fuzz coverage <TARGET>` and its `coverage/<TARGET>`
+    /// directory would then clash with a fuzz target named `history`.
+    History(CovHistory),
+}
+
+impl RunCommand for Cov {
+    fn run_command(&mut self) -> Result<()> {
+        match &mut self.command {
+            CovCommand::Show(x) => x.run("show"),
+            CovCommand::Report(x) => x.run("report"),
+            CovCommand::Diff(x) => x.run_command(),
+            CovCommand::History(x) => x.run_command(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct LlvmCov {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Sets the path to the LLVM bin directory. By default, it will use the one installed with rustc
+    #[arg(long)]
+    pub llvm_path: Option<PathBuf>,
+
+    /// Include the code of dependencies, of the standard library and of the
+    /// fuzz targets in coverage reports
+    #[arg(long)]
+    pub include_deps: bool,
+
+    /// Name of the fuzz target, which `cargo fuzz coverage` has been run for
+    pub target: String,
+
+    #[arg(last(true))]
+    /// Additional arguments passed through to llvm-cov
+    pub args: Vec<String>,
+}
+
+impl LlvmCov {
+    fn run(&mut self, subcommand: &str) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        // The binary to read is the one built by `cargo fuzz coverage`.
+        self.build.coverage = true;
+        project.exec_llvm_cov(subcommand, self)
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct CovDiff {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Sets the path to the LLVM bin directory. By default, it will use the one installed with rustc
+    #[arg(long)]
+    pub llvm_path: Option<PathBuf>,
+
+    /// Include the code of dependencies, of the standard library and of the
+    /// fuzz targets in coverage reports
+    #[arg(long)]
+    pub include_deps: bool,
+
+    /// Name of the fuzz target, which `cargo fuzz coverage` has been run for
+    pub target: String,
+
+    /// Coverage data of the earlier run, e.g. a copy of an older
+    /// `coverage/<target>/coverage.profdata`
+    pub old: PathBuf,
+
+    /// Coverage data of the later run, `coverage/<target>/coverage.profdata`
+    /// by default
+    pub new: Option<PathBuf>,
+}
+
+impl RunCommand for CovDiff {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        self.build.coverage = true;
+        project.exec_cov_diff(self)
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct CovHistory {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    #[arg(required_unless_present = "all")]
+    pub target: Option<String>,
+
+    /// Print the history of the merged coverage of `cargo fuzz coverage
+    /// --all` instead
+    #[arg(long, conflicts_with = "target")]
+    pub all: bool,
+
+    /// Print the recorded coverage summaries, with the coverage of every
+    /// file, as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl RunCommand for CovHistory {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_cov_history(self)
+    }
+}
@@ -6,9 +6,30 @@ use crate::{
     RunCommand,
 };
 use anyhow::{bail, Result};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use std::fmt as stdfmt;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum CoverageFormat {
+    Html,
+    Lcov,
+}
+
+impl stdfmt::Display for CoverageFormat {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                CoverageFormat::Html => "HTML report",
+                CoverageFormat::Lcov => "LCOV tracefile",
+            }
+        )
+    }
+}
 
 #[derive(Clone, Debug, Parser)]
+#[command(group(clap::ArgGroup::new("export").args(["format", "html"])))]
 pub struct Coverage {
     #[command(flatten)]
     pub build: BuildOptions,
@@ -20,12 +41,83 @@ pub struct Coverage {
     #[arg(long)]
     pub llvm_path: Option<PathBuf>,
 
+    /// Include the code of dependencies, of the standard library and of the
+    /// fuzz targets in coverage reports
+    #[arg(long)]
+    pub include_deps: bool,
+
+    /// Also export the coverage data as a browsable HTML report or as an LCOV
+    /// tracefile
+    #[arg(long, value_enum)]
+    pub format: Option<CoverageFormat>,
+
+    /// Also render a browsable HTML report, like `--format=html`
+    #[arg(long)]
+    pub html: bool,
+
+    /// Where to export the coverage data, `html` or `coverage.lcov` in
+    /// `coverage/<target>` by default
+    #[arg(short, long, requires = "export")]
+    pub output: Option<PathBuf>,
+
+    /// Open the HTML report in a browser once it is generated, which implies
+    /// `--html`
+    #[arg(long)]
+    pub open: bool,
+
     /// Name of the fuzz target
-    pub target: String,
+    #[arg(required_unless_present = "all")]
+    pub target: Option<String>,
 
     /// Custom corpus directories or artifact files
     pub corpus: Vec<String>,
 
+    #[arg(
+        short,
+        long,
+        default_value = "1",
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    /// Number of processes to split each corpus across
+    pub jobs: u16,
+
+    /// Command to run the fuzz target through, such as an emulator, when it
+    /// is built for a target other than the host. Defaults to the
+    /// `CARGO_TARGET_<TRIPLE>_RUNNER` environment variable. The runner has to
+    /// write the raw coverage data to the path in `LLVM_PROFILE_FILE` on the
+    /// host.
+    #[arg(long)]
+    pub runner: Option<String>,
+
+    /// Reuse the coverage build of a previous `cargo fuzz coverage` run
+    /// instead of invoking cargo, which is faster when the build is known to
+    /// be up to date
+    #[arg(long)]
+    pub no_build: bool,
+
+    /// Write a JSON summary of the coverage, with line, region and function
+    /// coverage per file and in total, to this path
+    #[arg(long)]
+    pub summary_json: Option<PathBuf>,
+
+    /// Write the line coverage percentage as a shields.io endpoint badge to
+    /// this path
+    #[arg(long)]
+    pub badge_json: Option<PathBuf>,
+
+    /// List the functions that the corpus never reaches, largest first
+    #[arg(long)]
+    pub uncovered: bool,
+
+    /// Fail if the total line or region coverage is below this percentage
+    #[arg(long, value_name = "PCT", value_parser = parse_percent)]
+    pub fail_under: Option<f64>,
+
+    /// Run every fuzz target over its corpus, and merge their coverage into a
+    /// single report in `coverage/all-targets`
+    #[arg(long, conflicts_with_all = ["target", "corpus"])]
+    pub all: bool,
+
     #[arg(last(true))]
     /// Additional libFuzzer arguments passed through to the binary
     pub args: Vec<String>,
@@ -39,8 +131,25 @@ impl RunCommand for Coverage {
                 see https://github.com/rust-lang/wg-cargo-std-aware/issues/63"
             );
         }
+        if self.html {
+            self.format = Some(CoverageFormat::Html);
+        }
+        match (self.open, self.format) {
+            (true, None) => self.format = Some(CoverageFormat::Html),
+            (true, Some(CoverageFormat::Lcov)) => {
+                bail!("`--open` only works with `--format=html`")
+            }
+            _ => {}
+        }
         let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
         self.build.coverage = true;
         project.exec_coverage(self)
     }
 }
+
+fn parse_percent(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(percent),
+        _ => Err("expected a percentage between 0 and 100".to_owned()),
+    }
+}
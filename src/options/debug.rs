@@ -0,0 +1,74 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use std::{fmt as stdfmt, path::PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Debugger {
+    Gdb,
+    Lldb,
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        // gdb is not available on macOS without a lot of effort, while lldb
+        // comes with the Xcode command line tools.
+        if cfg!(target_os = "macos") {
+            Debugger::Lldb
+        } else {
+            Debugger::Gdb
+        }
+    }
+}
+
+impl stdfmt::Display for Debugger {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Debugger::Gdb => "gdb",
+                Debugger::Lldb => "lldb",
+            }
+        )
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct Debug {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    /// Path to the artifact to debug
+    pub artifact: PathBuf,
+
+    #[arg(long, value_enum, default_value_t)]
+    /// Debugger to run the fuzz target under
+    pub debugger: Debugger,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Debug {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        // Like `repro`, default to a dev build so that the debugger can show
+        // variables and source lines. `--release` still overrides this.
+        if !self.build.release {
+            self.build.dev = true;
+        }
+        project.exec_debug(self)
+    }
+}
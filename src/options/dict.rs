@@ -0,0 +1,55 @@
+use crate::{options::FuzzDirWrapper, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Dict {
+    #[command(subcommand)]
+    pub command: DictCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum DictCommand {
+    /// Generate a libFuzzer dictionary for a fuzz target from the literals in
+    /// the source of the fuzzed crate and the frequent words of its corpus
+    Generate(DictGenerate),
+}
+
+impl RunCommand for Dict {
+    fn run_command(&mut self) -> Result<()> {
+        match &mut self.command {
+            DictCommand::Generate(x) => x.run_command(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct DictGenerate {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[arg(short, long)]
+    /// Where to write the dictionary, instead of
+    /// `dictionaries/<TARGET>.dict` in the fuzz directory
+    pub output: Option<PathBuf>,
+
+    #[arg(long, default_value = "2")]
+    /// Least number of corpus inputs a word must be in to make it into the
+    /// dictionary
+    pub min_inputs: usize,
+
+    #[arg(long)]
+    /// Leave the words of the corpus out of the dictionary
+    pub no_corpus: bool,
+}
+
+impl RunCommand for DictGenerate {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_dict_generate(self)
+    }
+}
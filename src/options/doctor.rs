@@ -0,0 +1,15 @@
+use crate::{doctor, options::BuildOptions, RunCommand};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Doctor {
+    #[command(flatten)]
+    pub build: BuildOptions,
+}
+
+impl RunCommand for Doctor {
+    fn run_command(&mut self) -> Result<()> {
+        doctor::exec_doctor(self)
+    }
+}
@@ -4,9 +4,21 @@ use crate::{
     RunCommand,
 };
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use std::path::PathBuf;
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum FmtOutput {
+    /// Print the `std::fmt::Debug` output to stderr
+    Stderr,
+    /// Print the `std::fmt::Debug` output to stdout, without a header for a
+    /// single input
+    Stdout,
+    /// Print a JSON object per input to stdout, with its path, its length in
+    /// bytes, and its `std::fmt::Debug` output or the error formatting it
+    Json,
+}
+
 #[derive(Clone, Debug, Parser)]
 pub struct Fmt {
     #[command(flatten)]
@@ -18,8 +30,38 @@ pub struct Fmt {
     /// Name of fuzz target
     pub target: String,
 
-    /// Path to the input testcase to debug print
-    pub input: PathBuf,
+    /// Paths to the input testcases to debug print
+    #[arg(value_name = "INPUT", required_unless_present = "all_artifacts")]
+    pub inputs: Vec<PathBuf>,
+
+    #[arg(long, conflicts_with = "inputs")]
+    /// Debug print every artifact of the fuzz target
+    pub all_artifacts: bool,
+
+    #[arg(long)]
+    /// Print a `#[test]` function that replays the input, instead of its
+    /// `std::fmt::Debug` output
+    pub emit_test: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "stderr",
+        conflicts_with = "emit_test"
+    )]
+    /// Where and how to print the `std::fmt::Debug` output
+    pub output: FmtOutput,
+
+    #[arg(long, conflicts_with_all = ["all_artifacts", "emit_test"])]
+    /// Print a diff of the `std::fmt::Debug` output of two inputs, like an
+    /// artifact and its minimized form, instead of the output of each
+    pub diff: bool,
+
+    #[arg(long)]
+    /// Run the fuzz target as built by a previous `cargo fuzz build` or
+    /// `cargo fuzz run`, instead of invoking cargo, which is faster when the
+    /// build is known to be up to date
+    pub no_build: bool,
 }
 
 impl RunCommand for Fmt {
@@ -0,0 +1,64 @@
+use crate::{options::FuzzDirWrapper, project::FuzzProject, sessions, utils, RunCommand};
+use anyhow::Result;
+use clap::Parser;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Debug, Parser)]
+pub struct History {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[arg(long, value_name = "WHEN", value_parser = parse_time)]
+    /// Only show the runs that started at or after WHEN, a day like
+    /// `2024-05-01` (in UTC) or an age like `7d`, `12h` or `30m`
+    pub since: Option<u64>,
+
+    #[arg(long, value_name = "WHEN", value_parser = parse_time)]
+    /// Only show the runs that started before WHEN, like `--since`
+    pub until: Option<u64>,
+
+    #[arg(long, value_name = "REV")]
+    /// Only show the runs at the git revision REV or at commits that came
+    /// after it, to tell whether the fuzz target was fuzzed since a change
+    pub rev: Option<String>,
+
+    #[arg(long)]
+    /// Print a JSON array of the runs instead of a table
+    pub json: bool,
+}
+
+impl RunCommand for History {
+    fn run_command(&mut self) -> Result<()> {
+        if !cfg!(feature = "sessions") {
+            return Err(utils::missing_feature("sessions", "`cargo fuzz history`"));
+        }
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_history(self)
+    }
+}
+
+/// Parses a day or an age into seconds since the Unix epoch.
+fn parse_time(s: &str) -> Result<u64, String> {
+    if let Some(date) = sessions::parse_date(s) {
+        return Ok(date);
+    }
+    let unit = match s.chars().last() {
+        Some('d') => 86400,
+        Some('h') => 3600,
+        Some('m') => 60,
+        _ => 0,
+    };
+    match s[..s.len().saturating_sub(1)].parse::<u64>() {
+        Ok(age) if unit > 0 => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|e| e.to_string())?
+                .as_secs();
+            Ok(now.saturating_sub(age * unit))
+        }
+        _ => Err("expected a day like `2024-05-01` or an age like `7d`, `12h` or `30m`".to_owned()),
+    }
+}
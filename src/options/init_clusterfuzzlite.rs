@@ -0,0 +1,27 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Clone, Debug, Parser)]
+pub struct InitClusterfuzzlite {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[arg(long)]
+    /// Overwrite the files of an existing `.clusterfuzzlite/` directory
+    pub force: bool,
+}
+
+impl RunCommand for InitClusterfuzzlite {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_init_clusterfuzzlite(self)
+    }
+}
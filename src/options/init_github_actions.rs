@@ -0,0 +1,35 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Clone, Debug, Parser)]
+pub struct InitGithubActions {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[arg(long, value_name = "CRON", default_value = "0 3 * * *")]
+    /// When to fuzz, as a cron expression in UTC
+    pub schedule: String,
+
+    #[arg(long, value_name = "SECONDS", default_value = "1800")]
+    /// Time budget of `cargo fuzz ci` for each fuzz target
+    pub time_budget: u64,
+
+    #[arg(long)]
+    /// Overwrite an existing `.github/workflows/fuzz.yml`
+    pub force: bool,
+}
+
+impl RunCommand for InitGithubActions {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_init_github_actions(self)
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct InitOssFuzz {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[arg(long, value_name = "NAME")]
+    /// Name of the OSS-Fuzz project
+    pub project: String,
+
+    #[arg(long, value_name = "URL")]
+    /// Git repository that OSS-Fuzz clones, instead of the `repository` of the
+    /// package manifest
+    pub repository: Option<String>,
+
+    #[arg(long, value_name = "EMAIL")]
+    /// Primary contact of the project, instead of the email address of the
+    /// first author in the package manifest
+    pub contact: Option<String>,
+
+    #[arg(long, value_name = "DIR")]
+    /// Directory to write the files to, instead of `oss-fuzz/projects/<NAME>`
+    /// in the package, which mirrors their place in the OSS-Fuzz repository
+    pub out_dir: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Overwrite existing files
+    pub force: bool,
+}
+
+impl RunCommand for InitOssFuzz {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_init_oss_fuzz(self)
+    }
+}
@@ -0,0 +1,27 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Lint {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[arg(long)]
+    /// Don't check with `cargo check` that the input types of typed fuzz
+    /// targets implement `Arbitrary` and `Debug`
+    pub no_check: bool,
+}
+
+impl RunCommand for Lint {
+    fn run_command(&mut self) -> Result<()> {
+        FuzzProject::lint(self, self.fuzz_dir_wrapper.fuzz_dir.to_owned())
+    }
+}
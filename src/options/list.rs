@@ -1,4 +1,8 @@
-use crate::{options::FuzzDirWrapper, project::FuzzProject, RunCommand};
+use crate::{
+    options::FuzzDirWrapper,
+    project::{self, FuzzProject},
+    RunCommand,
+};
 use anyhow::Result;
 use clap::Parser;
 
@@ -6,11 +10,24 @@ use clap::Parser;
 pub struct List {
     #[command(flatten)]
     pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[arg(long)]
+    /// Print a JSON array with the source path, corpus size, number of
+    /// artifacts by category, and start of the last recorded run (in seconds
+    /// since the Unix epoch) of every fuzz target
+    pub json: bool,
 }
 
 impl RunCommand for List {
     fn run_command(&mut self) -> Result<()> {
+        if self.fuzz_dir_wrapper.fuzz_dir.is_none() {
+            // Several fuzz projects in the workspace are listed together.
+            let fuzz_dirs = project::workspace_fuzz_dirs()?;
+            if fuzz_dirs.len() > 1 {
+                return FuzzProject::list_workspace_targets(self, &fuzz_dirs);
+            }
+        }
         let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
-        project.list_targets()
+        project.list_targets(self)
     }
 }
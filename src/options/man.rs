@@ -0,0 +1,22 @@
+use crate::{man, RunCommand};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Man {
+    #[arg(short, long, value_name = "DIR")]
+    /// Write the man pages of cargo-fuzz and all of its subcommands to this
+    /// directory, instead of printing one
+    pub output_dir: Option<PathBuf>,
+
+    /// The subcommand whose man page to print, like `run` or `artifacts list`,
+    /// instead of the one of cargo-fuzz itself
+    pub command: Vec<String>,
+}
+
+impl RunCommand for Man {
+    fn run_command(&mut self) -> Result<()> {
+        man::exec_man(self)
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Mutate {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    /// The input to mutate
+    pub input: PathBuf,
+
+    #[arg(
+        short = 'n',
+        long,
+        value_name = "MUTANTS",
+        default_value = "20",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    /// How many mutations of the input to show
+    pub count: u32,
+
+    #[arg(long, value_name = "BYTES")]
+    /// The longest that mutations may make the input, by default twice its
+    /// length
+    pub max_len: Option<usize>,
+
+    #[arg(long)]
+    /// The libFuzzer seed to mutate with, to get the same mutations again
+    pub seed: Option<u32>,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary, like
+    /// `-only_ascii=1`
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Mutate {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_mutate(self)
+    }
+}
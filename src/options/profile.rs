@@ -0,0 +1,91 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    utils, RunCommand,
+};
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use std::{fmt as stdfmt, path::PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Profiler {
+    Perf,
+    Dtrace,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        // perf is Linux only, and macOS comes with DTrace.
+        if cfg!(target_os = "macos") {
+            Profiler::Dtrace
+        } else {
+            Profiler::Perf
+        }
+    }
+}
+
+impl stdfmt::Display for Profiler {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Profiler::Perf => "perf",
+                Profiler::Dtrace => "dtrace",
+            }
+        )
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+pub struct Profile {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    /// The corpus directory to profile with, instead of the target's default
+    /// corpus
+    pub corpus: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Profile fuzzing the corpus, rather than replaying its inputs without
+    /// mutating them
+    pub fuzz: bool,
+
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value = "10",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    /// About how long to profile for
+    pub time: u64,
+
+    #[arg(long, default_value_t = Profiler::default())]
+    /// The profiler to sample the fuzz target with. DTrace usually has to run
+    /// as root
+    pub profiler: Profiler,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Profile {
+    fn run_command(&mut self) -> Result<()> {
+        if !cfg!(feature = "flamegraphs") {
+            return Err(utils::missing_feature(
+                "flamegraphs",
+                "`cargo fuzz profile`",
+            ));
+        }
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        self.build.frame_pointers = true;
+        project.exec_profile(self)
+    }
+}
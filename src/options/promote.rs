@@ -0,0 +1,49 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Promote {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    /// Path to the artifact to promote
+    pub artifact: PathBuf,
+
+    #[arg(long)]
+    /// Minimize the artifact before adding it to the corpus. This requires the
+    /// artifact to still crash the fuzz target.
+    pub minimize: bool,
+
+    #[arg(
+        short = 'r',
+        long,
+        default_value = "255",
+        requires = "minimize",
+        value_parser = clap::value_parser!(u32).range(1..),
+    )]
+    /// Number of minimization attempts to perform
+    pub runs: u32,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Promote {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_promote(self)
+    }
+}
@@ -1,10 +1,11 @@
 use crate::{
-    options::{BuildOptions, FuzzDirWrapper},
+    options::{BuildOptions, FuzzDirWrapper, Limits, Sanitizer},
     project::FuzzProject,
-    RunCommand,
+    utils, RunCommand,
 };
 use anyhow::Result;
 use clap::Parser;
+use std::{net::SocketAddr, path::PathBuf};
 
 #[derive(Clone, Debug, Parser)]
 pub struct Run {
@@ -29,13 +30,200 @@ pub struct Run {
     /// Number of concurrent jobs to run
     pub jobs: u16,
 
+    #[arg(long, conflicts_with = "jobs")]
+    /// Run every input of the corpus and every artifact once, without fuzzing,
+    /// and report all the inputs that fail instead of stopping at the first
+    pub regression: bool,
+
+    #[arg(long, value_name = "PATH", requires = "regression")]
+    /// With `--regression`, write the results as a JUnit XML report, with every
+    /// input replayed as a test case that fails if it crashes
+    pub junit: Option<PathBuf>,
+
+    #[arg(
+        long = "feature-set",
+        value_name = "FEATURES",
+        conflicts_with_all = ["features", "all_features", "regression"]
+    )]
+    /// Build and fuzz the fuzz target with each of these comma-separated sets
+    /// of Cargo features in turn, like `--feature-set a --feature-set a,b`. An
+    /// empty set is the default features
+    pub feature_sets: Vec<String>,
+
+    #[arg(long, requires = "feature_sets", conflicts_with = "corpus")]
+    /// Fuzz each feature set with a corpus of its own, in
+    /// `feature-corpora/<TARGET>/<FEATURES>/` of the fuzz directory, instead of
+    /// sharing the corpus of the fuzz target
+    pub separate_corpora: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "SANITIZERS",
+        value_delimiter = ',',
+        conflicts_with_all = ["sanitizer", "jobs", "regression", "feature_sets"]
+    )]
+    /// Fuzz with a build for each of these sanitizers at once, like
+    /// `--ensemble address,none`, all on the same corpus. Faster builds without
+    /// a sanitizer explore, and the sanitized ones pick up what they find
+    pub ensemble: Vec<Sanitizer>,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["jobs", "regression", "feature_sets", "ensemble"]
+    )]
+    /// Replay a sample of the corpus several times instead of fuzzing, and
+    /// report the inputs that don't cover the same code every time, because
+    /// the fuzz target depends on time, randomness or global state
+    pub stability_check: bool,
+
+    #[arg(
+        long,
+        value_name = "RUNS",
+        default_value = "3",
+        requires = "stability_check",
+        value_parser = clap::value_parser!(u32).range(2..)
+    )]
+    /// How many times `--stability-check` replays each input
+    pub stability_runs: u32,
+
+    #[arg(
+        long,
+        value_name = "INPUTS",
+        default_value = "50",
+        requires = "stability_check",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    /// How many inputs of the corpus `--stability-check` replays, spread
+    /// evenly over it
+    pub stability_sample: u32,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["regression", "ensemble", "stability_check", "tui"]
+    )]
+    /// Run the `--jobs` workers separately instead of in libFuzzer's `-fork`
+    /// mode, each with its output in `logs/<TARGET>/worker-<N>.log` of the
+    /// fuzz directory, and only a summary of their progress on the console,
+    /// every `--progress` seconds or 10
+    pub worker_logs: bool,
+
+    #[arg(
+        long,
+        value_name = "MIB",
+        default_value = "64",
+        requires = "worker_logs",
+        value_parser = clap::value_parser!(u64).range(1..=u64::MAX >> 20)
+    )]
+    /// The size at which `--worker-logs` rotates a log to `<LOG>.1`. Older
+    /// rotations shift along up to `<LOG>.4`
+    pub max_log_size: u64,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        conflicts_with_all = ["regression", "ensemble", "stability_check"],
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    /// Print a summary of the progress every this many seconds: the elapsed
+    /// time, the executions and their rate, the coverage, the size of the
+    /// corpus and the artifacts found so far. It keeps coming in `-fork`
+    /// mode, where libFuzzer only reports when a job ends
+    pub progress: Option<u64>,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["regression", "ensemble", "stability_check", "progress"]
+    )]
+    /// Show a dashboard in the terminal instead of the output of libFuzzer,
+    /// with graphs of the throughput and coverage, the jobs of `-fork` mode
+    /// and a scrollable pane of the output. Keys pause the fuzzer, stop it, or
+    /// stop it and minimize the corpus
+    pub tui: bool,
+
+    #[arg(
+        long,
+        value_name = "ADDR",
+        conflicts_with_all = ["regression", "feature_sets", "ensemble", "stability_check"]
+    )]
+    /// Serve the progress of the fuzz target as Prometheus metrics at
+    /// `http://<ADDR>/metrics`, like `--metrics-addr 127.0.0.1:9100`: the
+    /// executions and their rate, the coverage, the corpus, the memory it uses
+    /// and the artifacts it found
+    pub metrics_addr: Option<SocketAddr>,
+
+    #[command(flatten)]
+    pub limits: Limits,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["regression", "ensemble", "stability_check", "worker_logs"]
+    )]
+    /// Keep fuzzing after a crash, saving the input as an artifact. Runs the
+    /// fuzz target in libFuzzer's `-fork` mode, like `--jobs`
+    pub ignore_crashes: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["regression", "ensemble", "stability_check", "worker_logs"]
+    )]
+    /// Keep fuzzing after an input exceeds `--rss-limit`, saving it as an
+    /// artifact. Runs the fuzz target in libFuzzer's `-fork` mode
+    pub ignore_ooms: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["regression", "ensemble", "stability_check", "worker_logs"]
+    )]
+    /// Keep fuzzing after an input exceeds `--timeout`, saving it as an
+    /// artifact. Runs the fuzz target in libFuzzer's `-fork` mode
+    pub ignore_timeouts: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "regression",
+            "feature_sets",
+            "ensemble",
+            "stability_check",
+            "worker_logs"
+        ]
+    )]
+    /// Show a desktop notification when the fuzz target stops, with the
+    /// number of artifacts it found, and with `--ignore-*`, whenever it finds
+    /// one
+    pub notify: bool,
+
     #[arg(last(true))]
     /// Additional libFuzzer arguments passed through to the binary
     pub args: Vec<String>,
 }
 
+impl Run {
+    /// Returns the artifact categories that `--ignore-*` keeps fuzzing after,
+    /// along with the libFuzzer flag for each.
+    pub fn ignored(&self) -> Vec<(&'static str, &'static str)> {
+        [
+            (self.ignore_crashes, "crash", "-ignore_crashes=1"),
+            (self.ignore_ooms, "oom", "-ignore_ooms=1"),
+            (self.ignore_timeouts, "timeout", "-ignore_timeouts=1"),
+        ]
+        .into_iter()
+        .filter(|&(ignored, _, _)| ignored)
+        .map(|(_, category, flag)| (category, flag))
+        .collect()
+    }
+}
+
 impl RunCommand for Run {
     fn run_command(&mut self) -> Result<()> {
+        if self.tui && !cfg!(feature = "dashboard") {
+            return Err(utils::missing_feature("dashboard", "`--tui`"));
+        }
+        if self.notify && !cfg!(feature = "notifications") {
+            return Err(utils::missing_feature("notifications", "`--notify`"));
+        }
+        self.args = self.limits.apply(&self.args);
         let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
         project.exec_fuzz(self)
     }
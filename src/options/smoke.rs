@@ -0,0 +1,38 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Smoke {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Names of the fuzz targets to check, all of them by default
+    pub targets: Vec<String>,
+
+    #[arg(long, default_value = "100")]
+    /// Number of inputs to run each fuzz target on, after its corpus
+    pub runs: u64,
+
+    #[arg(long, value_name = "SECONDS", default_value = "5")]
+    /// Longest time to run each fuzz target for
+    pub max_time: u64,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Smoke {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_smoke(self)
+    }
+}
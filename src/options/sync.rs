@@ -0,0 +1,24 @@
+use crate::{options::FuzzDirWrapper, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Sync {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[arg(long)]
+    /// Remove the `[[bin]]` sections whose source file is gone, instead of
+    /// only reporting them
+    pub remove: bool,
+
+    #[arg(long)]
+    /// Print the changes without making them
+    pub dry_run: bool,
+}
+
+impl RunCommand for Sync {
+    fn run_command(&mut self) -> Result<()> {
+        FuzzProject::sync(self, self.fuzz_dir_wrapper.fuzz_dir.to_owned())
+    }
+}
@@ -1,12 +1,26 @@
 use crate::{
-    options::{BuildOptions, FuzzDirWrapper},
+    options::{BuildOptions, FuzzDirWrapper, Limits},
     project::FuzzProject,
     RunCommand,
 };
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use std::path::PathBuf;
 
+/// The kind of failure that `cargo fuzz tmin` keeps while minimizing.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum TminKind {
+    /// Inputs that crash the fuzz target
+    #[default]
+    Crash,
+    /// Inputs that run for longer than `-timeout=`, stopping at ones that
+    /// crash instead
+    Timeout,
+    /// Inputs that use more memory than `-rss_limit_mb=`, stopping at ones
+    /// that time out instead
+    Oom,
+}
+
 #[derive(Clone, Debug, Parser)]
 pub struct Tmin {
     #[command(flatten)]
@@ -27,9 +41,56 @@ pub struct Tmin {
     /// Number of minimization attempts to perform
     pub runs: u32,
 
-    #[arg()]
+    #[arg(required_unless_present = "all_artifacts")]
     /// Path to the failing test case to be minimized
-    pub test_case: PathBuf,
+    pub test_case: Option<PathBuf>,
+
+    #[arg(long, conflicts_with = "test_case")]
+    /// Minimize every artifact of the fuzz target instead of a single test case
+    pub all_artifacts: bool,
+
+    #[arg(long, requires = "all_artifacts")]
+    /// Directory of artifacts to minimize with `--all-artifacts`, instead of
+    /// the target's artifacts directory
+    pub artifacts: Option<PathBuf>,
+
+    #[arg(short, long, value_name = "PATH")]
+    /// Write the minimized test case to this file, or into this directory as
+    /// `minimized-<test case name>`, instead of the target's artifacts
+    /// directory. A directory for the results of `--all-artifacts`
+    pub output: Option<PathBuf>,
+
+    #[arg(long, value_enum, default_value_t, conflicts_with = "structured")]
+    /// The kind of failure to minimize. Timeouts and OOMs are named
+    /// `timeout-minimized-*` and `oom-minimized-*`, and `--all-artifacts`
+    /// only picks the artifacts of that kind
+    pub kind: TminKind,
+
+    #[arg(long)]
+    /// Shrink typed inputs so they still decode: drop and zero bytes, keeping
+    /// only the inputs that crash the same way and whose `std::fmt::Debug`
+    /// output is no longer, instead of using libFuzzer's byte-level
+    /// minimization. Each of the `--runs` attempts replays one input
+    pub structured: bool,
+
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        conflicts_with_all = ["all_artifacts", "structured", "kind"]
+    )]
+    /// Minimize with an external minimizer instead: `halfempty`, or a command
+    /// with `{test}` for the script telling whether an input still crashes the
+    /// same way, `{input}` for a copy of the test case to shrink in place and
+    /// `{output}` for a file to write the result to instead
+    pub external: Option<String>,
+
+    #[arg(long, value_name = "HASH", hide = true, requires = "test_case")]
+    /// Only replay the test case, and fail unless it crashes with this stack
+    /// hash. The scripts of `--external` run this
+    pub check_stack_hash: Option<String>,
+
+    #[command(flatten)]
+    pub limits: Limits,
 
     #[arg(last(true))]
     /// Additional libFuzzer arguments passed through to the binary
@@ -38,6 +99,7 @@ pub struct Tmin {
 
 impl RunCommand for Tmin {
     fn run_command(&mut self) -> Result<()> {
+        self.args = self.limits.apply(&self.args);
         let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
         project.exec_tmin(self)
     }
@@ -0,0 +1,19 @@
+use crate::{options::FuzzDirWrapper, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use clap::Parser;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Upgrade {
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    #[arg(long)]
+    /// Print the changes without making them
+    pub dry_run: bool,
+}
+
+impl RunCommand for Upgrade {
+    fn run_command(&mut self) -> Result<()> {
+        FuzzProject::upgrade(self, self.fuzz_dir_wrapper.fuzz_dir.to_owned())
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{
+    options::{BuildOptions, FuzzDirWrapper},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Verify {
+    #[command(flatten)]
+    pub build: BuildOptions,
+
+    #[command(flatten)]
+    pub fuzz_dir_wrapper: FuzzDirWrapper,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[arg(long)]
+    /// Directory of artifacts to verify, instead of the target's artifacts directory
+    pub artifacts: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Move artifacts that no longer crash into the target's corpus, so they
+    /// are kept as regression inputs
+    pub move_fixed: bool,
+
+    #[arg(long)]
+    /// Also replay every input of the target's corpus, which fails the run if
+    /// any of them crashes
+    pub corpus: bool,
+
+    #[arg(long, value_name = "PATH")]
+    /// Write the results as a JUnit XML report, with every artifact, and every
+    /// input of the corpus with `--corpus`, as a test case that fails if it
+    /// crashes
+    pub junit: Option<PathBuf>,
+
+    #[arg(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Verify {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::new(self.fuzz_dir_wrapper.fuzz_dir.to_owned())?;
+        project.exec_verify(self)
+    }
+}
@@ -0,0 +1,88 @@
+//! Styling of the banners, hints and summaries that cargo-fuzz prints among
+//! the output of libFuzzer, so that they stand out in a terminal
+
+use clap::ValueEnum;
+use std::{
+    env,
+    io::{self, IsTerminal},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum ColorChoice {
+    /// Color the output when it goes to a terminal and `NO_COLOR` isn't set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Decides whether the output is colored, which it isn't until this is
+/// called.
+pub fn init(choice: ColorChoice) {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    COLOR.store(
+        use_color(choice, no_color, io::stderr().is_terminal()),
+        Ordering::Relaxed,
+    );
+}
+
+fn use_color(choice: ColorChoice, no_color: bool, terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Auto => terminal && !no_color,
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    }
+}
+
+/// Wraps `text` in the SGR escape sequence `code` when the output is
+/// colored.
+fn paint(code: &str, text: &str) -> String {
+    if COLOR.load(Ordering::Relaxed) {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_owned()
+    }
+}
+
+/// The line that sets off what cargo-fuzz prints from the output of
+/// libFuzzer.
+pub fn separator() -> String {
+    paint("1;34", &"─".repeat(80))
+}
+
+/// The heading of something that went wrong, like `Failing input:`.
+pub fn error(text: &str) -> String {
+    paint("1;31", text)
+}
+
+/// The heading of a hint or a result, like `Reproduce with:`.
+pub fn heading(text: &str) -> String {
+    paint("1", text)
+}
+
+/// A command line to copy, like the one that reproduces a crash.
+pub fn command(text: &str) -> String {
+    paint("36", text)
+}
+
+/// A summary of something that worked out.
+pub fn success(text: &str) -> String {
+    paint("32", text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decides_on_color() {
+        assert!(use_color(ColorChoice::Auto, false, true));
+        assert!(!use_color(ColorChoice::Auto, false, false));
+        assert!(!use_color(ColorChoice::Auto, true, true));
+        assert!(use_color(ColorChoice::Always, true, false));
+        assert!(!use_color(ColorChoice::Never, false, true));
+    }
+}
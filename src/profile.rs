@@ -0,0 +1,79 @@
+//! Drawing flamegraphs from the stacks sampled by perf or DTrace for
+//! `cargo fuzz profile`
+
+use crate::options::Profiler;
+use anyhow::Result;
+#[cfg(feature = "flamegraphs")]
+use anyhow::{bail, Context};
+#[cfg(feature = "flamegraphs")]
+use inferno::{
+    collapse::{dtrace, perf, Collapse},
+    flamegraph,
+};
+
+/// How many times a second the profilers sample the stack. It's a little off
+/// a round number so that the samples don't line up with periodic work.
+pub const FREQUENCY: u32 = 997;
+
+/// The DTrace script that samples the user stacks of the process started with
+/// `dtrace -c`.
+pub const DTRACE_SCRIPT: &str = "profile-997 /pid == $target/ { @[ustack(100)] = count(); }";
+
+/// Returns the SVG of the flamegraph of `stacks`, which is what `perf script`
+/// printed, or what the DTrace script aggregated.
+#[cfg(feature = "flamegraphs")]
+pub fn flamegraph(profiler: Profiler, stacks: &[u8], title: &str) -> Result<Vec<u8>> {
+    let mut collapsed = Vec::new();
+    match profiler {
+        Profiler::Perf => perf::Folder::default().collapse(stacks, &mut collapsed),
+        Profiler::Dtrace => dtrace::Folder::default().collapse(stacks, &mut collapsed),
+    }
+    .with_context(|| format!("failed to read the stacks sampled by {}", profiler))?;
+    if collapsed.is_empty() {
+        bail!("{} didn't sample any stacks", profiler);
+    }
+
+    let mut options = flamegraph::Options::default();
+    options.title = title.to_owned();
+    let mut svg = Vec::new();
+    flamegraph::from_reader(&mut options, collapsed.as_slice(), &mut svg)
+        .context("failed to draw the flamegraph")?;
+    Ok(svg)
+}
+
+#[cfg(not(feature = "flamegraphs"))]
+pub fn flamegraph(_profiler: Profiler, _stacks: &[u8], _title: &str) -> Result<Vec<u8>> {
+    Err(crate::utils::missing_feature(
+        "flamegraphs",
+        "`cargo fuzz profile`",
+    ))
+}
+
+#[cfg(all(test, feature = "flamegraphs"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flamegraphs() {
+        let stacks = "\
+fuzz_target 1234 1.000001: 1001001 cycles:
+\t    55d5a0 my_crate::parse+0x10 (/fuzz/target/fuzz_target)
+\t    55d5b0 rust_fuzzer_test_input+0x20 (/fuzz/target/fuzz_target)
+\t    55d5c0 main+0x30 (/fuzz/target/fuzz_target)
+
+fuzz_target 1234 1.001001: 1001001 cycles:
+\t    55d5d0 my_crate::validate+0x10 (/fuzz/target/fuzz_target)
+\t    55d5b0 rust_fuzzer_test_input+0x20 (/fuzz/target/fuzz_target)
+\t    55d5c0 main+0x30 (/fuzz/target/fuzz_target)
+
+";
+        let svg = flamegraph(Profiler::Perf, stacks.as_bytes(), "cargo fuzz profile").unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains("<svg"));
+        assert!(svg.contains("cargo fuzz profile"));
+        assert!(svg.contains("my_crate::parse"));
+        assert!(svg.contains("my_crate::validate"));
+
+        assert!(flamegraph(Profiler::Perf, b"", "cargo fuzz profile").is_err());
+    }
+}
@@ -1,24 +1,106 @@
-use crate::options::{self, BuildMode, BuildOptions, Sanitizer};
+use crate::compare::{self, Snapshot};
+use crate::config::{Config, Defaults, Hooks};
+use crate::coverage_history::{self, CoverageRecord};
+use crate::dict;
+use crate::diff;
+use crate::exit_code::{Fail, Failure};
+use crate::external;
+use crate::junit;
+use crate::llvm_cov;
+use crate::logs;
+use crate::metrics;
+use crate::notify;
+use crate::options::{
+    self, BuildMode, BuildOptions, CoverageFormat, Debugger, FmtOutput, Profiler, Sanitizer,
+    TminKind,
+};
+use crate::output;
+use crate::profile;
+use crate::regression_test::{self, FuzzTargetClosure};
+use crate::report::{self, CrashBucket, TargetReport};
 use crate::rustc_version::RustVersion;
-use crate::utils::default_target;
+use crate::sarif;
+use crate::sessions::{self, Sessions};
+use crate::shrink;
+use crate::stacktrace::{self, CrashReport, RecoveredErrors};
+use crate::stats;
+use crate::tui;
+use crate::utils::{self, default_target};
 use anyhow::{anyhow, bail, Context, Result};
-use cargo_metadata::MetadataCommand;
-use std::collections::HashSet;
-use std::io::Read;
-use std::io::Write;
+use cargo_metadata::{DependencyKind, Metadata, MetadataCommand, Package};
+use std::collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::io::{self, BufRead, Write};
 use std::path::{Path, PathBuf};
 use std::{
-    env, ffi, fs,
-    process::{Command, Stdio},
-    time,
+    env, ffi, fmt as stdfmt, fs,
+    process::{Command, ExitStatus, Stdio},
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Mutex, OnceLock,
+    },
+    thread, time,
 };
 
 const DEFAULT_FUZZ_DIR: &str = "fuzz";
 
+/// File name prefix of the inputs written by `cargo fuzz tmin --all-artifacts`.
+const MINIMIZED_PREFIX: &str = "minimized-";
+
+/// The target triple that OSS-Fuzz and ClusterFuzzLite build fuzz targets for.
+const OSS_FUZZ_TRIPLE: &str = "x86_64-unknown-linux-gnu";
+
+/// Suffix of the metadata file written next to every new artifact.
+const METADATA_SUFFIX: &str = ".meta.json";
+
+/// How many of the last lines a fuzz target printed to stderr are kept for
+/// artifact metadata.
+const CAPTURED_STDERR_LINES: usize = 1000;
+
+/// Directory under `coverage/` that holds the coverage of all fuzz targets
+/// merged together, next to the directories of single targets.
+const ALL_TARGETS_COVERAGE_DIR: &str = "all-targets";
+
+/// How much of a crashing input to hexdump when it can't be debug formatted,
+/// so that large inputs don't bury the rest of the crash report.
+const MAX_CRASH_HEXDUMP_LEN: usize = 1024;
+
+/// What came out of fuzzing a fuzz target with `FuzzProject::fuzz`.
+#[derive(Debug)]
+pub struct FuzzOutcome {
+    /// How the fuzz target exited, which is a failure when it found a crash.
+    pub status: ExitStatus,
+    /// The crashes, timeouts and other inputs that it saved as artifacts.
+    pub artifacts: Vec<PathBuf>,
+    /// The last status line that libFuzzer printed.
+    pub stats: Option<stats::Status>,
+    /// Everything that the fuzz target printed to stderr.
+    pub stderr: String,
+}
+
+/// What came out of measuring the coverage of a fuzz target with
+/// `FuzzProject::coverage`.
+#[derive(Debug)]
+pub struct CoverageOutcome {
+    /// The merged profile, to hand to `llvm-cov`.
+    pub profdata: PathBuf,
+    /// The total coverage of the fuzz target.
+    pub totals: llvm_cov::CoverageTotals,
+}
+
 pub struct FuzzProject {
     /// The project with fuzz targets
     fuzz_dir: PathBuf,
     targets: Vec<String>,
+    /// The source file of every fuzz target
+    sources: HashMap<String, PathBuf>,
+    /// The Cargo binary names of the fuzz targets in subdirectories of
+    /// `fuzz_targets/`, which are named after their path
+    bin_names: HashMap<String, String>,
+    /// The target directory that `cargo metadata` reports, once asked for
+    target_directory: OnceLock<PathBuf>,
+    /// The fuzz targets built in this session, by the `Debug` output of their
+    /// build options, so that they aren't built again and are run directly
+    built: Mutex<HashSet<(String, Option<String>)>>,
 }
 
 impl FuzzProject {
@@ -31,17 +113,47 @@ impl FuzzProject {
     /// path.
     pub fn new(fuzz_dir_opt: Option<PathBuf>) -> Result<Self> {
         let mut project = Self::manage_initial_instance(fuzz_dir_opt)?;
-        let manifest = project.manifest()?;
-        if !is_fuzz_manifest(&manifest) {
+        let manifest_path = project.manifest_path();
+        if !manifest_path.is_file() {
             bail!(
+                "could not read the manifest file: {}",
+                manifest_path.display()
+            );
+        }
+        let metadata = match cargo_metadata(&manifest_path) {
+            Ok(metadata) => metadata,
+            Err(_) if is_empty_fuzz_manifest(&manifest_path) => return Ok(project),
+            Err(e) => return Err(e),
+        };
+        let package = match find_manifest_package(&metadata, &manifest_path) {
+            Some(package) if is_fuzz_package(package) => package,
+            _ => bail!(
                 "manifest `{}` does not look like a cargo-fuzz manifest. \
                  Add following lines to override:\n\
                  [package.metadata]\n\
                  cargo-fuzz = true",
-                project.manifest_path().display()
-            );
+                manifest_path.display()
+            ),
+        };
+        let targets_dir = fs::canonicalize(project.fuzz_dir.join(crate::FUZZ_TARGETS_DIR)).ok();
+        for target in package
+            .targets
+            .iter()
+            .filter(|t| t.kind.iter().any(|kind| kind == "bin"))
+        {
+            let source = target.src_path.clone().into_std_path_buf();
+            let name = match nested_target_name(targets_dir.as_deref(), &source) {
+                Some(name) if utils::bin_name(&name) == target.name => {
+                    project.bin_names.insert(name.clone(), target.name.clone());
+                    name
+                }
+                _ => target.name.clone(),
+            };
+            project.targets.push(name.clone());
+            project.sources.insert(name, source);
         }
-        project.targets = collect_targets(&manifest);
+        // Always sort them, so that we have deterministic output.
+        project.targets.sort();
         Ok(project)
     }
 
@@ -50,6 +162,7 @@ impl FuzzProject {
     /// This will not clone libfuzzer-sys.
     /// Similar to `FuzzProject::new`, the fuzz directory will depend on `fuzz_dir_opt`.
     pub fn init(init: &options::Init, fuzz_dir_opt: Option<PathBuf>) -> Result<Self> {
+        let custom_fuzz_dir = fuzz_dir_opt.is_some();
         let project = Self::manage_initial_instance(fuzz_dir_opt)?;
         let fuzz_project = project.fuzz_dir();
         let manifest = Manifest::parse()?;
@@ -73,6 +186,15 @@ impl FuzzProject {
                 init.fuzzing_workspace
             ))
             .with_context(|| format!("failed to write to {}", cargo_toml.display()))?;
+        if let Some(overrides) = resolution_overrides(
+            &manifest.workspace_root,
+            fuzz_project,
+            init.fuzzing_workspace == Some(true),
+        )? {
+            cargo
+                .write_all(overrides.as_bytes())
+                .with_context(|| format!("failed to write to {}", cargo_toml.display()))?;
+        }
 
         let gitignore = fuzz_project.join(".gitignore");
         let mut ignore = fs::File::create(&gitignore)
@@ -89,18 +211,390 @@ impl FuzzProject {
                     init.target
                 )
             })?;
+        if custom_fuzz_dir {
+            record_fuzz_dir(fuzz_project)?;
+        }
         Ok(project)
     }
 
-    pub fn list_targets(&self) -> Result<()> {
-        for bin in &self.targets {
-            println!("{}", bin);
+    /// Migrates a fuzz project from the layout of older versions of
+    /// cargo-fuzz. Unlike `FuzzProject::new`, the fuzz manifest doesn't need
+    /// to have the cargo-fuzz metadata yet.
+    pub fn upgrade(upgrade: &options::Upgrade, fuzz_dir_opt: Option<PathBuf>) -> Result<()> {
+        let project = Self::manage_initial_instance(fuzz_dir_opt)?;
+        let manifest_path = project.manifest_path();
+        let manifest = fs::read_to_string(&manifest_path).with_context(|| {
+            format!(
+                "could not read the manifest file: {}",
+                manifest_path.display()
+            )
+        })?;
+
+        let old_dir = project.fuzz_dir().join(crate::FUZZ_TARGETS_DIR_OLD);
+        let new_dir = project.fuzz_dir().join(crate::FUZZ_TARGETS_DIR);
+        let rename = old_dir.is_dir() && !new_dir.exists();
+        let (upgraded, mut changes) =
+            crate::upgrade::upgrade_manifest(&manifest, rename || !old_dir.exists())?;
+        if rename {
+            changes.insert(
+                0,
+                format!(
+                    "rename `{}/` to `{}/`",
+                    crate::FUZZ_TARGETS_DIR_OLD,
+                    crate::FUZZ_TARGETS_DIR
+                ),
+            );
+        }
+
+        if changes.is_empty() {
+            eprintln!("The fuzz project is up to date");
+            return Ok(());
+        }
+        eprintln!(
+            "{} the fuzz project in {}:",
+            if upgrade.dry_run {
+                "Would upgrade"
+            } else {
+                "Upgrading"
+            },
+            strip_current_dir_prefix(project.fuzz_dir()).display()
+        );
+        for change in &changes {
+            eprintln!("  - {}", change);
+        }
+        if upgrade.dry_run {
+            return Ok(());
+        }
+        if rename {
+            fs::rename(&old_dir, &new_dir).with_context(|| {
+                format!(
+                    "failed to rename {} to {}",
+                    old_dir.display(),
+                    new_dir.display()
+                )
+            })?;
+        }
+        if upgraded != manifest {
+            fs::write(&manifest_path, upgraded)
+                .with_context(|| format!("failed to write to {}", manifest_path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Checks the fuzz targets and the manifest of a fuzz project for
+    /// mistakes that otherwise surface as confusing link-time or runtime
+    /// errors.
+    pub fn lint(lint: &options::Lint, fuzz_dir_opt: Option<PathBuf>) -> Result<()> {
+        let project = Self::manage_initial_instance(fuzz_dir_opt)?;
+        let manifest_path = project.manifest_path();
+        let manifest = fs::read_to_string(&manifest_path).with_context(|| {
+            format!(
+                "could not read the manifest file: {}",
+                manifest_path.display()
+            )
+        })?;
+        let (bins, manifest_problems) = crate::lint::check_manifest(&manifest)?;
+        let manifest_display = strip_current_dir_prefix(&manifest_path).to_owned();
+        let mut problems: Vec<(PathBuf, String)> = manifest_problems
+            .into_iter()
+            .map(|problem| (manifest_display.clone(), problem))
+            .collect();
+
+        let mut typed = Vec::new();
+        let mut sources = HashSet::new();
+        for bin in &bins {
+            let path = project.fuzz_dir.join(&bin.path);
+            let display = strip_current_dir_prefix(&path).to_owned();
+            let source = match fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(_) => {
+                    problems.push((
+                        manifest_display.clone(),
+                        format!(
+                            "fuzz target `{}` has no source file at {}",
+                            bin.name,
+                            display.display()
+                        ),
+                    ));
+                    continue;
+                }
+            };
+            sources.insert(fs::canonicalize(&path)?);
+            let source_problems = crate::lint::check_source(&source);
+            if source_problems.is_empty() {
+                if let Some(ty) = crate::lint::input_type(&source) {
+                    typed.push((bin.name.as_str(), display.clone(), ty));
+                }
+            }
+            problems.extend(
+                source_problems
+                    .into_iter()
+                    .map(|problem| (display.clone(), problem)),
+            );
+        }
+
+        // Fuzz targets that aren't declared in the manifest are never built.
+        let targets_dir = project.fuzz_dir.join(crate::FUZZ_TARGETS_DIR);
+        for path in fuzz_target_files(&targets_dir)? {
+            if !sources.contains(&fs::canonicalize(&path)?) {
+                problems.push((
+                    strip_current_dir_prefix(&path).to_owned(),
+                    "fuzz target without a `[[bin]]` in the manifest, so it is never built"
+                        .to_owned(),
+                ));
+            }
+        }
+
+        if !lint.no_check {
+            for (name, display, ty) in typed {
+                if let Some(problem) = project.check_input_type(&lint.build, name, &ty)? {
+                    problems.push((display, problem));
+                }
+            }
+        }
+
+        for (path, problem) in &problems {
+            println!("{}: {}", path.display(), problem);
+        }
+        if !problems.is_empty() {
+            bail!("{} problem(s) found", problems.len());
+        }
+        eprintln!("No problems found in {} fuzz target(s)", bins.len());
+        Ok(())
+    }
+
+    /// Adds `[[bin]]` sections for the fuzz target files that the manifest is
+    /// missing, and reports, or removes, those whose file is gone.
+    pub fn sync(sync: &options::Sync, fuzz_dir_opt: Option<PathBuf>) -> Result<()> {
+        let project = Self::manage_initial_instance(fuzz_dir_opt)?;
+        let manifest_path = project.manifest_path();
+        let manifest = fs::read_to_string(&manifest_path).with_context(|| {
+            format!(
+                "could not read the manifest file: {}",
+                manifest_path.display()
+            )
+        })?;
+        let (bins, _) = crate::lint::check_manifest(&manifest)?;
+
+        let mut sources = HashSet::new();
+        let mut stale = Vec::new();
+        for bin in &bins {
+            match fs::canonicalize(project.fuzz_dir.join(&bin.path)) {
+                Ok(source) => {
+                    sources.insert(source);
+                }
+                Err(_) => stale.push(bin.name.clone()),
+            }
+        }
+
+        let mut new = Vec::new();
+        let targets_dir = project.fuzz_dir.join(crate::FUZZ_TARGETS_DIR);
+        for path in fuzz_target_files(&targets_dir)? {
+            if sources.contains(&fs::canonicalize(&path)?) {
+                continue;
+            }
+            let name = match nested_target_name(Some(&targets_dir), &path) {
+                Some(name) => name,
+                None => path.file_stem().unwrap().to_string_lossy().into_owned(),
+            };
+            if bins.iter().any(|bin| bin.name == utils::bin_name(&name)) {
+                eprintln!(
+                    "warning: not adding {}, there is already a fuzz target named `{}`",
+                    strip_current_dir_prefix(&path).display(),
+                    utils::bin_name(&name)
+                );
+                continue;
+            }
+            new.push(name);
+        }
+
+        if new.is_empty() && stale.is_empty() {
+            eprintln!(
+                "The manifest is in sync with `{}/`",
+                crate::FUZZ_TARGETS_DIR
+            );
+            return Ok(());
+        }
+        let mode = if sync.dry_run { "Would add" } else { "Adding" };
+        for name in &new {
+            eprintln!("{} a `[[bin]]` for fuzz target `{}`", mode, name);
+        }
+        for name in &stale {
+            if sync.remove {
+                eprintln!(
+                    "{} the `[[bin]]` of fuzz target `{}`, whose source file is gone",
+                    if sync.dry_run {
+                        "Would remove"
+                    } else {
+                        "Removing"
+                    },
+                    name
+                );
+            } else {
+                eprintln!(
+                    "warning: the source file of fuzz target `{}` is gone, pass `--remove` to \
+                     remove its `[[bin]]`",
+                    name
+                );
+            }
+        }
+        if sync.dry_run {
+            return Ok(());
+        }
+
+        let mut synced = crate::sync::add_bins(&manifest, &new);
+        if sync.remove {
+            synced = crate::sync::remove_bins(&synced, &stale);
+        }
+        if synced != manifest {
+            fs::write(&manifest_path, synced)
+                .with_context(|| format!("failed to write to {}", manifest_path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Type-checks a typed fuzz target, to tell whether its input type `ty`
+    /// implements `Arbitrary` and `Debug`, as `fuzz_target!` requires.
+    fn check_input_type(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        ty: &str,
+    ) -> Result<Option<String>> {
+        let mut cmd = self.cargo("check", build)?;
+        cmd.arg("--bin")
+            .arg(self.bin_name(target))
+            .arg("--message-format=short");
+        if let Some(target_dir) = self.target_dir(build)? {
+            cmd.arg("--target-dir").arg(target_dir);
+        }
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to execute: {:?}", cmd))?;
+        if output.status.success() {
+            return Ok(None);
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let errors: Vec<&str> = stderr
+            .lines()
+            .filter(|line| line.contains("error"))
+            .collect();
+        let missing: Vec<_> = ["Arbitrary", "Debug"]
+            .into_iter()
+            .filter(|t| errors.iter().any(|line| line.contains(t)))
+            .map(|t| format!("`{}`", t))
+            .collect();
+        Ok(Some(if missing.is_empty() {
+            format!(
+                "fuzz target `{}` doesn't compile: {}",
+                target,
+                errors
+                    .first()
+                    .map_or("see `cargo fuzz check`", |line| line.trim())
+            )
+        } else {
+            format!(
+                "input type `{}` doesn't implement {}",
+                ty,
+                missing.join(" and ")
+            )
+        }))
+    }
+
+    pub fn list_targets(&self, list: &options::List) -> Result<()> {
+        if !list.json {
+            for bin in &self.targets {
+                println!("{}", bin);
+            }
+            return Ok(());
+        }
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&self.target_summaries()?)?
+        );
+        Ok(())
+    }
+
+    /// Lists the fuzz targets of several fuzz projects, named after the
+    /// packages they fuzz, as `<package>::<target>`.
+    pub fn list_workspace_targets(
+        list: &options::List,
+        fuzz_dirs: &[(String, PathBuf)],
+    ) -> Result<()> {
+        let mut summaries = Vec::new();
+        for (package, fuzz_dir) in fuzz_dirs {
+            let project = FuzzProject::new(Some(fuzz_dir.clone()))?;
+            if !list.json {
+                for bin in &project.targets {
+                    println!("{}::{}", package, bin);
+                }
+                continue;
+            }
+            for mut summary in project.target_summaries()? {
+                summary["package"] = package.as_str().into();
+                summaries.push(summary);
+            }
+        }
+        if list.json {
+            println!("{}", serde_json::to_string_pretty(&summaries)?);
         }
         Ok(())
     }
 
+    /// Returns what `cargo fuzz list --json` prints about every fuzz target.
+    fn target_summaries(&self) -> Result<Vec<serde_json::Value>> {
+        let mut targets = Vec::new();
+        for target in &self.targets {
+            let (corpus_inputs, corpus_bytes) = self.corpus_size(target)?;
+            let mut artifacts = BTreeMap::new();
+            let artifacts_dir = self.fuzz_dir().join("artifacts").join(target);
+            if artifacts_dir.is_dir() {
+                for artifact in artifact_files(&artifacts_dir)? {
+                    *artifacts.entry(artifact_category(&artifact)).or_insert(0) += 1;
+                }
+            }
+            let last_run = stats::read_runs(&self.stats_for(target))?
+                .last()
+                .map(|run| run.started);
+            targets.push(serde_json::json!({
+                "name": target,
+                "path": strip_current_dir_prefix(&self.target_source_path(target)),
+                "corpus": {
+                    "inputs": corpus_inputs,
+                    "bytes": corpus_bytes,
+                },
+                "artifacts": artifacts,
+                "last_run": last_run,
+            }));
+        }
+        Ok(targets)
+    }
+
     /// Create a new fuzz target.
     pub fn add_target(&self, add: &options::Add, manifest: &Manifest) -> Result<()> {
+        if add
+            .target
+            .split('/')
+            .any(|part| part.is_empty() || part == "." || part == ".." || part.contains('\\'))
+        {
+            bail!(
+                "invalid fuzz target name {:?}, group fuzz targets in subdirectories with \
+                 names like `parser/http`",
+                add.target
+            );
+        }
+        let bin_name = utils::bin_name(&add.target);
+        if let Some(existing) = self
+            .targets
+            .iter()
+            .find(|t| **t != add.target && self.bin_name(t) == bin_name)
+        {
+            bail!(
+                "fuzz target `{}` already has the binary name `{}`",
+                existing,
+                bin_name
+            );
+        }
         // Create corpus and artifact directories for the newly added target
         self.corpus_for(&add.target)?;
         self.artifacts_for(&add.target)?;
@@ -116,7 +610,7 @@ impl FuzzProject {
         // targets yet, the `fuzz_targets` directory might not exist yet,
         // despite a `fuzz/Cargo.toml` manifest with the `metadata.cargo-fuzz`
         // key present. Make sure it does exist.
-        fs::create_dir_all(self.fuzz_targets_dir())
+        fs::create_dir_all(target_path.parent().unwrap())
             .context("ensuring that `fuzz_targets` directory exists failed")?;
 
         let mut script = fs::OpenOptions::new()
@@ -133,10 +627,21 @@ impl FuzzProject {
     }
 
     fn cargo(&self, subcommand: &str, build: &BuildOptions) -> Result<Command> {
+        self.cargo_for(subcommand, build, &self.manifest_path())
+    }
+
+    /// Like `cargo`, for the package of `manifest_path` rather than the fuzz
+    /// package.
+    fn cargo_for(
+        &self,
+        subcommand: &str,
+        build: &BuildOptions,
+        manifest_path: &Path,
+    ) -> Result<Command> {
         let mut cmd = Command::new("cargo");
         cmd.arg(subcommand)
             .arg("--manifest-path")
-            .arg(self.manifest_path())
+            .arg(manifest_path)
             // --target=<TARGET> won't pass rustflags to build scripts
             .arg("--target")
             .arg(&build.triple);
@@ -166,154 +671,379 @@ impl FuzzProject {
             cmd.arg("-Z").arg("build-std");
         }
 
-        let mut rustflags: String = "-Cpasses=sancov-module \
-                                     -Cllvm-args=-sanitizer-coverage-level=4 \
-                                     -Cllvm-args=-sanitizer-coverage-inline-8bit-counters \
-                                     -Cllvm-args=-sanitizer-coverage-pc-table"
-            .to_owned();
-
-        if !build.no_trace_compares {
-            rustflags.push_str(" -Cllvm-args=-sanitizer-coverage-trace-compares");
+        if is_wasi(build) {
+            check_wasi(build)?;
+        }
+        // So that `cargo run` runs the fuzz target under the runner too.
+        if let Some(runner) = self.target_runner(build) {
+            cmd.env(runner_var(&build.triple), runner);
         }
 
-        if build.disable_branch_folding.unwrap_or(true) {
-            rustflags.push_str(" -Cllvm-args=-simplifycfg-branch-fold-threshold=0");
+        cmd.env("RUSTFLAGS", rustflags(build)?);
+        configure_libfuzzer(&mut cmd, build)?;
+        configure_sanitizer_options(&mut cmd, build);
+        if build.print_rustflags {
+            print_command_env(&cmd);
         }
 
-        if !build.no_cfg_fuzzing {
-            rustflags.push_str(" --cfg fuzzing");
+        Ok(cmd)
+    }
+
+    /// Returns a command that runs a fuzz target. A fuzz target built in this
+    /// session is run directly, otherwise through `cargo run`.
+    fn cargo_run(&self, build: &options::BuildOptions, fuzz_target: &str) -> Result<Command> {
+        let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
+        artifact_arg.push(runner_path(build, &self.artifacts_for(fuzz_target)?)?);
+
+        if self.is_built(build, fuzz_target) {
+            let binary = self.fuzz_binary_path(build, fuzz_target)?;
+            let mut cmd = match self.target_runner(build) {
+                Some(runner) => {
+                    let mut runner = runner.split_whitespace();
+                    let mut cmd = Command::new(runner.next().context("the runner is empty")?);
+                    cmd.args(runner).arg(binary);
+                    cmd
+                }
+                None => Command::new(binary),
+            };
+            configure_sanitizer_options(&mut cmd, build);
+            configure_symbolizer(&mut cmd, build.sanitizer);
+            if is_ios_simulator(build) {
+                configure_simulator_env(&mut cmd);
+            }
+            cmd.arg(artifact_arg);
+            if build.no_leak_detection {
+                cmd.arg("-detect_leaks=0");
+            }
+            return Ok(cmd);
         }
 
-        if !build.strip_dead_code {
-            rustflags.push_str(" -Clink-dead-code");
+        let mut cmd = self.cargo("run", build)?;
+        cmd.arg("--bin").arg(self.bin_name(fuzz_target));
+
+        if let Some(target_dir) = &build.target_dir {
+            cmd.arg("--target-dir").arg(target_dir);
         }
 
-        if build.coverage {
-            rustflags.push_str(" -Cinstrument-coverage");
+        configure_symbolizer(&mut cmd, build.sanitizer);
+        if is_ios_simulator(build) {
+            configure_simulator_env(&mut cmd);
+        }
+        cmd.arg("--").arg(artifact_arg);
+        if build.no_leak_detection {
+            cmd.arg("-detect_leaks=0");
         }
 
-        if !matches!(build.sanitizer, Sanitizer::None) {
-            // Select the appropriate sanitizer flag for the given rustc version
-            let rust_version = RustVersion::discover()?;
-            let sanitizer_flag = match rust_version.has_sanitizers_on_stable() {
-                true => "-Csanitizer",
-                false => "-Zsanitizer",
-            };
+        Ok(cmd)
+    }
 
-            // Set rustc CLI arguments for the chosen sanitizer
-            match build.sanitizer {
-                Sanitizer::None => {} // needs no flags
-                Sanitizer::Memory => {
-                    // Memory sanitizer requires more flags to function than others:
-                    // https://doc.rust-lang.org/unstable-book/compiler-flags/sanitizer.html#memorysanitizer
-                    rustflags.push_str(&format!(
-                        " {sanitizer_flag}=memory -Zsanitizer-memory-track-origins"
-                    ))
-                }
-                _ => rustflags.push_str(&format!(" {sanitizer_flag}={}", build.sanitizer)),
-            }
-
-            // Not all sanitizers are stabilized on all platforms.
-            // It is infeasible to keep up this code to date with the list.
-            // So we just set `-Zunstable-options` required for some sanitizers
-            // whenever we're on nightly on a recent enough compiler,
-            // and let the compiler show an error message
-            // if the user tries to enable a sanitizer not supported on their stable compiler.
-            if rust_version.nightly && rust_version.has_sanitizers_on_stable() {
-                rustflags.push_str(" -Zunstable-options")
-            }
-        }
-
-        if build.careful_mode {
-            rustflags.push_str(" -Zextra-const-ub-checks -Zstrict-init-checks --cfg careful");
-        }
-        if build.triple.contains("-linux-") {
-            rustflags.push_str(" -Cllvm-args=-sanitizer-coverage-stack-depth");
-        }
-        if !build.release || build.debug_assertions || build.careful_mode {
-            rustflags.push_str(" -Cdebug-assertions");
-        }
-        if build.triple.contains("-msvc") && !build.no_include_main_msvc {
-            // This forces the MSVC linker (which runs on Windows systems) to
-            // find the entry point (i.e. the `main` function) within the
-            // LibFuzzer `.rlib` file produced during the build.
-            //
-            // The `--no-include-main-msvc` argument disables the addition of
-            // this linker argument. In certain situations, a user may not want
-            // this argument included as part of the MSVC invocation.
-            //
-            // For example, if the user is attempting to build and fuzz a
-            // Windows DLL (shared library), adding `/include:main` will force
-            // the DLL to compile with an external reference to `main`.
-            // DLLs/shared libraries are designed to be built as a separate
-            // object file, intentionally left *without* knowledge of the entry
-            // point. So, forcing a DLL to include `main` will cause linking to
-            // fail. Using `--no-include-main-msvc` will allow the DLL to be
-            // built without issue.
-            rustflags.push_str(" -Clink-arg=/include:main");
-        }
-
-        // If release mode is enabled then we force 1 CGU to be used in rustc.
-        // This will result in slower compilations but it looks like the sancov
-        // passes otherwise add `notEligibleToImport` annotations to functions
-        // in LLVM IR, meaning that *nothing* can get imported with ThinLTO.
-        // This means that in release mode, where ThinLTO is critical for
-        // performance, we're taking a huge hit relative to actual release mode.
-        // Local tests have once showed this to be a ~3x faster runtime where
-        // otherwise functions like `Vec::as_ptr` aren't inlined.
-        if !build.dev {
-            rustflags.push_str(" -C codegen-units=1");
+    /// Returns the runner command that fuzz targets built for another target
+    /// than the host run through, cargo's `CARGO_TARGET_<TRIPLE>_RUNNER`. WASI
+    /// fuzz targets run under wasmtime by default, with access to the fuzz
+    /// directory and the current directory, and iOS simulator fuzz targets in
+    /// the booted simulator.
+    fn target_runner(&self, build: &BuildOptions) -> Option<String> {
+        env_runner(&build.triple).or_else(|| {
+            if is_wasi(build) {
+                Some(format!(
+                    "wasmtime run --dir={0}::{0} --dir=.",
+                    self.fuzz_dir().display()
+                ))
+            } else if is_ios_simulator(build) {
+                Some("xcrun simctl spawn booted".to_owned())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Whether `exec_build` built `fuzz_target` with `build` in this session.
+    fn is_built(&self, build: &BuildOptions, fuzz_target: &str) -> bool {
+        let fingerprint = format!("{:?}", build);
+        let built = self.built.lock().unwrap();
+        built.contains(&(fingerprint.clone(), Some(fuzz_target.to_owned())))
+            || built.contains(&(fingerprint, None))
+    }
+}
+
+/// Returns the `RUSTFLAGS` that fuzz targets are built with. Our flags are
+/// sorted, so that they stay the same across versions of cargo-fuzz and don't
+/// invalidate the caches of cargo and sccache, and are followed by the
+/// `RUSTFLAGS` of the environment, in their order, so that they can override
+/// ours.
+fn rustflags(build: &BuildOptions) -> Result<String> {
+    let mut flags = BTreeSet::new();
+    flags.extend(
+        [
+            "-Cpasses=sancov-module",
+            "-Cllvm-args=-sanitizer-coverage-level=4",
+            "-Cllvm-args=-sanitizer-coverage-inline-8bit-counters",
+            "-Cllvm-args=-sanitizer-coverage-pc-table",
+        ]
+        .map(String::from),
+    );
+    if !build.no_trace_compares {
+        flags.insert("-Cllvm-args=-sanitizer-coverage-trace-compares".to_owned());
+    }
+
+    if build.disable_branch_folding.unwrap_or(true) {
+        flags.insert("-Cllvm-args=-simplifycfg-branch-fold-threshold=0".to_owned());
+    }
+
+    if !build.no_cfg_fuzzing {
+        flags.insert("--cfg fuzzing".to_owned());
+    }
+
+    if !build.strip_dead_code {
+        flags.insert("-Clink-dead-code".to_owned());
+    }
+
+    if build.coverage {
+        flags.insert("-Cinstrument-coverage".to_owned());
+    }
+
+    if build.repro {
+        flags.insert("--cfg fuzzing_repro".to_owned());
+    }
+
+    if build.frame_pointers {
+        flags.insert("-Cforce-frame-pointers=yes".to_owned());
+    }
+
+    if build.sanitizer_recover {
+        match build.sanitizer {
+            Sanitizer::Address | Sanitizer::Memory => {
+                flags.insert(format!("-Zsanitizer-recover={}", build.sanitizer));
+            }
+            _ => bail!(
+                "`--sanitizer-recover` only works with `--sanitizer=address` or `--sanitizer=memory`"
+            ),
         }
+    }
 
-        if let Ok(other_flags) = env::var("RUSTFLAGS") {
-            rustflags.push(' ');
-            rustflags.push_str(&other_flags);
+    if let Some(supported) = bsd_sanitizers(&build.triple) {
+        if build.sanitizer != Sanitizer::None && !supported.contains(&build.sanitizer) {
+            let mut supported: Vec<String> = supported.iter().map(|s| s.to_string()).collect();
+            supported.push("none".to_owned());
+            bail!(
+                "`--sanitizer={}` isn't available for `{}`, pick one of: {}",
+                build.sanitizer,
+                build.triple,
+                supported.join(", ")
+            );
         }
-        cmd.env("RUSTFLAGS", rustflags);
+    }
+
+    if !matches!(build.sanitizer, Sanitizer::None) {
+        // Select the appropriate sanitizer flag for the given rustc version
+        let rust_version = RustVersion::discover()?;
+        let sanitizer_flag = match rust_version.has_sanitizers_on_stable() {
+            true => "-Csanitizer",
+            false => "-Zsanitizer",
+        };
 
-        // For asan and tsan we have default options. Merge them to the given
-        // options, so users can still provide their own options to e.g. disable
-        // the leak sanitizer.  Options are colon-separated.
+        // Set rustc CLI arguments for the chosen sanitizer
         match build.sanitizer {
-            Sanitizer::Address => {
-                let mut asan_opts = env::var("ASAN_OPTIONS").unwrap_or_default();
-                if !asan_opts.is_empty() {
-                    asan_opts.push(':');
-                }
-                asan_opts.push_str("detect_odr_violation=0");
-                cmd.env("ASAN_OPTIONS", asan_opts);
+            Sanitizer::None => {} // needs no flags
+            Sanitizer::Memory => {
+                // Memory sanitizer requires more flags to function than others:
+                // https://doc.rust-lang.org/unstable-book/compiler-flags/sanitizer.html#memorysanitizer
+                flags.insert(format!("{sanitizer_flag}=memory"));
+                flags.insert("-Zsanitizer-memory-track-origins".to_owned());
             }
-
-            Sanitizer::Thread => {
-                let mut tsan_opts = env::var("TSAN_OPTIONS").unwrap_or_default();
-                if !tsan_opts.is_empty() {
-                    tsan_opts.push(':');
-                }
-                tsan_opts.push_str("report_signal_unsafe=0");
-                cmd.env("TSAN_OPTIONS", tsan_opts);
+            _ => {
+                flags.insert(format!("{sanitizer_flag}={}", build.sanitizer));
             }
+        }
 
-            _ => {}
+        // Not all sanitizers are stabilized on all platforms.
+        // It is infeasible to keep up this code to date with the list.
+        // So we just set `-Zunstable-options` required for some sanitizers
+        // whenever we're on nightly on a recent enough compiler,
+        // and let the compiler show an error message
+        // if the user tries to enable a sanitizer not supported on their stable compiler.
+        if rust_version.nightly && rust_version.has_sanitizers_on_stable() {
+            flags.insert("-Zunstable-options".to_owned());
         }
+    }
 
-        Ok(cmd)
+    if build.careful_mode {
+        flags.extend(
+            [
+                "-Zextra-const-ub-checks",
+                "-Zstrict-init-checks",
+                "--cfg careful",
+            ]
+            .map(String::from),
+        );
+    }
+    // The stack depth goes to a thread-local of libFuzzer, which Apple and
+    // OpenBSD targets can't link against from the instrumented code.
+    if ["-linux-", "-freebsd", "-netbsd"]
+        .iter()
+        .any(|os| build.triple.contains(os))
+    {
+        flags.insert("-Cllvm-args=-sanitizer-coverage-stack-depth".to_owned());
+    }
+    if !build.release || build.debug_assertions || build.careful_mode {
+        flags.insert("-Cdebug-assertions".to_owned());
+    }
+    if build.triple.contains("-msvc") && !build.no_include_main_msvc {
+        // This forces the MSVC linker (which runs on Windows systems) to
+        // find the entry point (i.e. the `main` function) within the
+        // LibFuzzer `.rlib` file produced during the build, or within the
+        // library given with `--libfuzzer`.
+        //
+        // The `--no-include-main-msvc` argument disables the addition of
+        // this linker argument. In certain situations, a user may not want
+        // this argument included as part of the MSVC invocation.
+        //
+        // For example, if the user is attempting to build and fuzz a
+        // Windows DLL (shared library), adding `/include:main` will force
+        // the DLL to compile with an external reference to `main`.
+        // DLLs/shared libraries are designed to be built as a separate
+        // object file, intentionally left *without* knowledge of the entry
+        // point. So, forcing a DLL to include `main` will cause linking to
+        // fail. Using `--no-include-main-msvc` will allow the DLL to be
+        // built without issue.
+        flags.insert("-Clink-arg=/include:main".to_owned());
     }
 
-    fn cargo_run(&self, build: &options::BuildOptions, fuzz_target: &str) -> Result<Command> {
-        let mut cmd = self.cargo("run", build)?;
-        cmd.arg("--bin").arg(fuzz_target);
+    // If release mode is enabled then we force 1 CGU to be used in rustc.
+    // This will result in slower compilations but it looks like the sancov
+    // passes otherwise add `notEligibleToImport` annotations to functions
+    // in LLVM IR, meaning that *nothing* can get imported with ThinLTO.
+    // This means that in release mode, where ThinLTO is critical for
+    // performance, we're taking a huge hit relative to actual release mode.
+    // Local tests have once showed this to be a ~3x faster runtime where
+    // otherwise functions like `Vec::as_ptr` aren't inlined.
+    if !build.dev {
+        flags.insert("-Ccodegen-units=1".to_owned());
+    }
 
-        if let Some(target_dir) = &build.target_dir {
-            cmd.arg("--target-dir").arg(target_dir);
+    let mut rustflags: Vec<String> = flags.into_iter().collect();
+    if let Ok(other_flags) = env::var("RUSTFLAGS") {
+        rustflags.extend(other_flags.split_whitespace().map(String::from));
+    }
+    Ok(rustflags.join(" "))
+}
+
+/// Returns the sanitizers that rustc has for a BSD target, which are fewer
+/// than for Linux, or `None` for other targets, where rustc gets to check.
+fn bsd_sanitizers(triple: &str) -> Option<&'static [Sanitizer]> {
+    match triple {
+        "x86_64-unknown-freebsd" => {
+            Some(&[Sanitizer::Address, Sanitizer::Memory, Sanitizer::Thread])
         }
+        "x86_64-unknown-netbsd" => Some(&[
+            Sanitizer::Address,
+            Sanitizer::Leak,
+            Sanitizer::Memory,
+            Sanitizer::Thread,
+        ]),
+        _ if triple.contains("-openbsd") => Some(&[]),
+        _ => None,
+    }
+}
 
-        let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
-        artifact_arg.push(self.artifacts_for(fuzz_target)?);
-        cmd.arg("--").arg(artifact_arg);
+/// Prints the environment and the command line of the cargo command `cmd`,
+/// in a form that can be pasted into a POSIX shell to replicate the build.
+fn print_command_env(cmd: &Command) {
+    for (name, value) in cmd.get_envs() {
+        if let Some(value) = value {
+            let value = utils::shell_quote(&value.to_string_lossy());
+            println!("{}={}", name.to_string_lossy(), value);
+        }
+    }
+    let mut command_line = utils::shell_quote(&cmd.get_program().to_string_lossy());
+    for arg in cmd.get_args() {
+        command_line.push(' ');
+        command_line.push_str(&utils::shell_quote(&arg.to_string_lossy()));
+    }
+    println!("{}", command_line);
+}
 
-        Ok(cmd)
+/// Makes libfuzzer-sys link the libFuzzer library of `--libfuzzer`, or of a
+/// `CUSTOM_LIBFUZZER_PATH` in the environment, instead of building its own.
+/// That library is linked statically, so the C++ standard library it needs has
+/// to be linked too, and the default of libfuzzer-sys, `stdc++`, doesn't exist
+/// on every target.
+fn configure_libfuzzer(cmd: &mut Command, build: &BuildOptions) -> Result<()> {
+    if let Some(libfuzzer) = &build.libfuzzer {
+        if !libfuzzer.is_file() {
+            bail!("libFuzzer library {} doesn't exist", libfuzzer.display());
+        }
+        // The build script of libfuzzer-sys runs in a directory of its own.
+        cmd.env("CUSTOM_LIBFUZZER_PATH", env::current_dir()?.join(libfuzzer));
+    } else if env::var_os("CUSTOM_LIBFUZZER_PATH").is_none() {
+        return Ok(());
+    }
+    if env::var_os("CUSTOM_LIBFUZZER_STD_CXX").is_none() {
+        if build.triple.contains("-msvc") {
+            // The MSVC linker picks the C++ runtime by itself.
+            cmd.env("CUSTOM_LIBFUZZER_STD_CXX", "none");
+        } else if build.triple.contains("-apple-") {
+            cmd.env("CUSTOM_LIBFUZZER_STD_CXX", "c++");
+        }
+    }
+    Ok(())
+}
+
+/// Sets the runtime options of the sanitizers. For asan and tsan we have
+/// default options. Merge them to the given options, so users can still
+/// provide their own options to e.g. disable the leak sanitizer. Options are
+/// colon-separated. With `--sanitizer-recover` the sanitizers also keep going
+/// after an error.
+fn configure_sanitizer_options(cmd: &mut Command, build: &BuildOptions) {
+    match build.sanitizer {
+        Sanitizer::Address => {
+            let mut asan_opts = env::var("ASAN_OPTIONS").unwrap_or_default();
+            if !asan_opts.is_empty() {
+                asan_opts.push(':');
+            }
+            asan_opts.push_str("detect_odr_violation=0");
+            // There is no leak sanitizer on FreeBSD to look for leaks with.
+            if build.triple.contains("-freebsd") || build.no_leak_detection {
+                asan_opts.push_str(":detect_leaks=0");
+            }
+            if build.sanitizer_recover {
+                asan_opts.push_str(":halt_on_error=0");
+            }
+            cmd.env("ASAN_OPTIONS", asan_opts);
+        }
+
+        Sanitizer::Leak if build.no_leak_detection => {
+            let mut lsan_opts = env::var("LSAN_OPTIONS").unwrap_or_default();
+            if !lsan_opts.is_empty() {
+                lsan_opts.push(':');
+            }
+            lsan_opts.push_str("detect_leaks=0");
+            cmd.env("LSAN_OPTIONS", lsan_opts);
+        }
+
+        Sanitizer::Memory if build.sanitizer_recover => {
+            let mut msan_opts = env::var("MSAN_OPTIONS").unwrap_or_default();
+            if !msan_opts.is_empty() {
+                msan_opts.push(':');
+            }
+            msan_opts.push_str("halt_on_error=0");
+            cmd.env("MSAN_OPTIONS", msan_opts);
+        }
+
+        Sanitizer::Thread => {
+            let mut tsan_opts = env::var("TSAN_OPTIONS").unwrap_or_default();
+            if !tsan_opts.is_empty() {
+                tsan_opts.push(':');
+            }
+            tsan_opts.push_str("report_signal_unsafe=0");
+            cmd.env("TSAN_OPTIONS", tsan_opts);
+        }
+
+        _ => {}
     }
+}
 
+impl FuzzProject {
     // note: never returns Ok(None) if build.coverage is true
     fn target_dir(&self, build: &options::BuildOptions) -> Result<Option<PathBuf>> {
         // Use the user-provided target directory, if provided. Otherwise if building for coverage,
@@ -322,10 +1052,10 @@ impl FuzzProject {
             return Ok(Some(PathBuf::from(target_dir)));
         } else if build.coverage {
             // To ensure that fuzzing and coverage-output generation can run in parallel, we
-            // produce a separate binary for the coverage command.
-            let current_dir = env::current_dir()?;
+            // produce a separate binary for the coverage command. It goes
+            // next to the package, wherever in it the command is run.
             Ok(Some(
-                current_dir
+                find_package()?
                     .join("target")
                     .join(default_target())
                     .join("coverage"),
@@ -335,20 +1065,71 @@ impl FuzzProject {
         }
     }
 
-    pub fn exec_build(
-        &self,
-        mode: options::BuildMode,
-        build: &options::BuildOptions,
-        fuzz_target: Option<&str>,
-    ) -> Result<()> {
-        let cargo_subcommand = match mode {
-            options::BuildMode::Build => "build",
-            options::BuildMode::Check => "check",
-        };
+    /// Returns the path of the fuzz target binary that `exec_build` produces.
+    pub fn fuzz_binary_path(&self, build: &BuildOptions, fuzz_target: &str) -> Result<PathBuf> {
+        Ok(self.fuzz_binary_dir(build)?.join(format!(
+            "{}{}",
+            self.bin_name(fuzz_target),
+            if build.triple.starts_with("wasm32-") {
+                ".wasm"
+            } else {
+                env::consts::EXE_SUFFIX
+            }
+        )))
+    }
+
+    /// Returns the Cargo binary name of a fuzz target.
+    fn bin_name<'a>(&'a self, target: &'a str) -> &'a str {
+        self.bin_names.get(target).map_or(target, String::as_str)
+    }
+
+    /// Returns the directory of the fuzz target binaries that `exec_build`
+    /// produces.
+    fn fuzz_binary_dir(&self, build: &BuildOptions) -> Result<PathBuf> {
+        let target_dir = match self.target_dir(build)? {
+            Some(target_dir) => target_dir,
+            None => self.cargo_target_dir()?,
+        };
+        let profile_subdir = if build.dev { "debug" } else { "release" };
+        Ok(target_dir.join(&build.triple).join(profile_subdir))
+    }
+
+    /// Returns the target directory that cargo builds in by default.
+    fn cargo_target_dir(&self) -> Result<PathBuf> {
+        if let Some(target_dir) = self.target_directory.get() {
+            return Ok(target_dir.clone());
+        }
+        let target_dir = MetadataCommand::new()
+            .manifest_path(self.manifest_path())
+            .no_deps()
+            .exec()?
+            .target_directory
+            .into_std_path_buf();
+        Ok(self.target_directory.get_or_init(|| target_dir).clone())
+    }
+
+    pub fn exec_build(
+        &self,
+        mode: options::BuildMode,
+        build: &options::BuildOptions,
+        fuzz_target: Option<&str>,
+    ) -> Result<()> {
+        let cargo_subcommand = match mode {
+            options::BuildMode::Build => "build",
+            options::BuildMode::Check => "check",
+        };
+        // Running cargo again with the same options would only find the build
+        // up to date, so within a session every build is done once.
+        let fingerprint = (format!("{:?}", build), fuzz_target.map(String::from));
+        if mode == options::BuildMode::Build
+            && self.is_built(build, fuzz_target.unwrap_or_default())
+        {
+            return Ok(());
+        }
         let mut cmd = self.cargo(cargo_subcommand, build)?;
 
         if let Some(fuzz_target) = fuzz_target {
-            cmd.arg("--bin").arg(fuzz_target);
+            cmd.arg("--bin").arg(self.bin_name(fuzz_target));
         } else {
             cmd.arg("--bins");
         }
@@ -359,11 +1140,68 @@ impl FuzzProject {
 
         let status = cmd
             .status()
-            .with_context(|| format!("failed to execute: {:?}", cmd))?;
+            .with_context(|| format!("failed to execute: {:?}", cmd))
+            .fail(Failure::Environment)?;
         if !status.success() {
-            bail!("failed to build fuzz script: {:?}", cmd);
+            return Err(Failure::Build.wrap(anyhow!("failed to build fuzz script: {:?}", cmd)));
+        }
+
+        if mode == options::BuildMode::Build {
+            self.built.lock().unwrap().insert(fingerprint);
+        }
+        Ok(())
+    }
+
+    /// Builds the dependencies of the fuzz package as `exec_build` would, so
+    /// that building the fuzz targets afterwards only compiles them.
+    pub fn exec_build_deps(&self, build: &BuildOptions) -> Result<()> {
+        let manifest_path = self.manifest_path();
+        let metadata = MetadataCommand::new()
+            .manifest_path(&manifest_path)
+            .other_options(vec!["--filter-platform".to_owned(), build.triple.clone()])
+            .exec()
+            .with_context(|| {
+                format!("failed to read the metadata of {}", manifest_path.display())
+            })?;
+        let package = find_manifest_package(&metadata, &manifest_path)
+            .with_context(|| format!("no package in {}", manifest_path.display()))?;
+        let node = metadata
+            .resolve
+            .as_ref()
+            .and_then(|resolve| resolve.nodes.iter().find(|node| node.id == package.id))
+            .with_context(|| format!("cargo resolved no dependencies of `{}`", package.name))?;
+
+        // With the fuzz package selected too, the dependencies get the
+        // features it enables, and `--lib` leaves out its binaries.
+        let mut cmd = self.cargo("build", build)?;
+        cmd.arg("--package").arg(&package.name);
+        for dep in &node.deps {
+            if dep
+                .dep_kinds
+                .iter()
+                .all(|info| info.kind == DependencyKind::Development)
+            {
+                continue;
+            }
+            let dep = &metadata[&dep.pkg];
+            cmd.arg("--package")
+                .arg(format!("{}@{}", dep.name, dep.version));
+        }
+        cmd.arg("--lib");
+        if let Some(target_dir) = self.target_dir(build)? {
+            cmd.arg("--target-dir").arg(target_dir);
         }
 
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to execute: {:?}", cmd))
+            .fail(Failure::Environment)?;
+        if !status.success() {
+            return Err(Failure::Build.wrap(anyhow!(
+                "failed to build the dependencies of the fuzz targets: {:?}",
+                cmd
+            )));
+        }
         Ok(())
     }
 
@@ -395,7 +1233,7 @@ impl FuzzProject {
             let modified = metadata
                 .modified()
                 .context("failed to get artifact modification time")?;
-            if !metadata.is_file() || modified <= *since {
+            if !metadata.is_file() || modified <= *since || is_artifact_metadata(&entry.path()) {
                 continue;
             }
 
@@ -405,15 +1243,32 @@ impl FuzzProject {
         Ok(artifacts)
     }
 
+    /// Runs the fuzz target, as built by `exec_build`, to get the debug
+    /// formatting of `artifact`. The binary is run directly rather than
+    /// through `cargo run`, which would check the whole build again.
     fn run_fuzz_target_debug_formatter(
         &self,
         build: &BuildOptions,
         target: &str,
         artifact: &Path,
+    ) -> Result<String> {
+        let binary = self.fuzz_binary_path(build, target)?;
+        self.run_debug_formatter(&binary, build, target, artifact)
+    }
+
+    fn run_debug_formatter(
+        &self,
+        binary: &Path,
+        build: &BuildOptions,
+        target: &str,
+        artifact: &Path,
     ) -> Result<String> {
         let debug_output = tempfile::NamedTempFile::new().context("failed to create temp file")?;
 
-        let mut cmd = self.cargo_run(build, target)?;
+        let mut cmd = Command::new(binary);
+        configure_sanitizer_options(&mut cmd, build);
+        configure_symbolizer(&mut cmd, build.sanitizer);
+
         cmd.stdin(Stdio::null());
         cmd.env("RUST_LIBFUZZER_DEBUG_PATH", debug_output.path());
         cmd.arg(artifact);
@@ -443,54 +1298,578 @@ impl FuzzProject {
         }
 
         let debug = fs::read_to_string(&debug_output).context("failed to read temp file")?;
+        if debug.is_empty() {
+            bail!(
+                "the fuzz target wrote no debug output, either because the input is too \
+                 short to decode or because it uses a libfuzzer-sys older than 0.4.0"
+            );
+        }
+        if let Some(error) = debug.strip_prefix("Arbitrary Error: ") {
+            bail!("the input could not be decoded: {}", error.trim_end());
+        }
         Ok(debug)
     }
 
-    /// Prints the debug output of an input test case
+    /// Prints the `std::fmt::Debug` output of an artifact, or a hexdump of it
+    /// when the fuzz target can't format it.
+    fn print_debug_fmt(&self, build: &BuildOptions, target: &str, artifact: &Path) {
+        match self.run_fuzz_target_debug_formatter(build, target, artifact) {
+            Ok(debug) => {
+                eprintln!("Output of `std::fmt::Debug`:\n");
+                for l in debug.lines() {
+                    eprintln!("\t{}", l);
+                }
+                eprintln!();
+            }
+            Err(e) => print_hexdump(artifact, &e, MAX_CRASH_HEXDUMP_LEN),
+        }
+    }
+
+    /// Prints the debug output of input test cases
     pub fn debug_fmt_input(&self, debugfmt: &options::Fmt) -> Result<()> {
-        if !debugfmt.input.exists() {
+        let inputs = if debugfmt.all_artifacts {
+            let artifacts = artifact_files(&self.artifacts_for(&debugfmt.target)?)?;
+            if artifacts.is_empty() {
+                eprintln!("No artifacts for `{}`", debugfmt.target);
+                return Ok(());
+            }
+            artifacts
+        } else {
+            for input in &debugfmt.inputs {
+                if !input.exists() {
+                    bail!("Input test case does not exist: {}", input.display());
+                }
+            }
+            debugfmt.inputs.clone()
+        };
+        if debugfmt.diff {
+            if inputs.len() != 2 {
+                bail!("`--diff` takes two inputs, not {}", inputs.len());
+            }
+            if debugfmt.output == FmtOutput::Json {
+                bail!("`--diff` can't print JSON");
+            }
+        }
+
+        if !debugfmt.no_build {
+            self.exec_build(BuildMode::Build, &debugfmt.build, Some(&debugfmt.target))?;
+        }
+        // Each input needs a run of the fuzz target of its own, since the
+        // debug output is overwritten for every input it runs.
+        let binary = self.fuzz_binary_path(&debugfmt.build, &debugfmt.target)?;
+        if !binary.exists() {
             bail!(
-                "Input test case does not exist: {}",
-                debugfmt.input.display()
+                "no build of `{}` at {}, run `cargo fuzz build {}` first",
+                debugfmt.target,
+                binary.display(),
+                debugfmt.target
             );
         }
 
-        let debug = self
-            .run_fuzz_target_debug_formatter(&debugfmt.build, &debugfmt.target, &debugfmt.input)
+        if debugfmt.emit_test {
+            for (i, input) in inputs.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                self.emit_regression_test(debugfmt, input)?;
+            }
+            return Ok(());
+        }
+
+        if debugfmt.diff {
+            return self.print_debug_fmt_diff(debugfmt, &binary, &inputs[0], &inputs[1]);
+        }
+
+        let batch = debugfmt.all_artifacts || inputs.len() > 1;
+        for input in &inputs {
+            let debug = self.run_debug_formatter(&binary, &debugfmt.build, &debugfmt.target, input);
+
+            if debugfmt.output == FmtOutput::Json {
+                let len = fs::metadata(input)
+                    .with_context(|| format!("failed to read metadata of {}", input.display()))?
+                    .len();
+                let (debug, error) = match debug {
+                    Ok(debug) => (Some(debug.trim_end().to_owned()), None),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+                let json = serde_json::json!({
+                    "path": strip_current_dir_prefix(input),
+                    "len": len,
+                    "debug": debug,
+                    "error": error,
+                });
+                println!("{}", json);
+                continue;
+            }
+
+            let mut out: Box<dyn Write> = match debugfmt.output {
+                FmtOutput::Stdout => Box::new(io::stdout()),
+                _ => Box::new(io::stderr()),
+            };
+            if batch {
+                writeln!(
+                    out,
+                    "\n==> {} <==\n",
+                    strip_current_dir_prefix(input).display()
+                )?;
+            }
+            match debug {
+                Ok(debug) => {
+                    if !batch && debugfmt.output == FmtOutput::Stderr {
+                        writeln!(out, "\nOutput of `std::fmt::Debug`:\n")?;
+                    }
+                    for l in debug.lines() {
+                        writeln!(out, "{}", l)?;
+                    }
+                }
+                Err(e) => print_hexdump(input, &e, usize::MAX),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prints the diff of the debug output of `old` and `new`, with a line of
+    /// its own for every field or element that changed.
+    fn print_debug_fmt_diff(
+        &self,
+        debugfmt: &options::Fmt,
+        binary: &Path,
+        old: &Path,
+        new: &Path,
+    ) -> Result<()> {
+        let debug_fmt = |input: &Path| {
+            self.run_debug_formatter(binary, &debugfmt.build, &debugfmt.target, input)
+                .with_context(|| format!("failed to debug format {}", input.display()))
+        };
+        let (old_debug, new_debug) = (debug_fmt(old)?, debug_fmt(new)?);
+        let lines = diff::lines(&old_debug, &new_debug);
+
+        let mut out: Box<dyn Write> = match debugfmt.output {
+            FmtOutput::Stdout => Box::new(io::stdout()),
+            _ => Box::new(io::stderr()),
+        };
+        if lines.iter().all(|line| matches!(line, diff::Line::Same(_))) {
+            writeln!(
+                out,
+                "The `std::fmt::Debug` output of both inputs is the same"
+            )?;
+            return Ok(());
+        }
+        writeln!(out, "--- {}", strip_current_dir_prefix(old).display())?;
+        writeln!(out, "+++ {}", strip_current_dir_prefix(new).display())?;
+        for line in &lines {
+            writeln!(out, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    /// Prints a `#[test]` function that feeds an input to the code of a fuzz
+    /// target.
+    fn emit_regression_test(&self, debugfmt: &options::Fmt, input: &Path) -> Result<()> {
+        let data =
+            fs::read(input).with_context(|| format!("failed to read {}", input.display()))?;
+
+        let target_path = self.target_path(&debugfmt.target);
+        let closure = fs::read_to_string(&target_path)
+            .ok()
+            .and_then(|source| FuzzTargetClosure::parse(&source));
+        if closure.is_none() {
+            eprintln!(
+                "warning: could not find the `fuzz_target!` closure in {}, \
+                 the test will only define the input",
+                target_path.display()
+            );
+        }
+
+        // The `Debug` output of raw bytes is no more readable than the byte
+        // string literal itself.
+        let debug = match &closure {
+            Some(closure) if !closure.takes_bytes() => self
+                .run_fuzz_target_debug_formatter(&debugfmt.build, &debugfmt.target, input)
+                .ok(),
+            _ => None,
+        };
+
+        let name = format!(
+            "fuzz_{}_{}",
+            debugfmt
+                .target
+                .replace(|c: char| !c.is_ascii_alphanumeric(), "_"),
+            &utils::content_hash(&data)[..8]
+        );
+        let comment = format!(
+            "Regression test for fuzz target `{}`, from {}",
+            debugfmt.target,
+            strip_current_dir_prefix(input).display()
+        );
+        print!(
+            "{}",
+            regression_test::emit(&name, &comment, &data, closure.as_ref(), debug.as_deref())
+        );
+
+        Ok(())
+    }
+
+    pub fn exec_mutate(&self, mutate: &options::Mutate) -> Result<()> {
+        let input = fs::read(&mutate.input)
+            .with_context(|| format!("failed to read {}", mutate.input.display()))?;
+        let max_len = mutate
+            .max_len
+            .unwrap_or(input.len() * 2)
+            .max(input.len())
+            .max(1);
+        let mutator = self.build_mutator(&mutate.build)?;
+
+        let mutants = tempfile::tempdir().context("failed to create temp dir")?;
+        let mut cmd = Command::new(&mutator);
+        cmd.args(self.with_dictionary(&mutate.target, &mutate.args));
+        if let Some(seed) = mutate.seed {
+            cmd.arg(format!("-seed={}", seed));
+        }
+        cmd.arg(&mutate.input)
+            .env("CARGO_FUZZ_MUTANTS_DIR", mutants.path())
+            .env("CARGO_FUZZ_MUTANTS", mutate.count.to_string())
+            .env("CARGO_FUZZ_MUTANTS_MAX_LEN", max_len.to_string())
+            .stdin(Stdio::null());
+        configure_sanitizer_options(&mut cmd, &mutate.build);
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run {:?}", cmd))?;
+        if !output.status.success() {
+            io::stderr().write_all(&output.stderr)?;
+            bail!("the mutator exited with {}", output.status);
+        }
+
+        // The `Debug` output of raw bytes is no more readable than their
+        // hexdump, and decoding the inputs doesn't run the fuzz target itself.
+        let typed = fs::read_to_string(self.target_path(&mutate.target))
+            .ok()
+            .and_then(|source| FuzzTargetClosure::parse(&source))
+            .is_none_or(|closure| !closure.takes_bytes());
+        if typed {
+            self.exec_build(BuildMode::Build, &mutate.build, Some(&mutate.target))?;
+        }
+
+        let inputs = std::iter::once((
+            strip_current_dir_prefix(&mutate.input)
+                .display()
+                .to_string(),
+            mutate.input.clone(),
+        ))
+        .chain((0..mutate.count).map(|i| {
+            (
+                format!("mutant {} of {}", i + 1, mutate.count),
+                mutants.path().join(i.to_string()),
+            )
+        }));
+        for (i, (name, path)) in inputs.enumerate() {
+            let data =
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            if i > 0 {
+                println!();
+            }
+            println!("==> {} ({} bytes) <==\n", name, data.len());
+            print!("{}", utils::hexdump(&data));
+            if typed {
+                match self.run_fuzz_target_debug_formatter(&mutate.build, &mutate.target, &path) {
+                    Ok(debug) => println!("\n{}", debug.trim_end()),
+                    Err(e) => println!("\n{}", output::error(&e.to_string())),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the program that `cargo fuzz mutate` gets mutations from: a
+    /// fuzz target of its own, next to the fuzz targets, that uses the
+    /// libFuzzer of the fuzz package.
+    fn build_mutator(&self, build: &BuildOptions) -> Result<PathBuf> {
+        let manifest_path = self.manifest_path();
+        let metadata = MetadataCommand::new()
+            .manifest_path(&manifest_path)
+            .other_options(vec!["--filter-platform".to_owned(), build.triple.clone()])
+            .exec()
+            .with_context(|| {
+                format!("failed to read the metadata of {}", manifest_path.display())
+            })?;
+        let libfuzzer_sys = metadata
+            .packages
+            .iter()
+            .find(|package| package.name == "libfuzzer-sys")
             .with_context(|| {
                 format!(
-                    "failed to run `cargo fuzz fmt` on input: {}",
-                    debugfmt.input.display()
+                    "{} doesn't depend on libfuzzer-sys",
+                    manifest_path.display()
                 )
             })?;
+        let features = metadata
+            .resolve
+            .as_ref()
+            .and_then(|resolve| {
+                resolve
+                    .nodes
+                    .iter()
+                    .find(|node| node.id == libfuzzer_sys.id)
+            })
+            .map_or_else(Vec::new, |node| node.features.clone());
+        let libfuzzer_sys_dir = libfuzzer_sys
+            .manifest_path
+            .parent()
+            .context("libfuzzer-sys has no package directory")?;
+
+        let target_dir = match self.target_dir(build)? {
+            Some(target_dir) => target_dir,
+            None => self.cargo_target_dir()?,
+        };
+        let package = target_dir.join("cargo-fuzz-mutate");
+        let files = [
+            (
+                PathBuf::from("Cargo.toml"),
+                mutate_toml_template!(libfuzzer_sys_dir.as_str(), features).to_string(),
+            ),
+            (
+                Path::new("src").join("main.rs"),
+                mutate_target_template!().to_string(),
+            ),
+        ];
+        for (file, contents) in files {
+            // Rewriting the same sources would only make cargo rebuild them.
+            let path = package.join(file);
+            if fs::read_to_string(&path).is_ok_and(|old| old == contents) {
+                continue;
+            }
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(&path, contents)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        // The lock file of the fuzz package has the versions of the
+        // dependencies of libfuzzer-sys.
+        let lock = metadata.workspace_root.join("Cargo.lock");
+        if lock.exists() && !package.join("Cargo.lock").exists() {
+            fs::copy(&lock, package.join("Cargo.lock"))
+                .with_context(|| format!("failed to copy {}", lock))?;
+        }
 
-        eprintln!("\nOutput of `std::fmt::Debug`:\n");
-        for l in debug.lines() {
-            eprintln!("{}", l);
+        // None of the features of the fuzz package exist in the mutator.
+        let build = BuildOptions {
+            no_default_features: false,
+            all_features: false,
+            features: None,
+            ..build.clone()
+        };
+        let mut cmd = self.cargo_for("build", &build, &package.join("Cargo.toml"))?;
+        cmd.arg("--target-dir").arg(&target_dir);
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to execute: {:?}", cmd))
+            .fail(Failure::Environment)?;
+        if !status.success() {
+            return Err(Failure::Build.wrap(anyhow!("failed to build the mutator: {:?}", cmd)));
         }
+        Ok(self
+            .fuzz_binary_dir(&build)?
+            .join(format!("cargo-fuzz-mutate{}", env::consts::EXE_SUFFIX)))
+    }
 
-        Ok(())
+    /// The dictionary of a fuzz target: the one of the project configuration,
+    /// or else the one at `dictionary_path` if there is one.
+    fn dictionary_for(&self, target: &str) -> Option<PathBuf> {
+        let configured = Config::load(&self.fuzz_dir)
+            .ok()
+            .flatten()
+            .and_then(|config| config.for_target(Some(target), None).ok())
+            .and_then(|defaults| defaults.dict)
+            .map(|dict| self.fuzz_dir.join(dict));
+        configured.or_else(|| {
+            let dict = self.dictionary_path(target);
+            dict.is_file().then_some(dict)
+        })
+    }
+
+    /// Returns the libFuzzer arguments `args` with a `-dict=` for the
+    /// dictionary of a fuzz target, unless they pass one already.
+    fn with_dictionary(&self, target: &str, args: &[String]) -> Vec<String> {
+        let mut args = args.to_vec();
+        if !args.iter().any(|arg| arg.starts_with("-dict=")) {
+            if let Some(dict) = self.dictionary_for(target) {
+                args.insert(0, format!("-dict={}", dict.display()));
+            }
+        }
+        args
+    }
+
+    /// Returns the `cargo fuzz run` command line that reproduces `artifact`.
+    fn reproduce_command(
+        &self,
+        options: &dyn stdfmt::Display,
+        target: &str,
+        artifact: &Path,
+    ) -> String {
+        let fuzz_dir = if self.fuzz_dir_is_default_path() {
+            String::new()
+        } else {
+            format!(" --fuzz-dir {}", self.fuzz_dir().display())
+        };
+        let dict = match self.dictionary_for(target) {
+            Some(dict) => format!(" -- -dict={}", strip_current_dir_prefix(&dict).display()),
+            None => String::new(),
+        };
+        format!(
+            "cargo fuzz run{fuzz_dir}{options} {target} {artifact}{dict}",
+            fuzz_dir = &fuzz_dir,
+            options = options,
+            target = target,
+            artifact = artifact.display(),
+            dict = dict
+        )
+    }
+
+    /// Writes `<artifact>.meta.json`, recording how `artifact` was found so it
+    /// can still be reproduced long after the fact. Failures only print a
+    /// warning, since the artifact itself is what matters.
+    fn write_artifact_metadata(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        artifact: &Path,
+        captured: &CapturedStderr,
+    ) {
+        let metadata = self.artifact_metadata(build, target, artifact, captured);
+        let path = artifact_metadata_path(artifact);
+        let written = serde_json::to_vec_pretty(&metadata)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(fs::write(&path, json)?));
+        if let Err(e) = written {
+            eprintln!(
+                "warning: failed to write artifact metadata to {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+
+    /// The metadata of `artifact`: how to reproduce it, the toolchain, build
+    /// options and commit it was found with, and the backtrace in `captured`.
+    fn artifact_metadata(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        artifact: &Path,
+        captured: &CapturedStderr,
+    ) -> serde_json::Value {
+        let output = captured.output();
+        let git_commit = git_output(self.fuzz_dir(), &["rev-parse", "HEAD"]);
+        let created = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs());
+
+        serde_json::json!({
+            "target": target,
+            "reproduce": self.reproduce_command(build, target, artifact),
+            "build_options": {
+                "flags": build.to_string().trim(),
+                "dev": build.dev,
+                "release": build.release,
+                "debug_assertions": build.debug_assertions,
+                "sanitizer": build.sanitizer.to_string(),
+                "triple": build.triple,
+                "features": build.features,
+                "no_default_features": build.no_default_features,
+                "all_features": build.all_features,
+                "careful": build.careful_mode,
+            },
+            "rustc": crate::rustc_version::rust_version_string().ok().map(|v| v.trim().to_owned()),
+            "seed": captured.seed,
+            "git_commit": git_commit,
+            "created": created,
+            "backtrace": stacktrace::crash_excerpt(&output),
+        })
+    }
+
+    /// Builds a fuzz target and fuzzes it over its corpus with the libFuzzer
+    /// options `args`, capturing what it prints instead of reporting on it
+    /// like `cargo fuzz run` does.
+    pub fn fuzz(&self, build: &BuildOptions, target: &str, args: &[String]) -> Result<FuzzOutcome> {
+        self.exec_build(BuildMode::Build, build, Some(target))?;
+        let mut cmd = self.cargo_run(build, target)?;
+        cmd.args(self.with_dictionary(target, &with_slow_unit_threshold(args)))
+            .arg(runner_path(build, &self.corpus_for(target)?)?)
+            .stdin(Stdio::null());
+
+        let before_fuzzing = time::SystemTime::now();
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        let mut artifacts: Vec<_> = self
+            .get_artifacts_since(target, &before_fuzzing)?
+            .into_iter()
+            .collect();
+        artifacts.sort();
+        Ok(FuzzOutcome {
+            status: output.status,
+            artifacts,
+            stats: stats::last_status(&stderr),
+            stderr,
+        })
     }
 
     /// Fuzz a given fuzz target
     pub fn exec_fuzz(&self, run: &options::Run) -> Result<()> {
+        if run.regression {
+            return self.exec_regression(run);
+        }
+        if !run.feature_sets.is_empty() {
+            return self.exec_feature_sets(run);
+        }
+        if !run.ensemble.is_empty() {
+            return self.exec_ensemble(run);
+        }
+        if run.stability_check {
+            return self.exec_stability_check(run);
+        }
+        if run.worker_logs {
+            return self.exec_workers(run);
+        }
+
         self.exec_build(BuildMode::Build, &run.build, Some(&run.target))?;
         let mut cmd = self.cargo_run(&run.build, &run.target)?;
 
-        for arg in &run.args {
+        for arg in self.with_dictionary(&run.target, &with_slow_unit_threshold(&run.args)) {
             cmd.arg(arg);
         }
 
         if !run.corpus.is_empty() {
             for corpus in &run.corpus {
-                cmd.arg(corpus);
+                cmd.arg(runner_path(&run.build, Path::new(corpus))?);
             }
         } else {
-            cmd.arg(self.corpus_for(&run.target)?);
+            cmd.arg(runner_path(&run.build, &self.corpus_for(&run.target)?)?);
         }
 
-        if run.jobs != 1 {
+        let ignored = run.ignored();
+        if run.jobs != 1 || !ignored.is_empty() {
+            if is_wasi(&run.build) {
+                bail!(
+                    "WASI fuzz targets can't start the processes of `--jobs` and `--ignore-*`, \
+                     add `--worker-logs` to run the workers separately"
+                );
+            }
             cmd.arg(format!("-fork={}", run.jobs));
+            cmd.args(ignored.iter().map(|(_, flag)| flag));
+        }
+
+        let hooks = match Config::load(&self.fuzz_dir)? {
+            Some(config) => {
+                config
+                    .for_target(Some(&run.target), run.build.profile.as_deref())?
+                    .hooks
+            }
+            None => Hooks::default(),
+        };
+        if let Some(pre_run) = &hooks.pre_run {
+            self.run_hook("pre-run", pre_run, &run.target, |_| {})?;
         }
 
         // When libfuzzer finds failing inputs, those inputs will end up in the
@@ -499,213 +1878,3806 @@ impl FuzzProject {
         // after now.
         let before_fuzzing = time::SystemTime::now();
 
-        let mut child = cmd
-            .spawn()
-            .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
-        let status = child
-            .wait()
-            .with_context(|| format!("failed to wait on child process for command: {:?}", cmd))?;
-        if status.success() {
+        let mut recorder = stats::Recorder::new(&self.stats_for(&run.target))?;
+        let session = self.start_session(run, &before_fuzzing);
+        let mut recovered = RecoveredErrors::default();
+        let progress = Mutex::new(stats::Status::default());
+        let mut on_line = |line: &str| {
+            recorder.observe(line);
+            if let Some(status) = stats::Status::parse(line) {
+                progress.lock().unwrap().update(status);
+            }
+            if run.build.sanitizer_recover {
+                recovered.observe(line);
+            }
+        };
+        let metrics_listener = run.metrics_addr.map(metrics::listen).transpose()?;
+        let (status, captured, ending) = thread::scope(|scope| {
+            // The summaries and notifications come from threads of their own,
+            // so that they keep coming while libFuzzer is quiet. Dropping
+            // `stop` ends them.
+            let (stop, stopped) = mpsc::channel::<()>();
+            let (stop_serving, serving_stopped) = mpsc::channel::<()>();
+            if let Some(listener) = metrics_listener {
+                let progress = &progress;
+                scope.spawn(move || {
+                    metrics::serve(listener, serving_stopped, || {
+                        let artifacts = self.artifact_counts_since(&run.target, &before_fuzzing);
+                        metrics::render(&run.target, &progress.lock().unwrap(), &artifacts)
+                    })
+                });
+            }
+            if let Some(interval) = run.progress {
+                let progress = &progress;
+                scope.spawn(move || {
+                    let start = time::Instant::now();
+                    let interval = time::Duration::from_secs(interval);
+                    while stopped.recv_timeout(interval) == Err(RecvTimeoutError::Timeout) {
+                        let artifacts = self
+                            .get_artifacts_since(&run.target, &before_fuzzing)
+                            .map_or(0, |artifacts| artifacts.len());
+                        let status = progress.lock().unwrap();
+                        eprintln!(
+                            "{}",
+                            stats::progress_line(start.elapsed(), &status, artifacts)
+                        );
+                    }
+                });
+            }
+            // The fuzz target only keeps going after an artifact with
+            // `--ignore-*`, otherwise the notification of its end tells.
+            let (stop_notifying, notifying_stopped) = mpsc::channel::<()>();
+            if run.notify && !ignored.is_empty() {
+                scope.spawn(move || {
+                    self.notify_new_artifacts(&run.target, &before_fuzzing, notifying_stopped)
+                });
+            }
+            let result = if run.tui {
+                self.spawn_with_dashboard(&mut cmd, run, &before_fuzzing, &mut on_line)
+            } else {
+                spawn_and_capture_stderr(&mut cmd, &mut on_line)
+                    .map(|(status, captured)| (status, captured, tui::Ending::Exited))
+            };
+            drop(stop);
+            drop(stop_serving);
+            drop(stop_notifying);
+            result
+        })?;
+        // Stopping the fuzz target from the dashboard interrupts it like
+        // Ctrl-C, which isn't a failure.
+        let ignored: Vec<_> = ignored.iter().map(|(category, _)| *category).collect();
+        // libFuzzer exits with an error after the failures it ignored, too.
+        let only_ignored = !ignored.is_empty() && {
+            let failures: Vec<_> = self
+                .get_artifacts_since(&run.target, &before_fuzzing)?
+                .iter()
+                .map(|artifact| artifact_category(artifact))
+                .filter(|&category| category != "slow-unit")
+                .collect();
+            !failures.is_empty() && failures.iter().all(|category| ignored.contains(category))
+        };
+        let succeeded = status.success()
+            || only_ignored
+            || (ending != tui::Ending::Exited && tui::interrupted(&status));
+        if let Err(e) = recorder.finish(!succeeded) {
+            eprintln!("warning: failed to record the stats of this run: {:#}", e);
+        }
+        if let Some((sessions, id)) = session {
+            let status = progress.lock().unwrap().clone();
+            self.finish_session(sessions, id, run, &before_fuzzing, &status, !succeeded);
+        }
+        if run.notify {
+            self.notify_end(&run.target, &before_fuzzing, &status, succeeded);
+        }
+        print_recovered_errors(&recovered);
+        self.print_new_slow_units(&run.target, &before_fuzzing);
+        self.print_ignored_artifacts(&run.target, &before_fuzzing, &ignored);
+        let set_exit_status = |hook: &mut Command| {
+            if let Some(code) = status.code() {
+                hook.env("CARGO_FUZZ_EXIT_STATUS", code.to_string());
+            }
+        };
+        if succeeded {
+            if let Some(post_run) = &hooks.post_run {
+                self.run_hook("post-run", post_run, &run.target, set_exit_status)?;
+            }
+            if ending == tui::Ending::Minimize {
+                self.exec_cmin(&options::Cmin {
+                    build: run.build.clone(),
+                    fuzz_dir_wrapper: run.fuzz_dir_wrapper.clone(),
+                    target: run.target.clone(),
+                    corpus: run.corpus.first().map(PathBuf::from),
+                    backup_dir: None,
+                    no_backup: false,
+                    args: Vec::new(),
+                })?;
+            }
+            if !recovered.errors.is_empty() {
+                return Err(Failure::Crash.wrap(anyhow!(
+                    "Fuzz target reported {} distinct sanitizer error(s)",
+                    recovered.errors.len()
+                )));
+            }
             return Ok(());
         }
 
+        print_folded_backtrace(&captured);
+
         // Get and print the `Debug` formatting of any new artifacts, along with
         // tips about how to reproduce failures and/or minimize test cases.
 
         let new_artifacts = self.get_artifacts_since(&run.target, &before_fuzzing)?;
 
         for artifact in new_artifacts {
+            // Slow units and the ignored failures are written along the way
+            // and aren't what failed.
+            let category = artifact_category(&artifact);
+            if category == "slow-unit" || ignored.contains(&category) {
+                continue;
+            }
             // To make the artifact a little easier to read, strip the current
             // directory prefix when possible.
             let artifact = strip_current_dir_prefix(&artifact);
-
-            eprintln!("\n{:─<80}", "");
-            eprintln!("\nFailing input:\n\n\t{}\n", artifact.display());
-
-            // Note: ignore errors when running the debug formatter. This most
-            // likely just means that we're dealing with a fuzz target that uses
-            // an older version of the libfuzzer crate, and doesn't support
-            // `RUST_LIBFUZZER_DEBUG_PATH`.
-            if let Ok(debug) =
-                self.run_fuzz_target_debug_formatter(&run.build, &run.target, artifact)
-            {
-                eprintln!("Output of `std::fmt::Debug`:\n");
-                for l in debug.lines() {
-                    eprintln!("\t{}", l);
+            self.print_failing_input(&run.build, &run.limits, &run.target, artifact, &captured);
+
+            if let Some(on_crash) = &hooks.on_crash {
+                let result = self.run_hook("on-crash", on_crash, &run.target, |hook| {
+                    set_exit_status(hook);
+                    hook.env("CARGO_FUZZ_ARTIFACT", artifact);
+                });
+                if let Err(e) = result {
+                    eprintln!("warning: {:#}", e);
                 }
-                eprintln!();
             }
+        }
 
-            let fuzz_dir = if self.fuzz_dir_is_default_path() {
-                String::new()
-            } else {
-                format!(" --fuzz-dir {}", self.fuzz_dir().display())
-            };
-
-            eprintln!(
-                "Reproduce with:\n\n\tcargo fuzz run{fuzz_dir}{options} {target} {artifact}\n",
-                fuzz_dir = &fuzz_dir,
-                options = &run.build,
-                target = &run.target,
-                artifact = artifact.display()
-            );
-            eprintln!(
-                "Minimize test case with:\n\n\tcargo fuzz tmin{fuzz_dir}{options} {target} {artifact}\n",
-                fuzz_dir = &fuzz_dir,
-                options = &run.build,
-                target = &run.target,
-                artifact = artifact.display()
-            );
+        eprintln!("{}\n", output::separator());
+        if let Some(post_run) = &hooks.post_run {
+            if let Err(e) = self.run_hook("post-run", post_run, &run.target, set_exit_status) {
+                eprintln!("warning: {:#}", e);
+            }
         }
+        Err(Failure::Crash.wrap(anyhow!("Fuzz target exited with {}", status)))
+    }
 
-        eprintln!("{:─<80}\n", "");
-        bail!("Fuzz target exited with {}", status)
+    /// Records the start of `run` in the session database, which only warns
+    /// when it fails, like the stats. Without the `sessions` feature, runs
+    /// aren't recorded at all.
+    fn start_session(
+        &self,
+        run: &options::Run,
+        started: &time::SystemTime,
+    ) -> Option<(Sessions, i64)> {
+        if !cfg!(feature = "sessions") {
+            return None;
+        }
+        let mut options = format!("{}{}", run.build, run.limits);
+        if run.jobs != 1 {
+            options.push_str(&format!(" --jobs={}", run.jobs));
+        }
+        let git_commit = git_output(self.fuzz_dir(), &["rev-parse", "HEAD"]);
+        let started = started
+            .duration_since(time::UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs());
+        let result = Sessions::open(&self.sessions_db()).and_then(|sessions| {
+            let id = sessions.start_run(&sessions::RunStart {
+                target: &run.target,
+                git_commit: git_commit.as_deref(),
+                started,
+                options: &options,
+                args: &run.args,
+            })?;
+            Ok((sessions, id))
+        });
+        match result {
+            Ok(session) => Some(session),
+            Err(e) => {
+                eprintln!("warning: failed to record this run: {:#}", e);
+                None
+            }
+        }
     }
 
-    pub fn exec_tmin(&self, tmin: &options::Tmin) -> Result<()> {
-        self.exec_build(BuildMode::Build, &tmin.build, Some(&tmin.target))?;
-        let mut cmd = self.cargo_run(&tmin.build, &tmin.target)?;
-        cmd.arg("-minimize_crash=1")
-            .arg(format!("-runs={}", tmin.runs))
-            .arg(&tmin.test_case);
+    /// Records how the run `id` ended in the session database, along with
+    /// the artifacts it wrote since `since`.
+    fn finish_session(
+        &self,
+        mut sessions: Sessions,
+        id: i64,
+        run: &options::Run,
+        since: &time::SystemTime,
+        status: &stats::Status,
+        failed: bool,
+    ) {
+        let mut artifacts: Vec<_> = self
+            .get_artifacts_since(&run.target, since)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|artifact| {
+                let category = artifact_category(&artifact);
+                (
+                    strip_current_dir_prefix(&artifact).display().to_string(),
+                    category,
+                )
+            })
+            .collect();
+        artifacts.sort();
+        let result = sessions.finish_run(
+            id,
+            &sessions::RunEnd {
+                elapsed_secs: since.elapsed().map_or(0, |elapsed| elapsed.as_secs()),
+                status,
+                failed,
+                artifacts: &artifacts,
+            },
+        );
+        if let Err(e) = result {
+            eprintln!("warning: failed to record this run: {:#}", e);
+        }
+    }
 
-        for arg in &tmin.args {
-            cmd.arg(arg);
+    /// Shows a desktop notification whenever libFuzzer writes a new artifact,
+    /// other than a slow unit, until `stopped` disconnects.
+    fn notify_new_artifacts(
+        &self,
+        target: &str,
+        since: &time::SystemTime,
+        stopped: mpsc::Receiver<()>,
+    ) {
+        let interval = time::Duration::from_secs(5);
+        let mut seen = 0;
+        while stopped.recv_timeout(interval) == Err(RecvTimeoutError::Timeout) {
+            let artifacts = self.get_failures_since(target, since);
+            if artifacts.len() > seen {
+                seen = artifacts.len();
+                notify::send(
+                    &format!("cargo fuzz run {}: new artifact", target),
+                    &format!(
+                        "{} artifact(s) so far, the last {}",
+                        seen,
+                        artifacts.last().expect("there are artifacts").display()
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Shows a desktop notification that the fuzz target stopped with
+    /// `status`, along with the number of artifacts it wrote since `since`.
+    fn notify_end(
+        &self,
+        target: &str,
+        since: &time::SystemTime,
+        status: &ExitStatus,
+        succeeded: bool,
+    ) {
+        let artifacts = self.get_failures_since(target, since).len();
+        let summary = if succeeded {
+            format!("cargo fuzz run {} finished", target)
+        } else {
+            format!("cargo fuzz run {} failed", target)
+        };
+        notify::send(
+            &summary,
+            &format!("Exited with {}, found {} artifact(s)", status, artifacts),
+        );
+    }
+
+    /// Returns the artifacts other than slow units that libFuzzer wrote since
+    /// `since`, oldest first.
+    fn get_failures_since(&self, target: &str, since: &time::SystemTime) -> Vec<PathBuf> {
+        let mut artifacts: Vec<_> = self
+            .get_artifacts_since(target, since)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|artifact| artifact_category(artifact) != "slow-unit")
+            .collect();
+        artifacts.sort_by_key(|artifact| fs::metadata(artifact).and_then(|m| m.modified()).ok());
+        artifacts
+    }
+
+    /// Counts the artifacts that libFuzzer wrote since `since`, by category.
+    fn artifact_counts_since(
+        &self,
+        target: &str,
+        since: &time::SystemTime,
+    ) -> BTreeMap<&'static str, usize> {
+        let mut counts = BTreeMap::new();
+        for artifact in self.get_artifacts_since(target, since).unwrap_or_default() {
+            *counts.entry(artifact_category(&artifact)).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Points out the slow units that libFuzzer wrote since `since`, which
+    /// don't stop a run.
+    fn print_new_slow_units(&self, target: &str, since: &time::SystemTime) {
+        let slow_units = self
+            .get_artifacts_since(target, since)
+            .map_or(0, |artifacts| {
+                artifacts
+                    .iter()
+                    .filter(|artifact| artifact_category(artifact) == "slow-unit")
+                    .count()
+            });
+        if slow_units > 0 {
+            eprintln!(
+                "Found {} slow unit(s), list them with `cargo fuzz slow {}`",
+                slow_units, target
+            );
         }
+    }
 
-        let before_tmin = time::SystemTime::now();
+    /// Sums up the artifacts of the `ignored` categories that libFuzzer wrote
+    /// since `since`, which it kept fuzzing after.
+    fn print_ignored_artifacts(&self, target: &str, since: &time::SystemTime, ignored: &[&str]) {
+        if ignored.is_empty() {
+            return;
+        }
+        let mut counts = self.artifact_counts_since(target, since);
+        counts.retain(|category, _| ignored.contains(category));
+        let ignoring = ignored.join(", ");
+        if counts.is_empty() {
+            eprintln!("Ignoring {}: no such artifacts", ignoring);
+            return;
+        }
+        let counts = counts
+            .iter()
+            .map(|(category, count)| format!("{} {}", count, category))
+            .collect::<Vec<_>>()
+            .join(", ");
+        eprintln!(
+            "{}",
+            output::error(&format!(
+                "Ignoring {}: kept fuzzing after {} artifact(s), list them with \
+                 `cargo fuzz artifacts list {}`",
+                ignoring, counts, target
+            ))
+        );
+    }
 
+    /// Runs the fuzz target of `cmd` with the dashboard of `--tui` showing its
+    /// output, which goes to `on_line` and is captured like with
+    /// `spawn_and_capture_stderr`.
+    fn spawn_with_dashboard(
+        &self,
+        cmd: &mut Command,
+        run: &options::Run,
+        before_fuzzing: &time::SystemTime,
+        mut on_line: impl FnMut(&str),
+    ) -> Result<(ExitStatus, CapturedStderr, tui::Ending)> {
+        // Pausing and stopping signal the process group, which also takes
+        // in the processes of `-fork` mode.
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(cmd, 0);
         let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
             .spawn()
             .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
-        let status = child
-            .wait()
-            .with_context(|| format!("failed to wait on child process for command: {:?}", cmd))?;
-        if !status.success() {
-            eprintln!("\n{:─<80}\n", "");
-            return Err(anyhow!("Command `{:?}` exited with {}", cmd, status)).with_context(|| {
-                "Test case minimization failed.\n\
-                 \n\
-                 Usually this isn't a hard error, and just means that libfuzzer\n\
-                 doesn't know how to minimize the test case any further while\n\
-                 still reproducing the original crash.\n\
-                 \n\
-                 See the logs above for details."
-            });
+
+        let (sender, output) = mpsc::channel();
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+        send_lines(stdout, sender.clone(), tui::Output::Stdout);
+        send_lines(stderr, sender, tui::Output::Stderr);
+
+        let mut captured = CapturedStderr::default();
+        let mut dashboard = tui::Dashboard::new(format!("cargo fuzz run {}", run.target));
+        let (status, ending) = tui::run(
+            &mut child,
+            &output,
+            &mut dashboard,
+            |line| {
+                on_line(line);
+                captured.push(line.to_owned());
+            },
+            || {
+                self.get_artifacts_since(&run.target, before_fuzzing)
+                    .map_or(0, |artifacts| artifacts.len())
+            },
+        )?;
+        Ok((status, captured, ending))
+    }
+
+    /// Prints the `Debug` formatting of a new artifact, along with tips about
+    /// how to reproduce the failure and minimize the test case, and records
+    /// how it was found.
+    fn print_failing_input(
+        &self,
+        build: &BuildOptions,
+        limits: &options::Limits,
+        target: &str,
+        artifact: &Path,
+        captured: &CapturedStderr,
+    ) {
+        eprintln!("\n{}", output::separator());
+        eprintln!(
+            "\n{}\n\n\t{}\n",
+            output::error("Failing input:"),
+            artifact.display()
+        );
+
+        self.print_debug_fmt(build, target, artifact);
+
+        self.write_artifact_metadata(build, target, artifact, captured);
+
+        let fuzz_dir = if self.fuzz_dir_is_default_path() {
+            String::new()
+        } else {
+            format!(" --fuzz-dir {}", self.fuzz_dir().display())
+        };
+
+        let category = artifact_category(artifact);
+        if let Some(advice) = limit_advice(category, limits) {
+            eprintln!("{}\n", advice);
+        }
+        let options = format!("{}{}", build, limits);
+        eprintln!(
+            "{}\n\n\t{}\n",
+            output::heading("Reproduce with:"),
+            output::command(&self.reproduce_command(&options, target, artifact))
+        );
+        let kind = match category {
+            "timeout" | "oom" => format!(" --kind={}", category),
+            _ => String::new(),
+        };
+        eprintln!(
+            "{}\n\n\t{}\n",
+            output::heading("Minimize test case with:"),
+            output::command(&format!(
+                "cargo fuzz tmin{fuzz_dir}{options}{kind} {target} {artifact}",
+                fuzz_dir = &fuzz_dir,
+                options = options,
+                kind = kind,
+                target = target,
+                artifact = artifact.display()
+            ))
+        );
+    }
+
+    /// Fuzzes the fuzz target with a build for each of the sanitizers of
+    /// `run` at once, all on the same corpus. libFuzzer reloads the corpus
+    /// periodically, so every build picks up what the others find. Stops
+    /// all of them once one crashes.
+    fn exec_ensemble(&self, run: &options::Run) -> Result<()> {
+        let base_dir = match &run.build.target_dir {
+            Some(target_dir) => PathBuf::from(target_dir),
+            None => self.cargo_target_dir()?,
+        };
+        let mut members = Vec::new();
+        for &sanitizer in &run.ensemble {
+            let name = match sanitizer {
+                Sanitizer::None => "none".to_owned(),
+                sanitizer => sanitizer.to_string(),
+            };
+            // Each build gets a target directory of its own, so that they
+            // don't overwrite each other's binaries.
+            let build = BuildOptions {
+                sanitizer,
+                target_dir: Some(base_dir.join("ensemble").join(&name).display().to_string()),
+                ..run.build.clone()
+            };
+            self.exec_build(BuildMode::Build, &build, Some(&run.target))?;
+            members.push((name, build));
         }
 
-        // Find and display the most recently modified artifact, which is
-        // presumably the result of minification. Yeah, this is a little hacky,
-        // but it seems to work. I don't want to parse libfuzzer's stderr output
-        // and hope it never changes.
-        let minimized_artifact = self
-            .get_artifacts_since(&tmin.target, &before_tmin)?
-            .into_iter()
-            .max_by_key(|a| {
-                a.metadata()
-                    .and_then(|m| m.modified())
-                    .unwrap_or(time::SystemTime::UNIX_EPOCH)
-            });
+        let corpora = if run.corpus.is_empty() {
+            vec![self.corpus_for(&run.target)?.into_os_string()]
+        } else {
+            run.corpus.iter().map(ffi::OsString::from).collect()
+        };
+        let args = self.with_dictionary(&run.target, &with_slow_unit_threshold(&run.args));
+        let before_fuzzing = time::SystemTime::now();
+        let mut children = Vec::new();
+        for (name, build) in &members {
+            let mut cmd = self.cargo_run(build, &run.target)?;
+            cmd.args(&args).args(&corpora).stderr(Stdio::piped());
+            let child = cmd
+                .spawn()
+                .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
+            children.push((name, build, child));
+        }
+
+        let (results, crashed) = thread::scope(|scope| -> Result<_> {
+            let readers: Vec<_> = children
+                .iter_mut()
+                .map(|(name, _, child)| {
+                    let stderr = child.stderr.take().expect("stderr is piped");
+                    let prefix = format!("[{}] ", name);
+                    scope.spawn(move || forward_and_capture(stderr, &prefix, |_| {}))
+                })
+                .collect();
+            let mut processes: Vec<_> = children.iter_mut().map(|(_, _, child)| child).collect();
+            let (statuses, crashed) = wait_until_first_failure(&mut processes)?;
+            let results = readers
+                .into_iter()
+                .zip(statuses)
+                .map(|(reader, status)| Ok((reader.join().unwrap()?, status)))
+                .collect::<Result<Vec<_>>>()?;
+            Ok((results, crashed))
+        })?;
 
-        if let Some(artifact) = minimized_artifact {
+        let Some(crashed) = crashed else {
+            return Ok(());
+        };
+        let (name, build, _) = &children[crashed];
+        let (captured, status) = &results[crashed];
+        eprintln!(
+            "\n{}",
+            output::error(&format!("The `{}` build of `{}` crashed", name, run.target))
+        );
+        print_folded_backtrace(captured);
+        for artifact in self.get_artifacts_since(&run.target, &before_fuzzing)? {
             let artifact = strip_current_dir_prefix(&artifact);
+            self.print_failing_input(build, &run.limits, &run.target, artifact, captured);
+        }
+        eprintln!("{}\n", output::separator());
+        Err(Failure::Crash.wrap(anyhow!("Fuzz target exited with {}", status)))
+    }
 
-            eprintln!("\n{:─<80}\n", "");
-            eprintln!("Minimized artifact:\n\n\t{}\n", artifact.display());
+    /// Fuzzes the fuzz target with `--jobs` workers on the same corpus, each
+    /// with its output in a log file of its own and only a summary of their
+    /// progress on the console. Stops all of them once one crashes.
+    fn exec_workers(&self, run: &options::Run) -> Result<()> {
+        self.exec_build(BuildMode::Build, &run.build, Some(&run.target))?;
+        let corpora = if run.corpus.is_empty() {
+            vec![self.corpus_for(&run.target)?.into_os_string()]
+        } else {
+            run.corpus.iter().map(ffi::OsString::from).collect()
+        };
+        let args = self.with_dictionary(&run.target, &with_slow_unit_threshold(&run.args));
+        let logs_dir = self.logs_for(&run.target);
+        let before_fuzzing = time::SystemTime::now();
+        let mut children = Vec::new();
+        let mut outputs = Vec::new();
+        for i in 0..run.jobs {
+            let log_path = logs_dir.join(format!("worker-{}.log", i));
+            let log = logs::RotatingLog::create(&log_path, run.max_log_size << 20)?;
+            let mut cmd = self.cargo_run(&run.build, &run.target)?;
+            cmd.args(&args).args(&corpora).stderr(Stdio::piped());
+            let mut child = cmd
+                .spawn()
+                .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
+            let stderr = child.stderr.take().expect("stderr is piped");
+            children.push((log_path, child));
+            outputs.push((stderr, log));
+        }
+        eprintln!(
+            "Fuzzing `{}` with {} worker(s), logging to {}",
+            run.target,
+            run.jobs,
+            logs_dir.display()
+        );
 
-            // Note: ignore errors when running the debug formatter. This most
-            // likely just means that we're dealing with a fuzz target that uses
-            // an older version of the libfuzzer crate, and doesn't support
-            // `RUST_LIBFUZZER_DEBUG_PATH`.
-            if let Ok(debug) =
-                self.run_fuzz_target_debug_formatter(&tmin.build, &tmin.target, artifact)
-            {
-                eprintln!("Output of `std::fmt::Debug`:\n");
-                for l in debug.lines() {
-                    eprintln!("\t{}", l);
-                }
-                eprintln!();
+        let progress = Mutex::new(vec![stats::Status::default(); children.len()]);
+        let metrics_listener = run.metrics_addr.map(metrics::listen).transpose()?;
+        let (results, crashed) = thread::scope(|scope| -> Result<_> {
+            let readers: Vec<_> = outputs
+                .into_iter()
+                .enumerate()
+                .map(|(i, (stderr, mut log))| {
+                    let progress = &progress;
+                    scope.spawn(move || {
+                        log_and_capture(stderr, &mut log, |line| {
+                            if let Some(status) = stats::Status::parse(line) {
+                                progress.lock().unwrap()[i].update(status);
+                            }
+                        })
+                    })
+                })
+                .collect();
+
+            let (stop, stopped) = mpsc::channel::<()>();
+            let progress = &progress;
+            let before_fuzzing = &before_fuzzing;
+            let (stop_serving, serving_stopped) = mpsc::channel::<()>();
+            if let Some(listener) = metrics_listener {
+                scope.spawn(move || {
+                    metrics::serve(listener, serving_stopped, || {
+                        let artifacts = self.artifact_counts_since(&run.target, before_fuzzing);
+                        let status = stats::combine(&progress.lock().unwrap());
+                        metrics::render(&run.target, &status, &artifacts)
+                    })
+                });
             }
+            scope.spawn(move || {
+                let start = time::Instant::now();
+                let interval = time::Duration::from_secs(run.progress.unwrap_or(10));
+                while stopped.recv_timeout(interval) == Err(RecvTimeoutError::Timeout) {
+                    let artifacts = self
+                        .get_artifacts_since(&run.target, before_fuzzing)
+                        .map_or(0, |artifacts| artifacts.len());
+                    let status = stats::combine(&progress.lock().unwrap());
+                    eprintln!(
+                        "{}",
+                        stats::progress_line(start.elapsed(), &status, artifacts)
+                    );
+                }
+            });
+
+            let mut processes: Vec<_> = children.iter_mut().map(|(_, child)| child).collect();
+            let waited = wait_until_first_failure(&mut processes);
+            drop(stop);
+            drop(stop_serving);
+            let (statuses, crashed) = waited?;
+            let results = readers
+                .into_iter()
+                .zip(statuses)
+                .map(|(reader, status)| Ok((reader.join().unwrap()?, status)))
+                .collect::<Result<Vec<_>>>()?;
+            Ok((results, crashed))
+        })?;
+
+        let Some(crashed) = crashed else {
+            return Ok(());
+        };
+        let (log_path, _) = &children[crashed];
+        let (captured, status) = &results[crashed];
+        eprintln!(
+            "\nWorker {} of `{}` crashed, see {} for its output",
+            crashed,
+            run.target,
+            log_path.display()
+        );
+        print_folded_backtrace(captured);
+        for artifact in self.get_artifacts_since(&run.target, &before_fuzzing)? {
+            let artifact = strip_current_dir_prefix(&artifact);
+            self.print_failing_input(&run.build, &run.limits, &run.target, artifact, captured);
+        }
+        eprintln!("{}\n", output::separator());
+        Err(Failure::Crash.wrap(anyhow!("Fuzz target exited with {}", status)))
+    }
 
-            let fuzz_dir = if self.fuzz_dir_is_default_path() {
-                String::new()
+    /// Builds and fuzzes the fuzz target with each of the feature sets of
+    /// `run` in turn, stopping at the first that fails.
+    fn exec_feature_sets(&self, run: &options::Run) -> Result<()> {
+        for features in &run.feature_sets {
+            let features: Vec<_> = features
+                .split(',')
+                .map(str::trim)
+                .filter(|feature| !feature.is_empty())
+                .collect();
+            let name = if features.is_empty() {
+                "default".to_owned()
             } else {
-                format!(" --fuzz-dir {}", self.fuzz_dir().display())
+                features.join("+")
             };
+            eprintln!("Fuzzing `{}` with the features: {}", run.target, name);
+
+            let mut run = run.clone();
+            run.feature_sets.clear();
+            run.build.features = (!features.is_empty()).then(|| features.join(","));
+            if run.separate_corpora {
+                let corpus = self
+                    .fuzz_dir()
+                    .join("feature-corpora")
+                    .join(&run.target)
+                    .join(&name);
+                fs::create_dir_all(&corpus).with_context(|| {
+                    format!("could not make a corpus directory at {}", corpus.display())
+                })?;
+                run.corpus = vec![corpus.display().to_string()];
+            }
+            self.exec_fuzz(&run)
+                .with_context(|| format!("fuzzing with the features `{}` failed", name))?;
+        }
+        Ok(())
+    }
 
-            eprintln!(
-                "Reproduce with:\n\n\tcargo fuzz run{fuzz_dir}{options} {target} {artifact}\n",
-                fuzz_dir = &fuzz_dir,
-                options = &tmin.build,
-                target = &tmin.target,
-                artifact = artifact.display()
+    /// Replays a sample of the corpus `--stability-runs` times, and reports
+    /// the inputs whose coverage differs between the runs.
+    fn exec_stability_check(&self, run: &options::Run) -> Result<()> {
+        self.exec_build(BuildMode::Build, &run.build, Some(&run.target))?;
+
+        let corpora = if run.corpus.is_empty() {
+            vec![self.corpus_for(&run.target)?]
+        } else {
+            run.corpus.iter().map(PathBuf::from).collect()
+        };
+        let mut inputs = Vec::new();
+        for corpus in corpora {
+            if corpus.is_dir() {
+                inputs.extend(sorted_files(&corpus)?);
+            } else {
+                inputs.push(corpus);
+            }
+        }
+        if inputs.is_empty() {
+            bail!(
+                "there are no inputs in the corpus of `{}` to replay, fuzz it first",
+                run.target
             );
         }
+        let sample = (run.stability_sample as usize).min(inputs.len());
+        let inputs: Vec<_> = (0..sample)
+            .map(|i| &inputs[i * inputs.len() / sample])
+            .collect();
+
+        let scratch = tempfile::tempdir().context("failed to create temp dir")?;
+        let mut unstable = 0;
+        for input in &inputs {
+            let mut coverage = Vec::new();
+            for i in 0..run.stability_runs {
+                coverage.push(self.replay_coverage(run, input, i > 0, scratch.path())?);
+            }
+            if coverage.windows(2).any(|runs| runs[0] != runs[1]) {
+                unstable += 1;
+                println!(
+                    "{}: coverage/features {}",
+                    strip_current_dir_prefix(input).display(),
+                    coverage
+                        .iter()
+                        .map(|(cov, ft)| format!("{}/{}", cov, ft))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+        }
 
+        if unstable > 0 {
+            bail!(
+                "{} of {} sampled input(s) didn't cover the same code in every run, so \
+                 `{}` depends on time, randomness or global state",
+                unstable,
+                inputs.len(),
+                run.target
+            );
+        }
+        println!(
+            "All {} sampled input(s) covered the same code in {} runs",
+            inputs.len(),
+            run.stability_runs
+        );
         Ok(())
     }
 
-    pub fn exec_cmin(&self, cmin: &options::Cmin) -> Result<()> {
-        self.exec_build(BuildMode::Build, &cmin.build, Some(&cmin.target))?;
-        let mut cmd = self.cargo_run(&cmin.build, &cmin.target)?;
+    /// Runs the fuzz target on `input` in a fresh process, and returns the
+    /// coverage and features libFuzzer counted. Running it `twice` in a row
+    /// shows behavior that depends on what ran before in the process.
+    fn replay_coverage(
+        &self,
+        run: &options::Run,
+        input: &Path,
+        twice: bool,
+        scratch: &Path,
+    ) -> Result<(u64, u64)> {
+        let corpus = scratch.join("corpus");
+        if corpus.exists() {
+            fs::remove_dir_all(&corpus)
+                .with_context(|| format!("failed to remove {}", corpus.display()))?;
+        }
+        fs::create_dir_all(&corpus)
+            .with_context(|| format!("failed to create directory {}", corpus.display()))?;
+        for copy in if twice { &["0", "1"][..] } else { &["0"] } {
+            fs::copy(input, corpus.join(copy))
+                .with_context(|| format!("failed to copy {}", input.display()))?;
+        }
 
-        for arg in &cmin.args {
-            cmd.arg(arg);
+        let mut cmd = self.cargo_run(&run.build, &run.target)?;
+        cmd.args(&run.args)
+            .arg("-runs=0")
+            .arg(format!("-artifact_prefix={}/", scratch.display()))
+            .arg(&corpus)
+            .stdin(Stdio::null());
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run {:?}", cmd))?;
+        if !output.status.success() {
+            bail!(
+                "{} crashes `{}`, so its stability can't be checked",
+                input.display(),
+                run.target
+            );
         }
+        String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .rev()
+            .filter_map(stats::Status::parse)
+            .find_map(|status| Some((status.cov?, status.features?)))
+            .with_context(|| format!("libFuzzer reported no coverage for {}", input.display()))
+    }
 
-        let corpus = if let Some(corpus) = cmin.corpus.clone() {
-            corpus
+    /// Replays every input of the corpus and every artifact, or the inputs
+    /// given on the command line, each in a process of its own so that one
+    /// crash doesn't hide the next.
+    fn exec_regression(&self, run: &options::Run) -> Result<()> {
+        let mut inputs = Vec::new();
+        if run.corpus.is_empty() {
+            inputs.extend(sorted_files(&self.corpus_for(&run.target)?)?);
+            inputs.extend(artifact_files(&self.artifacts_for(&run.target)?)?);
         } else {
-            self.corpus_for(&cmin.target)?
-        };
-        let corpus = corpus
-            .to_str()
-            .ok_or_else(|| anyhow!("corpus must be valid unicode"))?
-            .to_owned();
+            for corpus in run.corpus.iter().map(Path::new) {
+                if corpus.is_dir() {
+                    inputs.extend(sorted_files(corpus)?);
+                } else {
+                    inputs.push(corpus.to_owned());
+                }
+            }
+        }
+        if inputs.is_empty() {
+            eprintln!("No inputs to replay for `{}`", run.target);
+            if let Some(junit) = &run.junit {
+                write_junit(junit, &run.target, &[])?;
+            }
+            return Ok(());
+        }
 
-        let tmp = tempfile::TempDir::new_in(self.fuzz_dir())?;
-        let tmp_corpus = tmp.path().join("corpus");
-        fs::create_dir(&tmp_corpus)?;
+        self.exec_build(BuildMode::Build, &run.build, Some(&run.target))?;
 
-        cmd.arg("-merge=1").arg(&tmp_corpus).arg(&corpus);
+        eprintln!("Replaying {} input(s) of `{}`", inputs.len(), run.target);
+        let mut failures = Vec::new();
+        let mut cases = Vec::new();
+        for input in &inputs {
+            let start = time::Instant::now();
+            let (success, stderr) = self.replay_input(&run.build, &run.target, input, &run.args)?;
+            cases.push(junit::TestCase {
+                name: strip_current_dir_prefix(input).display().to_string(),
+                time: start.elapsed(),
+                failure: (!success).then(|| crash_failure(&stderr)),
+            });
+            if !success {
+                eprintln!("{}: FAILED", strip_current_dir_prefix(input).display());
+                failures.push((input, stderr));
+            }
+        }
 
-        // Spawn cmd in child process instead of exec-ing it
+        for (input, stderr) in &failures {
+            eprintln!("\n{}", output::separator());
+            eprintln!(
+                "\n{}\n\n\t{}\n",
+                output::error("Failing input:"),
+                strip_current_dir_prefix(input).display()
+            );
+            eprintln!(
+                "{}\n\n\t{}\n",
+                output::heading("Failure:"),
+                crash_failure(stderr).message
+            );
+            self.print_debug_fmt(&run.build, &run.target, input);
+        }
+        if !failures.is_empty() {
+            eprintln!("{}\n", output::separator());
+        }
+        if let Some(junit) = &run.junit {
+            write_junit(junit, &run.target, &cases)?;
+        }
+
+        let summary = format!(
+            "Replayed {} input(s): {} passed, {} failed",
+            inputs.len(),
+            inputs.len() - failures.len(),
+            failures.len()
+        );
+        if failures.is_empty() {
+            eprintln!("{}", output::success(&summary));
+        } else {
+            eprintln!("{}", output::error(&summary));
+        }
+        if !failures.is_empty() {
+            return Err(Failure::Crash.wrap(anyhow!("{} input(s) failed", failures.len())));
+        }
+        Ok(())
+    }
+
+    /// Runs a hook script of the project configuration for `target`.
+    fn run_hook(
+        &self,
+        name: &str,
+        hook: &Path,
+        target: &str,
+        configure: impl FnOnce(&mut Command),
+    ) -> Result<()> {
+        let path = self.fuzz_dir.join(hook);
+        let mut cmd = Command::new(&path);
+        cmd.env("CARGO_FUZZ_TARGET", target)
+            .env("CARGO_FUZZ_DIR", &self.fuzz_dir);
+        configure(&mut cmd);
         let status = cmd
             .status()
-            .with_context(|| format!("could not execute command: {:?}", cmd))?;
+            .with_context(|| format!("failed to run the {} hook {}", name, path.display()))?;
+        if !status.success() {
+            bail!(
+                "the {} hook {} exited with {}",
+                name,
+                path.display(),
+                status
+            );
+        }
+        Ok(())
+    }
+
+    pub fn exec_repro(&self, repro: &options::Repro) -> Result<()> {
+        self.exec_build(BuildMode::Build, &repro.build, Some(&repro.target))?;
+
+        self.print_debug_fmt(&repro.build, &repro.target, &repro.artifact);
+
+        let mut cmd = self.cargo_run(&repro.build, &repro.target)?;
+        cmd.args(&repro.args).arg(&repro.artifact);
+        if env::var_os("RUST_BACKTRACE").is_none() {
+            cmd.env("RUST_BACKTRACE", "full");
+        }
+
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
         if status.success() {
-            // move corpus directory into tmp to auto delete it
-            fs::rename(&corpus, tmp.path().join("old"))?;
-            fs::rename(tmp.path().join("corpus"), corpus)?;
-        } else {
-            println!("Failed to minimize corpus: {}", status);
+            eprintln!(
+                "\n{} did not crash `{}`",
+                strip_current_dir_prefix(&repro.artifact).display(),
+                repro.target
+            );
+            return Ok(());
         }
 
-        Ok(())
+        Err(Failure::Reproduced.wrap(anyhow!("Fuzz target exited with {}", status)))
     }
 
-    /// Produce coverage information for a given corpus
-    pub fn exec_coverage(self, coverage: &options::Coverage) -> Result<()> {
-        // Build project with source-based coverage generation enabled.
-        self.exec_build(BuildMode::Build, &coverage.build, Some(&coverage.target))?;
+    pub fn exec_debug(&self, debug: &options::Debug) -> Result<()> {
+        self.exec_build(BuildMode::Build, &debug.build, Some(&debug.target))?;
+        let binary = self.fuzz_binary_path(&debug.build, &debug.target)?;
+
+        // Stop at the panic or the sanitizer report, before the process
+        // unwinds or exits.
+        let breakpoints = ["rust_panic", "__sanitizer::Die"];
+        let mut cmd = Command::new(debug.debugger.to_string());
+        match debug.debugger {
+            Debugger::Gdb => {
+                // The sanitizer runtime may not be loaded yet when the
+                // breakpoints are set, so don't ask whether to defer them.
+                cmd.args(["-ex", "set breakpoint pending on"]);
+                for breakpoint in breakpoints {
+                    cmd.arg("-ex").arg(format!("break {}", breakpoint));
+                }
+                cmd.args(["-ex", "run", "--args"]);
+            }
+            Debugger::Lldb => {
+                for breakpoint in breakpoints {
+                    cmd.arg("-o")
+                        .arg(format!("breakpoint set --name {}", breakpoint));
+                }
+                cmd.args(["-o", "run", "--"]);
+            }
+        }
+        cmd.arg(&binary).args(&debug.args).arg(&debug.artifact);
 
-        // Retrieve corpus directories.
-        let corpora = if coverage.corpus.is_empty() {
-            vec![self.corpus_for(&coverage.target)?]
+        // Without `abort_on_error`, ASan exits the process after reporting an
+        // error instead of raising a signal the debugger would catch.
+        let mut asan_opts = env::var("ASAN_OPTIONS").unwrap_or_default();
+        if !asan_opts.is_empty() {
+            asan_opts.push(':');
+        }
+        asan_opts.push_str("detect_odr_violation=0:abort_on_error=1");
+        cmd.env("ASAN_OPTIONS", asan_opts);
+        configure_symbolizer(&mut cmd, debug.build.sanitizer);
+        if env::var_os("RUST_BACKTRACE").is_none() {
+            cmd.env("RUST_BACKTRACE", "1");
+        }
+
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to run {}, is it installed?", debug.debugger))
+            .fail(Failure::Environment)?;
+        if !status.success() {
+            bail!("{} exited with {}", debug.debugger, status);
+        }
+        Ok(())
+    }
+
+    pub fn exec_bench_corpus(&self, bench: &options::BenchCorpus) -> Result<()> {
+        let corpus = match &bench.corpus {
+            Some(corpus) => corpus.clone(),
+            None => self.fuzz_dir().join("corpus").join(&bench.target),
+        };
+        let inputs = if corpus.is_dir() {
+            sorted_files(&corpus)?
         } else {
-            coverage
-                .corpus
-                .iter()
-                .map(|name| Path::new(name).to_path_buf())
-                .collect()
+            Vec::new()
         };
+        if inputs.is_empty() {
+            bail!("no inputs found in {}", corpus.display());
+        }
 
-        // Collect the (non-directory) readable input files from the corpora.
-        let files_and_dirs = corpora.iter().flat_map(fs::read_dir).flatten().flatten();
-        let mut readable_input_files = files_and_dirs
-            .filter(|file| match file.file_type() {
-                Ok(ft) => ft.is_file(),
-                _ => false,
+        self.exec_build(BuildMode::Build, &bench.build, Some(&bench.target))?;
+
+        let mut timed = self.replay_batches(&bench.build, &bench.target, &bench.args, &inputs)?;
+        timed.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+        let total: u64 = timed.iter().map(|(ms, _)| ms).sum();
+        println!("Replayed {} inputs in {} ms\n", timed.len(), total);
+        println!("{:>10} {:>10}  PATH", "TIME", "SIZE");
+        for (ms, input) in timed.iter().take(bench.slowest as usize) {
+            let size = fs::metadata(input)
+                .with_context(|| format!("failed to read metadata of {}", input.display()))?
+                .len();
+            println!(
+                "{:>10} {:>10}  {}",
+                format!("{} ms", ms),
+                size,
+                strip_current_dir_prefix(input).display()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn exec_bench(&self, bench: &options::Bench) -> Result<()> {
+        let corpus = match &bench.corpus {
+            Some(corpus) => corpus.clone(),
+            None => self.fuzz_dir().join("corpus").join(&bench.target),
+        };
+        let inputs = if corpus.is_dir() {
+            sorted_files(&corpus)?
+        } else {
+            Vec::new()
+        };
+        if inputs.is_empty() {
+            bail!("no inputs found in {}", corpus.display());
+        }
+
+        self.exec_build(BuildMode::Build, &bench.build, Some(&bench.target))?;
+        let budget_ms = bench.time * 1000;
+
+        let (runs, replay_ms) =
+            self.calibrate_replay(&bench.build, &bench.target, &bench.args, &inputs, budget_ms)?;
+        let replay_execs = runs * inputs.len() as u64;
+
+        // Fuzzing adds inputs to the corpus, so it starts from a copy to keep
+        // the next benchmark comparable.
+        let scratch = tempfile::tempdir().context("failed to create temp dir")?;
+        let scratch_corpus = scratch.path().join("corpus");
+        fs::create_dir(&scratch_corpus)
+            .with_context(|| format!("failed to create directory {}", scratch_corpus.display()))?;
+        for input in &inputs {
+            let copy = scratch_corpus.join(&*file_name(input));
+            fs::copy(input, &copy)
+                .with_context(|| format!("failed to copy {}", input.display()))?;
+        }
+        let mut cmd = self.cargo_run(&bench.build, &bench.target)?;
+        cmd.args(&bench.args)
+            .arg(format!("-max_total_time={}", bench.time))
+            .arg(format!("-seed={}", bench.seed))
+            .arg(format!("-artifact_prefix={}/", scratch.path().display()))
+            .arg(&scratch_corpus)
+            .stdin(Stdio::null());
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run {:?}", cmd))?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() {
+            eprint!("{}", stderr);
+            return Err(Failure::Crash.wrap(anyhow!(
+                "Fuzz target exited with {} while fuzzing",
+                output.status
+            )));
+        }
+        let status = stats::last_status(&stderr).unwrap_or_default();
+        let fuzz_execs_per_sec = status.execs_per_sec.unwrap_or(status.execs / bench.time);
+
+        let replay_execs_per_sec = replay_execs * 1000 / replay_ms.max(1);
+        if bench.json {
+            let json = serde_json::json!({
+                "target": bench.target,
+                "inputs": inputs.len(),
+                "replay": {
+                    "execs": replay_execs,
+                    "millis": replay_ms,
+                    "execs_per_sec": replay_execs_per_sec,
+                },
+                "fuzz": {
+                    "execs": status.execs,
+                    "millis": budget_ms,
+                    "execs_per_sec": fuzz_execs_per_sec,
+                },
+            });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+            return Ok(());
+        }
+
+        println!(
+            "Benchmarked `{}` with the {} input(s) of {}\n",
+            bench.target,
+            inputs.len(),
+            strip_current_dir_prefix(&corpus).display()
+        );
+        let seconds = |ms: u64| format!("{:.1}s", ms as f64 / 1000.0);
+        print_table(
+            &[
+                ["MODE", "EXECS", "TIME", "EXEC/S"].map(str::to_owned),
+                [
+                    "replay".to_owned(),
+                    replay_execs.to_string(),
+                    seconds(replay_ms),
+                    replay_execs_per_sec.to_string(),
+                ],
+                [
+                    "fuzz".to_owned(),
+                    status.execs.to_string(),
+                    seconds(budget_ms),
+                    fuzz_execs_per_sec.to_string(),
+                ],
+            ],
+            &[true, false, false, false],
+        );
+        Ok(())
+    }
+
+    pub fn exec_profile(&self, profile: &options::Profile) -> Result<()> {
+        let corpus = match &profile.corpus {
+            Some(corpus) => corpus.clone(),
+            None => self.fuzz_dir().join("corpus").join(&profile.target),
+        };
+        let inputs = if corpus.is_dir() {
+            sorted_files(&corpus)?
+        } else {
+            Vec::new()
+        };
+        if inputs.is_empty() && !profile.fuzz {
+            bail!(
+                "no inputs found in {} to replay, fuzz `{}` first or profile it with `--fuzz`",
+                corpus.display(),
+                profile.target
+            );
+        }
+
+        self.exec_build(BuildMode::Build, &profile.build, Some(&profile.target))?;
+        let binary = self.fuzz_binary_path(&profile.build, &profile.target)?;
+
+        let mut args: Vec<ffi::OsString> = profile.args.iter().map(Into::into).collect();
+        if profile.fuzz {
+            let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
+            artifact_arg.push(self.artifacts_for(&profile.target)?);
+            args.push(artifact_arg);
+            args.push(format!("-max_total_time={}", profile.time).into());
+            if corpus.is_dir() {
+                args.push(corpus.clone().into());
+            }
+        } else {
+            // The profiler follows a single process, so a large corpus is
+            // sampled down to a batch, which replays for about the time asked
+            // for.
+            let sample = inputs.len().min(BENCH_BATCH_SIZE);
+            let inputs: Vec<_> = (0..sample)
+                .map(|i| inputs[i * inputs.len() / sample].clone())
+                .collect();
+            let budget_ms = profile.time * 1000;
+            let (runs, ms) = self.calibrate_replay(
+                &profile.build,
+                &profile.target,
+                &profile.args,
+                &inputs,
+                budget_ms,
+            )?;
+            let runs = (runs * budget_ms / ms.max(1)).max(1);
+            args.push(format!("-runs={}", runs).into());
+            args.extend(inputs.into_iter().map(Into::into));
+        }
+
+        let dir = self.fuzz_dir().join("profile").join(&profile.target);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create directory {}", dir.display()))?;
+        let mut cmd = Command::new(profile.profiler.to_string());
+        let samples = match profile.profiler {
+            Profiler::Perf => {
+                let samples = dir.join("perf.data");
+                cmd.args(["record", "-F", &profile::FREQUENCY.to_string(), "-g", "-o"])
+                    .arg(&samples)
+                    .arg("--")
+                    .arg(&binary)
+                    .args(&args);
+                samples
+            }
+            Profiler::Dtrace => {
+                // `dtrace -c` splits its command line at whitespace itself.
+                let samples = dir.join("dtrace.stacks");
+                let mut command = binary.into_os_string();
+                for arg in &args {
+                    command.push(" ");
+                    command.push(arg);
+                }
+                cmd.args(["-x", "ustackframes=100", "-n", profile::DTRACE_SCRIPT, "-o"])
+                    .arg(&samples)
+                    .arg("-c")
+                    .arg(command);
+                samples
+            }
+        };
+        cmd.stdin(Stdio::null());
+        configure_sanitizer_options(&mut cmd, &profile.build);
+        eprintln!(
+            "Profiling `{}` with {} for about {}s",
+            profile.target, profile.profiler, profile.time
+        );
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to run {}, is it installed?", profile.profiler))
+            .fail(Failure::Environment)?;
+        if !status.success() {
+            bail!("{} exited with {}", profile.profiler, status);
+        }
+
+        let stacks = match profile.profiler {
+            Profiler::Perf => {
+                let mut cmd = Command::new("perf");
+                cmd.arg("script").arg("-i").arg(&samples);
+                let output = cmd
+                    .output()
+                    .with_context(|| format!("failed to run {:?}", cmd))?;
+                if !output.status.success() {
+                    io::stderr().write_all(&output.stderr)?;
+                    bail!("`perf script` exited with {}", output.status);
+                }
+                output.stdout
+            }
+            Profiler::Dtrace => fs::read(&samples)
+                .with_context(|| format!("failed to read {}", samples.display()))?,
+        };
+        let title = format!("cargo fuzz profile {}", profile.target);
+        let svg = profile::flamegraph(profile.profiler, &stacks, &title)?;
+        let flamegraph = dir.join("flamegraph.svg");
+        fs::write(&flamegraph, svg)
+            .with_context(|| format!("failed to write {}", flamegraph.display()))?;
+
+        println!(
+            "Wrote the flamegraph of `{}` to {}",
+            profile.target,
+            strip_current_dir_prefix(&flamegraph).display()
+        );
+        if profile.profiler == Profiler::Perf {
+            println!(
+                "\nView the samples with:\n\n\tperf report -i {}",
+                strip_current_dir_prefix(&samples).display()
+            );
+        }
+        Ok(())
+    }
+
+    /// Finds how many times `target` has to run each of `inputs` with
+    /// `-runs=`, which replays them without mutating them, for that to take
+    /// at least half of `budget_ms`. Returns that and how long it took, in
+    /// milliseconds, which libFuzzer reports too coarsely for replaying them
+    /// just once.
+    fn calibrate_replay(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        args: &[String],
+        inputs: &[PathBuf],
+        budget_ms: u64,
+    ) -> Result<(u64, u64)> {
+        let mut runs: u64 = 1;
+        loop {
+            let mut args = args.to_vec();
+            args.push(format!("-runs={}", runs));
+            let ms: u64 = self
+                .replay_batches(build, target, &args, inputs)?
+                .iter()
+                .map(|(ms, _)| ms)
+                .sum();
+            if ms * 2 >= budget_ms {
+                return Ok((runs, ms));
+            }
+            runs = runs.saturating_mul((budget_ms / ms.max(1)).clamp(2, 100));
+        }
+    }
+
+    /// Runs `target` on each of `inputs` with the libFuzzer arguments `args`,
+    /// returning how many milliseconds each one took.
+    fn replay_batches(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        args: &[String],
+        inputs: &[PathBuf],
+    ) -> Result<Vec<(u64, PathBuf)>> {
+        // libFuzzer runs each file it is given and reports how long it took,
+        // so a whole batch of inputs replays in one process. The batches keep
+        // the command lines short enough for every platform.
+        let mut timed = Vec::new();
+        for batch in inputs.chunks(BENCH_BATCH_SIZE) {
+            let mut cmd = self.cargo_run(build, target)?;
+            cmd.args(args).args(batch).stdin(Stdio::null());
+            let output = cmd
+                .output()
+                .with_context(|| format!("failed to run {:?}", cmd))?;
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !output.status.success() {
+                eprint!("{}", stderr);
+                let input = stderr
+                    .lines()
+                    .rev()
+                    .find_map(|line| line.strip_prefix("Running: "))
+                    .unwrap_or("an input");
+                return Err(Failure::Crash.wrap(anyhow!(
+                    "Fuzz target exited with {} while replaying {}",
+                    output.status,
+                    input
+                )));
+            }
+            timed.extend(
+                stderr
+                    .lines()
+                    .filter_map(parse_executed)
+                    .map(|(input, ms)| (ms, PathBuf::from(input))),
+            );
+        }
+        Ok(timed)
+    }
+
+    pub fn exec_slow(&self, slow: &options::Slow) -> Result<()> {
+        let artifacts_dir = self.artifacts_for(&slow.target)?;
+        let units: Vec<PathBuf> = artifact_files(&artifacts_dir)?
+            .into_iter()
+            .filter(|artifact| artifact_category(artifact) == "slow-unit")
+            .collect();
+        if units.is_empty() {
+            println!("No slow units in {}", artifacts_dir.display());
+            return Ok(());
+        }
+
+        self.exec_build(BuildMode::Build, &slow.build, Some(&slow.target))?;
+
+        // libFuzzer reports how long running each file took, which is what
+        // the units are sorted by. Units that crash now have no time.
+        let mut timed = Vec::new();
+        for unit in units {
+            let (success, stderr) =
+                self.replay_input(&slow.build, &slow.target, &unit, &slow.args)?;
+            let millis = stderr
+                .lines()
+                .find_map(parse_executed)
+                .map(|(_, ms)| ms)
+                .filter(|_| success);
+            timed.push((millis, unit));
+        }
+        timed.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+        println!("{:>10} {:>10}  PATH", "TIME", "SIZE");
+        for (millis, unit) in &timed {
+            let size = fs::metadata(unit)
+                .with_context(|| format!("failed to read metadata of {}", unit.display()))?
+                .len();
+            println!(
+                "{:>10} {:>10}  {}",
+                millis.map_or_else(|| "crashes".to_owned(), |ms| format!("{} ms", ms)),
+                size,
+                strip_current_dir_prefix(unit).display()
+            );
+        }
+
+        let slowest = &timed[0].1;
+        if !slow.perf {
+            println!(
+                "\nProfile the slowest unit with:\n\n\tcargo fuzz slow{} --perf {}",
+                slow.build, slow.target
+            );
+            return Ok(());
+        }
+
+        let profiles = self.fuzz_dir().join("profiles").join(&slow.target);
+        fs::create_dir_all(&profiles)
+            .with_context(|| format!("failed to create directory {}", profiles.display()))?;
+        let mut profile = profiles.join(&*file_name(slowest)).into_os_string();
+        profile.push(".perf.data");
+        let profile = PathBuf::from(profile);
+
+        let binary = self.fuzz_binary_path(&slow.build, &slow.target)?;
+        let mut cmd = Command::new("perf");
+        cmd.args(["record", "-g", "-o"])
+            .arg(&profile)
+            .arg("--")
+            .arg(&binary)
+            .args(&slow.args)
+            .arg(slowest)
+            .stdin(Stdio::null());
+        configure_sanitizer_options(&mut cmd, &slow.build);
+        let status = cmd
+            .status()
+            .context("failed to run perf, is it installed?")
+            .fail(Failure::Environment)?;
+        if !status.success() {
+            bail!("perf exited with {}", status);
+        }
+        println!(
+            "\nView the profile of {} with:\n\n\tperf report -i {}",
+            strip_current_dir_prefix(slowest).display(),
+            strip_current_dir_prefix(&profile).display()
+        );
+        Ok(())
+    }
+
+    pub fn exec_tmin(&self, tmin: &options::Tmin) -> Result<()> {
+        if let (Some(hash), Some(test_case)) = (&tmin.check_stack_hash, &tmin.test_case) {
+            return self.check_stack_hash(tmin, test_case, hash);
+        }
+        self.exec_build(BuildMode::Build, &tmin.build, Some(&tmin.target))?;
+        let test_case = match &tmin.test_case {
+            Some(test_case) if !tmin.all_artifacts => test_case,
+            _ => return self.exec_tmin_all(tmin),
+        };
+
+        if tmin.structured {
+            return self.exec_tmin_structured(tmin, test_case);
+        }
+        if let Some(external) = &tmin.external {
+            return self.exec_tmin_external(tmin, test_case, external);
+        }
+
+        let artifact = self.tmin_output(tmin, test_case)?;
+        let mut cmd = self.cargo_run(&tmin.build, &tmin.target)?;
+        cmd.arg("-minimize_crash=1")
+            .arg(format!("-runs={}", tmin.runs))
+            .arg(format!(
+                "-exact_artifact_path={}",
+                runner_path(&tmin.build, &artifact)?.display()
+            ))
+            .arg(runner_path(&tmin.build, test_case)?);
+
+        for arg in self.with_dictionary(&tmin.target, &tmin_kind_args(tmin.kind, &tmin.args)) {
+            cmd.arg(arg);
+        }
+
+        let (status, captured) = spawn_and_capture_stderr(&mut cmd, |_| {})?;
+        if !status.success() {
+            eprintln!("\n{}\n", output::separator());
+            return Err(anyhow!("Command `{:?}` exited with {}", cmd, status)).with_context(|| {
+                "Test case minimization failed.\n\
+                 \n\
+                 Usually this isn't a hard error, and just means that libfuzzer\n\
+                 doesn't know how to minimize the test case any further while\n\
+                 still reproducing the original crash.\n\
+                 \n\
+                 See the logs above for details."
+            });
+        }
+
+        if artifact.is_file() {
+            let same_crash = tmin.kind != TminKind::Crash
+                || self.ensure_same_crash(
+                    &tmin.build,
+                    &tmin.target,
+                    test_case,
+                    &artifact,
+                    tmin.runs,
+                    &tmin.args,
+                )?;
+            let artifact = strip_current_dir_prefix(&artifact);
+
+            eprintln!("\n{}\n", output::separator());
+            eprintln!(
+                "{}\n\n\t{}\n",
+                output::heading("Minimized artifact:"),
+                artifact.display()
+            );
+
+            self.print_debug_fmt(&tmin.build, &tmin.target, artifact);
+
+            // The output of libFuzzer is about another crash otherwise.
+            if same_crash {
+                self.write_artifact_metadata(&tmin.build, &tmin.target, artifact, &captured);
+            }
+
+            eprintln!(
+                "{}\n\n\t{}\n",
+                output::heading("Reproduce with:"),
+                output::command(&self.reproduce_command(
+                    &format!("{}{}", tmin.build, tmin.limits),
+                    &tmin.target,
+                    artifact
+                ))
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Replays `original` and its `minimized` version, and when they crash
+    /// differently, warns and minimizes `original` again with the stricter
+    /// `--structured` minimization, which keeps the stack hash. libFuzzer's
+    /// minimization keeps any smaller input that crashes, even one that
+    /// triggers another bug. Returns whether the crash was the same.
+    fn ensure_same_crash(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        original: &Path,
+        minimized: &Path,
+        runs: u32,
+        args: &[String],
+    ) -> Result<bool> {
+        let scratch = tempfile::tempdir().context("failed to create temp dir")?;
+        // Every replay would leave an artifact behind otherwise.
+        let mut replay_args = self.with_dictionary(target, args);
+        replay_args.push(format!("-artifact_prefix={}/", scratch.path().display()));
+        let stack_hash = |input: &Path| -> Result<Option<String>> {
+            let (success, stderr) = self.replay_input(build, target, input, &replay_args)?;
+            Ok((!success).then(|| CrashReport::parse(&stderr).stack_hash()))
+        };
+        let before = stack_hash(original)?;
+        let after = stack_hash(minimized)?;
+        if before == after {
+            return Ok(true);
+        }
+
+        let describe = |hash: Option<String>| match hash {
+            Some(hash) => format!("crashes with stack hash {}", hash),
+            None => "doesn't crash".to_owned(),
+        };
+        eprintln!(
+            "{}",
+            output::error(&format!(
+                "warning: {} {}, but the minimized input {}, so it's likely a different bug",
+                strip_current_dir_prefix(original).display(),
+                describe(before),
+                describe(after)
+            ))
+        );
+        eprintln!("Minimizing again, keeping the stack hash");
+        self.minimize_structured(build, target, original, minimized, runs, args)?;
+        Ok(false)
+    }
+
+    /// Returns where `cargo fuzz tmin` writes the minimized `test_case`: to
+    /// `--output`, into it when it's a directory, or else next to the other
+    /// artifacts of the fuzz target, as `minimized-<test case name>`.
+    fn tmin_output(&self, tmin: &options::Tmin, test_case: &Path) -> Result<PathBuf> {
+        let name = minimized_name(tmin.kind, test_case);
+        let output = match &tmin.output {
+            Some(output) if output.is_dir() => output.join(name),
+            Some(output) => output.clone(),
+            None => self.artifacts_for(&tmin.target)?.join(name),
+        };
+        if let Some(dir) = output.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create directory {}", dir.display()))?;
+        }
+        Ok(output)
+    }
+
+    fn exec_tmin_structured(&self, tmin: &options::Tmin, test_case: &Path) -> Result<()> {
+        let artifact = self.tmin_output(tmin, test_case)?;
+        let crashed = self.minimize_structured(
+            &tmin.build,
+            &tmin.target,
+            test_case,
+            &artifact,
+            tmin.runs,
+            &tmin.args,
+        )?;
+        if !crashed {
+            bail!(
+                "the test case {} doesn't crash fuzz target `{}`, so there is nothing to minimize",
+                test_case.display(),
+                tmin.target
+            );
+        }
+        self.print_minimized(tmin, test_case, &artifact)
+    }
+
+    /// Minimizes `test_case` with the external minimizer of `--external`,
+    /// which keeps the inputs that its interestingness test accepts: those
+    /// that crash the fuzz target with the same stack hash.
+    fn exec_tmin_external(
+        &self,
+        tmin: &options::Tmin,
+        test_case: &Path,
+        external: &str,
+    ) -> Result<()> {
+        let scratch = tempfile::tempdir().context("failed to create temp dir")?;
+        // Every replay would leave an artifact behind otherwise.
+        let mut args = self.with_dictionary(&tmin.target, &tmin.args);
+        args.push(format!("-artifact_prefix={}/", scratch.path().display()));
+
+        let (success, stderr) = self.replay_input(&tmin.build, &tmin.target, test_case, &args)?;
+        if success {
+            bail!(
+                "the test case {} doesn't crash fuzz target `{}`, so there is nothing to minimize",
+                test_case.display(),
+                tmin.target
+            );
+        }
+        let hash = CrashReport::parse(&stderr).stack_hash();
+
+        let exe = env::current_exe().context("failed to find the cargo-fuzz executable")?;
+        let fuzz_dir = fs::canonicalize(self.fuzz_dir())
+            .with_context(|| format!("failed to canonicalize {}", self.fuzz_dir().display()))?;
+        let build_args = tmin.build.to_string();
+        let mut check: Vec<&ffi::OsStr> = vec![exe.as_ref(), "tmin".as_ref()];
+        check.extend(build_args.split_whitespace().map(ffi::OsStr::new));
+        check.extend([
+            "--fuzz-dir".as_ref(),
+            fuzz_dir.as_os_str(),
+            "--check-stack-hash".as_ref(),
+            hash.as_ref(),
+            tmin.target.as_ref(),
+        ]);
+        check.push("--".as_ref());
+        check.extend(args.iter().map(ffi::OsStr::new));
+        let cwd = env::current_dir().context("failed to get the current directory")?;
+        let test = scratch.path().join("interesting.sh");
+        fs::write(&test, external::script(&check, &cwd, scratch.path()))
+            .with_context(|| format!("failed to write {}", test.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&test, fs::Permissions::from_mode(0o755))
+                .with_context(|| format!("failed to make {} executable", test.display()))?;
+        }
+
+        let input = scratch.path().join(file_name(test_case).as_ref());
+        fs::copy(test_case, &input)
+            .with_context(|| format!("failed to copy {}", test_case.display()))?;
+        let output = scratch.path().join("output");
+        let command = external::command(external, &test, &input, &output)?;
+        let mut cmd = Command::new(&command[0]);
+        cmd.args(&command[1..]).stdin(Stdio::null());
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to run {:?}", cmd))?;
+        if !status.success() {
+            bail!("the external minimizer exited with {}", status);
+        }
+
+        let result = if output.is_file() { output } else { input };
+        let artifact = self.tmin_output(tmin, test_case)?;
+        fs::copy(&result, &artifact)
+            .with_context(|| format!("failed to write {}", artifact.display()))?;
+        self.print_minimized(tmin, test_case, &artifact)
+    }
+
+    /// Replays `test_case` for the interestingness test of `--external`, and
+    /// fails unless it crashes with the stack hash `hash`.
+    fn check_stack_hash(&self, tmin: &options::Tmin, test_case: &Path, hash: &str) -> Result<()> {
+        // The `cargo fuzz tmin --external` running the test built the fuzz
+        // target already, and cargo would only find it up to date.
+        if self.fuzz_binary_path(&tmin.build, &tmin.target)?.is_file() {
+            self.built
+                .lock()
+                .unwrap()
+                .insert((format!("{:?}", tmin.build), Some(tmin.target.clone())));
+        }
+        let (success, stderr) =
+            self.replay_input(&tmin.build, &tmin.target, test_case, &tmin.args)?;
+        let actual = CrashReport::parse(&stderr).stack_hash();
+        if success || actual != hash {
+            bail!(
+                "{} doesn't crash with stack hash {}",
+                test_case.display(),
+                hash
+            );
+        }
+        Ok(())
+    }
+
+    /// Prints where `test_case` was minimized to, how much, and how to
+    /// reproduce the crash.
+    fn print_minimized(
+        &self,
+        tmin: &options::Tmin,
+        test_case: &Path,
+        artifact: &Path,
+    ) -> Result<()> {
+        let before = fs::metadata(test_case)
+            .with_context(|| format!("failed to read metadata of {}", test_case.display()))?
+            .len();
+        let after = fs::metadata(artifact)
+            .with_context(|| format!("failed to read metadata of {}", artifact.display()))?
+            .len();
+        let artifact = strip_current_dir_prefix(artifact);
+
+        eprintln!("\n{}\n", output::separator());
+        eprintln!(
+            "{}\n\n\t{}\n",
+            output::heading(&format!(
+                "Minimized artifact, from {} to {} bytes:",
+                before, after
+            )),
+            artifact.display()
+        );
+
+        self.print_debug_fmt(&tmin.build, &tmin.target, artifact);
+
+        eprintln!(
+            "{}\n\n\t{}\n",
+            output::heading("Reproduce with:"),
+            output::command(&self.reproduce_command(
+                &format!("{}{}", tmin.build, tmin.limits),
+                &tmin.target,
+                artifact
+            ))
+        );
+
+        Ok(())
+    }
+
+    /// Shrinks `input` with `shrink::candidates`, keeping a candidate when it
+    /// crashes the fuzz target with the same stack hash and its debug
+    /// formatting is no longer, until no candidate is kept or `runs` of them
+    /// have been replayed. Writes the result to `output`, and returns whether
+    /// `input` crashed the fuzz target in the first place.
+    fn minimize_structured(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        input: &Path,
+        output: &Path,
+        runs: u32,
+        args: &[String],
+    ) -> Result<bool> {
+        let scratch = tempfile::tempdir().context("failed to create temp dir")?;
+        let candidate_path = scratch.path().join("candidate");
+        // Every crashing candidate would leave an artifact behind otherwise.
+        let mut args = args.to_vec();
+        args.push(format!("-artifact_prefix={}/", scratch.path().display()));
+
+        let (success, stderr) = self.replay_input(build, target, input, &args)?;
+        if success {
+            return Ok(false);
+        }
+        let hash = CrashReport::parse(&stderr).stack_hash();
+        // Inputs that can't be debug formatted are as long as it gets.
+        let debug_len = |path: &Path| {
+            self.run_fuzz_target_debug_formatter(build, target, path)
+                .map_or(usize::MAX, |debug| debug.len())
+        };
+
+        let mut best =
+            fs::read(input).with_context(|| format!("failed to read {}", input.display()))?;
+        let mut best_debug_len = debug_len(input);
+        let mut attempts = 0;
+        loop {
+            let mut shrunk = None;
+            for candidate in shrink::candidates(&best) {
+                if attempts == runs {
+                    break;
+                }
+                attempts += 1;
+                fs::write(&candidate_path, &candidate)
+                    .with_context(|| format!("failed to write {}", candidate_path.display()))?;
+                let (success, stderr) = self.replay_input(build, target, &candidate_path, &args)?;
+                if success || CrashReport::parse(&stderr).stack_hash() != hash {
+                    continue;
+                }
+                let candidate_debug_len = debug_len(&candidate_path);
+                if candidate_debug_len <= best_debug_len {
+                    shrunk = Some((candidate, candidate_debug_len));
+                    break;
+                }
+            }
+            match shrunk {
+                Some((candidate, candidate_debug_len)) => {
+                    best = candidate;
+                    best_debug_len = candidate_debug_len;
+                }
+                None => break,
+            }
+        }
+
+        fs::write(output, &best)
+            .with_context(|| format!("failed to write {}", output.display()))?;
+        Ok(true)
+    }
+
+    /// Run libFuzzer's crash minimization on `input`, quietly, writing the
+    /// result to `output`. Returns whether minimization succeeded.
+    fn minimize_crash(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        input: &Path,
+        output: &Path,
+        runs: u32,
+        args: &[String],
+    ) -> Result<bool> {
+        let mut cmd = self.cargo_run(build, target)?;
+        cmd.arg("-minimize_crash=1")
+            .arg(format!("-runs={}", runs))
+            .arg(format!("-exact_artifact_path={}", output.display()))
+            .args(args)
+            .arg(input)
+            .stdin(Stdio::null());
+        let status = cmd
+            .output()
+            .with_context(|| format!("failed to run {:?}", cmd))?
+            .status;
+        Ok(status.success() && output.is_file())
+    }
+
+    /// Minimize every artifact of a fuzz target, writing each result next to
+    /// it, or into the `--output` directory, as `minimized-<artifact name>`.
+    fn exec_tmin_all(&self, tmin: &options::Tmin) -> Result<()> {
+        let artifacts_dir = if let Some(dir) = tmin.artifacts.clone() {
+            dir
+        } else {
+            self.artifacts_for(&tmin.target)?
+        };
+        if let Some(output) = &tmin.output {
+            if output.exists() && !output.is_dir() {
+                bail!(
+                    "`--output` must be a directory with `--all-artifacts`, but {} is a file",
+                    output.display()
+                );
+            }
+            fs::create_dir_all(output)
+                .with_context(|| format!("failed to create directory {}", output.display()))?;
+        }
+        // The results of a previous run aren't minimized again.
+        let mut artifacts = artifact_files(&artifacts_dir)?;
+        artifacts.retain(|artifact| match tmin.kind {
+            TminKind::Crash => true,
+            TminKind::Timeout => artifact_category(artifact) == "timeout",
+            TminKind::Oom => artifact_category(artifact) == "oom",
+        });
+        if artifacts.is_empty() {
+            eprintln!("No artifacts found in {}", artifacts_dir.display());
+            return Ok(());
+        }
+
+        let args = self.with_dictionary(&tmin.target, &tmin_kind_args(tmin.kind, &tmin.args));
+        let mut results = Vec::new();
+        for (i, artifact) in artifacts.iter().enumerate() {
+            eprintln!(
+                "Minimizing artifact {}/{}: {}",
+                i + 1,
+                artifacts.len(),
+                strip_current_dir_prefix(artifact).display()
+            );
+            let name = minimized_name(tmin.kind, artifact);
+            let minimized = match &tmin.output {
+                Some(output) => output.join(name),
+                None => artifact.with_file_name(name),
+            };
+
+            let minimize = if tmin.structured {
+                Self::minimize_structured
+            } else {
+                Self::minimize_crash
+            };
+            let minimized_ok = minimize(
+                self,
+                &tmin.build,
+                &tmin.target,
+                artifact,
+                &minimized,
+                tmin.runs,
+                &args,
+            )?;
+            if minimized_ok && !tmin.structured && tmin.kind == TminKind::Crash {
+                self.ensure_same_crash(
+                    &tmin.build,
+                    &tmin.target,
+                    artifact,
+                    &minimized,
+                    tmin.runs,
+                    &tmin.args,
+                )?;
+            }
+
+            let before = fs::metadata(artifact)
+                .with_context(|| format!("failed to read metadata of {}", artifact.display()))?
+                .len();
+            let after = match fs::metadata(&minimized) {
+                Ok(metadata) if minimized_ok => Some(metadata.len()),
+                _ => None,
+            };
+            results.push((artifact, before, after));
+        }
+
+        eprintln!("\n{}\n", output::separator());
+        let (mut total_before, mut total_after, mut failed) = (0, 0, 0);
+        for (artifact, before, after) in &results {
+            let artifact = strip_current_dir_prefix(artifact);
+            match after {
+                Some(after) => {
+                    total_before += before;
+                    total_after += after;
+                    eprintln!("\t{}: {} -> {} bytes", artifact.display(), before, after);
+                }
+                None => {
+                    failed += 1;
+                    eprintln!("\t{}: failed to minimize", artifact.display());
+                }
+            }
+        }
+        eprintln!(
+            "\nMinimized {} of {} artifact(s) from {} to {} bytes",
+            results.len() - failed,
+            results.len(),
+            total_before,
+            total_after
+        );
+
+        Ok(())
+    }
+
+    pub fn exec_cmin(&self, cmin: &options::Cmin) -> Result<()> {
+        self.exec_build(BuildMode::Build, &cmin.build, Some(&cmin.target))?;
+        let mut cmd = self.cargo_run(&cmin.build, &cmin.target)?;
+
+        for arg in self.with_dictionary(&cmin.target, &cmin.args) {
+            cmd.arg(arg);
+        }
+
+        let corpus = if let Some(corpus) = cmin.corpus.clone() {
+            corpus
+        } else {
+            self.corpus_for(&cmin.target)?
+        };
+        let corpus = corpus
+            .to_str()
+            .ok_or_else(|| anyhow!("corpus must be valid unicode"))?
+            .to_owned();
+
+        let tmp = tempfile::TempDir::new_in(self.fuzz_dir())?;
+        let tmp_corpus = tmp.path().join("corpus");
+        fs::create_dir(&tmp_corpus)?;
+
+        cmd.arg("-merge=1").arg(&tmp_corpus).arg(&corpus);
+
+        // Spawn cmd in child process instead of exec-ing it
+        let status = cmd
+            .status()
+            .with_context(|| format!("could not execute command: {:?}", cmd))?;
+        if !status.success() {
+            println!("Failed to minimize corpus: {}", status);
+            return Ok(());
+        }
+
+        // A target that crashes or misbehaves during the merge can leave us with
+        // an empty corpus; never throw away the original in that case.
+        let is_empty = |dir: &Path| -> Result<bool> {
+            Ok(fs::read_dir(dir)
+                .with_context(|| format!("failed to read directory {}", dir.display()))?
+                .next()
+                .is_none())
+        };
+        if is_empty(&tmp_corpus)? && !is_empty(Path::new(&corpus))? {
+            bail!(
+                "the minimized corpus is empty, leaving the original corpus at {} untouched",
+                corpus
+            );
+        }
+
+        if cmin.no_backup {
+            // move corpus directory into tmp to auto delete it
+            fs::rename(&corpus, tmp.path().join("old"))?;
+        } else {
+            let backup_dir = match &cmin.backup_dir {
+                Some(backup_dir) => backup_dir.clone(),
+                None => {
+                    let timestamp = time::SystemTime::now()
+                        .duration_since(time::UNIX_EPOCH)?
+                        .as_secs();
+                    PathBuf::from(format!(
+                        "{}.bak-{}",
+                        corpus.trim_end_matches('/'),
+                        timestamp
+                    ))
+                }
+            };
+            if backup_dir.exists() {
+                bail!("backup directory {} already exists", backup_dir.display());
+            }
+            fs::rename(&corpus, &backup_dir).with_context(|| {
+                format!(
+                    "failed to move the original corpus {} to {}",
+                    corpus,
+                    backup_dir.display()
+                )
+            })?;
+            eprintln!("Original corpus backed up to {}", backup_dir.display());
+        }
+        fs::rename(tmp_corpus, corpus)?;
+
+        Ok(())
+    }
+
+    /// Merge the corpus of one fuzz target into the corpus of another.
+    ///
+    /// The destination target's binary decides which inputs are kept, so only
+    /// inputs that add new coverage to it end up in its corpus.
+    pub fn exec_cross_pollinate(&self, cross: &options::CrossPollinate) -> Result<()> {
+        if cross.from == cross.to {
+            bail!(
+                "cannot cross-pollinate the corpus of `{}` with itself",
+                cross.from
+            );
+        }
+
+        self.exec_build(BuildMode::Build, &cross.build, Some(&cross.to))?;
+        let mut cmd = self.cargo_run(&cross.build, &cross.to)?;
+
+        for arg in &cross.args {
+            cmd.arg(arg);
+        }
+
+        let from_corpus = self.corpus_for(&cross.from)?;
+        let to_corpus = self.corpus_for(&cross.to)?;
+        cmd.arg("-merge=1").arg(&to_corpus).arg(&from_corpus);
+
+        let status = cmd
+            .status()
+            .with_context(|| format!("could not execute command: {:?}", cmd))?;
+        if !status.success() {
+            bail!(
+                "Failed to merge the corpus of `{}` into `{}`: {}",
+                cross.from,
+                cross.to,
+                status
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Remove byte-identical inputs from a corpus.
+    ///
+    /// This only looks at file contents, so unlike `cmin` it does not need to
+    /// build the fuzz target.
+    pub fn exec_dedup(&self, dedup: &options::Dedup) -> Result<()> {
+        let corpus = if let Some(corpus) = dedup.corpus.clone() {
+            corpus
+        } else {
+            self.corpus_for(&dedup.target)?
+        };
+
+        // Sorted so that the same input is kept on every run.
+        let inputs = sorted_files(&corpus)?;
+
+        let mut seen = HashMap::new();
+        let mut removed_bytes = 0;
+        let mut removed = 0;
+        for input in inputs {
+            let data = fs::read(&input)
+                .with_context(|| format!("failed to read input {}", input.display()))?;
+            let hash = utils::content_hash(&data);
+            match seen.entry(hash) {
+                Entry::Occupied(_) => {
+                    fs::remove_file(&input)
+                        .with_context(|| format!("failed to remove {}", input.display()))?;
+                    removed += 1;
+                    removed_bytes += data.len();
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(input);
+                }
+            }
+        }
+
+        if dedup.rename {
+            for (hash, input) in &seen {
+                let renamed = corpus.join(hash);
+                // Never clobber an unrelated input that happens to have this name.
+                if *input == renamed || renamed.exists() {
+                    continue;
+                }
+                fs::rename(input, &renamed).with_context(|| {
+                    format!(
+                        "failed to rename {} to {}",
+                        input.display(),
+                        renamed.display()
+                    )
+                })?;
+            }
+        }
+
+        eprintln!(
+            "Removed {} duplicate inputs ({} bytes) from {}, {} unique inputs remain",
+            removed,
+            removed_bytes,
+            corpus.display(),
+            seen.len()
+        );
+
+        Ok(())
+    }
+
+    /// Import the findings of AFL/AFL++ into a fuzz target's corpus and
+    /// artifacts.
+    ///
+    /// Queue entries are merged into the corpus with `-merge=1`, so only the
+    /// ones that add coverage are kept. Crashes and hangs are copied into the
+    /// artifacts directory with libFuzzer's `crash-` and `timeout-` prefixes.
+    pub fn exec_import_afl(&self, import: &options::ImportAfl) -> Result<()> {
+        let out_dir = &import.afl_out_dir;
+        if !out_dir.is_dir() {
+            bail!("AFL output directory does not exist: {}", out_dir.display());
+        }
+
+        // A single AFL instance writes `queue/` directly into the output
+        // directory, while parallel instances (`-M`/`-S`) each get their own
+        // subdirectory.
+        let instances = if out_dir.join("queue").is_dir() {
+            vec![out_dir.clone()]
+        } else {
+            let mut instances = fs::read_dir(out_dir)
+                .with_context(|| format!("failed to read directory {}", out_dir.display()))?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<Vec<_>>>()?;
+            instances.retain(|dir| dir.join("queue").is_dir());
+            instances.sort();
+            instances
+        };
+        if instances.is_empty() {
+            bail!(
+                "{} does not look like an AFL output directory: no `queue/` found",
+                out_dir.display()
+            );
+        }
+
+        self.exec_build(BuildMode::Build, &import.build, Some(&import.target))?;
+        let mut cmd = self.cargo_run(&import.build, &import.target)?;
+        for arg in &import.args {
+            cmd.arg(arg);
+        }
+        // libFuzzer reads the directories it merges recursively, but only the
+        // top-level files of a queue are inputs: AFL keeps its own bookkeeping
+        // in `queue/.state/`. Those are gathered into a directory of their own,
+        // prefixed by their instance as every instance numbers them from 0.
+        let queue = tempfile::tempdir().context("failed to create temp dir")?;
+        for (i, instance) in instances.iter().enumerate() {
+            let dir = instance.join("queue");
+            for entry in fs::read_dir(&dir)
+                .with_context(|| format!("failed to read directory {}", dir.display()))?
+            {
+                let entry = entry?;
+                if !entry.file_type()?.is_file() {
+                    continue;
+                }
+                let mut name = ffi::OsString::from(format!("{}-", i));
+                name.push(entry.file_name());
+                fs::copy(entry.path(), queue.path().join(name))
+                    .with_context(|| format!("failed to copy {}", entry.path().display()))?;
+            }
+        }
+        cmd.arg("-merge=1")
+            .arg(self.corpus_for(&import.target)?)
+            .arg(queue.path());
+
+        let status = cmd
+            .status()
+            .with_context(|| format!("could not execute command: {:?}", cmd))?;
+        if !status.success() {
+            bail!("Failed to merge the AFL queue into the corpus: {}", status);
+        }
+
+        let artifacts = self.artifacts_for(&import.target)?;
+        let mut imported = 0;
+        for instance in &instances {
+            for (afl_dir, prefix) in [("crashes", "crash"), ("hangs", "timeout")] {
+                let dir = instance.join(afl_dir);
+                if !dir.is_dir() {
+                    continue;
+                }
+                for entry in fs::read_dir(&dir)
+                    .with_context(|| format!("failed to read directory {}", dir.display()))?
+                {
+                    let path = entry?.path();
+                    // AFL drops a `README.txt` next to its crashes.
+                    if !path.is_file() || path.file_name() == Some("README.txt".as_ref()) {
+                        continue;
+                    }
+                    let data = fs::read(&path)
+                        .with_context(|| format!("failed to read {}", path.display()))?;
+                    let artifact =
+                        artifacts.join(format!("{}-{}", prefix, utils::content_hash(&data)));
+                    if artifact.exists() {
+                        continue;
+                    }
+                    fs::write(&artifact, &data)
+                        .with_context(|| format!("failed to write {}", artifact.display()))?;
+                    imported += 1;
+                }
+            }
+        }
+
+        eprintln!(
+            "Imported the queue of {} AFL instance(s) and {} new crash/hang artifact(s)",
+            instances.len(),
+            imported
+        );
+
+        Ok(())
+    }
+
+    /// Run a single input through a fuzz target, returning whether it exited
+    /// successfully and everything it wrote to stderr.
+    fn replay_input(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        input: &Path,
+        args: &[String],
+    ) -> Result<(bool, String)> {
+        let mut cmd = self.cargo_run(build, target)?;
+        cmd.args(args)
+            .arg(runner_path(build, input)?)
+            .stdin(Stdio::null());
+        if env::var_os("RUST_BACKTRACE").is_none() {
+            cmd.env("RUST_BACKTRACE", "1");
+        }
+
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run {:?}", cmd))?;
+        Ok((
+            output.status.success(),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ))
+    }
+
+    /// Replays every artifact of a fuzz target and groups the crashes by the
+    /// hash of their stack, to report each distinct bug once.
+    pub fn exec_triage(&self, triage: &options::Triage) -> Result<()> {
+        let artifacts_dir = if let Some(dir) = triage.artifacts.clone() {
+            dir
+        } else {
+            self.artifacts_for(&triage.target)?
+        };
+        let artifacts = artifact_files(&artifacts_dir)?;
+        if artifacts.is_empty() {
+            eprintln!("No artifacts found in {}", artifacts_dir.display());
+            return Ok(());
+        }
+
+        self.exec_build(BuildMode::Build, &triage.build, Some(&triage.target))?;
+
+        struct Bucket {
+            hash: String,
+            report: CrashReport,
+            artifacts: Vec<PathBuf>,
+            representative: (u64, PathBuf),
+        }
+
+        let mut buckets: Vec<Bucket> = Vec::new();
+        let mut not_reproduced = Vec::new();
+        for (i, artifact) in artifacts.iter().enumerate() {
+            eprintln!(
+                "Replaying artifact {}/{}: {}",
+                i + 1,
+                artifacts.len(),
+                strip_current_dir_prefix(artifact).display()
+            );
+            let (success, stderr) =
+                self.replay_input(&triage.build, &triage.target, artifact, &triage.args)?;
+            if success {
+                not_reproduced.push(artifact.clone());
+                continue;
+            }
+
+            let report = CrashReport::parse(&stderr);
+            let hash = report.stack_hash();
+            let size = fs::metadata(artifact)
+                .with_context(|| format!("failed to read metadata of {}", artifact.display()))?
+                .len();
+            match buckets.iter_mut().find(|bucket| bucket.hash == hash) {
+                Some(bucket) => {
+                    bucket.artifacts.push(artifact.clone());
+                    if size < bucket.representative.0 {
+                        bucket.representative = (size, artifact.clone());
+                    }
+                }
+                None => buckets.push(Bucket {
+                    hash,
+                    report,
+                    artifacts: vec![artifact.clone()],
+                    representative: (size, artifact.clone()),
+                }),
+            }
+        }
+
+        println!(
+            "Found {} unique crash(es) among {} artifact(s)",
+            buckets.len(),
+            artifacts.len()
+        );
+        for bucket in &buckets {
+            println!();
+            println!("[{}] {} artifact(s)", bucket.hash, bucket.artifacts.len());
+            if let Some(kind) = &bucket.report.kind {
+                println!("    {}", kind);
+            }
+            if let Some(location) = &bucket.report.panic_location {
+                println!("    panicked at {}", location);
+            }
+            for frame in bucket
+                .report
+                .user_frames()
+                .take(stacktrace::SIGNATURE_FRAMES)
+            {
+                match &frame.location {
+                    Some(location) => println!("      in {} {}", frame.function, location),
+                    None => println!("      in {}", frame.function),
+                }
+            }
+            println!(
+                "    representative: {}",
+                strip_current_dir_prefix(&bucket.representative.1).display()
+            );
+        }
+
+        if !not_reproduced.is_empty() {
+            println!();
+            println!("{} artifact(s) did not reproduce:", not_reproduced.len());
+            for artifact in &not_reproduced {
+                println!("    {}", strip_current_dir_prefix(artifact).display());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replays every artifact of a fuzz target to tell the ones that still
+    /// crash from the ones that have been fixed.
+    pub fn exec_verify(&self, verify: &options::Verify) -> Result<()> {
+        let artifacts_dir = if let Some(dir) = verify.artifacts.clone() {
+            dir
+        } else {
+            self.artifacts_for(&verify.target)?
+        };
+        let artifacts = artifact_files(&artifacts_dir)?;
+        let corpus = if verify.corpus {
+            sorted_files(&self.corpus_for(&verify.target)?)?
+        } else {
+            Vec::new()
+        };
+        if artifacts.is_empty() && corpus.is_empty() {
+            eprintln!("No artifacts found in {}", artifacts_dir.display());
+            if let Some(junit) = &verify.junit {
+                write_junit(junit, &verify.target, &[])?;
+            }
+            return Ok(());
+        }
+
+        self.exec_build(BuildMode::Build, &verify.build, Some(&verify.target))?;
+
+        let mut cases = Vec::new();
+        let mut regressions = Vec::new();
+        for input in corpus {
+            let start = time::Instant::now();
+            let (success, stderr) =
+                self.replay_input(&verify.build, &verify.target, &input, &verify.args)?;
+            cases.push(junit::TestCase {
+                name: format!("corpus/{}", file_name(&input)),
+                time: start.elapsed(),
+                failure: (!success).then(|| crash_failure(&stderr)),
+            });
+            if !success {
+                regressions.push(input);
+            }
+        }
+
+        let mut crashing = Vec::new();
+        let mut fixed = Vec::new();
+        for artifact in artifacts {
+            let start = time::Instant::now();
+            let (success, stderr) =
+                self.replay_input(&verify.build, &verify.target, &artifact, &verify.args)?;
+            cases.push(junit::TestCase {
+                name: file_name(&artifact).into_owned(),
+                time: start.elapsed(),
+                failure: (!success).then(|| crash_failure(&stderr)),
+            });
+            if success {
+                fixed.push(artifact);
+            } else {
+                crashing.push(artifact);
+            }
+        }
+        if let Some(junit) = &verify.junit {
+            write_junit(junit, &verify.target, &cases)?;
+        }
+
+        println!("Fixed: {}", fixed.len());
+        for artifact in &fixed {
+            println!("    {}", strip_current_dir_prefix(artifact).display());
+        }
+        println!("Still crashing: {}", crashing.len());
+        for artifact in &crashing {
+            println!("    {}", strip_current_dir_prefix(artifact).display());
+        }
+        if verify.corpus {
+            println!("Crashing corpus inputs: {}", regressions.len());
+            for input in &regressions {
+                println!("    {}", strip_current_dir_prefix(input).display());
+            }
+        }
+
+        if verify.move_fixed && !fixed.is_empty() {
+            let corpus = self.corpus_for(&verify.target)?;
+            for artifact in &fixed {
+                move_to_corpus(artifact, artifact, &corpus)?;
+            }
+            eprintln!(
+                "Moved {} fixed artifact(s) into {}",
+                fixed.len(),
+                corpus.display()
+            );
+        }
+
+        if !crashing.is_empty() {
+            return Err(
+                Failure::Reproduced.wrap(anyhow!("{} artifact(s) still crash", crashing.len()))
+            );
+        }
+        if !regressions.is_empty() {
+            return Err(Failure::Reproduced.wrap(anyhow!(
+                "{} input(s) of the corpus crash",
+                regressions.len()
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn exec_ci(&self, ci: &options::Ci) -> Result<()> {
+        let deadline = time::Instant::now() + time::Duration::from_secs(ci.time_budget);
+
+        let mut targets = if ci.targets.is_empty() {
+            self.targets.clone()
+        } else {
+            for target in &ci.targets {
+                if !self.targets.contains(target) {
+                    bail!("no fuzz target named `{}`", target);
+                }
+            }
+            ci.targets.clone()
+        };
+        if let Some(rev) = &ci.changed_since {
+            targets = self.changed_targets(&targets, rev)?;
+        }
+
+        for target in &targets {
+            self.exec_build(BuildMode::Build, &ci.build, Some(target))?;
+        }
+
+        struct TargetResult {
+            name: String,
+            defaults: Defaults,
+            cases: Vec<junit::TestCase>,
+            crash: Option<CiCrash>,
+            fuzzed: Option<time::Duration>,
+        }
+
+        let config = Config::load(&self.fuzz_dir)?;
+        let mut results = targets
+            .into_iter()
+            .map(|name| {
+                let defaults = match &config {
+                    Some(config) => config.for_target(Some(&name), ci.build.profile.as_deref())?,
+                    None => Defaults::default(),
+                };
+                Ok(TargetResult {
+                    name,
+                    defaults,
+                    cases: Vec::new(),
+                    crash: None,
+                    fuzzed: None,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Replay all corpora first, as crashes there are regressions that
+        // take no fuzzing time to find.
+        for result in &mut results {
+            eprintln!("Replaying the corpus of `{}`", result.name);
+            let mut args = result.defaults.libfuzzer_args("run", &self.fuzz_dir);
+            args.push("-runs=0".to_owned());
+            let start = time::Instant::now();
+            result.crash = self.ci_run(&ci.build, &result.name, &args, &result.defaults)?;
+            result.cases.push(junit::TestCase {
+                name: "corpus".to_owned(),
+                time: start.elapsed(),
+                failure: result.crash.as_ref().and_then(CiCrash::failure),
+            });
+        }
+
+        // Then split the rest of the time budget between the targets that
+        // didn't crash already.
+        let mut remaining = results.iter().filter(|r| r.crash.is_none()).count() as u32;
+        for result in results.iter_mut().filter(|r| r.crash.is_none()) {
+            let budget = deadline.saturating_duration_since(time::Instant::now()) / remaining;
+            remaining -= 1;
+            if budget.as_secs() == 0 {
+                eprintln!("warning: no time left to fuzz `{}`", result.name);
+                continue;
+            }
+
+            eprintln!("Fuzzing `{}` for {}s", result.name, budget.as_secs());
+            let mut args = result.defaults.libfuzzer_args("run", &self.fuzz_dir);
+            args.push(format!("-max_total_time={}", budget.as_secs()));
+            args.extend(ci.args.iter().cloned());
+            let start = time::Instant::now();
+            result.crash = self.ci_run(&ci.build, &result.name, &args, &result.defaults)?;
+            result.fuzzed = Some(start.elapsed());
+            result.cases.push(junit::TestCase {
+                name: "fuzz".to_owned(),
+                time: start.elapsed(),
+                failure: result.crash.as_ref().and_then(CiCrash::failure),
+            });
+        }
+
+        if let Some(path) = &ci.junit {
+            let suites: Vec<_> = results
+                .iter()
+                .map(|result| (result.name.as_str(), &result.cases[..]))
+                .collect();
+            fs::write(path, junit::render_suites(&suites))
+                .with_context(|| format!("failed to write JUnit report to {}", path.display()))?;
+        }
+        if let Some(path) = &ci.sarif {
+            let crashes: Vec<_> = results
+                .iter()
+                .filter_map(|result| {
+                    let crash = result.crash.as_ref()?;
+                    Some(sarif::Crash {
+                        target: &result.name,
+                        report: &crash.report,
+                        artifact: crash.artifact.as_deref(),
+                        suppressed: crash.suppressed,
+                    })
+                })
+                .collect();
+            fs::write(path, sarif::render(&crashes))
+                .with_context(|| format!("failed to write SARIF report to {}", path.display()))?;
+        }
+
+        println!();
+        let mut new_crashes = 0;
+        for result in &results {
+            let crash = match &result.crash {
+                Some(crash) => crash,
+                None => {
+                    match result.fuzzed {
+                        Some(time) => {
+                            println!("{}: ok, fuzzed for {}s", result.name, time.as_secs())
+                        }
+                        None => println!("{}: ok, but not fuzzed", result.name),
+                    }
+                    continue;
+                }
+            };
+            if !crash.suppressed {
+                new_crashes += 1;
+            }
+            println!(
+                "{}: {} crash {}",
+                result.name,
+                if crash.suppressed {
+                    "suppressed"
+                } else {
+                    "new"
+                },
+                crash.summary()
+            );
+            if let Some(artifact) = &crash.artifact {
+                println!("    {}", strip_current_dir_prefix(artifact).display());
+            }
+        }
+
+        if new_crashes > 0 {
+            return Err(Failure::Crash.wrap(anyhow!("{} new crash(es) found", new_crashes)));
+        }
+        Ok(())
+    }
+
+    pub fn exec_smoke(&self, smoke: &options::Smoke) -> Result<()> {
+        let targets = if smoke.targets.is_empty() {
+            self.exec_build(BuildMode::Build, &smoke.build, None)?;
+            self.targets.clone()
+        } else {
+            for target in &smoke.targets {
+                if !self.targets.contains(target) {
+                    bail!("no fuzz target named `{}`", target);
+                }
+                self.exec_build(BuildMode::Build, &smoke.build, Some(target))?;
+            }
+            smoke.targets.clone()
+        };
+
+        let config = Config::load(&self.fuzz_dir)?;
+        let mut failures = Vec::new();
+        for target in &targets {
+            let defaults = match &config {
+                Some(config) => config.for_target(Some(target), smoke.build.profile.as_deref())?,
+                None => Defaults::default(),
+            };
+            eprintln!("Smoke testing `{}`", target);
+            // libFuzzer always starts with the empty input, then the corpus.
+            let mut args = defaults.libfuzzer_args("run", &self.fuzz_dir);
+            args.push(format!("-runs={}", smoke.runs));
+            args.push(format!("-max_total_time={}", smoke.max_time));
+            args.extend(smoke.args.iter().cloned());
+            let start = time::Instant::now();
+            let crash = self.ci_run(&smoke.build, target, &args, &defaults)?;
+            let elapsed = start.elapsed();
+            match crash {
+                None => println!("{}: ok in {}ms", target, elapsed.as_millis()),
+                Some(crash) => {
+                    println!("{}: failed {}", target, crash.summary());
+                    if let Some(artifact) = &crash.artifact {
+                        println!("    {}", strip_current_dir_prefix(artifact).display());
+                    }
+                    failures.push(target);
+                }
+            }
+        }
+
+        if !failures.is_empty() {
+            return Err(Failure::Crash.wrap(anyhow!(
+                "{} of {} fuzz target(s) failed the smoke test",
+                failures.len(),
+                targets.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Runs a fuzz target over its corpus for `cargo fuzz ci` and `cargo fuzz
+    /// smoke`, returning the crash if there was one.
+    fn ci_run(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        args: &[String],
+        defaults: &Defaults,
+    ) -> Result<Option<CiCrash>> {
+        let before = time::SystemTime::now();
+        let mut cmd = self.cargo_run(build, target)?;
+        cmd.args(args).arg(self.corpus_for(target)?);
+        let (status, captured) = spawn_and_capture_stderr(&mut cmd, |_| {})?;
+        if status.success() {
+            return Ok(None);
+        }
+
+        let mut artifacts: Vec<_> = self
+            .get_artifacts_since(target, &before)?
+            .into_iter()
+            .collect();
+        artifacts.sort();
+        for artifact in &artifacts {
+            self.write_artifact_metadata(build, target, artifact, &captured);
+        }
+        let output = captured.output();
+        let report = CrashReport::parse(stacktrace::crash_excerpt(&output).unwrap_or(&output));
+        Ok(Some(CiCrash {
+            suppressed: defaults.suppress.contains(&report.stack_hash()),
+            report,
+            artifact: artifacts.into_iter().next(),
+            output,
+        }))
+    }
+
+    pub fn exec_init_clusterfuzzlite(&self, init: &options::InitClusterfuzzlite) -> Result<()> {
+        let (root, fuzz_dir) = self.package_relative_fuzz_dir()?;
+        let name = Manifest::parse()?.crate_name;
+        let build_sh =
+            self.oss_fuzz_build_script("init-clusterfuzzlite", &init.build, &name, &fuzz_dir)?;
+
+        let dir = root.join(".clusterfuzzlite");
+        write_generated_files(
+            &dir,
+            [
+                (
+                    "Dockerfile",
+                    clusterfuzzlite_dockerfile_template!(name).to_string(),
+                ),
+                ("build.sh", build_sh),
+                (
+                    "project.yaml",
+                    clusterfuzzlite_project_template!().to_string(),
+                ),
+            ],
+            init.force,
+        )?;
+        eprintln!(
+            "Wrote the ClusterFuzzLite configuration of {} fuzz target(s) to {}",
+            self.targets.len(),
+            strip_current_dir_prefix(&dir).display()
+        );
+        Ok(())
+    }
+
+    pub fn exec_init_oss_fuzz(&self, init: &options::InitOssFuzz) -> Result<()> {
+        let (root, fuzz_dir) = self.package_relative_fuzz_dir()?;
+        let manifest = Manifest::parse()?;
+        let build_sh = self.oss_fuzz_build_script(
+            "init-oss-fuzz",
+            &init.build,
+            &manifest.crate_name,
+            &fuzz_dir,
+        )?;
+
+        let mut placeholders = Vec::new();
+        let mut or_placeholder = |value: Option<String>, field: &'static str| {
+            value.unwrap_or_else(|| {
+                placeholders.push(field);
+                format!("TODO: {}", field)
+            })
+        };
+        let repository =
+            or_placeholder(init.repository.clone().or(manifest.repository), "main_repo");
+        let homepage = manifest.homepage.unwrap_or_else(|| repository.clone());
+        // Authors are usually written as `Name <email>`.
+        let contact = or_placeholder(
+            init.contact.clone().or_else(|| {
+                let author = manifest.authors.first()?;
+                let email = author.split('<').nth(1)?.split('>').next()?;
+                Some(email.to_owned())
+            }),
+            "primary_contact",
+        );
+
+        let dir = match &init.out_dir {
+            Some(dir) => dir.clone(),
+            None => root.join("oss-fuzz").join("projects").join(&init.project),
+        };
+        write_generated_files(
+            &dir,
+            [
+                (
+                    "Dockerfile",
+                    oss_fuzz_dockerfile_template!(manifest.crate_name, repository).to_string(),
+                ),
+                ("build.sh", build_sh),
+                (
+                    "project.yaml",
+                    oss_fuzz_project_template!(homepage, repository, contact).to_string(),
+                ),
+            ],
+            init.force,
+        )?;
+        eprintln!(
+            "Wrote the OSS-Fuzz project `{}` with {} fuzz target(s) to {}",
+            init.project,
+            self.targets.len(),
+            strip_current_dir_prefix(&dir).display()
+        );
+        if !placeholders.is_empty() {
+            eprintln!(
+                "warning: fill in the `{}` of project.yaml before submitting it",
+                placeholders.join("` and `")
+            );
+        }
+        Ok(())
+    }
+
+    pub fn exec_init_github_actions(&self, init: &options::InitGithubActions) -> Result<()> {
+        if self.targets.is_empty() {
+            bail!("there are no fuzz targets to fuzz, add one with `cargo fuzz add`");
+        }
+
+        // The workflow runs in a checkout of the whole repository, which may
+        // have the package in a subdirectory.
+        let (root, fuzz_dir) = self.package_relative_fuzz_dir()?;
+        let repository = git_output(&root, &["rev-parse", "--show-toplevel"])
+            .map(PathBuf::from)
+            .unwrap_or_else(|| root.clone());
+        let package_dir = root.strip_prefix(&repository).unwrap_or(Path::new(""));
+        let working_directory = if package_dir.as_os_str().is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\n    defaults:\n      run:\n        working-directory: {}",
+                package_dir.display()
+            )
+        };
+
+        let targets: Vec<_> = self
+            .targets
+            .iter()
+            .map(|target| format!("          - {}", target))
+            .collect();
+        let mut fuzz_args = init.build.to_string();
+        if fuzz_dir != Path::new(DEFAULT_FUZZ_DIR) {
+            fuzz_args.push_str(&format!(" --fuzz-dir={}", fuzz_dir.display()));
+        }
+        let workflow = github_actions_workflow_template!(
+            init.schedule,
+            targets.join("\n"),
+            working_directory,
+            package_dir.join(&fuzz_dir).display(),
+            fuzz_args,
+            init.time_budget
+        )
+        .to_string();
+
+        let dir = repository.join(".github").join("workflows");
+        write_generated_files(&dir, [("fuzz.yml", workflow)], init.force)?;
+        eprintln!(
+            "Wrote a workflow that fuzzes {} fuzz target(s) to {}",
+            self.targets.len(),
+            strip_current_dir_prefix(&dir.join("fuzz.yml")).display()
+        );
+        Ok(())
+    }
+
+    /// Returns the root of the package and the fuzz directory relative to it,
+    /// which is where OSS-Fuzz finds it in its checkout of the package.
+    fn package_relative_fuzz_dir(&self) -> Result<(PathBuf, PathBuf)> {
+        let root = fs::canonicalize(find_package()?)?;
+        let fuzz_dir = fs::canonicalize(self.fuzz_dir())
+            .with_context(|| format!("failed to resolve {}", self.fuzz_dir().display()))?;
+        match fuzz_dir.strip_prefix(&root) {
+            Ok(relative) => Ok((root, relative.to_owned())),
+            Err(_) => bail!(
+                "the fuzz directory {} is outside of the package at {}",
+                fuzz_dir.display(),
+                root.display()
+            ),
+        }
+    }
+
+    /// Renders the `build.sh` that OSS-Fuzz and ClusterFuzzLite run to build
+    /// all fuzz targets and copy them, their seed corpora and dictionaries to
+    /// `$OUT`.
+    fn oss_fuzz_build_script(
+        &self,
+        command: &str,
+        build: &BuildOptions,
+        name: &str,
+        fuzz_dir: &Path,
+    ) -> Result<String> {
+        if self.targets.is_empty() {
+            bail!("there are no fuzz targets to build, add one with `cargo fuzz add`");
+        }
+
+        // Build for Linux, into a target directory of its own so that the
+        // binaries are found regardless of any `CARGO_TARGET_DIR`.
+        let build = BuildOptions {
+            release: !build.dev,
+            triple: OSS_FUZZ_TRIPLE.to_owned(),
+            target_dir: Some(fuzz_dir.join("target").display().to_string()),
+            ..build.clone()
+        };
+        let binary_dir = self.fuzz_binary_dir(&build)?;
+        let mut build_args = build.to_string();
+        if fuzz_dir != Path::new(DEFAULT_FUZZ_DIR) {
+            build_args.push_str(&format!(" --fuzz-dir={}", fuzz_dir.display()));
+        }
+
+        let config = Config::load(&self.fuzz_dir)?;
+        let mut dictionaries = String::new();
+        for target in &self.targets {
+            let defaults = match &config {
+                Some(config) => config.for_target(Some(target), build.profile.as_deref())?,
+                None => Defaults::default(),
+            };
+            if let Some(dict) = &defaults.dict {
+                dictionaries.push_str(&format!(
+                    "cp \"{}\" \"$OUT/{}.dict\"\n",
+                    fuzz_dir.join(dict).display(),
+                    self.bin_name(target)
+                ));
+            }
+        }
+        if !dictionaries.is_empty() {
+            dictionaries.insert(0, '\n');
+        }
+
+        Ok(oss_fuzz_build_template!(
+            command,
+            name,
+            build_args,
+            fuzz_dir.display(),
+            binary_dir.display(),
+            self.targets
+                .iter()
+                .map(|target| self.bin_name(target))
+                .collect::<Vec<_>>()
+                .join(" "),
+            dictionaries
+        )
+        .to_string())
+    }
+
+    /// Returns the fuzz targets among `targets` that the changes since the git
+    /// revision `rev` may affect: the ones whose source or corpus changed, or
+    /// all of them if anything else changed, like the fuzzed crate.
+    fn changed_targets(&self, targets: &[String], rev: &str) -> Result<Vec<String>> {
+        let git = |args: &[&str]| -> Result<String> {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(self.fuzz_dir())
+                .args(args)
+                .output()
+                .context("failed to run git")?;
+            if !output.status.success() {
+                bail!(
+                    "`git {}` failed: {}",
+                    args.join(" "),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        };
+        let root = PathBuf::from(git(&["rev-parse", "--show-toplevel"])?.trim());
+        let diff = git(&["diff", "--name-only", rev, "--"])?;
+
+        // Git prints paths relative to the root of the repository, which has
+        // its symlinks resolved.
+        let fuzz_dir = fs::canonicalize(self.fuzz_dir())
+            .with_context(|| format!("failed to resolve {}", self.fuzz_dir().display()))?;
+        let sources: Vec<_> = targets
+            .iter()
+            .map(|target| {
+                let source = self.target_source_path(target);
+                match source.strip_prefix(self.fuzz_dir()) {
+                    Ok(relative) => fuzz_dir.join(relative),
+                    Err(_) => source,
+                }
+            })
+            .collect();
+
+        let mut changed = HashSet::new();
+        for path in diff.lines().map(|line| root.join(line)) {
+            let target = targets.iter().zip(&sources).find(|(target, source)| {
+                path == **source || path.starts_with(fuzz_dir.join("corpus").join(target))
+            });
+            match target {
+                Some((target, _)) => {
+                    changed.insert(target);
+                }
+                None => return Ok(targets.to_vec()),
+            }
+        }
+        Ok(targets
+            .iter()
+            .filter(|target| changed.contains(target))
+            .cloned()
+            .collect())
+    }
+
+    pub fn exec_dict_generate(&self, generate: &options::DictGenerate) -> Result<()> {
+        let target = &generate.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+
+        // The sources of the fuzzed crate, and of the fuzz target itself.
+        let mut sources = Vec::new();
+        let crate_src = find_package()?.join("src");
+        if crate_src.is_dir() {
+            sources = rust_files(&crate_src)?;
+        }
+        sources.push(self.target_source_path(target));
+        let mut source_tokens = BTreeSet::new();
+        for source in &sources {
+            let source = fs::read_to_string(source)
+                .with_context(|| format!("failed to read {}", source.display()))?;
+            source_tokens.extend(dict::source_tokens(&source));
+        }
+
+        let mut corpus_tokens = Vec::new();
+        let corpus = self.fuzz_dir().join("corpus").join(target);
+        if !generate.no_corpus && corpus.is_dir() {
+            let inputs = sorted_files(&corpus)?
+                .iter()
+                .map(|input| {
+                    fs::read(input).with_context(|| format!("failed to read {}", input.display()))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            corpus_tokens = dict::corpus_tokens(&inputs, generate.min_inputs);
+        }
+
+        let mut tokens = source_tokens;
+        tokens.extend(corpus_tokens);
+        tokens.retain(|token| (1..=dict::MAX_TOKEN_LEN).contains(&token.len()));
+
+        let default_output = self.dictionary_path(target);
+        let output = generate.output.as_ref().unwrap_or(&default_output);
+        if let Some(dir) = output.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create directory {}", dir.display()))?;
+        }
+        let comment = format!("Generated by `cargo fuzz dict generate {}`", target);
+        fs::write(output, dict::render(&comment, &tokens))
+            .with_context(|| format!("failed to write {}", output.display()))?;
+
+        eprintln!(
+            "Wrote {} entries to {}",
+            tokens.len(),
+            strip_current_dir_prefix(output).display()
+        );
+        if *output == default_output {
+            eprintln!("`cargo fuzz run`, `tmin` and `cmin` use it from now on");
+        } else if let Ok(relative) = output.strip_prefix(self.fuzz_dir()) {
+            eprintln!(
+                "Fuzz with it by setting `dict = \"{}\"` for `{}` in fuzz.toml",
+                relative.display(),
+                target
+            );
+        }
+        Ok(())
+    }
+
+    pub fn exec_artifacts_list(&self, list: &options::ArtifactsList) -> Result<()> {
+        let artifacts = all_artifact_files(&self.artifacts_for(&list.target)?)?;
+        let now = time::SystemTime::now();
+
+        println!("{:<10} {:>10} {:>8}  PATH", "CATEGORY", "SIZE", "AGE");
+        for artifact in &artifacts {
+            let metadata = fs::metadata(artifact)
+                .with_context(|| format!("failed to read metadata of {}", artifact.display()))?;
+            let age = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .map_or_else(|| "?".to_owned(), format_age);
+            println!(
+                "{:<10} {:>10} {:>8}  {}",
+                artifact_category(artifact),
+                metadata.len(),
+                age,
+                strip_current_dir_prefix(artifact).display()
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn exec_artifacts_prune(&self, prune: &options::ArtifactsPrune) -> Result<()> {
+        let artifacts = all_artifact_files(&self.artifacts_for(&prune.target)?)?;
+
+        let mut pruned = Vec::new();
+        // A cutoff too far back to represent leaves every artifact newer.
+        let cutoff = prune.older_than.and_then(|days| {
+            let age = time::Duration::from_secs(days.checked_mul(24 * 60 * 60)?);
+            time::SystemTime::now().checked_sub(age)
+        });
+        if let Some(cutoff) = cutoff {
+            for artifact in &artifacts {
+                let modified = fs::metadata(artifact)
+                    .and_then(|metadata| metadata.modified())
+                    .with_context(|| {
+                        format!("failed to read modification time of {}", artifact.display())
+                    })?;
+                if modified < cutoff {
+                    pruned.push(artifact.clone());
+                }
+            }
+        }
+
+        if prune.fixed {
+            self.exec_build(BuildMode::Build, &prune.build, Some(&prune.target))?;
+            for artifact in &artifacts {
+                if pruned.contains(artifact) {
+                    continue;
+                }
+                let (success, _) =
+                    self.replay_input(&prune.build, &prune.target, artifact, &prune.args)?;
+                if success {
+                    pruned.push(artifact.clone());
+                }
+            }
+        }
+
+        pruned.sort();
+        for artifact in &pruned {
+            if prune.dry_run {
+                println!(
+                    "Would delete {}",
+                    strip_current_dir_prefix(artifact).display()
+                );
+            } else {
+                remove_artifact(artifact)?;
+                println!("Deleted {}", strip_current_dir_prefix(artifact).display());
+            }
+        }
+        eprintln!(
+            "{} {} of {} artifact(s)",
+            if prune.dry_run {
+                "Would prune"
+            } else {
+                "Pruned"
+            },
+            pruned.len(),
+            artifacts.len()
+        );
+
+        Ok(())
+    }
+
+    pub fn exec_artifacts_archive(&self, archive: &options::ArtifactsArchive) -> Result<()> {
+        let artifacts_dir = self.artifacts_for(&archive.target)?;
+        let artifacts = if archive.artifacts.is_empty() {
+            all_artifact_files(&artifacts_dir)?
+        } else {
+            archive
+                .artifacts
+                .iter()
+                .map(|name| {
+                    let artifact = artifacts_dir.join(name);
+                    if !artifact.is_file() {
+                        bail!(
+                            "no artifact named `{}` in {}",
+                            name,
+                            artifacts_dir.display()
+                        );
+                    }
+                    Ok(artifact)
+                })
+                .collect::<Result<Vec<_>>>()?
+        };
+        if artifacts.is_empty() {
+            bail!("no artifacts found in {}", artifacts_dir.display());
+        }
+
+        let created = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)?
+            .as_secs();
+        let output = match &archive.output {
+            Some(output) => output.clone(),
+            None => PathBuf::from(format!("{}-artifacts-{}.tar.gz", archive.target, created)),
+        };
+
+        let file = fs::File::create(&output)
+            .with_context(|| format!("failed to create {}", output.display()))?;
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            file,
+            flate2::Compression::default(),
+        ));
+
+        let mut entries = Vec::new();
+        for artifact in &artifacts {
+            let data = fs::read(artifact)
+                .with_context(|| format!("failed to read {}", artifact.display()))?;
+            let modified = fs::metadata(artifact)?
+                .modified()?
+                .duration_since(time::UNIX_EPOCH)?
+                .as_secs();
+            let name = file_name(artifact);
+            entries.push(serde_json::json!({
+                "name": name,
+                "category": artifact_category(artifact),
+                "size": data.len(),
+                "modified": modified,
+                "sha1": utils::content_hash(&data),
+            }));
+            builder
+                .append_path_with_name(artifact, format!("{}/{}", archive.target, name))
+                .with_context(|| format!("failed to archive {}", artifact.display()))?;
+            let sidecar = artifact_metadata_path(artifact);
+            if sidecar.is_file() {
+                builder
+                    .append_path_with_name(
+                        &sidecar,
+                        format!("{}/{}", archive.target, file_name(&sidecar)),
+                    )
+                    .with_context(|| format!("failed to archive {}", sidecar.display()))?;
+            }
+        }
+
+        let metadata = serde_json::to_vec_pretty(&serde_json::json!({
+            "target": archive.target,
+            "created": created,
+            "artifacts": entries,
+        }))?;
+        tar_append(
+            &mut builder,
+            &format!("{}/metadata.json", archive.target),
+            &metadata,
+            created,
+        )?;
+        builder
+            .into_inner()?
+            .finish()
+            .with_context(|| format!("failed to write {}", output.display()))?;
+
+        eprintln!(
+            "Archived {} artifact(s) to {}",
+            artifacts.len(),
+            output.display()
+        );
+
+        Ok(())
+    }
+
+    pub fn exec_bundle(&self, bundle: &options::Bundle) -> Result<()> {
+        let artifact = &bundle.artifact;
+        let data =
+            fs::read(artifact).with_context(|| format!("failed to read {}", artifact.display()))?;
+        self.exec_build(BuildMode::Build, &bundle.build, Some(&bundle.target))?;
+
+        let (success, stderr) = self.replay_input(&bundle.build, &bundle.target, artifact, &[])?;
+        if success {
+            eprintln!(
+                "warning: {} doesn't crash `{}` anymore, bundling it anyway",
+                artifact.display(),
+                bundle.target
+            );
+        }
+        let captured = CapturedStderr {
+            seed: None,
+            lines: stderr.split_inclusive('\n').map(String::from).collect(),
+        };
+        let mut metadata =
+            self.artifact_metadata(&bundle.build, &bundle.target, artifact, &captured);
+        metadata["sha1"] = utils::content_hash(&data).into();
+        metadata["rustflags"] = rustflags(&bundle.build).ok().into();
+        metadata["reproduces"] = (!success).into();
+        metadata["stack_hash"] = (!success)
+            .then(|| CrashReport::parse(&stderr).stack_hash())
+            .into();
+
+        let debug = self
+            .run_fuzz_target_debug_formatter(&bundle.build, &bundle.target, artifact)
+            .unwrap_or_else(|_| {
+                format!(
+                    "The input can't be debug formatted, so here is a hexdump of it:\n\n{}",
+                    utils::hexdump(&data)
+                )
+            });
+        let backtrace = stacktrace::crash_excerpt(&stderr).unwrap_or(&stderr);
+
+        let artifact_name = file_name(artifact).into_owned();
+        let mut files = vec![
+            (artifact_name.clone(), data),
+            (
+                "metadata.json".to_owned(),
+                serde_json::to_vec_pretty(&metadata)?,
+            ),
+            ("debug.txt".to_owned(), debug.into_bytes()),
+            ("backtrace.txt".to_owned(), backtrace.as_bytes().to_vec()),
+        ];
+        // The metadata of when the artifact was found, with the seed.
+        let sidecar = artifact_metadata_path(artifact);
+        if sidecar.is_file() {
+            let found = fs::read(&sidecar)
+                .with_context(|| format!("failed to read {}", sidecar.display()))?;
+            files.push((file_name(&sidecar).into_owned(), found));
+        }
+        if bundle.dockerfile {
+            let dockerfile = self.bundle_dockerfile(bundle, &artifact_name)?;
+            files.push(("Dockerfile".to_owned(), dockerfile.into_bytes()));
+        }
+
+        let name = format!("{}-{}", utils::bin_name(&bundle.target), artifact_name);
+        let output = match &bundle.output {
+            Some(output) => output.clone(),
+            None => PathBuf::from(format!("{}.tar.gz", name)),
+        };
+        let file = fs::File::create(&output)
+            .with_context(|| format!("failed to create {}", output.display()))?;
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            file,
+            flate2::Compression::default(),
+        ));
+        let created = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)?
+            .as_secs();
+        for (file_name, contents) in &files {
+            tar_append(
+                &mut builder,
+                &format!("{}/{}", name, file_name),
+                contents,
+                created,
+            )?;
+        }
+        builder
+            .into_inner()?
+            .finish()
+            .with_context(|| format!("failed to write {}", output.display()))?;
+
+        eprintln!(
+            "Bundled {} with {} to {}",
+            artifact_name,
+            files[1..]
+                .iter()
+                .map(|(file_name, _)| file_name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            output.display()
+        );
+        Ok(())
+    }
+
+    /// Renders the Dockerfile of a bundle, which builds the fuzz target at the
+    /// current commit and replays the bundled artifact.
+    fn bundle_dockerfile(&self, bundle: &options::Bundle, artifact_name: &str) -> Result<String> {
+        let (root, fuzz_dir) = self.package_relative_fuzz_dir()?;
+        let toplevel = git_output(&root, &["rev-parse", "--show-toplevel"])
+            .map(PathBuf::from)
+            .unwrap_or_else(|| root.clone());
+        let package_dir = root.strip_prefix(&toplevel).unwrap_or(Path::new(""));
+        let repository = git_output(&root, &["remote", "get-url", "origin"]);
+        let commit = git_output(&root, &["rev-parse", "HEAD"]);
+        if repository.is_none() || commit.is_none() {
+            eprintln!(
+                "warning: the fuzzed package has no git remote `origin` or no commit, fill them \
+                 in in the Dockerfile of the bundle"
+            );
+        }
+
+        let fuzz_dir = if fuzz_dir == Path::new("fuzz") {
+            String::new()
+        } else {
+            format!(" --fuzz-dir {}", fuzz_dir.display())
+        };
+        let command = format!(
+            "cargo fuzz run{}{} {} /crash/{}",
+            fuzz_dir, bundle.build, bundle.target, artifact_name
+        );
+        Ok(bundle_dockerfile_template!(
+            repository.as_deref().unwrap_or("<repository>"),
+            commit.as_deref().unwrap_or("<commit>"),
+            package_dir.display(),
+            artifact_name,
+            command
+        )
+        .to_string())
+    }
+
+    pub fn exec_promote(&self, promote: &options::Promote) -> Result<()> {
+        if !promote.artifact.is_file() {
+            bail!("{} is not a file", promote.artifact.display());
+        }
+        let corpus = self.corpus_for(&promote.target)?;
+
+        let input = if promote.minimize {
+            self.exec_build(BuildMode::Build, &promote.build, Some(&promote.target))?;
+            let minimized = tempfile::NamedTempFile::new().context("failed to create temp file")?;
+            if !self.minimize_crash(
+                &promote.build,
+                &promote.target,
+                &promote.artifact,
+                minimized.path(),
+                promote.runs,
+                &promote.args,
+            )? {
+                bail!(
+                    "failed to minimize {}; only inputs that still crash `{}` can be minimized",
+                    promote.artifact.display(),
+                    promote.target
+                );
+            }
+            move_to_corpus(minimized.path(), &promote.artifact, &corpus)?
+        } else {
+            move_to_corpus(&promote.artifact, &promote.artifact, &corpus)?
+        };
+
+        eprintln!(
+            "Promoted {} to {}",
+            strip_current_dir_prefix(&promote.artifact).display(),
+            strip_current_dir_prefix(&input).display()
+        );
+
+        Ok(())
+    }
+
+    pub fn exec_report(&self, report: &options::Report) -> Result<()> {
+        let targets = if report.targets.is_empty() {
+            self.targets.clone()
+        } else {
+            for target in &report.targets {
+                if !self.targets.contains(target) {
+                    bail!("no fuzz target named `{}`", target);
+                }
+            }
+            report.targets.clone()
+        };
+
+        let mut reports = Vec::new();
+        for target in targets {
+            let (corpus_inputs, corpus_bytes) = self.corpus_size(&target)?;
+
+            let artifacts_dir = self.fuzz_dir().join("artifacts").join(&target);
+            let artifacts = if artifacts_dir.is_dir() {
+                artifact_files(&artifacts_dir)?
+            } else {
+                Vec::new()
+            };
+            let mut crashes: Vec<CrashBucket> = Vec::new();
+            for artifact in &artifacts {
+                let metadata: Option<serde_json::Value> =
+                    fs::read(artifact_metadata_path(artifact))
+                        .ok()
+                        .and_then(|json| serde_json::from_slice(&json).ok());
+                let backtrace = metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata["backtrace"].as_str())
+                    .map(str::to_owned);
+                let crash_report = backtrace.as_deref().map(CrashReport::parse);
+                let hash = crash_report.as_ref().map(CrashReport::stack_hash);
+
+                let artifact_name = strip_current_dir_prefix(artifact).display().to_string();
+                if let Some(bucket) = crashes.iter_mut().find(|bucket| bucket.hash == hash) {
+                    bucket.artifacts.push(artifact_name);
+                    continue;
+                }
+                let reproduce = metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata["reproduce"].as_str())
+                    .map(str::to_owned)
+                    .unwrap_or_else(|| {
+                        let artifact = strip_current_dir_prefix(artifact);
+                        self.reproduce_command(&"", &target, artifact)
+                    });
+                let crash_report = crash_report.unwrap_or_default();
+                crashes.push(CrashBucket {
+                    hash,
+                    kind: crash_report.kind,
+                    panic_location: crash_report.panic_location,
+                    artifacts: vec![artifact_name],
+                    backtrace,
+                    reproduce,
+                });
+            }
+
+            reports.push(TargetReport {
+                runs: stats::read_runs(&self.stats_for(&target))?,
+                target,
+                corpus_inputs,
+                corpus_bytes,
+                crashes,
+            });
+        }
+
+        let rendered = report::render(report.format, &reports);
+        match &report.output {
+            Some(output) => {
+                fs::write(output, rendered)
+                    .with_context(|| format!("failed to write {}", output.display()))?;
+                eprintln!("Wrote report to {}", output.display());
+            }
+            None => print!("{}", rendered),
+        }
+
+        Ok(())
+    }
+
+    pub fn exec_history(&self, history: &options::History) -> Result<()> {
+        let db = self.sessions_db();
+        let mut runs = if db.exists() {
+            Sessions::open(&db)?.runs(&history.target, history.since, history.until)?
+        } else {
+            Vec::new()
+        };
+        if let Some(rev) = &history.rev {
+            let rev_commit = git_output(
+                self.fuzz_dir(),
+                &["rev-parse", "--verify", &format!("{}^{{commit}}", rev)],
+            )
+            .with_context(|| format!("`{}` isn't a git revision", rev))?;
+            let mut after_rev = HashMap::new();
+            runs.retain(|run| match &run.git_commit {
+                Some(commit) => *after_rev.entry(commit.clone()).or_insert_with(|| {
+                    Command::new("git")
+                        .arg("-C")
+                        .arg(self.fuzz_dir())
+                        .args(["merge-base", "--is-ancestor", &rev_commit, commit])
+                        .output()
+                        .is_ok_and(|output| output.status.success())
+                }),
+                None => false,
+            });
+        }
+
+        if history.json {
+            println!("{}", serde_json::to_string_pretty(&runs)?);
+            return Ok(());
+        }
+        if runs.is_empty() {
+            eprintln!("No recorded runs of `{}`", history.target);
+            return Ok(());
+        }
+
+        let optional = |value: Option<u64>| value.map_or_else(|| "-".to_owned(), |v| v.to_string());
+        let mut rows = vec![[
+            "ID",
+            "STARTED",
+            "COMMIT",
+            "DURATION",
+            "EXECS",
+            "EXEC/S",
+            "COV",
+            "FT",
+            "CORPUS",
+            "ARTIFACTS",
+            "RESULT",
+        ]
+        .map(str::to_owned)];
+        for run in &runs {
+            let mut categories = BTreeMap::new();
+            for artifact in &run.artifacts {
+                *categories.entry(artifact.category.as_str()).or_insert(0) += 1;
+            }
+            let artifacts = categories
+                .iter()
+                .map(|(category, count)| format!("{} {}", count, category))
+                .collect::<Vec<_>>()
+                .join(", ");
+            rows.push([
+                run.id.to_string(),
+                sessions::format_time(run.started),
+                run.git_commit
+                    .as_deref()
+                    .map_or("-", |commit| &commit[..commit.len().min(8)])
+                    .to_owned(),
+                run.elapsed_secs
+                    .map_or_else(|| "-".to_owned(), report::format_duration),
+                optional(run.execs),
+                optional(run.execs_per_sec),
+                optional(run.cov),
+                optional(run.features),
+                optional(run.corpus_inputs),
+                if artifacts.is_empty() {
+                    "0".to_owned()
+                } else {
+                    artifacts
+                },
+                run.result().to_owned(),
+            ]);
+        }
+        // The times, commits and artifacts are left-aligned, the numbers
+        // right-aligned.
+        print_table(
+            &rows,
+            &[
+                false, true, true, false, false, false, false, false, false, true, true,
+            ],
+        );
+
+        let fuzzed: u64 = runs.iter().filter_map(|run| run.elapsed_secs).sum();
+        let artifacts: usize = runs.iter().map(|run| run.artifacts.len()).sum();
+        eprintln!(
+            "\n{} run(s), {} of fuzzing, {} artifact(s)",
+            runs.len(),
+            report::format_duration(fuzzed),
+            artifacts
+        );
+        Ok(())
+    }
+
+    pub fn exec_compare(&self, compare: &options::Compare) -> Result<()> {
+        let (base_label, base) = self.snapshot(&compare.base)?;
+        let (new_label, new) = self.snapshot(&compare.new)?;
+        let deltas = compare::compare(&base, &new, f64::from(compare.threshold));
+        if deltas.is_empty() {
+            bail!(
+                "`{}` and `{}` have no numbers in common to compare",
+                compare.base,
+                compare.new
+            );
+        }
+
+        println!("Comparing {}\n     with {}\n", base_label, new_label);
+        let mut rows = vec![["", "BASE", "NEW", "CHANGE"].map(str::to_owned)];
+        for delta in &deltas {
+            let change = if delta.regression {
+                output::error(&delta.change)
+            } else if delta.improvement {
+                output::success(&delta.change)
+            } else {
+                delta.change.clone()
+            };
+            rows.push([
+                delta.metric.to_owned(),
+                delta.base.clone(),
+                delta.new.clone(),
+                change,
+            ]);
+        }
+        print_table(&rows, &[true, false, false, false]);
+
+        let regressions: Vec<_> = deltas
+            .iter()
+            .filter(|delta| delta.regression)
+            .map(|delta| delta.metric)
+            .collect();
+        if regressions.is_empty() {
+            eprintln!("\n{}", output::success("No regressions"));
+            return Ok(());
+        }
+        let message = format!("Regressed: {}", regressions.join(", "));
+        if compare.fail_on_regression {
+            bail!(message);
+        }
+        eprintln!("\n{}", output::error(&message));
+        Ok(())
+    }
+
+    /// Reads the numbers of `run` for `cargo fuzz compare`, along with a
+    /// description of it.
+    fn snapshot(&self, run: &str) -> Result<(String, Snapshot)> {
+        if let Ok(id) = run.parse::<i64>() {
+            let db = self.sessions_db();
+            let record = if db.exists() {
+                Sessions::open(&db)?.run(id)?
+            } else {
+                None
+            };
+            let record = record.with_context(|| {
+                format!("no recorded run {}, `cargo fuzz history` lists them", id)
+            })?;
+            let commit = record
+                .git_commit
+                .as_deref()
+                .map_or(String::new(), |commit| {
+                    format!(" at {}", &commit[..commit.len().min(8)])
+                });
+            let label = format!(
+                "run {} of `{}`{}, {}",
+                id,
+                record.target,
+                commit,
+                sessions::format_time(record.started)
+            );
+            return Ok((label, Snapshot::from_run(&record)));
+        }
+
+        let json = fs::read(run).with_context(|| format!("failed to read {}", run))?;
+        if let Ok(stats) = serde_json::from_slice::<stats::RunStats>(&json) {
+            return Ok((run.to_owned(), Snapshot::from_stats(&stats)));
+        }
+        let totals = llvm_cov::totals(&json).with_context(|| {
+            format!(
+                "{} is neither a stats file nor a coverage summary of `cargo fuzz coverage \
+                 --summary-json`",
+                run
+            )
+        })?;
+        Ok((run.to_owned(), Snapshot::from_coverage(totals)))
+    }
+
+    pub fn exec_status(&self, status: &options::Status) -> Result<()> {
+        let builds = [
+            ("release", false, false),
+            ("dev", true, false),
+            ("coverage", false, true),
+        ]
+        .into_iter()
+        .map(|(name, dev, coverage)| {
+            let build = BuildOptions {
+                dev,
+                release: false,
+                coverage,
+                ..status.build.clone()
+            };
+            Ok((name, self.fuzz_binary_dir(&build)?, build))
+        })
+        .collect::<Result<Vec<_>>>()?;
+        let now = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)?
+            .as_secs();
+
+        let mut rows = vec![[
+            "TARGET".to_owned(),
+            "BUILT".to_owned(),
+            "CORPUS".to_owned(),
+            "ARTIFACTS".to_owned(),
+            "LAST FUZZED".to_owned(),
+            "COVERAGE".to_owned(),
+        ]];
+        for target in &self.targets {
+            let binary = format!("{}{}", self.bin_name(target), env::consts::EXE_SUFFIX);
+            let built: Vec<_> = builds
+                .iter()
+                .filter(|(_, dir, _)| dir.join(&binary).exists())
+                .map(|(name, _, _)| *name)
+                .collect();
+            let built = if built.is_empty() {
+                "no".to_owned()
+            } else {
+                built.join(", ")
+            };
+
+            let (corpus_inputs, corpus_bytes) = self.corpus_size(target)?;
+            let corpus = format!("{} ({})", corpus_inputs, format_size(corpus_bytes));
+
+            let artifacts_dir = self.fuzz_dir().join("artifacts").join(target);
+            let artifacts = if artifacts_dir.is_dir() {
+                artifact_files(&artifacts_dir)?.len()
+            } else {
+                0
+            };
+
+            let last_fuzzed = stats::read_runs(&self.stats_for(target))?
+                .last()
+                .map_or_else(
+                    || "never".to_owned(),
+                    |run| {
+                        let ended = run.started + run.elapsed_secs;
+                        let age = time::Duration::from_secs(now.saturating_sub(ended));
+                        format!("{} ago", format_age(age))
+                    },
+                );
+
+            // Coverage is only available after `cargo fuzz coverage`, and
+            // with llvm-cov at hand.
+            let (_, _, coverage_build) = &builds[2];
+            let coverage = self
+                .coverage_totals(status.llvm_path.as_deref(), coverage_build, target)
+                .map_or_else(|_| "-".to_owned(), |totals| format!("{:.1}%", totals.lines));
+
+            rows.push([
+                target.clone(),
+                built,
+                corpus,
+                artifacts.to_string(),
+                last_fuzzed,
+                coverage,
+            ]);
+        }
+
+        // The names and builds are left-aligned, the numbers right-aligned.
+        print_table(&rows, &[true, true, false, false, false, false]);
+
+        Ok(())
+    }
+
+    /// Returns the total coverage of a fuzz target, as measured by the last
+    /// `cargo fuzz coverage` run.
+    fn coverage_totals(
+        &self,
+        llvm_path: Option<&Path>,
+        build: &BuildOptions,
+        target: &str,
+    ) -> Result<llvm_cov::CoverageTotals> {
+        let profdata = self
+            .fuzz_dir()
+            .join("coverage")
+            .join(target)
+            .join("coverage.profdata");
+        if !profdata.exists() || !self.fuzz_binary_path(build, target)?.exists() {
+            bail!("no coverage data for `{}`", target);
+        }
+        let mut cov_cmd = self.llvm_cov(llvm_path, "export", build, &[target], &profdata, false)?;
+        cov_cmd.arg("--format=text").arg("--summary-only");
+        let output = cov_cmd
+            .stderr(Stdio::null())
+            .output()
+            .with_context(|| format!("Failed to run command: {:?}", cov_cmd))
+            .fail(Failure::Environment)?;
+        if !output.status.success() {
+            bail!("llvm-cov export exited with {}", output.status);
+        }
+        llvm_cov::totals(&output.stdout)
+    }
+
+    /// Measures the coverage of the fuzz target of `coverage` over its
+    /// corpora like `cargo fuzz coverage` does, and returns its totals.
+    pub fn coverage(&self, coverage: &options::Coverage) -> Result<CoverageOutcome> {
+        let target = match &coverage.target {
+            Some(target) if !coverage.all => target,
+            _ => bail!("the coverage of a single fuzz target is measured at a time"),
+        };
+        self.exec_coverage(coverage)?;
+        let (_, profdata) = self.coverage_for(target)?;
+        let summary = self.coverage_summary_json(coverage, &[target], &profdata)?;
+        Ok(CoverageOutcome {
+            totals: llvm_cov::totals(&summary)?,
+            profdata,
+        })
+    }
+
+    /// Produce coverage information for a given corpus
+    pub fn exec_coverage(&self, coverage: &options::Coverage) -> Result<()> {
+        let target = match &coverage.target {
+            Some(target) if !coverage.all => target,
+            _ => return self.exec_coverage_all(coverage),
+        };
+
+        // Build project with source-based coverage generation enabled.
+        self.build_for_coverage(coverage, Some(target))?;
+
+        // Retrieve corpus directories.
+        let corpora = if coverage.corpus.is_empty() {
+            vec![self.corpus_for(target)?]
+        } else {
+            coverage
+                .corpus
+                .iter()
+                .map(|name| Path::new(name).to_path_buf())
+                .collect()
+        };
+
+        // Collect the (non-directory) readable input files from the corpora.
+        let files_and_dirs = corpora.iter().flat_map(fs::read_dir).flatten().flatten();
+        let mut readable_input_files = files_and_dirs
+            .filter(|file| match file.file_type() {
+                Ok(ft) => ft.is_file(),
+                _ => false,
             })
             .peekable();
         if readable_input_files.peek().is_none() {
@@ -717,324 +5689,2106 @@ impl FuzzProject {
             )
         }
 
-        let (coverage_out_raw_dir, coverage_out_file) = self.coverage_for(&coverage.target)?;
+        let (coverage_out_raw_dir, coverage_out_file) = self.coverage_for(target)?;
+        self.generate_coverage(coverage, target, &corpora, &coverage_out_raw_dir)?;
+
+        let mut profdata_bin_path = coverage.llvm_path.clone().unwrap_or(rustlib()?);
+        profdata_bin_path.push(format!("llvm-profdata{}", env::consts::EXE_SUFFIX));
+        self.merge_coverage(
+            &profdata_bin_path,
+            &[coverage_out_raw_dir],
+            &coverage_out_file,
+        )?;
+
+        if let Some(format) = coverage.format {
+            let output = match (&coverage.output, format) {
+                (Some(output), _) => output.clone(),
+                (None, CoverageFormat::Html) => coverage_out_file.with_file_name("html"),
+                (None, CoverageFormat::Lcov) => coverage_out_file.with_file_name("coverage.lcov"),
+            };
+            self.export_coverage(coverage, &[target], &coverage_out_file, format, &output)?;
+        }
+        if coverage.uncovered {
+            let functions = self.export_functions(
+                coverage.llvm_path.as_deref(),
+                &coverage.build,
+                &[target],
+                &coverage_out_file,
+                coverage.include_deps,
+            )?;
+            print!("{}", llvm_cov::render_uncovered(&functions));
+        }
+        // Last, since `--fail-under` fails the command.
+        self.export_coverage_summary(coverage, &[target], &coverage_out_file)?;
+
+        Ok(())
+    }
+
+    /// Runs every fuzz target over its corpus and merges the coverage of all
+    /// of them, to tell how much of the code the fuzz targets cover together.
+    fn exec_coverage_all(&self, coverage: &options::Coverage) -> Result<()> {
+        self.build_for_coverage(coverage, None)?;
+
+        let mut targets = Vec::new();
+        let mut raw_dirs = Vec::new();
+        for target in &self.targets {
+            let corpus = self.corpus_for(target)?;
+            if sorted_files(&corpus)?.is_empty() {
+                eprintln!("Skipping `{}`, its corpus is empty", target);
+                continue;
+            }
+            let (coverage_out_raw_dir, _) = self.coverage_for(target)?;
+            self.generate_coverage(coverage, target, &[corpus], &coverage_out_raw_dir)?;
+            targets.push(&target[..]);
+            raw_dirs.push(coverage_out_raw_dir);
+        }
+        if targets.is_empty() {
+            bail!(
+                "The corpora of all fuzz targets are empty. \
+                 Try running the fuzzers first (`cargo fuzz run ...`) to generate corpora."
+            )
+        }
+
+        let coverage_out_dir = self
+            .fuzz_dir()
+            .join("coverage")
+            .join(ALL_TARGETS_COVERAGE_DIR);
+        fs::create_dir_all(&coverage_out_dir).with_context(|| {
+            format!(
+                "could not make a coverage directory at {:?}",
+                coverage_out_dir
+            )
+        })?;
+        let coverage_out_file = coverage_out_dir.join("coverage.profdata");
+
+        let mut profdata_bin_path = coverage.llvm_path.clone().unwrap_or(rustlib()?);
+        profdata_bin_path.push(format!("llvm-profdata{}", env::consts::EXE_SUFFIX));
+        self.merge_coverage(&profdata_bin_path, &raw_dirs, &coverage_out_file)?;
+
+        let mut report_cmd = self.llvm_cov(
+            coverage.llvm_path.as_deref(),
+            "report",
+            &coverage.build,
+            &targets,
+            &coverage_out_file,
+            coverage.include_deps,
+        )?;
+        let status = report_cmd
+            .status()
+            .with_context(|| format!("Failed to run command: {:?}", report_cmd))?;
+        if !status.success() {
+            bail!("llvm-cov report exited with {}", status);
+        }
+
+        if let Some(format) = coverage.format {
+            let output = match (&coverage.output, format) {
+                (Some(output), _) => output.clone(),
+                (None, CoverageFormat::Html) => coverage_out_dir.join("html"),
+                (None, CoverageFormat::Lcov) => coverage_out_dir.join("coverage.lcov"),
+            };
+            self.export_coverage(coverage, &targets, &coverage_out_file, format, &output)?;
+        }
+        if coverage.uncovered {
+            let functions = self.export_functions(
+                coverage.llvm_path.as_deref(),
+                &coverage.build,
+                &targets,
+                &coverage_out_file,
+                coverage.include_deps,
+            )?;
+            print!("{}", llvm_cov::render_uncovered(&functions));
+        }
+        // Last, since `--fail-under` fails the command.
+        self.export_coverage_summary(coverage, &targets, &coverage_out_file)?;
+
+        Ok(())
+    }
+
+    /// Builds one or all fuzz targets with coverage instrumentation, unless
+    /// `--no-build` asks to reuse an existing build.
+    fn build_for_coverage(
+        &self,
+        coverage: &options::Coverage,
+        fuzz_target: Option<&str>,
+    ) -> Result<()> {
+        if !coverage.no_build {
+            return self.exec_build(BuildMode::Build, &coverage.build, fuzz_target);
+        }
+        let targets = match fuzz_target {
+            Some(target) => vec![target],
+            None => self.targets.iter().map(String::as_str).collect(),
+        };
+        for target in targets {
+            let bin_path = self.fuzz_binary_path(&coverage.build, target)?;
+            if !bin_path.exists() {
+                bail!(
+                    "no coverage build of `{}` at {}, run `cargo fuzz coverage` without \
+                     `--no-build` first",
+                    target,
+                    bin_path.display()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the coverage build of `target` over `corpora`, writing the raw
+    /// coverage data to `coverage_out_raw_dir`. With `--jobs`, each corpus is
+    /// split into shards that are run in parallel, each writing its own raw
+    /// coverage file.
+    fn generate_coverage(
+        &self,
+        coverage: &options::Coverage,
+        target: &str,
+        corpora: &[PathBuf],
+        coverage_out_raw_dir: &Path,
+    ) -> Result<()> {
+        for corpus in corpora.iter() {
+            // Raw coverage data will be saved in `coverage/<target>` directory.
+            let corpus_name = corpus
+                .file_name()
+                .and_then(|x| x.to_str())
+                .with_context(|| format!("Invalid corpus directory: {:?}", corpus))?;
+            // Don't merge the data of an earlier run with a different number
+            // of jobs.
+            remove_stale_profraws(coverage_out_raw_dir, corpus_name)?;
+
+            let files = if corpus.is_dir() {
+                sorted_files(corpus)?
+            } else {
+                Vec::new()
+            };
+            let jobs = usize::from(coverage.jobs).min(files.len());
+            if jobs <= 1 {
+                let profraw_path =
+                    coverage_out_raw_dir.join(format!("default-{}.profraw", corpus_name));
+                // _tmp_dir is deleted when it goes of of scope.
+                let (mut cmd, _tmp_dir) =
+                    self.create_coverage_cmd(coverage, target, &profraw_path, corpus)?;
+                eprintln!("Generating coverage data for corpus {:?}", corpus);
+                run_coverage_cmd(&mut cmd)?;
+                check_profraw_written(coverage, &profraw_path)?;
+                continue;
+            }
+
+            let shards = (0..jobs)
+                .map(|_| tempfile::tempdir())
+                .collect::<io::Result<Vec<_>>>()?;
+            for (i, file) in files.iter().enumerate() {
+                let shard_file = shards[i % jobs].path().join(file_name(file).as_ref());
+                // Hard links are cheap, but don't work across file systems.
+                if fs::hard_link(file, &shard_file).is_err() {
+                    fs::copy(file, &shard_file)
+                        .with_context(|| format!("failed to copy {}", file.display()))?;
+                }
+            }
+            let profraw_paths: Vec<_> = (0..jobs)
+                .map(|i| {
+                    coverage_out_raw_dir.join(format!("default-{}.{}.profraw", corpus_name, i))
+                })
+                .collect();
+            let cmds = shards
+                .iter()
+                .zip(&profraw_paths)
+                .map(|(shard, profraw_path)| {
+                    self.create_coverage_cmd(coverage, target, profraw_path, shard.path())
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            eprintln!(
+                "Generating coverage data for corpus {:?} with {} jobs",
+                corpus, jobs
+            );
+            thread::scope(|scope| {
+                let jobs: Vec<_> = cmds
+                    .into_iter()
+                    .map(|(mut cmd, tmp_dir)| {
+                        scope.spawn(move || {
+                            let _tmp_dir = tmp_dir;
+                            run_coverage_cmd(&mut cmd)
+                        })
+                    })
+                    .collect();
+                jobs.into_iter()
+                    .try_for_each(|job| job.join().expect("coverage job panicked"))
+            })?;
+            for profraw_path in &profraw_paths {
+                check_profraw_written(coverage, profraw_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn create_coverage_cmd(
+        &self,
+        coverage: &options::Coverage,
+        target: &str,
+        profraw_path: &Path,
+        corpus_dir: &Path,
+    ) -> Result<(Command, tempfile::TempDir)> {
+        let bin_path = self.fuzz_binary_path(&coverage.build, target)?;
+        let mut cmd = match coverage_runner(coverage) {
+            Some(runner) => {
+                let mut runner = runner.split_whitespace();
+                let mut cmd = Command::new(runner.next().context("the runner is empty")?);
+                cmd.args(runner).arg(bin_path);
+                cmd
+            }
+            None => Command::new(bin_path),
+        };
+
+        cmd.env("LLVM_PROFILE_FILE", strip_verbatim_prefix(profraw_path));
+        if let Some(dll_dir) = msvc_asan_dll_dir(&coverage.build) {
+            let mut paths = vec![dll_dir];
+            if let Some(path) = env::var_os("PATH") {
+                paths.extend(env::split_paths(&path));
+            }
+            cmd.env("PATH", env::join_paths(paths)?);
+        }
+        cmd.arg("-merge=1");
+        let dummy_corpus = tempfile::tempdir()?;
+        cmd.arg(dummy_corpus.path());
+        cmd.arg(corpus_dir);
+
+        for arg in &coverage.args {
+            cmd.arg(arg);
+        }
+
+        Ok((cmd, dummy_corpus))
+    }
+
+    pub fn exec_llvm_cov(&self, subcommand: &str, cov: &options::LlvmCov) -> Result<()> {
+        let (_, profdata_path) = self.coverage_for(&cov.target)?;
+        if !profdata_path.exists() {
+            bail!(
+                "no coverage data for `{}` in {}, run `cargo fuzz coverage {}` first",
+                cov.target,
+                profdata_path.display(),
+                cov.target
+            );
+        }
+
+        let mut cov_cmd = self.llvm_cov(
+            cov.llvm_path.as_deref(),
+            subcommand,
+            &cov.build,
+            &[&cov.target],
+            &profdata_path,
+            cov.include_deps,
+        )?;
+        cov_cmd.args(&cov.args);
+        let status = cov_cmd
+            .status()
+            .with_context(|| format!("Failed to run command: {:?}", cov_cmd))?;
+        if !status.success() {
+            bail!("llvm-cov {} exited with {}", subcommand, status);
+        }
+        Ok(())
+    }
+
+    fn merge_coverage(
+        &self,
+        profdata_bin_path: &Path,
+        profdata_raw_paths: &[PathBuf],
+        profdata_out_path: &Path,
+    ) -> Result<()> {
+        let mut merge_cmd = Command::new(profdata_bin_path);
+        merge_cmd.arg("merge").arg("-sparse");
+        merge_cmd.args(profdata_raw_paths);
+        merge_cmd.arg("-o").arg(profdata_out_path);
+
+        eprintln!("Merging raw coverage data...");
+        let status = merge_cmd
+            .status()
+            .with_context(|| format!("Failed to run command: {:?}", merge_cmd))
+            .with_context(|| "Merging raw coverage files failed.\n\
+                              \n\
+                              Do you have LLVM coverage tools installed?\n\
+                              https://doc.rust-lang.org/rustc/instrument-coverage.html#installing-llvm-coverage-tools")
+            .fail(Failure::Environment)?;
+        if !status.success() {
+            Err(anyhow!(
+                "Command exited with failure status {}: {:?}",
+                status,
+                merge_cmd
+            ))
+            .context("Merging raw coverage files failed")?;
+        }
+
+        if profdata_out_path.exists() {
+            eprintln!(
+                "{}",
+                output::success(&format!(
+                    "Coverage data merged and saved in {:?}.",
+                    profdata_out_path
+                ))
+            );
+            Ok(())
+        } else {
+            bail!("Coverage data could not be merged.")
+        }
+    }
+
+    /// Renders the merged coverage data as an HTML report in the `output`
+    /// directory, or exports it to the `output` file.
+    fn export_coverage(
+        &self,
+        coverage: &options::Coverage,
+        targets: &[&str],
+        profdata_path: &Path,
+        format: CoverageFormat,
+        output: &Path,
+    ) -> Result<()> {
+        let subcommand = match format {
+            CoverageFormat::Html => "show",
+            CoverageFormat::Lcov => "export",
+        };
+        let mut cov_cmd = self.llvm_cov(
+            coverage.llvm_path.as_deref(),
+            subcommand,
+            &coverage.build,
+            targets,
+            profdata_path,
+            coverage.include_deps,
+        )?;
+        match format {
+            CoverageFormat::Html => {
+                cov_cmd
+                    .arg("--format=html")
+                    .arg(format!("--output-dir={}", output.display()))
+                    .arg("--show-line-counts-or-regions");
+            }
+            CoverageFormat::Lcov => {
+                cov_cmd.arg("--format=lcov");
+                let file = fs::File::create(output)
+                    .with_context(|| format!("failed to create {}", output.display()))?;
+                cov_cmd.stdout(file);
+            }
+        }
+
+        eprintln!("Exporting coverage data as {}...", format);
+        let status = cov_cmd
+            .status()
+            .with_context(|| format!("Failed to run command: {:?}", cov_cmd))
+            .with_context(|| "Exporting coverage data failed.\n\
+                              \n\
+                              Do you have LLVM coverage tools installed?\n\
+                              https://doc.rust-lang.org/rustc/instrument-coverage.html#installing-llvm-coverage-tools")
+            .fail(Failure::Environment)?;
+        if !status.success() {
+            Err(anyhow!(
+                "Command exited with failure status {}: {:?}",
+                status,
+                cov_cmd
+            ))
+            .context("Exporting coverage data failed")?;
+        }
+
+        let output = match format {
+            CoverageFormat::Html => output.join("index.html"),
+            CoverageFormat::Lcov => output.to_owned(),
+        };
+        eprintln!(
+            "{}",
+            output::success(&format!("Coverage {} saved in {:?}.", format, output))
+        );
+        if coverage.open {
+            open_in_browser(&output);
+        }
+        Ok(())
+    }
+
+    pub fn exec_cov_diff(&self, diff: &options::CovDiff) -> Result<()> {
+        let new = match &diff.new {
+            Some(new) => new.clone(),
+            None => self.coverage_for(&diff.target)?.1,
+        };
+
+        let mut functions = Vec::new();
+        for profdata_path in [&diff.old, &new] {
+            if !profdata_path.exists() {
+                bail!("coverage data {} does not exist", profdata_path.display());
+            }
+            // Both runs are read through the current coverage build, so
+            // functions that changed since the old run may be reported as
+            // mismatched by llvm-cov.
+            functions.push(self.export_functions(
+                diff.llvm_path.as_deref(),
+                &diff.build,
+                &[&diff.target],
+                profdata_path,
+                diff.include_deps,
+            )?);
+        }
+
+        print!("{}", llvm_cov::render_diff(&functions[0], &functions[1]));
+        Ok(())
+    }
+
+    /// Returns the coverage of every function, as exported by llvm-cov.
+    fn export_functions(
+        &self,
+        llvm_path: Option<&Path>,
+        build: &BuildOptions,
+        fuzz_targets: &[&str],
+        profdata_path: &Path,
+        include_deps: bool,
+    ) -> Result<llvm_cov::Functions> {
+        let mut export_cmd = self.llvm_cov(
+            llvm_path,
+            "export",
+            build,
+            fuzz_targets,
+            profdata_path,
+            include_deps,
+        )?;
+        export_cmd.arg("--format=text");
+        let output = export_cmd
+            .stderr(Stdio::inherit())
+            .output()
+            .with_context(|| format!("Failed to run command: {:?}", export_cmd))?;
+        if !output.status.success() {
+            bail!("llvm-cov export exited with {}", output.status);
+        }
+        llvm_cov::parse(&output.stdout)
+    }
+
+    /// Records the JSON summary of `llvm-cov export --summary-only` in the
+    /// coverage history, writes it and the badge derived from it, as
+    /// requested by `--summary-json` and `--badge-json`, and enforces
+    /// `--fail-under`.
+    fn export_coverage_summary(
+        &self,
+        coverage: &options::Coverage,
+        targets: &[&str],
+        profdata_path: &Path,
+    ) -> Result<()> {
+        let summary = self.coverage_summary_json(coverage, targets, profdata_path)?;
+        let name = if coverage.all {
+            ALL_TARGETS_COVERAGE_DIR
+        } else {
+            targets[0]
+        };
+        if let Err(e) = self.record_coverage(name, &summary) {
+            eprintln!("warning: failed to record the coverage history: {:#}", e);
+        }
+        if let Some(summary_path) = &coverage.summary_json {
+            fs::write(summary_path, &summary)
+                .with_context(|| format!("failed to write {}", summary_path.display()))?;
+            eprintln!(
+                "{}",
+                output::success(&format!("Coverage summary saved in {:?}.", summary_path))
+            );
+        }
+        let totals = llvm_cov::totals(&summary)?;
+        if let Some(badge_path) = &coverage.badge_json {
+            fs::write(badge_path, llvm_cov::badge(totals))
+                .with_context(|| format!("failed to write {}", badge_path.display()))?;
+            eprintln!(
+                "{}",
+                output::success(&format!("Coverage badge saved in {:?}.", badge_path))
+            );
+        }
+        if let Some(threshold) = coverage.fail_under {
+            llvm_cov::check_fail_under(totals, threshold)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the JSON summary of `llvm-cov export --summary-only`.
+    fn coverage_summary_json(
+        &self,
+        coverage: &options::Coverage,
+        targets: &[&str],
+        profdata_path: &Path,
+    ) -> Result<Vec<u8>> {
+        let mut cov_cmd = self.llvm_cov(
+            coverage.llvm_path.as_deref(),
+            "export",
+            &coverage.build,
+            targets,
+            profdata_path,
+            coverage.include_deps,
+        )?;
+        cov_cmd.arg("--format=text").arg("--summary-only");
+        let output = cov_cmd
+            .stderr(Stdio::inherit())
+            .output()
+            .with_context(|| format!("Failed to run command: {:?}", cov_cmd))?;
+        if !output.status.success() {
+            bail!("llvm-cov export exited with {}", output.status);
+        }
+        Ok(output.stdout)
+    }
+
+    /// Appends the coverage in `summary_json` to the history of `name`, a
+    /// fuzz target or all of them, with the source files relative to the
+    /// project.
+    fn record_coverage(&self, name: &str, summary_json: &[u8]) -> Result<()> {
+        let summary = llvm_cov::summary(summary_json)?;
+        let project_dir = self.fuzz_dir().parent().unwrap_or(self.fuzz_dir());
+        let record = CoverageRecord {
+            time: time::SystemTime::now()
+                .duration_since(time::UNIX_EPOCH)
+                .map_or(0, |since_epoch| since_epoch.as_secs()),
+            git_commit: git_output(self.fuzz_dir(), &["rev-parse", "HEAD"]),
+            totals: summary.totals,
+            files: summary
+                .files
+                .into_iter()
+                .map(|(file, totals)| {
+                    let file = Path::new(&file)
+                        .strip_prefix(project_dir)
+                        .map_or(file.clone(), |relative| relative.display().to_string());
+                    (file, totals)
+                })
+                .collect(),
+        };
+        coverage_history::append(&self.coverage_history_for(name), &record)
+    }
+
+    /// Prints the total coverage that every `cargo fuzz coverage` run of a
+    /// fuzz target measured, and the files whose coverage the last run
+    /// changed.
+    pub fn exec_cov_history(&self, history: &options::CovHistory) -> Result<()> {
+        let name = match &history.target {
+            Some(target) if !history.all => target,
+            _ => ALL_TARGETS_COVERAGE_DIR,
+        };
+        let records = coverage_history::read(&self.coverage_history_for(name))?;
+        if history.json {
+            println!("{}", serde_json::to_string_pretty(&records)?);
+            return Ok(());
+        }
+        let (last, previous) = match records.split_last() {
+            Some((last, rest)) => (last, rest.last()),
+            None => {
+                eprintln!("No recorded coverage of `{}`", name);
+                return Ok(());
+            }
+        };
+
+        let mut rows = vec![["TIME", "COMMIT", "LINES", "REGIONS", "CHANGE"].map(str::to_owned)];
+        let mut previous_lines = None;
+        for record in &records {
+            let change = previous_lines.map_or_else(String::new, |previous_lines: f64| {
+                let change = record.totals.lines - previous_lines;
+                if change.abs() < 0.05 {
+                    "0.0".to_owned()
+                } else {
+                    format!("{:+.1}", change)
+                }
+            });
+            previous_lines = Some(record.totals.lines);
+            rows.push([
+                sessions::format_time(record.time),
+                record
+                    .git_commit
+                    .as_deref()
+                    .map_or("-", |commit| &commit[..commit.len().min(8)])
+                    .to_owned(),
+                format!("{:.1}%", record.totals.lines),
+                format!("{:.1}%", record.totals.regions),
+                change,
+            ]);
+        }
+        // The times and commits are left-aligned, the percentages
+        // right-aligned.
+        print_table(&rows, &[true, true, false, false, false]);
+
+        if let Some(previous) = previous {
+            println!();
+            print!("{}", coverage_history::render_file_changes(previous, last));
+        }
+        Ok(())
+    }
+
+    /// Returns a regex matching the source files that coverage reports leave
+    /// out by default, so that they only cover the code under test: those of
+    /// dependencies, of the standard library and of the fuzz targets
+    /// themselves.
+    fn coverage_ignore_regex(&self) -> String {
+        let mut patterns = vec![
+            r"[/\\]\.cargo[/\\](registry|git)[/\\]".to_owned(),
+            // The standard library, as remapped in the debug info of the
+            // prebuilt std.
+            r"[/\\]rustc[/\\][0-9a-f]+[/\\]".to_owned(),
+        ];
+        let mut dirs = Vec::new();
+        if let Some(cargo_home) = env::var_os("CARGO_HOME") {
+            dirs.push(PathBuf::from(cargo_home));
+        }
+        if let Ok(sysroot) = sysroot() {
+            dirs.push(PathBuf::from(sysroot));
+        }
+        dirs.push(self.fuzz_dir().to_owned());
+        for dir in dirs {
+            let dir = dir
+                .canonicalize()
+                .map_or(dir, |dir| strip_verbatim_prefix(&dir));
+            // Match the directory itself rather than siblings that share its
+            // name as a prefix.
+            patterns.push(utils::regex_escape(&dir.join("").to_string_lossy()));
+        }
+        patterns.join("|")
+    }
+
+    /// Returns an `llvm-cov <subcommand>` command for the coverage builds of
+    /// fuzz targets and their merged coverage data.
+    fn llvm_cov(
+        &self,
+        llvm_path: Option<&Path>,
+        subcommand: &str,
+        build: &BuildOptions,
+        fuzz_targets: &[&str],
+        profdata_path: &Path,
+        include_deps: bool,
+    ) -> Result<Command> {
+        let mut cov_bin_path = match llvm_path {
+            Some(llvm_path) => llvm_path.to_owned(),
+            None => rustlib()?,
+        };
+        cov_bin_path.push(format!("llvm-cov{}", env::consts::EXE_SUFFIX));
+
+        let mut cov_cmd = Command::new(cov_bin_path);
+        cov_cmd
+            .arg(subcommand)
+            .arg(format!("--instr-profile={}", profdata_path.display()));
+        if !include_deps {
+            cov_cmd.arg(format!(
+                "--ignore-filename-regex={}",
+                self.coverage_ignore_regex()
+            ));
+        }
+        // The first binary is positional, any further ones are passed as
+        // `-object`.
+        for (i, fuzz_target) in fuzz_targets.iter().enumerate() {
+            if i > 0 {
+                cov_cmd.arg("-object");
+            }
+            cov_cmd.arg(self.fuzz_binary_path(build, fuzz_target)?);
+        }
+        // llvm-cov doesn't demangle Rust symbols on its own, and `export`
+        // doesn't support a demangler at all.
+        if subcommand != "export" {
+            if let Some(rustfilt) = find_on_path("rustfilt") {
+                cov_cmd.arg(format!("-Xdemangler={}", rustfilt.display()));
+            }
+        }
+        Ok(cov_cmd)
+    }
+
+    pub fn targets(&self) -> &[String] {
+        &self.targets
+    }
+
+    pub(crate) fn fuzz_dir(&self) -> &Path {
+        &self.fuzz_dir
+    }
+
+    fn manifest_path(&self) -> PathBuf {
+        self.fuzz_dir().join("Cargo.toml")
+    }
+
+    /// Returns paths to the `coverage/<target>/raw` directory and `coverage/<target>/coverage.profdata` file.
+    fn coverage_for(&self, target: &str) -> Result<(PathBuf, PathBuf)> {
+        let mut coverage_data = self.fuzz_dir().to_owned();
+        coverage_data.push("coverage");
+        coverage_data.push(target);
+        let mut coverage_raw = coverage_data.clone();
+        coverage_data.push("coverage.profdata");
+        coverage_raw.push("raw");
+        fs::create_dir_all(&coverage_raw).with_context(|| {
+            format!("could not make a coverage directory at {:?}", coverage_raw)
+        })?;
+        Ok((coverage_raw, coverage_data))
+    }
+
+    fn coverage_history_for(&self, name: &str) -> PathBuf {
+        self.fuzz_dir()
+            .join("coverage")
+            .join(".history")
+            .join(format!("{}.jsonl", name))
+    }
+
+    fn corpus_for(&self, target: &str) -> Result<PathBuf> {
+        let mut p = self.fuzz_dir().to_owned();
+        p.push("corpus");
+        p.push(target);
+        fs::create_dir_all(&p)
+            .with_context(|| format!("could not make a corpus directory at {:?}", p))?;
+        Ok(p)
+    }
+
+    /// Returns the number of inputs in the corpus of a fuzz target and their
+    /// total size in bytes, without creating the corpus if it doesn't exist.
+    fn corpus_size(&self, target: &str) -> Result<(usize, u64)> {
+        let corpus = self.fuzz_dir().join("corpus").join(target);
+        let (mut inputs, mut bytes) = (0, 0);
+        if corpus.is_dir() {
+            for input in sorted_files(&corpus)? {
+                inputs += 1;
+                bytes += fs::metadata(&input)
+                    .with_context(|| format!("failed to read metadata of {}", input.display()))?
+                    .len();
+            }
+        }
+        Ok((inputs, bytes))
+    }
+
+    /// Where the dictionary of a fuzz target goes by convention, which is
+    /// used without being configured.
+    fn dictionary_path(&self, target: &str) -> PathBuf {
+        self.fuzz_dir()
+            .join("dictionaries")
+            .join(format!("{}.dict", target))
+    }
+
+    fn logs_for(&self, target: &str) -> PathBuf {
+        self.fuzz_dir().join("logs").join(target)
+    }
+
+    fn stats_for(&self, target: &str) -> PathBuf {
+        self.fuzz_dir().join("stats").join(target)
+    }
+
+    fn sessions_db(&self) -> PathBuf {
+        self.fuzz_dir().join(".cargo-fuzz").join("sessions.db")
+    }
+
+    fn artifacts_for(&self, target: &str) -> Result<PathBuf> {
+        let mut p = self.fuzz_dir().to_owned();
+        p.push("artifacts");
+        p.push(target);
+
+        // This adds a trailing slash, which is necessary for libFuzzer, because
+        // it does simple string concatenation when joining paths.
+        p.push("");
+
+        fs::create_dir_all(&p)
+            .with_context(|| format!("could not make a artifact directory at {:?}", p))?;
+
+        Ok(p)
+    }
+
+    fn fuzz_targets_dir(&self) -> PathBuf {
+        let mut root = self.fuzz_dir().to_owned();
+        if root.join(crate::FUZZ_TARGETS_DIR_OLD).exists() {
+            println!(
+                "warning: The `fuzz/fuzzers/` directory has renamed to `fuzz/fuzz_targets/`. \
+                 Please rename the directory as such, or run `cargo fuzz upgrade`. This will \
+                 become a hard error in the future."
+            );
+            root.push(crate::FUZZ_TARGETS_DIR_OLD);
+        } else {
+            root.push(crate::FUZZ_TARGETS_DIR);
+        }
+        root
+    }
+
+    fn target_path(&self, target: &str) -> PathBuf {
+        let mut root = self.fuzz_targets_dir();
+        root.push(target);
+        root.set_extension("rs");
+        root
+    }
+
+    /// Returns the source file of a fuzz target as `cargo metadata` reports
+    /// it, falling back to where `cargo fuzz add` creates it.
+    fn target_source_path(&self, target: &str) -> PathBuf {
+        match self.sources.get(target) {
+            Some(source) => source.clone(),
+            None => self.target_path(target),
+        }
+    }
+
+    // If `fuzz_dir_opt` is `None`, returns a new instance with the default fuzz project
+    // path. Otherwise, returns a new instance with the inner content of `fuzz_dir_opt`.
+    fn manage_initial_instance(fuzz_dir_opt: Option<PathBuf>) -> Result<Self> {
+        let fuzz_dir = if let Some(el) = fuzz_dir_opt {
+            find_package()?;
+            el
+        } else {
+            default_fuzz_dir()?
+        };
+        Ok(FuzzProject {
+            fuzz_dir,
+            targets: Vec::new(),
+            sources: HashMap::new(),
+            bin_names: HashMap::new(),
+            target_directory: OnceLock::new(),
+            built: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// Returns whether the fuzz directory is found without `--fuzz-dir`.
+    fn fuzz_dir_is_default_path(&self) -> bool {
+        match (
+            default_fuzz_dir().and_then(|dir| Ok(fs::canonicalize(dir)?)),
+            fs::canonicalize(self.fuzz_dir()),
+        ) {
+            (Ok(default), Ok(fuzz_dir)) => default == fuzz_dir,
+            _ => self.fuzz_dir.ends_with(DEFAULT_FUZZ_DIR),
+        }
+    }
+}
+
+pub fn sysroot() -> Result<String> {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = Command::new(rustc).arg("--print").arg("sysroot").output()?;
+    // Note: We must trim() to remove the `\n` from the end of stdout
+    Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+}
+
+pub fn rustlib() -> Result<PathBuf> {
+    let sysroot = sysroot().fail(Failure::Environment)?;
+    let mut pathbuf = PathBuf::from(sysroot);
+    pathbuf.push("lib");
+    pathbuf.push("rustlib");
+    pathbuf.push(
+        rustc_version::version_meta()
+            .fail(Failure::Environment)?
+            .host,
+    );
+    pathbuf.push("bin");
+    Ok(pathbuf)
+}
+
+/// Points the sanitizer runtimes at `llvm-symbolizer`, so that their reports
+/// show function names and source locations rather than raw addresses.
+/// Variables that the user already set are left alone.
+fn configure_symbolizer(cmd: &mut Command, sanitizer: Sanitizer) {
+    if matches!(sanitizer, Sanitizer::None) {
+        return;
+    }
+    let vars = ["ASAN_SYMBOLIZER_PATH", "MSAN_SYMBOLIZER_PATH"];
+    if vars.iter().all(|var| env::var_os(var).is_some()) {
+        return;
+    }
+
+    // Looking for the symbolizer runs rustc, and fuzz targets may be spawned
+    // once per input, so only look (and warn) once.
+    static SYMBOLIZER: OnceLock<Option<PathBuf>> = OnceLock::new();
+    let symbolizer = SYMBOLIZER.get_or_init(|| {
+        let symbolizer = find_symbolizer();
+        if symbolizer.is_none() {
+            eprintln!(
+                "warning: could not find `llvm-symbolizer` in the Rust sysroot or on the PATH, \
+                 sanitizer reports will not be symbolized. Install LLVM or set \
+                 ASAN_SYMBOLIZER_PATH to fix this."
+            );
+        }
+        symbolizer
+    });
+    if let Some(symbolizer) = symbolizer {
+        for var in vars {
+            if env::var_os(var).is_none() {
+                cmd.env(var, symbolizer);
+            }
+        }
+    }
+}
+
+/// Finds `llvm-symbolizer`, preferring the one shipped with the Rust
+/// toolchain, which matches the LLVM version used to build fuzz targets.
+pub fn find_symbolizer() -> Option<PathBuf> {
+    let name = format!("llvm-symbolizer{}", env::consts::EXE_SUFFIX);
+    if let Ok(rustlib) = rustlib() {
+        let symbolizer = rustlib.join(&name);
+        if symbolizer.is_file() {
+            return Some(symbolizer);
+        }
+    }
+    find_on_path("llvm-symbolizer")
+}
+
+/// Opens a file in a browser the way `cargo doc --open` does: with the
+/// `BROWSER` environment variable if it is set, and with the platform's
+/// default handler otherwise. Failing to do so only warrants a warning, since
+/// the report has been saved either way.
+fn open_in_browser(path: &Path) {
+    let mut cmd = match env::var_os("BROWSER").filter(|browser| !browser.is_empty()) {
+        Some(browser) => Command::new(browser),
+        None if cfg!(target_os = "macos") => Command::new("open"),
+        None if cfg!(windows) => {
+            let mut cmd = Command::new("cmd");
+            cmd.args(["/C", "start", ""]);
+            cmd
+        }
+        None => Command::new("xdg-open"),
+    };
+    cmd.arg(path);
+    // Don't wait for the browser, which may only exit once it is closed.
+    if let Err(e) = cmd.spawn() {
+        eprintln!("warning: failed to run {:?}: {}", cmd, e);
+    }
+}
+
+/// Prints a hexdump of at most `max_len` bytes of an input, in place of the
+/// `std::fmt::Debug` output that failed with `error`.
+fn print_hexdump(input: &Path, error: &anyhow::Error, max_len: usize) {
+    // Only the gist of the error, which may contain the whole output of the
+    // fuzz target.
+    let reason = error.to_string();
+    let reason = reason.lines().next().unwrap_or_default();
+    let data = match fs::read(input) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("warning: failed to read {}: {}\n", input.display(), e);
+            return;
+        }
+    };
+    eprintln!(
+        "Hexdump of the input ({} bytes), as it could not be formatted with \
+         `std::fmt::Debug`: {}\n",
+        data.len(),
+        reason
+    );
+    let shown = &data[..data.len().min(max_len)];
+    for l in utils::hexdump(shown).lines() {
+        eprintln!("\t{}", l);
+    }
+    if shown.len() < data.len() {
+        eprintln!("\t... {} more bytes", data.len() - shown.len());
+    }
+    eprintln!();
+}
+
+/// Looks for an executable in the directories of the `PATH`.
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let name = format!("{}{}", name, env::consts::EXE_SUFFIX);
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(&name))
+        .find(|path| path.is_file())
+}
+
+pub struct Manifest {
+    crate_name: String,
+    edition: Option<String>,
+    repository: Option<String>,
+    homepage: Option<String>,
+    authors: Vec<String>,
+    workspace_root: PathBuf,
+}
+
+impl Manifest {
+    /// Reads the manifest of the package that is fuzzed. In a virtual
+    /// workspace, that is the first member that isn't a fuzz package.
+    ///
+    /// Fields inherited with `field.workspace = true` are resolved by Cargo,
+    /// so the fuzz package gets the edition the fuzzed package actually uses.
+    pub fn parse() -> Result<Self> {
+        let manifest_path = find_package()?.join("Cargo.toml");
+        let metadata = cargo_metadata(&manifest_path)?;
+        let package = find_manifest_package(&metadata, &manifest_path)
+            .or_else(|| metadata.packages.iter().find(|p| !is_fuzz_package(p)))
+            .with_context(|| {
+                anyhow!(
+                    "Expected to find at least one package in {}",
+                    metadata.workspace_root
+                )
+            })?;
+        let crate_name = package.name.clone();
+        let edition = Some(String::from(package.edition.as_str()));
+
+        Ok(Manifest {
+            crate_name,
+            edition,
+            repository: package.repository.clone(),
+            homepage: package.homepage.clone(),
+            authors: package.authors.clone(),
+            workspace_root: metadata.workspace_root.clone().into_std_path_buf(),
+        })
+    }
+}
+
+/// Returns the `[patch]` and `[replace]` sections of the root manifest of the
+/// workspace of the fuzzed package, to append to a new fuzz package that is
+/// its own workspace, so that dependencies resolve the same. That is the case
+/// with `--fuzzing-workspace`, or when the fuzzed package has no explicit
+/// `[workspace]` to add the fuzz package to.
+///
+/// Registries and patches configured in `.cargo/config.toml` need no copying,
+/// as Cargo looks them up in the parent directories of the fuzz package too.
+fn resolution_overrides(
+    workspace_root: &Path,
+    fuzz_dir: &Path,
+    fuzzing_workspace: bool,
+) -> Result<Option<String>> {
+    let manifest_path = workspace_root.join("Cargo.toml");
+    let manifest: toml::Value = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?
+        .parse()
+        .with_context(|| {
+            format!(
+                "could not decode the manifest file at {}",
+                manifest_path.display()
+            )
+        })?;
+    if manifest.get("workspace").is_some() && !fuzzing_workspace {
+        return Ok(None);
+    }
+
+    let fuzz_dir = fs::canonicalize(fuzz_dir)
+        .with_context(|| format!("failed to resolve {}", fuzz_dir.display()))?;
+    let mut overrides = toml::value::Table::new();
+    for section in ["patch", "replace"] {
+        let Some(mut value) = manifest.get(section).cloned() else {
+            continue;
+        };
+        // `[patch.<registry>.<crate>]` and `[replace.<crate>]` tables.
+        let depth = if section == "patch" { 2 } else { 1 };
+        rebase_paths(&mut value, depth, workspace_root, &fuzz_dir);
+        overrides.insert(section.to_owned(), value);
+    }
+    if overrides.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(format!(
+        "\n# Copied from {}\n{}",
+        utils::relative_path(&fuzz_dir, &fs::canonicalize(&manifest_path)?).display(),
+        toml::to_string(&overrides)?
+    )))
+}
+
+/// Makes the relative `path`s of the dependency tables `depth` levels down
+/// in `value` relative to `fuzz_dir` instead of `root`.
+fn rebase_paths(value: &mut toml::Value, depth: usize, root: &Path, fuzz_dir: &Path) {
+    let Some(table) = value.as_table_mut() else {
+        return;
+    };
+    if depth > 0 {
+        for (_, value) in table.iter_mut() {
+            rebase_paths(value, depth - 1, root, fuzz_dir);
+        }
+        return;
+    }
+    if let Some(toml::Value::String(path)) = table.get_mut("path") {
+        if Path::new(path).is_relative() {
+            let absolute = root.join(&*path);
+            let absolute = fs::canonicalize(&absolute).unwrap_or(absolute);
+            *path = utils::relative_path(fuzz_dir, &absolute)
+                .to_string_lossy()
+                .into_owned();
+        }
+    }
+}
+
+/// Runs `cargo metadata` without dependencies for a manifest.
+fn cargo_metadata(manifest_path: &Path) -> Result<Metadata> {
+    MetadataCommand::new()
+        .manifest_path(manifest_path)
+        .no_deps()
+        .exec()
+        .with_context(|| format!("failed to read the metadata of {}", manifest_path.display()))
+}
+
+/// Returns the package of a manifest, or `None` if it's a virtual manifest.
+fn find_manifest_package<'a>(metadata: &'a Metadata, manifest_path: &Path) -> Option<&'a Package> {
+    // Cargo reports manifest paths with symlinks resolved.
+    let manifest_path = fs::canonicalize(manifest_path).ok()?;
+    metadata.packages.iter().find(|package| {
+        fs::canonicalize(&package.manifest_path).is_ok_and(|path| path == manifest_path)
+    })
+}
+
+fn is_fuzz_package(package: &Package) -> bool {
+    // Either `cargo-fuzz = true`, or a table with the project configuration.
+    // A table with the `dir` of the fuzz package is in the fuzzed package.
+    match package.metadata.get("cargo-fuzz") {
+        Some(serde_json::Value::Bool(is_fuzz)) => *is_fuzz,
+        Some(serde_json::Value::Object(table)) => !table.contains_key("dir"),
+        _ => false,
+    }
+}
+
+/// Returns the fuzz target files in `dir` and its subdirectories, sorted.
+/// Files without a `fuzz_target!` are taken for modules shared by fuzz
+/// targets, and left out.
+fn fuzz_target_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = rust_files(dir)?;
+    files.retain(|path| {
+        fs::read_to_string(path).is_ok_and(|source| source.contains("fuzz_target!"))
+    });
+    Ok(files)
+}
+
+/// Returns the `.rs` files in `dir` and its subdirectories, sorted by path.
+fn rust_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let entries =
+        fs::read_dir(dir).with_context(|| format!("failed to read directory {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(rust_files(&path)?);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Returns the name of a fuzz target in a subdirectory of `fuzz_targets/`,
+/// like `parser/http` for `fuzz_targets/parser/http.rs`.
+fn nested_target_name(targets_dir: Option<&Path>, source: &Path) -> Option<String> {
+    let relative = source.strip_prefix(targets_dir?).ok()?.with_extension("");
+    let parts: Vec<_> = relative
+        .components()
+        .map(|part| part.as_os_str().to_str())
+        .collect::<Option<_>>()?;
+    (parts.len() > 1).then(|| parts.join("/"))
+}
+
+/// Returns whether a manifest is a fuzz package without fuzz targets yet.
+/// Cargo rejects it for having no targets at all, but `cargo fuzz add` is
+/// how its first target gets added.
+fn is_empty_fuzz_manifest(manifest_path: &Path) -> bool {
+    let Some(manifest) = fs::read_to_string(manifest_path)
+        .ok()
+        .and_then(|data| data.parse::<toml::Value>().ok())
+    else {
+        return false;
+    };
+    let is_fuzz = manifest
+        .get("package")
+        .and_then(|package| package.get("metadata"))
+        .and_then(|metadata| metadata.get("cargo-fuzz"));
+    let is_fuzz = match is_fuzz {
+        Some(toml::Value::Boolean(is_fuzz)) => *is_fuzz,
+        Some(toml::Value::Table(table)) => !table.contains_key("dir"),
+        _ => false,
+    };
+    is_fuzz && manifest.get("bin").is_none()
+}
+
+/// Returns the fuzz directory of the package in the current directory, when
+/// none is given with `--fuzz-dir`: the one the current directory is in,
+/// `fuzz` if it exists, the one that `cargo fuzz init` recorded in the
+/// manifest, or else `fuzz`.
+pub fn default_fuzz_dir() -> Result<PathBuf> {
+    let (package_dir, fuzz_dir) = find_package_and_fuzz_dir()?;
+    if let Some(fuzz_dir) = fuzz_dir {
+        return Ok(fuzz_dir);
+    }
+    // Most projects use the default, which spares running `cargo metadata`.
+    let default = package_dir.join(DEFAULT_FUZZ_DIR);
+    if default.is_dir() {
+        return Ok(default);
+    }
+    let manifest_path = package_dir.join("Cargo.toml");
+    let metadata = cargo_metadata(&manifest_path)?;
+    let recorded = match find_manifest_package(&metadata, &manifest_path) {
+        Some(package) => recorded_fuzz_dir(&package.metadata),
+        None => recorded_fuzz_dir(&metadata.workspace_metadata),
+    };
+    Ok(package_dir.join(recorded.unwrap_or(DEFAULT_FUZZ_DIR)))
+}
+
+/// Returns the fuzz directory recorded in the metadata of a package or
+/// workspace by `record_fuzz_dir`.
+fn recorded_fuzz_dir(metadata: &serde_json::Value) -> Option<&str> {
+    metadata.get("cargo-fuzz")?.get("dir")?.as_str()
+}
+
+/// Returns the fuzz directories of all the packages in the workspace of the
+/// package in the current directory, with the names of the packages, sorted
+/// by name.
+pub fn workspace_fuzz_dirs() -> Result<Vec<(String, PathBuf)>> {
+    let metadata = cargo_metadata(&find_package()?.join("Cargo.toml"))?;
+    let mut fuzz_dirs: Vec<_> = metadata
+        .workspace_packages()
+        .into_iter()
+        .filter(|package| !is_fuzz_package(package))
+        .filter_map(|package| {
+            let package_dir = package.manifest_path.parent()?.as_std_path();
+            let fuzz_dir =
+                package_dir.join(recorded_fuzz_dir(&package.metadata).unwrap_or(DEFAULT_FUZZ_DIR));
+            let manifest_path = fuzz_dir.join("Cargo.toml");
+            let is_fuzz = is_empty_fuzz_manifest(&manifest_path)
+                || cargo_metadata(&manifest_path).is_ok_and(|metadata| {
+                    find_manifest_package(&metadata, &manifest_path).is_some_and(is_fuzz_package)
+                });
+            is_fuzz.then(|| (package.name.clone(), fuzz_dir))
+        })
+        .collect();
+    fuzz_dirs.sort();
+    Ok(fuzz_dirs)
+}
+
+/// Records a custom fuzz directory in the manifest of the fuzzed package, for
+/// `default_fuzz_dir` to find it.
+fn record_fuzz_dir(fuzz_dir: &Path) -> Result<()> {
+    let package_dir = fs::canonicalize(find_package()?)?;
+    let fuzz_dir = fs::canonicalize(fuzz_dir)
+        .with_context(|| format!("failed to resolve {}", fuzz_dir.display()))?;
+    let dir = utils::relative_path(&package_dir, &fuzz_dir);
+    if dir == Path::new(DEFAULT_FUZZ_DIR) {
+        return Ok(());
+    }
+
+    let manifest_path = package_dir.join("Cargo.toml");
+    let mut data = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+    let manifest: toml::Value = data.parse().with_context(|| {
+        format!(
+            "could not decode the manifest file at {}",
+            manifest_path.display()
+        )
+    })?;
+    // Virtual manifests have no package to add metadata to.
+    let section = if manifest.get("package").is_some() {
+        "package"
+    } else {
+        "workspace"
+    };
+    let table = format!("[{}.metadata.cargo-fuzz]", section);
+    let recorded = manifest
+        .get(section)
+        .and_then(|section| section.get("metadata"))
+        .and_then(|metadata| metadata.get("cargo-fuzz"))
+        .is_some();
+    let dir = toml::Value::String(dir.to_string_lossy().into_owned());
+    if !recorded {
+        data.push_str(&format!("\n{}\ndir = {}\n", table, dir));
+    }
+    if recorded || data.parse::<toml::Value>().is_err() {
+        eprintln!(
+            "warning: could not record the fuzz directory in {}, add the \
+             following lines to it to not have to pass `--fuzz-dir`:\n\
+             {}\n\
+             dir = {}",
+            manifest_path.display(),
+            table,
+            dir
+        );
+        return Ok(());
+    }
+    fs::write(&manifest_path, data)
+        .with_context(|| format!("failed to write to {}", manifest_path.display()))
+}
+
+/// Returns the path for the first found non-fuzz Cargo package
+fn find_package() -> Result<PathBuf> {
+    Ok(find_package_and_fuzz_dir()?.0)
+}
+
+/// Returns the path for the first found non-fuzz Cargo package, and the fuzz
+/// package that the current directory is in, if any.
+fn find_package_and_fuzz_dir() -> Result<(PathBuf, Option<PathBuf>)> {
+    let mut dir = env::current_dir()?;
+    let mut fuzz_dir = None;
+    loop {
+        let manifest_path = dir.join("Cargo.toml");
+        if manifest_path.is_file() {
+            let is_fuzz = is_empty_fuzz_manifest(&manifest_path) || {
+                let metadata = cargo_metadata(&manifest_path)?;
+                find_manifest_package(&metadata, &manifest_path).is_some_and(is_fuzz_package)
+            };
+            if !is_fuzz {
+                // Not a cargo-fuzz project => must be a proper cargo project
+                // or workspace :)
+                return Ok((dir, fuzz_dir));
+            }
+            if fuzz_dir.is_none() {
+                fuzz_dir = Some(dir.clone());
+            }
+        }
+        if !dir.pop() {
+            break;
+        }
+    }
+    bail!("could not find a cargo project")
+}
+
+/// Copies the contents of `input` into `corpus` under the SHA-1 of its
+/// contents, like libFuzzer names corpus entries, and then removes `artifact`.
+/// Returns the path of the corpus entry.
+fn move_to_corpus(input: &Path, artifact: &Path, corpus: &Path) -> Result<PathBuf> {
+    let data = fs::read(input).with_context(|| format!("failed to read {}", input.display()))?;
+    let entry = corpus.join(utils::content_hash(&data));
+    if !entry.exists() {
+        fs::write(&entry, &data).with_context(|| format!("failed to write {}", entry.display()))?;
+    }
+    remove_artifact(artifact)?;
+    Ok(entry)
+}
+
+/// Summarizes the output of a crashing fuzz target for a JUnit report.
+fn crash_failure(stderr: &str) -> junit::Failure {
+    let report = CrashReport::parse(stderr);
+    let message = match (&report.kind, &report.panic_location) {
+        (_, Some(location)) => format!("panicked at {}", location),
+        (Some(kind), None) => kind.clone(),
+        (None, None) => "fuzz target failed".to_owned(),
+    };
+    let details = match stacktrace::crash_excerpt(stderr) {
+        Some(excerpt) => excerpt.to_owned(),
+        None => stderr.to_owned(),
+    };
+    junit::Failure { message, details }
+}
+
+/// Writes generated files to `dir`, unless any of them exists and `force`
+/// isn't set. Shell scripts are made executable.
+fn write_generated_files<const N: usize>(
+    dir: &Path,
+    files: [(&str, String); N],
+    force: bool,
+) -> Result<()> {
+    if !force {
+        for (file, _) in &files {
+            let path = dir.join(file);
+            if path.exists() {
+                bail!(
+                    "{} already exists, pass `--force` to overwrite it",
+                    path.display()
+                );
+            }
+        }
+    }
+    fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create directory {}", dir.display()))?;
+    for (file, contents) in &files {
+        let path = dir.join(file);
+        fs::write(&path, contents)
+            .with_context(|| format!("failed to write to {}", path.display()))?;
+        #[cfg(unix)]
+        if file.ends_with(".sh") {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+                .with_context(|| format!("failed to make {} executable", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+fn write_junit(path: &Path, target: &str, cases: &[junit::TestCase]) -> Result<()> {
+    fs::write(path, junit::render(target, cases))
+        .with_context(|| format!("failed to write JUnit report to {}", path.display()))
+}
+
+/// Returns the artifacts directly inside `dir`, sorted by path, leaving out
+/// their metadata files and the results of `cargo fuzz tmin`, which only
+/// reproduce the bugs of other artifacts again.
+fn artifact_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut artifacts = all_artifact_files(dir)?;
+    artifacts.retain(|artifact| !is_minimized(artifact));
+    Ok(artifacts)
+}
+
+/// Returns the artifacts directly inside `dir` like `artifact_files`, along
+/// with the results of `cargo fuzz tmin`, for the commands that manage the
+/// files rather than replay them.
+fn all_artifact_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut artifacts = sorted_files(dir)?;
+    artifacts.retain(|artifact| !is_artifact_metadata(artifact));
+    Ok(artifacts)
+}
+
+fn artifact_metadata_path(artifact: &Path) -> PathBuf {
+    let mut path = artifact.as_os_str().to_owned();
+    path.push(METADATA_SUFFIX);
+    PathBuf::from(path)
+}
+
+fn is_artifact_metadata(path: &Path) -> bool {
+    file_name(path).ends_with(METADATA_SUFFIX)
+}
+
+/// Removes an artifact together with its metadata file, if it has one.
+fn remove_artifact(artifact: &Path) -> Result<()> {
+    fs::remove_file(artifact)
+        .with_context(|| format!("failed to remove {}", artifact.display()))?;
+    let metadata = artifact_metadata_path(artifact);
+    if metadata.exists() {
+        fs::remove_file(&metadata)
+            .with_context(|| format!("failed to remove {}", metadata.display()))?;
+    }
+    Ok(())
+}
 
-        for corpus in corpora.iter() {
-            // _tmp_dir is deleted when it goes of of scope.
-            let (mut cmd, _tmp_dir) =
-                self.create_coverage_cmd(coverage, &coverage_out_raw_dir, &corpus.as_path())?;
-            eprintln!("Generating coverage data for corpus {:?}", corpus);
-            let status = cmd
-                .status()
-                .with_context(|| format!("Failed to run command: {:?}", cmd))?;
-            if !status.success() {
-                Err(anyhow!(
-                    "Command exited with failure status {}: {:?}",
-                    status,
-                    cmd
-                ))
-                .context("Failed to generage coverage data")?;
-            }
+/// A crash found by `cargo fuzz ci`.
+struct CiCrash {
+    report: CrashReport,
+    /// The first of the new artifacts
+    artifact: Option<PathBuf>,
+    /// The captured stderr of the fuzz target
+    output: String,
+    /// Whether the crash is a known one, that doesn't fail the run
+    suppressed: bool,
+}
+
+impl CiCrash {
+    /// The JUnit failure for this crash, unless it is suppressed.
+    fn failure(&self) -> Option<junit::Failure> {
+        (!self.suppressed).then(|| crash_failure(&self.output))
+    }
+
+    /// The stack hash of the crash and where it panicked, or what kind of
+    /// crash it is.
+    fn summary(&self) -> String {
+        let mut summary = format!("[{}]", self.report.stack_hash());
+        match (&self.report.kind, &self.report.panic_location) {
+            (_, Some(location)) => summary.push_str(&format!(" panicked at {}", location)),
+            (Some(kind), None) => summary.push_str(&format!(" {}", kind)),
+            (None, None) => {}
         }
+        summary
+    }
+}
 
-        let mut profdata_bin_path = coverage.llvm_path.clone().unwrap_or(rustlib()?);
-        profdata_bin_path.push(format!("llvm-profdata{}", env::consts::EXE_SUFFIX));
-        self.merge_coverage(
-            &profdata_bin_path,
-            &coverage_out_raw_dir,
-            &coverage_out_file,
-        )?;
+/// The last lines a fuzz target printed to stderr, and the seed libFuzzer
+/// reported among them.
+#[derive(Default)]
+struct CapturedStderr {
+    seed: Option<u64>,
+    lines: VecDeque<String>,
+}
 
-        Ok(())
+impl CapturedStderr {
+    fn output(&self) -> String {
+        self.lines.iter().map(String::as_str).collect()
     }
 
-    fn create_coverage_cmd(
-        &self,
-        coverage: &options::Coverage,
-        coverage_dir: &Path,
-        corpus_dir: &Path,
-    ) -> Result<(Command, tempfile::TempDir)> {
-        let bin_path = {
-            let profile_subdir = if coverage.build.dev {
-                "debug"
-            } else {
-                "release"
-            };
+    fn push(&mut self, line: String) {
+        if self.seed.is_none() {
+            self.seed = line
+                .trim()
+                .strip_prefix("INFO: Seed: ")
+                .and_then(|seed| seed.parse().ok());
+        }
+        if self.lines.len() == CAPTURED_STDERR_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+}
 
-            let target_dir = self
-                .target_dir(&coverage.build)?
-                .expect("target dir for coverage command should never be None");
-            target_dir
-                .join(&coverage.build.triple)
-                .join(profile_subdir)
-                .join(&coverage.target)
-        };
-
-        let mut cmd = Command::new(bin_path);
-
-        // Raw coverage data will be saved in `coverage/<target>` directory.
-        let corpus_dir_name = corpus_dir
-            .file_name()
-            .and_then(|x| x.to_str())
-            .with_context(|| format!("Invalid corpus directory: {:?}", corpus_dir))?;
-        cmd.env(
-            "LLVM_PROFILE_FILE",
-            coverage_dir.join(format!("default-{}.profraw", corpus_dir_name)),
-        );
-        cmd.arg("-merge=1");
-        let dummy_corpus = tempfile::tempdir()?;
-        cmd.arg(dummy_corpus.path());
-        cmd.arg(corpus_dir);
+/// Spawns `cmd` and forwards its stderr to ours line by line, capturing it
+/// along the way.
+fn spawn_and_capture_stderr(
+    cmd: &mut Command,
+    on_line: impl FnMut(&str),
+) -> Result<(ExitStatus, CapturedStderr)> {
+    let mut child = cmd
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
+    let captured = forward_and_capture(child.stderr.take().expect("stderr is piped"), "", on_line)?;
+    let status = child
+        .wait()
+        .with_context(|| format!("failed to wait on child process for command: {:?}", cmd))?;
+    Ok((status, captured))
+}
 
-        for arg in &coverage.args {
-            cmd.arg(arg);
+/// Prints each distinct error that the sanitizer recovered from during a
+/// fuzzing session, with how often it was reported and its backtrace.
+fn print_recovered_errors(recovered: &RecoveredErrors) {
+    if recovered.errors.is_empty() {
+        return;
+    }
+    eprintln!("\n{}", output::separator());
+    eprintln!(
+        "\n{}",
+        output::error(&format!(
+            "Recovered from {} sanitizer error(s), {} distinct:",
+            recovered.total(),
+            recovered.errors.len()
+        ))
+    );
+    for (i, (hash, report, count)) in recovered.errors.iter().enumerate() {
+        let parsed = CrashReport::parse(report);
+        eprintln!(
+            "\n{}. {} (reported {} time(s), stack hash {})\n",
+            i + 1,
+            parsed.kind.as_deref().unwrap_or("unknown error"),
+            count,
+            hash
+        );
+        match parsed.folded_backtrace() {
+            Some(backtrace) => {
+                for l in backtrace.lines() {
+                    eprintln!("\t{}", l);
+                }
+            }
+            None => eprint!("{}", report),
         }
+    }
+    eprintln!();
+}
 
-        Ok((cmd, dummy_corpus))
+/// Waits for all of `children` to exit, killing the others once the first
+/// one fails. Returns their exit statuses, and which one failed first.
+fn wait_until_first_failure(
+    children: &mut [&mut std::process::Child],
+) -> Result<(Vec<ExitStatus>, Option<usize>)> {
+    let mut failed = None;
+    let mut statuses = vec![None; children.len()];
+    while statuses.iter().any(Option::is_none) {
+        for i in 0..children.len() {
+            if statuses[i].is_some() {
+                continue;
+            }
+            let Some(status) = children[i].try_wait()? else {
+                continue;
+            };
+            statuses[i] = Some(status);
+            if !status.success() && failed.is_none() {
+                failed = Some(i);
+                for other in children.iter_mut() {
+                    let _ = other.kill();
+                }
+            }
+        }
+        thread::sleep(time::Duration::from_millis(100));
     }
+    Ok((statuses.into_iter().map(Option::unwrap).collect(), failed))
+}
 
-    fn merge_coverage(
-        &self,
-        profdata_bin_path: &Path,
-        profdata_raw_path: &Path,
-        profdata_out_path: &Path,
-    ) -> Result<()> {
-        let mut merge_cmd = Command::new(profdata_bin_path);
-        merge_cmd.arg("merge").arg("-sparse");
-        merge_cmd.arg(profdata_raw_path);
-        merge_cmd.arg("-o").arg(profdata_out_path);
+/// Writes the lines of a worker's `stderr` to its `log`, capturing them along
+/// the way like `forward_and_capture`.
+fn log_and_capture(
+    stderr: impl io::Read,
+    log: &mut logs::RotatingLog,
+    mut on_line: impl FnMut(&str),
+) -> Result<CapturedStderr> {
+    let mut captured = CapturedStderr::default();
+    let mut reader = io::BufReader::new(stderr);
+    let mut line = Vec::new();
+    let mut warned = false;
+    while reader
+        .read_until(b'\n', &mut line)
+        .context("failed to read the output of the fuzz target")?
+        > 0
+    {
+        let text = String::from_utf8_lossy(&line).into_owned();
+        // Keep reading after a failed write, so that the worker doesn't block
+        // on a full pipe.
+        if let Err(e) = log.write_line(&text) {
+            if !warned {
+                eprintln!("warning: {:#}", e);
+                warned = true;
+            }
+        }
+        on_line(&text);
+        captured.push(text);
+        line.clear();
+    }
+    Ok(captured)
+}
 
-        eprintln!("Merging raw coverage data...");
-        let status = merge_cmd
-            .status()
-            .with_context(|| format!("Failed to run command: {:?}", merge_cmd))
-            .with_context(|| "Merging raw coverage files failed.\n\
-                              \n\
-                              Do you have LLVM coverage tools installed?\n\
-                              https://doc.rust-lang.org/rustc/instrument-coverage.html#installing-llvm-coverage-tools")?;
-        if !status.success() {
-            Err(anyhow!(
-                "Command exited with failure status {}: {:?}",
-                status,
-                merge_cmd
-            ))
-            .context("Merging raw coverage files failed")?;
+/// Sends the lines of `output` to `sender`, as `kind` of output, from a thread
+/// of its own.
+fn send_lines(
+    output: impl io::Read + Send + 'static,
+    sender: mpsc::Sender<tui::Output>,
+    kind: fn(String) -> tui::Output,
+) {
+    thread::spawn(move || {
+        let mut reader = io::BufReader::new(output);
+        let mut line = Vec::new();
+        while matches!(reader.read_until(b'\n', &mut line), Ok(n) if n > 0) {
+            let text = String::from_utf8_lossy(&line).into_owned();
+            if sender.send(kind(text)).is_err() {
+                break;
+            }
+            line.clear();
         }
+    });
+}
 
-        if profdata_out_path.exists() {
-            eprintln!("Coverage data merged and saved in {:?}.", profdata_out_path);
-            Ok(())
-        } else {
-            bail!("Coverage data could not be merged.")
+/// Prints the backtrace of a crash again without the frames of the fuzzing
+/// and sanitizer runtimes, which bury the interesting ones in the raw one.
+fn print_folded_backtrace(captured: &CapturedStderr) {
+    let output = captured.output();
+    let report = CrashReport::parse(stacktrace::crash_excerpt(&output).unwrap_or(&output));
+    if let Some(backtrace) = report.folded_backtrace() {
+        eprintln!("\n{}", output::separator());
+        eprintln!(
+            "\n{}\n",
+            output::heading("Backtrace, with runtime frames folded:")
+        );
+        for l in backtrace.lines() {
+            eprintln!("\t{}", l);
         }
     }
+}
 
-    pub(crate) fn fuzz_dir(&self) -> &Path {
-        &self.fuzz_dir
+/// Forwards the stderr of a fuzz target to ours line by line, after `prefix`,
+/// capturing it along the way.
+fn forward_and_capture(
+    stderr: impl io::Read,
+    prefix: &str,
+    mut on_line: impl FnMut(&str),
+) -> Result<CapturedStderr> {
+    let mut captured = CapturedStderr::default();
+    let mut reader = io::BufReader::new(stderr);
+    let mut line = Vec::new();
+    while reader
+        .read_until(b'\n', &mut line)
+        .context("failed to read the output of the fuzz target")?
+        > 0
+    {
+        // Don't let a closed stderr of our own interrupt the fuzz target.
+        let _ = io::stderr().write_all(&[prefix.as_bytes(), &line].concat());
+
+        let text = String::from_utf8_lossy(&line).into_owned();
+        on_line(&text);
+        captured.push(text);
+        line.clear();
     }
+    Ok(captured)
+}
 
-    fn manifest_path(&self) -> PathBuf {
-        self.fuzz_dir().join("Cargo.toml")
+/// Runs `git` in `dir` with `args`, and returns what it printed, trimmed, if
+/// it succeeded.
+fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// Appends a file with `contents` to an archive, as `path`.
+fn tar_append(
+    builder: &mut tar::Builder<impl Write>,
+    path: &str,
+    contents: &[u8],
+    mtime: u64,
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    builder
+        .append_data(&mut header, path, contents)
+        .with_context(|| format!("failed to archive {}", path))
+}
+
+/// Returns the regular files directly inside `dir`, sorted by path.
+fn sorted_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory {}", dir.display()))?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    files.retain(|path| path.is_file());
+    files.sort();
+    Ok(files)
+}
+
+fn run_coverage_cmd(cmd: &mut Command) -> Result<()> {
+    let status = cmd
+        .status()
+        .with_context(|| format!("Failed to run command: {:?}", cmd))?;
+    if !status.success() {
+        Err(anyhow!(
+            "Command exited with failure status {}: {:?}",
+            status,
+            cmd
+        ))
+        .context("Failed to generage coverage data")?;
     }
+    Ok(())
+}
 
-    /// Returns paths to the `coverage/<target>/raw` directory and `coverage/<target>/coverage.profdata` file.
-    fn coverage_for(&self, target: &str) -> Result<(PathBuf, PathBuf)> {
-        let mut coverage_data = self.fuzz_dir().to_owned();
-        coverage_data.push("coverage");
-        coverage_data.push(target);
-        let mut coverage_raw = coverage_data.clone();
-        coverage_data.push("coverage.profdata");
-        coverage_raw.push("raw");
-        fs::create_dir_all(&coverage_raw).with_context(|| {
-            format!("could not make a coverage directory at {:?}", coverage_raw)
-        })?;
-        Ok((coverage_raw, coverage_data))
+/// Returns the runner command that coverage builds for another target than
+/// the host run through, following cargo's `CARGO_TARGET_<TRIPLE>_RUNNER`.
+fn coverage_runner(coverage: &options::Coverage) -> Option<String> {
+    if coverage.runner.is_some() {
+        return coverage.runner.clone();
     }
+    env_runner(&coverage.build.triple)
+}
 
-    fn corpus_for(&self, target: &str) -> Result<PathBuf> {
-        let mut p = self.fuzz_dir().to_owned();
-        p.push("corpus");
-        p.push(target);
-        fs::create_dir_all(&p)
-            .with_context(|| format!("could not make a corpus directory at {:?}", p))?;
-        Ok(p)
+/// Returns the `CARGO_TARGET_<TRIPLE>_RUNNER` of the environment for a target
+/// other than the host.
+fn env_runner(triple: &str) -> Option<String> {
+    if triple == default_target() {
+        return None;
     }
+    env::var(runner_var(triple)).ok()
+}
 
-    fn artifacts_for(&self, target: &str) -> Result<PathBuf> {
-        let mut p = self.fuzz_dir().to_owned();
-        p.push("artifacts");
-        p.push(target);
+/// Returns the name of the variable that cargo reads the runner of a target
+/// from.
+fn runner_var(triple: &str) -> String {
+    format!(
+        "CARGO_TARGET_{}_RUNNER",
+        triple.to_uppercase().replace(['-', '.'], "_")
+    )
+}
 
-        // This adds a trailing slash, which is necessary for libFuzzer, because
-        // it does simple string concatenation when joining paths.
-        p.push("");
+/// Whether `build` is for WebAssembly with WASI, which fuzz targets run under
+/// a WebAssembly runtime for.
+fn is_wasi(build: &BuildOptions) -> bool {
+    build.triple.starts_with("wasm32-wasi")
+}
 
-        fs::create_dir_all(&p)
-            .with_context(|| format!("could not make a artifact directory at {:?}", p))?;
+/// Whether `build` is for the iOS simulator, which fuzz targets run in through
+/// `simctl spawn`.
+fn is_ios_simulator(build: &BuildOptions) -> bool {
+    build.triple.ends_with("-apple-ios-sim") || build.triple == "x86_64-apple-ios"
+}
 
-        Ok(p)
+/// Passes the sanitizer options and `RUST_BACKTRACE` of `cmd` on to a fuzz
+/// target in the iOS simulator. `simctl spawn` only gives the process the
+/// variables that start with `SIMCTL_CHILD_`, without that prefix.
+fn configure_simulator_env(cmd: &mut Command) {
+    let mut vars: Vec<(ffi::OsString, ffi::OsString)> = cmd
+        .get_envs()
+        .filter(|(name, _)| name.to_str().is_some_and(|name| name.ends_with("_OPTIONS")))
+        .filter_map(|(name, value)| Some((name.to_owned(), value?.to_owned())))
+        .collect();
+    if let Some(backtrace) = env::var_os("RUST_BACKTRACE") {
+        vars.push(("RUST_BACKTRACE".into(), backtrace));
     }
+    for (name, value) in vars {
+        let mut child_name = ffi::OsString::from("SIMCTL_CHILD_");
+        child_name.push(name);
+        cmd.env(child_name, value);
+    }
+}
 
-    fn fuzz_targets_dir(&self) -> PathBuf {
-        let mut root = self.fuzz_dir().to_owned();
-        if root.join(crate::FUZZ_TARGETS_DIR_OLD).exists() {
-            println!(
-                "warning: The `fuzz/fuzzers/` directory has renamed to `fuzz/fuzz_targets/`. \
-                 Please rename the directory as such. This will become a hard error in the \
-                 future."
-            );
-            root.push(crate::FUZZ_TARGETS_DIR_OLD);
-        } else {
-            root.push(crate::FUZZ_TARGETS_DIR);
-        }
-        root
+/// Returns `path` the way the fuzz target of `build` gets it. Processes in the
+/// iOS simulator see the file system of the host, but don't start in the
+/// current directory, so relative paths are made absolute for them.
+fn runner_path(build: &BuildOptions, path: &Path) -> Result<PathBuf> {
+    if is_ios_simulator(build) && path.is_relative() {
+        Ok(env::current_dir()?.join(path))
+    } else {
+        Ok(path.to_owned())
     }
+}
 
-    fn target_path(&self, target: &str) -> PathBuf {
-        let mut root = self.fuzz_targets_dir();
-        root.push(target);
-        root.set_extension("rs");
-        root
+/// Checks that `build` can work for a WASI target. There are no sanitizers
+/// for WebAssembly, and libfuzzer-sys can't build libFuzzer for it, so it has
+/// to come from `--libfuzzer`, like one built with wasi-sdk.
+fn check_wasi(build: &BuildOptions) -> Result<()> {
+    if build.sanitizer != Sanitizer::None {
+        bail!(
+            "there are no sanitizers for `--target={}`, build with `--sanitizer=none`",
+            build.triple
+        );
+    }
+    if build.libfuzzer.is_none() && env::var_os("CUSTOM_LIBFUZZER_PATH").is_none() {
+        bail!(
+            "libfuzzer-sys can't build libFuzzer for `--target={}`, \
+             pass a libFuzzer built for it with `--libfuzzer`",
+            build.triple
+        );
     }
+    Ok(())
+}
 
-    fn manifest(&self) -> Result<toml::Value> {
-        let filename = self.manifest_path();
-        let mut file = fs::File::open(&filename)
-            .with_context(|| format!("could not read the manifest file: {}", filename.display()))?;
-        let mut data = Vec::new();
-        file.read_to_end(&mut data)?;
-        toml::from_slice(&data).with_context(|| {
-            format!(
-                "could not decode the manifest file at {}",
-                filename.display()
-            )
-        })
+fn check_profraw_written(coverage: &options::Coverage, profraw_path: &Path) -> Result<()> {
+    if profraw_path.exists() {
+        return Ok(());
     }
+    let hint = if coverage_runner(coverage).is_some() {
+        "\n\nThe runner has to write the raw coverage data to the path in \
+         `LLVM_PROFILE_FILE` on the host, e.g. by copying it back from the device."
+    } else {
+        ""
+    };
+    bail!(
+        "no coverage data was written to {}{}",
+        profraw_path.display(),
+        hint
+    )
+}
 
-    // If `fuzz_dir_opt` is `None`, returns a new instance with the default fuzz project
-    // path. Otherwise, returns a new instance with the inner content of `fuzz_dir_opt`.
-    fn manage_initial_instance(fuzz_dir_opt: Option<PathBuf>) -> Result<Self> {
-        let project_dir = find_package()?;
-        let fuzz_dir = if let Some(el) = fuzz_dir_opt {
-            el
-        } else {
-            project_dir.join(DEFAULT_FUZZ_DIR)
+/// Removes the raw coverage files that an earlier run wrote for a corpus,
+/// either `default-<corpus>.profraw` or `default-<corpus>.<job>.profraw`.
+fn remove_stale_profraws(coverage_out_raw_dir: &Path, corpus_name: &str) -> Result<()> {
+    let prefix = format!("default-{}.", corpus_name);
+    for path in sorted_files(coverage_out_raw_dir)? {
+        let name = file_name(&path);
+        let is_stale = match name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix("profraw"))
+        {
+            Some("") => true,
+            Some(job) => job
+                .strip_suffix('.')
+                .is_some_and(|job| job.parse::<usize>().is_ok()),
+            None => false,
         };
-        Ok(FuzzProject {
-            fuzz_dir,
-            targets: Vec::new(),
-        })
+        if is_stale {
+            fs::remove_file(&path)
+                .with_context(|| format!("failed to remove {}", path.display()))?;
+        }
     }
+    Ok(())
+}
 
-    fn fuzz_dir_is_default_path(&self) -> bool {
-        self.fuzz_dir.ends_with(DEFAULT_FUZZ_DIR)
+/// Parses libFuzzer's `Executed <file> in <n> ms` line into the file and how
+/// many milliseconds running it took.
+fn parse_executed(line: &str) -> Option<(&str, u64)> {
+    let (file, time) = line.strip_prefix("Executed ")?.rsplit_once(" in ")?;
+    Some((file, time.strip_suffix(" ms")?.parse().ok()?))
+}
+
+/// How many inputs `cargo fuzz bench-corpus` and `cargo fuzz bench` pass to
+/// each run of a fuzz target.
+const BENCH_BATCH_SIZE: usize = 256;
+
+/// libFuzzer only reports inputs that take longer than this many seconds as
+/// slow units by default.
+const DEFAULT_SLOW_UNIT_SECS: u64 = 10;
+
+/// Returns the libFuzzer arguments `args` with a `-report_slow_units=` of
+/// half the `-timeout=`, unless they pass one already. With a timeout at or
+/// below the default threshold, inputs would time out before libFuzzer ever
+/// reported them as slow.
+fn with_slow_unit_threshold(args: &[String]) -> Vec<String> {
+    let mut args = args.to_vec();
+    let timeout = args
+        .iter()
+        .rev()
+        .find_map(|arg| arg.strip_prefix("-timeout=")?.parse::<u64>().ok());
+    if let Some(timeout) = timeout {
+        if (1..=DEFAULT_SLOW_UNIT_SECS).contains(&timeout)
+            && !args
+                .iter()
+                .any(|arg| arg.starts_with("-report_slow_units="))
+        {
+            args.push(format!("-report_slow_units={}", (timeout / 2).max(1)));
+        }
     }
+    args
 }
 
-fn sysroot() -> Result<String> {
-    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
-    let output = Command::new(rustc).arg("--print").arg("sysroot").output()?;
-    // Note: We must trim() to remove the `\n` from the end of stdout
-    Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+/// The `-timeout=` of `cargo fuzz tmin --kind=timeout`, unless one is given.
+/// Every smaller input that still hangs takes this long to tell.
+const DEFAULT_TMIN_TIMEOUT_SECS: u64 = 10;
+
+/// The `-rss_limit_mb=` of `cargo fuzz tmin --kind=oom`, unless one is given,
+/// which is libFuzzer's default.
+const DEFAULT_TMIN_RSS_LIMIT_MB: u64 = 2048;
+
+/// libFuzzer's default `-timeout_exitcode=`.
+const TIMEOUT_EXITCODE: i32 = 70;
+
+/// Returns the libFuzzer arguments `args` for minimizing a failure of `kind`.
+/// libFuzzer's crash minimization keeps every smaller input that makes the
+/// fuzz target exit with an error, so the exit code of the other failures is
+/// zeroed: crashes when minimizing timeouts, and timeouts when minimizing
+/// OOMs. Minimization then ends on them, at the last input that failed the
+/// right way.
+fn tmin_kind_args(kind: TminKind, args: &[String]) -> Vec<String> {
+    let mut args = args.to_vec();
+    let has = |args: &[String], flag: &str| args.iter().any(|arg| arg.starts_with(flag));
+    match kind {
+        TminKind::Crash => {}
+        TminKind::Timeout => {
+            if !has(&args, "-timeout=") {
+                args.push(format!("-timeout={}", DEFAULT_TMIN_TIMEOUT_SECS));
+            }
+            args.push(format!("-timeout_exitcode={}", TIMEOUT_EXITCODE));
+            args.push("-error_exitcode=0".to_owned());
+        }
+        TminKind::Oom => {
+            if !has(&args, "-rss_limit_mb=") {
+                args.push(format!("-rss_limit_mb={}", DEFAULT_TMIN_RSS_LIMIT_MB));
+            }
+            args.push("-timeout_exitcode=0".to_owned());
+        }
+    }
+    args
 }
 
-fn rustlib() -> Result<PathBuf> {
-    let sysroot = sysroot()?;
-    let mut pathbuf = PathBuf::from(sysroot);
-    pathbuf.push("lib");
-    pathbuf.push("rustlib");
-    pathbuf.push(rustc_version::version_meta()?.host);
-    pathbuf.push("bin");
-    Ok(pathbuf)
+/// Returns the file name of the minimized `input`: `minimized-<name>` for
+/// crashes, and `timeout-minimized-<name>` or `oom-minimized-<name>` for
+/// the other kinds, so that they are listed among the artifacts of their
+/// kind.
+fn minimized_name(kind: TminKind, input: &Path) -> String {
+    let name = file_name(input);
+    let kind_prefix = match kind {
+        TminKind::Crash => return format!("{}{}", MINIMIZED_PREFIX, name),
+        TminKind::Timeout => "timeout-",
+        TminKind::Oom => "oom-",
+    };
+    format!(
+        "{}{}{}",
+        kind_prefix,
+        MINIMIZED_PREFIX,
+        name.strip_prefix(kind_prefix).unwrap_or(&name)
+    )
+}
+
+/// Returns whether an artifact is the result of `cargo fuzz tmin`.
+fn is_minimized(path: &Path) -> bool {
+    let name = file_name(path);
+    ["timeout-", "oom-"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .unwrap_or(&name)
+        .starts_with(MINIMIZED_PREFIX)
+}
+
+/// Returns advice about the limit that an artifact of `category` ran into,
+/// which is often just too tight for the fuzz target.
+fn limit_advice(category: &str, limits: &options::Limits) -> Option<String> {
+    let value =
+        |value: Option<u64>, unit| value.map_or(String::new(), |v| format!(" of {} {}", v, unit));
+    match category {
+        "timeout" => Some(format!(
+            "The input ran for longer than the timeout{}. If the fuzz target is just \
+             slow on it rather than stuck, raise the limit with `--timeout`.",
+            value(limits.timeout, "seconds")
+        )),
+        "oom" => Some(format!(
+            "The input made the fuzz target use more memory than the RSS limit{}. If \
+             it legitimately needs that much, raise the limit with `--rss-limit`, \
+             or disable it with `--rss-limit=0`.",
+            value(limits.rss_limit, "MB")
+        )),
+        _ => None,
+    }
+}
+
+/// Returns the kind of an artifact, based on the prefix libFuzzer (or
+/// `cargo fuzz tmin --all-artifacts`) gave its file name.
+fn artifact_category(path: &Path) -> &'static str {
+    const CATEGORIES: &[(&str, &str)] = &[
+        (MINIMIZED_PREFIX, "minimized"),
+        ("crash-", "crash"),
+        ("timeout-", "timeout"),
+        ("oom-", "oom"),
+        ("leak-", "leak"),
+        ("slow-unit-", "slow-unit"),
+    ];
+    let name = file_name(path);
+    CATEGORIES
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map_or("other", |(_, category)| category)
 }
 
-fn collect_targets(value: &toml::Value) -> Vec<String> {
-    let bins = value
-        .as_table()
-        .and_then(|v| v.get("bin"))
-        .and_then(toml::Value::as_array);
-    let mut bins = if let Some(bins) = bins {
-        bins.iter()
-            .map(|bin| {
-                bin.as_table()
-                    .and_then(|v| v.get("name"))
-                    .and_then(toml::Value::as_str)
+/// Formats a size in bytes such as `512 B` or `3.2 MiB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Prints `rows` as columns as wide as their widest cell, the ones of
+/// `left_aligned` aligned to the left and the others to the right.
+fn print_table<const N: usize>(rows: &[[String; N]], left_aligned: &[bool; N]) {
+    let mut widths = [0; N];
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in rows {
+        let line = row
+            .iter()
+            .zip(widths.iter().zip(left_aligned))
+            .map(|(cell, (&width, &left))| {
+                if left {
+                    format!("{:<width$}", cell, width = width)
+                } else {
+                    format!("{:>width$}", cell, width = width)
+                }
             })
-            .filter_map(|name| name.map(String::from))
-            .collect()
-    } else {
-        Vec::new()
-    };
-    // Always sort them, so that we have deterministic output.
-    bins.sort();
-    bins
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
 }
 
-pub struct Manifest {
-    crate_name: String,
-    edition: Option<String>,
+/// Formats a duration as a short, coarse age such as `5m` or `3d`.
+fn format_age(age: time::Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
 }
 
-impl Manifest {
-    pub fn parse() -> Result<Self> {
-        let metadata = MetadataCommand::new().no_deps().exec()?;
-        let package = metadata.packages.first().with_context(|| {
-            anyhow!(
-                "Expected to find at least one package in {}",
-                metadata.target_directory
-            )
-        })?;
-        let crate_name = package.name.clone();
-        let edition = Some(String::from(package.edition.as_str()));
+fn file_name(path: &Path) -> std::borrow::Cow<'_, str> {
+    path.file_name().unwrap_or_default().to_string_lossy()
+}
 
-        Ok(Manifest {
-            crate_name,
-            edition,
-        })
+/// Removes the `\\?\` prefix of verbatim Windows paths, such as the ones
+/// returned by `fs::canonicalize`, which LLVM tools and the profiling runtime
+/// don't understand. UNC paths are left alone.
+fn strip_verbatim_prefix(path: &Path) -> PathBuf {
+    let path_str = path.to_string_lossy();
+    match path_str.strip_prefix(r"\\?\") {
+        Some(rest) if !rest.starts_with(r"UNC\") => PathBuf::from(rest),
+        _ => path.to_owned(),
     }
 }
 
-fn is_fuzz_manifest(value: &toml::Value) -> bool {
-    let is_fuzz = value
-        .as_table()
-        .and_then(|v| v.get("package"))
-        .and_then(toml::Value::as_table)
-        .and_then(|v| v.get("metadata"))
-        .and_then(toml::Value::as_table)
-        .and_then(|v| v.get("cargo-fuzz"))
-        .and_then(toml::Value::as_bool);
-    is_fuzz == Some(true)
+/// Returns the directory of the MSVC AddressSanitizer runtime DLL when a fuzz
+/// target built for an MSVC target needs it and it's not already on the
+/// `PATH`. Outside of a developer command prompt, the DLL is only found
+/// through the `VCToolsInstallDir` variable.
+pub fn msvc_asan_dll_dir(build: &BuildOptions) -> Option<PathBuf> {
+    let (dll, dir_arch) = msvc_asan_dll(build)?;
+    if is_on_path(&dll) {
+        return None;
+    }
+    let host_arch = if cfg!(target_arch = "aarch64") {
+        "arm64"
+    } else if cfg!(target_arch = "x86") {
+        "x86"
+    } else {
+        "x64"
+    };
+    let dir = PathBuf::from(env::var_os("VCToolsInstallDir")?)
+        .join("bin")
+        .join(format!("Host{}", host_arch))
+        .join(dir_arch);
+    if dir.join(&dll).is_file() {
+        Some(dir)
+    } else {
+        None
+    }
 }
 
-/// Returns the path for the first found non-fuzz Cargo package
-fn find_package() -> Result<PathBuf> {
-    let mut dir = env::current_dir()?;
-    let mut data = Vec::new();
-    loop {
-        let manifest_path = dir.join("Cargo.toml");
-        match fs::File::open(&manifest_path) {
-            Err(_) => {}
-            Ok(mut f) => {
-                data.clear();
-                f.read_to_end(&mut data)
-                    .with_context(|| format!("failed to read {}", manifest_path.display()))?;
-                let value: toml::Value = toml::from_slice(&data).with_context(|| {
-                    format!(
-                        "could not decode the manifest file at {}",
-                        manifest_path.display()
-                    )
-                })?;
-                if !is_fuzz_manifest(&value) {
-                    // Not a cargo-fuzz project => must be a proper cargo project :)
-                    return Ok(dir);
-                }
-            }
-        }
-        if !dir.pop() {
-            break;
-        }
+/// Returns the name of the MSVC AddressSanitizer runtime DLL that a fuzz
+/// target built with `build` needs, if any, and the directory for its
+/// architecture among the MSVC tools.
+pub fn msvc_asan_dll(build: &BuildOptions) -> Option<(String, &'static str)> {
+    if !build.triple.contains("-msvc") || !matches!(build.sanitizer, Sanitizer::Address) {
+        return None;
     }
-    bail!("could not find a cargo project")
+    let (dll_arch, dir_arch) = match build.triple.split('-').next()? {
+        "x86_64" => ("x86_64", "x64"),
+        "i586" | "i686" => ("i386", "x86"),
+        "aarch64" => ("aarch64", "arm64"),
+        _ => return None,
+    };
+    Some((format!("clang_rt.asan_dynamic-{}.dll", dll_arch), dir_arch))
+}
+
+/// Whether a file of this name is in one of the directories of the `PATH`.
+pub fn is_on_path(name: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|paths| env::split_paths(&paths).any(|dir| dir.join(name).is_file()))
 }
 
 fn strip_current_dir_prefix(path: &Path) -> &Path {
@@ -0,0 +1,301 @@
+//! Generation of regression tests from fuzzer inputs
+
+use std::fmt::Write;
+
+/// How many bytes of an input go on each line of the byte string literal.
+const BYTES_PER_LINE: usize = 32;
+
+/// The closure passed to `fuzz_target!`, split into its parts.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FuzzTargetClosure {
+    /// The pattern the input is bound to, e.g. `data`
+    pub pattern: String,
+    /// The type of the input, `None` when it is not annotated
+    pub ty: Option<String>,
+    /// The body of the closure, without surrounding braces
+    pub body: String,
+}
+
+impl FuzzTargetClosure {
+    /// Finds the closure of the `fuzz_target!` invocation in the source of a
+    /// fuzz target. This is a heuristic that only balances delimiters, so it
+    /// can be confused by unbalanced delimiters in string literals.
+    pub fn parse(source: &str) -> Option<Self> {
+        let start = source.find("fuzz_target!(")? + "fuzz_target!(".len();
+        let inner = &source[start..start + matching_delimiter(&source[start..])?];
+
+        let params_start = inner.find('|')? + 1;
+        let params_len = inner[params_start..].find('|')?;
+        let params = &inner[params_start..params_start + params_len];
+        let (pattern, ty) = match top_level_colon(params) {
+            Some(colon) => (
+                params[..colon].trim(),
+                Some(params[colon + 1..].trim().to_owned()),
+            ),
+            None => (params.trim(), None),
+        };
+
+        let body = inner[params_start + params_len + 1..].trim();
+        let body = body.trim_end_matches(',').trim_end();
+        let body = match body.strip_prefix('{') {
+            Some(block) => block.strip_suffix('}')?,
+            None => body,
+        };
+
+        Some(FuzzTargetClosure {
+            pattern: pattern.to_owned(),
+            ty,
+            body: dedent(body),
+        })
+    }
+
+    /// Whether the fuzz target takes raw bytes rather than an `Arbitrary` type.
+    pub fn takes_bytes(&self) -> bool {
+        match &self.ty {
+            None => true,
+            Some(ty) => ty.replace(char::is_whitespace, "") == "&[u8]",
+        }
+    }
+}
+
+/// Renders a `#[test]` function that replays `data` through `closure`, or
+/// only defines the input when the fuzz target could not be understood.
+pub fn emit(
+    name: &str,
+    comment: &str,
+    data: &[u8],
+    closure: Option<&FuzzTargetClosure>,
+    debug: Option<&str>,
+) -> String {
+    let mut test = String::new();
+    writeln!(test, "#[test]").unwrap();
+    writeln!(test, "fn {}() {{", name).unwrap();
+    for line in comment.lines() {
+        writeln!(test, "    // {}", line).unwrap();
+    }
+    if let Some(debug) = debug {
+        writeln!(test, "    //").unwrap();
+        writeln!(test, "    // Output of `std::fmt::Debug`:").unwrap();
+        writeln!(test, "    //").unwrap();
+        for line in debug.lines() {
+            writeln!(test, "    //     {}", line).unwrap();
+        }
+    }
+
+    match closure {
+        Some(closure) if closure.takes_bytes() => {
+            writeln!(
+                test,
+                "    let {}: &[u8] = {};",
+                closure.pattern,
+                byte_string(data)
+            )
+            .unwrap();
+        }
+        Some(closure) => {
+            writeln!(test, "    let bytes: &[u8] = {};", byte_string(data)).unwrap();
+            writeln!(
+                test,
+                "    let {}: {} = arbitrary::Arbitrary::arbitrary_take_rest(\n        \
+                 arbitrary::Unstructured::new(bytes),\n    )\n    .unwrap();",
+                closure.pattern,
+                closure.ty.as_deref().unwrap_or("_"),
+            )
+            .unwrap();
+        }
+        None => {
+            writeln!(test, "    let data: &[u8] = {};", byte_string(data)).unwrap();
+        }
+    }
+
+    match closure {
+        Some(closure) => {
+            for line in closure.body.lines() {
+                if line.is_empty() {
+                    writeln!(test).unwrap();
+                } else {
+                    writeln!(test, "    {}", line).unwrap();
+                }
+            }
+        }
+        None => {
+            writeln!(
+                test,
+                "    // Call the code exercised by the fuzz target here."
+            )
+            .unwrap();
+            writeln!(test, "    let _ = data;").unwrap();
+        }
+    }
+    writeln!(test, "}}").unwrap();
+    test
+}
+
+/// Renders `data` as a byte string literal, split over several lines with
+/// string continuations when it is long.
+fn byte_string(data: &[u8]) -> String {
+    let mut literal = String::from("b\"");
+    for (i, chunk) in data.chunks(BYTES_PER_LINE).enumerate() {
+        if i > 0 {
+            literal.push_str("\\\n          ");
+        }
+        for (j, &byte) in chunk.iter().enumerate() {
+            match byte {
+                // A string continuation skips leading whitespace on the next
+                // line, so a space there has to be escaped.
+                b' ' if i > 0 && j == 0 => literal.push_str("\\x20"),
+                b'\n' => literal.push_str("\\n"),
+                b'\r' => literal.push_str("\\r"),
+                b'\t' => literal.push_str("\\t"),
+                b'\0' => literal.push_str("\\0"),
+                b'"' => literal.push_str("\\\""),
+                b'\\' => literal.push_str("\\\\"),
+                b' '..=b'~' => literal.push(byte as char),
+                _ => write!(literal, "\\x{:02x}", byte).unwrap(),
+            }
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Returns the offset of the delimiter closing the one that `s` directly
+/// follows.
+fn matching_delimiter(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                if depth == 0 {
+                    return Some(i);
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns the offset of the first `:` in `params` that is not nested in a
+/// tuple pattern or type, and is not part of a `::` path separator.
+fn top_level_colon(params: &str) -> Option<usize> {
+    let bytes = params.as_bytes();
+    let mut depth = 0usize;
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'(' | b'[' | b'<' => depth += 1,
+            b')' | b']' | b'>' => depth = depth.saturating_sub(1),
+            b':' if depth == 0
+                && bytes.get(i + 1) != Some(&b':')
+                && (i == 0 || bytes[i - 1] != b':') =>
+            {
+                return Some(i)
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Removes the indentation common to all non-empty lines of `s`, along with
+/// leading and trailing empty lines.
+fn dedent(s: &str) -> String {
+    let lines: Vec<_> = s
+        .lines()
+        .skip_while(|line| line.trim().is_empty())
+        .collect();
+    let lines = match lines.iter().rposition(|line| !line.trim().is_empty()) {
+        Some(last) => &lines[..=last],
+        None => &[],
+    };
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_byte_closure() {
+        let source = r#"
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        my_crate::parse(s);
+    }
+});
+"#;
+        assert_eq!(
+            FuzzTargetClosure::parse(source),
+            Some(FuzzTargetClosure {
+                pattern: "data".to_owned(),
+                ty: Some("&[u8]".to_owned()),
+                body: "if let Ok(s) = std::str::from_utf8(data) {\n    my_crate::parse(s);\n}"
+                    .to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn parses_typed_closure() {
+        let source = "fuzz_target!(|input: (u8, my_crate::Config)| my_crate::run(input));";
+        assert_eq!(
+            FuzzTargetClosure::parse(source),
+            Some(FuzzTargetClosure {
+                pattern: "input".to_owned(),
+                ty: Some("(u8, my_crate::Config)".to_owned()),
+                body: "my_crate::run(input)".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn byte_strings() {
+        assert_eq!(byte_string(b""), "b\"\"");
+        assert_eq!(
+            byte_string(b"a\"b\\c\n\0\xff"),
+            "b\"a\\\"b\\\\c\\n\\0\\xff\""
+        );
+        let long = [b' '; BYTES_PER_LINE + 1];
+        assert!(byte_string(&long).ends_with("\\\n          \\x20\""));
+    }
+
+    #[test]
+    fn emits_typed_test() {
+        let closure = FuzzTargetClosure::parse("fuzz_target!(|n: u32| { check(n); });");
+        let test = emit(
+            "regression",
+            "From crash-1",
+            b"\x01",
+            closure.as_ref(),
+            None,
+        );
+        assert_eq!(
+            test,
+            "#[test]\n\
+             fn regression() {\n    \
+             // From crash-1\n    \
+             let bytes: &[u8] = b\"\\x01\";\n    \
+             let n: u32 = arbitrary::Arbitrary::arbitrary_take_rest(\n        \
+             arbitrary::Unstructured::new(bytes),\n    \
+             )\n    \
+             .unwrap();\n    \
+             check(n);\n\
+             }\n"
+        );
+    }
+}
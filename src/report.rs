@@ -0,0 +1,233 @@
+//! Rendering of `cargo fuzz report`
+
+use crate::{options::ReportFormat, stats::RunStats};
+use std::fmt::Write;
+
+/// Everything the report says about one fuzz target.
+pub struct TargetReport {
+    pub target: String,
+    pub corpus_inputs: usize,
+    pub corpus_bytes: u64,
+    pub runs: Vec<RunStats>,
+    pub crashes: Vec<CrashBucket>,
+}
+
+/// Artifacts that crashed with the same stack hash.
+pub struct CrashBucket {
+    /// The stack hash, `None` for artifacts without recorded metadata
+    pub hash: Option<String>,
+    pub kind: Option<String>,
+    pub panic_location: Option<String>,
+    pub artifacts: Vec<String>,
+    pub backtrace: Option<String>,
+    pub reproduce: String,
+}
+
+impl TargetReport {
+    fn fuzzing_secs(&self) -> u64 {
+        self.runs.iter().map(|run| run.elapsed_secs).sum()
+    }
+
+    fn execs(&self) -> u64 {
+        self.runs.iter().map(|run| run.execs).sum()
+    }
+
+    fn max_cov(&self) -> Option<u64> {
+        self.runs.iter().filter_map(|run| run.cov).max()
+    }
+
+    fn max_features(&self) -> Option<u64> {
+        self.runs.iter().filter_map(|run| run.features).max()
+    }
+}
+
+pub fn render(format: ReportFormat, targets: &[TargetReport]) -> String {
+    match format {
+        ReportFormat::Markdown => render_markdown(targets),
+        ReportFormat::Html => render_html(targets),
+    }
+}
+
+/// The rows of the summary table of a target, shared by both formats.
+fn summary(target: &TargetReport) -> Vec<(&'static str, String)> {
+    let optional =
+        |value: Option<u64>| value.map_or_else(|| "unknown".to_owned(), |v| v.to_string());
+    vec![
+        (
+            "Corpus",
+            format!(
+                "{} inputs, {} bytes",
+                target.corpus_inputs, target.corpus_bytes
+            ),
+        ),
+        ("Recorded runs", target.runs.len().to_string()),
+        ("Fuzzing time", format_duration(target.fuzzing_secs())),
+        ("Executions", target.execs().to_string()),
+        ("Edge coverage", optional(target.max_cov())),
+        ("Features", optional(target.max_features())),
+        ("Unique crashes", target.crashes.len().to_string()),
+    ]
+}
+
+fn crash_title(crash: &CrashBucket) -> String {
+    let mut title = match &crash.hash {
+        Some(hash) => format!("`{}`", hash),
+        None => "Artifacts without metadata".to_owned(),
+    };
+    if let Some(kind) = &crash.kind {
+        write!(title, ": {}", kind).unwrap();
+    }
+    if let Some(location) = &crash.panic_location {
+        write!(title, ", panicked at {}", location).unwrap();
+    }
+    title
+}
+
+fn render_markdown(targets: &[TargetReport]) -> String {
+    let mut md = String::from("# Fuzzing report\n");
+    for target in targets {
+        writeln!(md, "\n## `{}`\n", target.target).unwrap();
+        writeln!(md, "| | |\n|---|---|").unwrap();
+        for (name, value) in summary(target) {
+            writeln!(md, "| {} | {} |", name, value).unwrap();
+        }
+
+        for crash in &target.crashes {
+            writeln!(md, "\n### {}\n", crash_title(crash)).unwrap();
+            writeln!(md, "Artifacts:\n").unwrap();
+            for artifact in &crash.artifacts {
+                writeln!(md, "- `{}`", artifact).unwrap();
+            }
+            if let Some(backtrace) = &crash.backtrace {
+                writeln!(md, "\n```text\n{}\n```", backtrace.trim_end()).unwrap();
+            }
+            writeln!(md, "\nReproduce with:\n\n```sh\n{}\n```", crash.reproduce).unwrap();
+        }
+    }
+    md
+}
+
+fn render_html(targets: &[TargetReport]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Fuzzing report</title>\n</head>\n<body>\n<h1>Fuzzing report</h1>\n",
+    );
+    for target in targets {
+        writeln!(html, "<h2><code>{}</code></h2>", escape(&target.target)).unwrap();
+        writeln!(html, "<table>").unwrap();
+        for (name, value) in summary(target) {
+            writeln!(
+                html,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                name,
+                escape(&value)
+            )
+            .unwrap();
+        }
+        writeln!(html, "</table>").unwrap();
+
+        for crash in &target.crashes {
+            // The title uses Markdown backticks around the hash.
+            writeln!(
+                html,
+                "<h3>{}</h3>",
+                escape(&crash_title(crash).replace('`', ""))
+            )
+            .unwrap();
+            writeln!(html, "<ul>").unwrap();
+            for artifact in &crash.artifacts {
+                writeln!(html, "<li><code>{}</code></li>", escape(artifact)).unwrap();
+            }
+            writeln!(html, "</ul>").unwrap();
+            if let Some(backtrace) = &crash.backtrace {
+                writeln!(html, "<pre>{}</pre>", escape(backtrace.trim_end())).unwrap();
+            }
+            writeln!(
+                html,
+                "<p>Reproduce with:</p>\n<pre>{}</pre>",
+                escape(&crash.reproduce)
+            )
+            .unwrap();
+        }
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Formats a number of seconds as e.g. `3h 25m 7s`.
+pub fn format_duration(secs: u64) -> String {
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, secs)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> TargetReport {
+        TargetReport {
+            target: "parse<T>".to_owned(),
+            corpus_inputs: 3,
+            corpus_bytes: 120,
+            runs: vec![
+                RunStats {
+                    elapsed_secs: 3600,
+                    execs: 1000,
+                    cov: Some(50),
+                    ..RunStats::default()
+                },
+                RunStats {
+                    elapsed_secs: 61,
+                    execs: 24,
+                    cov: Some(40),
+                    ..RunStats::default()
+                },
+            ],
+            crashes: vec![CrashBucket {
+                hash: Some("0123456789abcdef".to_owned()),
+                kind: None,
+                panic_location: Some("src/lib.rs:1:2".to_owned()),
+                artifacts: vec!["fuzz/artifacts/parse/crash-1".to_owned()],
+                backtrace: Some("thread '<unnamed>' panicked at src/lib.rs:1:2:\n".to_owned()),
+                reproduce: "cargo fuzz run parse fuzz/artifacts/parse/crash-1".to_owned(),
+            }],
+        }
+    }
+
+    #[test]
+    fn markdown() {
+        let md = render(ReportFormat::Markdown, &[target()]);
+        assert!(md.contains("| Fuzzing time | 1h 1m 1s |"));
+        assert!(md.contains("| Executions | 1024 |"));
+        assert!(md.contains("| Edge coverage | 50 |"));
+        assert!(md.contains("### `0123456789abcdef`, panicked at src/lib.rs:1:2"));
+        assert!(md.contains("```sh\ncargo fuzz run parse fuzz/artifacts/parse/crash-1\n```"));
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        let html = render(ReportFormat::Html, &[target()]);
+        assert!(html.contains("<h2><code>parse&lt;T&gt;</code></h2>"));
+        assert!(html.contains("<pre>thread '&lt;unnamed&gt;' panicked at src/lib.rs:1:2:</pre>"));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(125), "2m 5s");
+        assert_eq!(format_duration(7322), "2h 2m 2s");
+    }
+}
@@ -10,7 +10,7 @@ fn is_nightly(version_string: &str) -> bool {
 }
 
 /// Returns the output of `rustc --version`
-fn rust_version_string() -> anyhow::Result<String> {
+pub fn rust_version_string() -> anyhow::Result<String> {
     // The path to rustc can be specified via an environment variable:
     // https://doc.rust-lang.org/cargo/reference/environment-variables.html#environment-variables-cargo-reads
     let rustc_path = std::env::var_os("RUSTC").unwrap_or("rustc".into());
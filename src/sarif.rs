@@ -0,0 +1,138 @@
+//! SARIF reports, for code scanning tools that annotate the source of crashes
+
+use crate::stacktrace::CrashReport;
+use serde_json::{json, Value};
+use std::path::Path;
+
+const RULE_ID: &str = "fuzz-crash";
+
+/// One crash found in a fuzz target.
+pub struct Crash<'a> {
+    pub target: &'a str,
+    pub report: &'a CrashReport,
+    pub artifact: Option<&'a Path>,
+    /// Whether the crash is known and doesn't fail the run
+    pub suppressed: bool,
+}
+
+/// Renders a report with a single run of cargo-fuzz.
+pub fn render(crashes: &[Crash]) -> String {
+    let results: Vec<_> = crashes.iter().map(result).collect();
+    let sarif = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "cargo-fuzz",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/rust-fuzz/cargo-fuzz",
+                    "rules": [{
+                        "id": RULE_ID,
+                        "shortDescription": { "text": "A fuzz target crashed" },
+                    }],
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&sarif).unwrap()
+}
+
+fn result(crash: &Crash) -> Value {
+    let report = crash.report;
+    let mut message = format!("`{}` crashed", crash.target);
+    match (&report.kind, &report.panic_location) {
+        (_, Some(location)) => message.push_str(&format!(": panicked at {}", location)),
+        (Some(kind), None) => message.push_str(&format!(": {}", kind)),
+        (None, None) => {}
+    }
+    if let Some(artifact) = crash.artifact {
+        message.push_str(&format!(", reproduce with {}", artifact.display()));
+    }
+
+    // The panic is where the bug shows, or else the innermost frame of the
+    // fuzzed code.
+    let location = report
+        .panic_location
+        .as_deref()
+        .or_else(|| {
+            report
+                .user_frames()
+                .find_map(|frame| frame.location.as_deref())
+        })
+        .and_then(physical_location);
+
+    let mut result = json!({
+        "ruleId": RULE_ID,
+        "level": "error",
+        "message": { "text": message },
+        "partialFingerprints": { "stackHash/v1": report.stack_hash() },
+    });
+    if let Some(location) = location {
+        result["locations"] = json!([{ "physicalLocation": location }]);
+    }
+    if crash.suppressed {
+        result["suppressions"] = json!([{ "kind": "external" }]);
+    }
+    result
+}
+
+/// Converts a `file:line:column` location to a SARIF physical location.
+fn physical_location(location: &str) -> Option<Value> {
+    let mut parts = location.rsplitn(3, ':');
+    let column: u64 = parts.next()?.parse().ok()?;
+    let line: u64 = parts.next()?.parse().ok()?;
+    let file = parts.next()?;
+    let uri = if Path::new(file).is_absolute() {
+        format!("file://{}", file)
+    } else {
+        file.to_owned()
+    };
+    Some(json!({
+        "artifactLocation": { "uri": uri },
+        "region": { "startLine": line, "startColumn": column },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_crashes() {
+        let report = CrashReport {
+            panic_location: Some("src/lib.rs:10:5".to_owned()),
+            ..CrashReport::default()
+        };
+        let crashes = [Crash {
+            target: "foo",
+            report: &report,
+            artifact: Some(Path::new("fuzz/artifacts/foo/crash-1")),
+            suppressed: true,
+        }];
+        let sarif: Value = serde_json::from_str(&render(&crashes)).unwrap();
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(
+            result["message"]["text"],
+            "`foo` crashed: panicked at src/lib.rs:10:5, reproduce with fuzz/artifacts/foo/crash-1"
+        );
+        assert_eq!(
+            result["locations"][0]["physicalLocation"],
+            json!({
+                "artifactLocation": { "uri": "src/lib.rs" },
+                "region": { "startLine": 10, "startColumn": 5 },
+            })
+        );
+        assert_eq!(result["suppressions"][0]["kind"], "external");
+    }
+
+    #[test]
+    fn parses_locations() {
+        assert_eq!(
+            physical_location("/src/lib.rs:1:2").unwrap()["artifactLocation"]["uri"],
+            "file:///src/lib.rs"
+        );
+        assert!(physical_location("src/lib.rs").is_none());
+    }
+}
@@ -0,0 +1,453 @@
+//! The database of fuzzing sessions, `.cargo-fuzz/sessions.db` in the fuzz
+//! directory: every `cargo fuzz run` with its options, stats and artifacts, by
+//! fuzz target and git commit
+//!
+//! Without the `sessions` feature there is no database, and runs aren't
+//! recorded.
+#![cfg_attr(not(feature = "sessions"), allow(dead_code))]
+
+use crate::stats::Status;
+#[cfg(feature = "sessions")]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(feature = "sessions")]
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+#[cfg(feature = "sessions")]
+use std::fs;
+use std::path::Path;
+
+#[cfg(feature = "sessions")]
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY,
+        target TEXT NOT NULL,
+        git_commit TEXT,
+        -- Seconds since the Unix epoch.
+        started INTEGER NOT NULL,
+        options TEXT NOT NULL,
+        -- The libFuzzer arguments, as a JSON array.
+        args TEXT NOT NULL,
+        -- The rest is only set once the run is over, and stays NULL for the
+        -- ones that were killed.
+        elapsed_secs INTEGER,
+        execs INTEGER,
+        execs_per_sec INTEGER,
+        cov INTEGER,
+        features INTEGER,
+        corpus_inputs INTEGER,
+        corpus_bytes INTEGER,
+        rss_bytes INTEGER,
+        failed INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS runs_of_target ON runs (target, git_commit);
+    CREATE TABLE IF NOT EXISTS artifacts (
+        run INTEGER NOT NULL REFERENCES runs (id),
+        path TEXT NOT NULL,
+        category TEXT NOT NULL
+    );
+";
+
+/// How a run of a fuzz target started.
+pub struct RunStart<'a> {
+    pub target: &'a str,
+    pub git_commit: Option<&'a str>,
+    pub started: u64,
+    /// The options of `cargo fuzz run`, like ` --sanitizer=none --jobs=2`.
+    pub options: &'a str,
+    pub args: &'a [String],
+}
+
+/// How a run of a fuzz target ended.
+pub struct RunEnd<'a> {
+    pub elapsed_secs: u64,
+    pub status: &'a Status,
+    pub failed: bool,
+    /// The artifacts that the run wrote, with their category.
+    pub artifacts: &'a [(String, &'a str)],
+}
+
+/// A run of a fuzz target as recorded in the database. What's only known
+/// once the run is over is `None` for the runs that got killed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RunRecord {
+    pub id: i64,
+    pub target: String,
+    pub git_commit: Option<String>,
+    pub started: u64,
+    pub options: String,
+    pub args: Vec<String>,
+    pub elapsed_secs: Option<u64>,
+    pub execs: Option<u64>,
+    pub execs_per_sec: Option<u64>,
+    pub cov: Option<u64>,
+    pub features: Option<u64>,
+    pub corpus_inputs: Option<u64>,
+    pub corpus_bytes: Option<u64>,
+    pub rss_bytes: Option<u64>,
+    pub failed: Option<bool>,
+    pub artifacts: Vec<ArtifactRecord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ArtifactRecord {
+    pub path: String,
+    pub category: String,
+}
+
+impl RunRecord {
+    #[cfg(feature = "sessions")]
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let args: String = row.get("args")?;
+        Ok(RunRecord {
+            id: row.get("id")?,
+            target: row.get("target")?,
+            git_commit: row.get("git_commit")?,
+            started: row.get("started")?,
+            options: row.get("options")?,
+            args: serde_json::from_str(&args).unwrap_or_default(),
+            elapsed_secs: row.get("elapsed_secs")?,
+            execs: row.get("execs")?,
+            execs_per_sec: row.get("execs_per_sec")?,
+            cov: row.get("cov")?,
+            features: row.get("features")?,
+            corpus_inputs: row.get("corpus_inputs")?,
+            corpus_bytes: row.get("corpus_bytes")?,
+            rss_bytes: row.get("rss_bytes")?,
+            failed: row.get("failed")?,
+            artifacts: Vec::new(),
+        })
+    }
+
+    /// How the run ended: `ok`, `failed`, or `killed` when it never got to
+    /// record it.
+    pub fn result(&self) -> &'static str {
+        match self.failed {
+            Some(false) => "ok",
+            Some(true) => "failed",
+            None => "killed",
+        }
+    }
+}
+
+#[cfg(feature = "sessions")]
+pub struct Sessions {
+    conn: Connection,
+}
+
+#[cfg(not(feature = "sessions"))]
+pub enum Sessions {}
+
+#[cfg(feature = "sessions")]
+impl Sessions {
+    /// Opens the database at `path`, creating it if needed.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create directory {}", dir.display()))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("failed to open the session database {}", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| format!("failed to set up the session database {}", path.display()))?;
+        Ok(Sessions { conn })
+    }
+
+    /// Records the start of a run, returning its id. Runs are recorded as
+    /// they start so that the ones that get killed show up too.
+    pub fn start_run(&self, run: &RunStart) -> Result<i64> {
+        self.conn
+            .execute(
+                "INSERT INTO runs (target, git_commit, started, options, args)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    run.target,
+                    run.git_commit,
+                    run.started,
+                    run.options.trim(),
+                    serde_json::to_string(run.args)?,
+                ],
+            )
+            .context("failed to record the start of the run")?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Records the end of the run `id`, with its stats and artifacts.
+    pub fn finish_run(&mut self, id: i64, end: &RunEnd) -> Result<()> {
+        let transaction = self.conn.transaction()?;
+        transaction
+            .execute(
+                "UPDATE runs SET elapsed_secs = ?2, execs = ?3, execs_per_sec = ?4, cov = ?5,
+                     features = ?6, corpus_inputs = ?7, corpus_bytes = ?8, rss_bytes = ?9,
+                     failed = ?10
+                 WHERE id = ?1",
+                params![
+                    id,
+                    end.elapsed_secs,
+                    end.status.execs,
+                    end.status.execs_per_sec,
+                    end.status.cov,
+                    end.status.features,
+                    end.status.corpus_inputs,
+                    end.status.corpus_bytes,
+                    end.status.rss_bytes,
+                    end.failed,
+                ],
+            )
+            .context("failed to record the end of the run")?;
+        for (path, category) in end.artifacts {
+            transaction
+                .execute(
+                    "INSERT INTO artifacts (run, path, category) VALUES (?1, ?2, ?3)",
+                    params![id, path, category],
+                )
+                .context("failed to record the artifacts of the run")?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// The runs of `target` that started within `since..until`, oldest
+    /// first.
+    pub fn runs(
+        &self,
+        target: &str,
+        since: Option<u64>,
+        until: Option<u64>,
+    ) -> Result<Vec<RunRecord>> {
+        let mut statement = self.conn.prepare(
+            "SELECT * FROM runs
+             WHERE target = ?1 AND started >= ?2 AND started < ?3
+             ORDER BY started, id",
+        )?;
+        let runs = statement
+            .query_map(
+                params![target, since.unwrap_or(0), until.unwrap_or(i64::MAX as u64)],
+                RunRecord::from_row,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read the recorded runs")?;
+        runs.into_iter()
+            .map(|run| self.with_artifacts(run))
+            .collect()
+    }
+
+    /// The run `id`, if there is one.
+    pub fn run(&self, id: i64) -> Result<Option<RunRecord>> {
+        let run = self
+            .conn
+            .query_row(
+                "SELECT * FROM runs WHERE id = ?1",
+                [id],
+                RunRecord::from_row,
+            )
+            .optional()
+            .context("failed to read the recorded run")?;
+        run.map(|run| self.with_artifacts(run)).transpose()
+    }
+
+    fn with_artifacts(&self, mut run: RunRecord) -> Result<RunRecord> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT path, category FROM artifacts WHERE run = ?1 ORDER BY path")?;
+        run.artifacts = statement
+            .query_map([run.id], |row| {
+                Ok(ArtifactRecord {
+                    path: row.get(0)?,
+                    category: row.get(1)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()
+            .context("failed to read the recorded artifacts")?;
+        Ok(run)
+    }
+}
+
+#[cfg(not(feature = "sessions"))]
+impl Sessions {
+    pub fn open(_path: &Path) -> Result<Self> {
+        Err(crate::utils::missing_feature(
+            "sessions",
+            "The session database",
+        ))
+    }
+
+    pub fn start_run(&self, _run: &RunStart) -> Result<i64> {
+        match *self {}
+    }
+
+    pub fn finish_run(&mut self, _id: i64, _end: &RunEnd) -> Result<()> {
+        match *self {}
+    }
+
+    pub fn runs(
+        &self,
+        _target: &str,
+        _since: Option<u64>,
+        _until: Option<u64>,
+    ) -> Result<Vec<RunRecord>> {
+        match *self {}
+    }
+
+    pub fn run(&self, _id: i64) -> Result<Option<RunRecord>> {
+        match *self {}
+    }
+}
+
+/// Parses a day like `2024-05-01` into the seconds since the Unix epoch at
+/// its start, in UTC.
+pub fn parse_date(date: &str) -> Option<u64> {
+    let mut parts = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    let days = days_from_civil(year, month, day);
+    // Days past the end of the month come out as another date.
+    if civil_from_days(days) != (year, month, day) || days < 0 {
+        return None;
+    }
+    Some(days as u64 * 86400)
+}
+
+/// Formats seconds since the Unix epoch as a UTC time like
+/// `2024-05-01 13:37`.
+pub fn format_time(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3600 % 24,
+        secs / 60 % 60
+    )
+}
+
+// The conversions between days since the Unix epoch and dates of the
+// proleptic Gregorian calendar, from
+// https://howardhinnant.github.io/date_algorithms.html.
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "sessions")]
+    fn records_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".cargo-fuzz").join("sessions.db");
+        let mut sessions = Sessions::open(&path).unwrap();
+        let args = ["-max_total_time=60".to_owned()];
+        let id = sessions
+            .start_run(&RunStart {
+                target: "parse",
+                git_commit: Some("0123abcd"),
+                started: 1_700_000_000,
+                options: " --sanitizer=none --jobs=2",
+                args: &args,
+            })
+            .unwrap();
+        let status =
+            Status::parse("#1024\tpulse  cov: 12 ft: 13 corp: 5/40b lim: 4 exec/s: 512 rss: 30Mb")
+                .unwrap();
+        sessions
+            .finish_run(
+                id,
+                &RunEnd {
+                    elapsed_secs: 60,
+                    status: &status,
+                    failed: true,
+                    artifacts: &[("fuzz/artifacts/parse/crash-0123".to_owned(), "crash")],
+                },
+            )
+            .unwrap();
+
+        // Reopening keeps what was recorded.
+        let sessions = Sessions::open(&path).unwrap();
+        let runs = sessions.runs("parse", None, None).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(sessions.run(id).unwrap().as_ref(), Some(&runs[0]));
+        assert_eq!(sessions.run(id + 1).unwrap(), None);
+        assert_eq!(runs[0].git_commit.as_deref(), Some("0123abcd"));
+        assert_eq!(runs[0].options, "--sanitizer=none --jobs=2");
+        assert_eq!(runs[0].args, args);
+        assert_eq!((runs[0].execs, runs[0].cov), (Some(1024), Some(12)));
+        assert_eq!(runs[0].result(), "failed");
+        assert_eq!(
+            runs[0].artifacts,
+            [ArtifactRecord {
+                path: "fuzz/artifacts/parse/crash-0123".to_owned(),
+                category: "crash".to_owned(),
+            }]
+        );
+
+        assert!(sessions
+            .runs("parse", Some(1_700_000_001), None)
+            .unwrap()
+            .is_empty());
+        assert!(sessions
+            .runs("parse", None, Some(1_700_000_000))
+            .unwrap()
+            .is_empty());
+        assert!(sessions.runs("other", None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "sessions")]
+    fn killed_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions = Sessions::open(&dir.path().join("sessions.db")).unwrap();
+        sessions
+            .start_run(&RunStart {
+                target: "parse",
+                git_commit: None,
+                started: 1_700_000_000,
+                options: "",
+                args: &[],
+            })
+            .unwrap();
+        let runs = sessions.runs("parse", None, None).unwrap();
+        assert_eq!(runs[0].result(), "killed");
+        assert_eq!(runs[0].elapsed_secs, None);
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2024-03-01"), Some(1_709_251_200));
+        assert_eq!(parse_date("2024-02-29"), Some(1_709_164_800));
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("yesterday"), None);
+        assert_eq!(
+            format_time(1_709_251_200 + 13 * 3600 + 37 * 60),
+            "2024-03-01 13:37"
+        );
+        assert_eq!(format_time(0), "1970-01-01 00:00");
+    }
+}
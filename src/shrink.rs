@@ -0,0 +1,60 @@
+//! Shrinking the inputs of typed fuzz targets without breaking their
+//! `Arbitrary` decoding
+//!
+//! `cargo fuzz` can't decode an input into the type of a fuzz target itself,
+//! so it shrinks the raw bytes in the ways that map onto smaller values, and
+//! lets the fuzz target decide which of them to keep. Dropping a chunk of
+//! bytes drops the fields and elements decoded from it, and zeroing a byte
+//! ends a collection early, since `Arbitrary` reads a "keep going" byte before
+//! each element.
+
+use std::iter;
+
+/// Returns the candidates for shrinking `input`, from the most to the least
+/// aggressive: the removal of chunks of halving sizes, then the
+/// simplification of single bytes.
+pub fn candidates(input: &[u8]) -> impl Iterator<Item = Vec<u8>> + '_ {
+    let removals = iter::successors(Some(input.len().div_ceil(2)), |chunk| Some(chunk / 2))
+        .take_while(|&chunk| chunk > 0)
+        .flat_map(move |chunk| {
+            (0..input.len()).step_by(chunk).map(move |start| {
+                let end = (start + chunk).min(input.len());
+                [&input[..start], &input[end..]].concat()
+            })
+        });
+    let simplifications = input.iter().enumerate().flat_map(move |(i, &byte)| {
+        iter::once(0)
+            .chain((byte > 1).then_some(byte / 2))
+            .filter(move |_| byte > 0)
+            .map(move |simpler| {
+                let mut candidate = input.to_vec();
+                candidate[i] = simpler;
+                candidate
+            })
+    });
+    removals.chain(simplifications)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_candidates() {
+        let shrunk: Vec<_> = candidates(&[1, 4, 0]).collect();
+        assert_eq!(
+            shrunk,
+            [
+                vec![0],
+                vec![1, 4],
+                vec![4, 0],
+                vec![1, 0],
+                vec![1, 4],
+                vec![0, 4, 0],
+                vec![1, 0, 0],
+                vec![1, 2, 0],
+            ]
+        );
+        assert_eq!(candidates(&[]).count(), 0);
+    }
+}
@@ -0,0 +1,563 @@
+//! Parsing of the crash output produced by fuzz targets
+
+use crate::utils;
+use std::fmt::Write;
+
+/// How many frames of the user's code go into a crash signature. More frames
+/// split a single bug into several buckets when it is reached through
+/// different paths, fewer frames merge unrelated bugs in shared helpers.
+pub const SIGNATURE_FRAMES: usize = 3;
+
+/// Function name prefixes of frames that belong to the fuzzing, sanitizer or
+/// standard library runtimes rather than to the code under test.
+const RUNTIME_FRAME_PREFIXES: &[&str] = &[
+    "__sanitizer",
+    "__asan",
+    "__msan",
+    "__tsan",
+    "__lsan",
+    "__ubsan",
+    "__interceptor",
+    "__rust",
+    "__libc",
+    "__GI_",
+    "__pthread",
+    "__restore_rt",
+    "fuzzer::",
+    "libfuzzer_sys::",
+    "std::",
+    "core::",
+    "alloc::",
+    "<std::",
+    "<core::",
+    "<alloc::",
+    "rust_begin_unwind",
+    "rust_panic",
+    "rust_fuzzer_test_input",
+    "panic_abort",
+];
+
+/// Exact function names of runtime frames that are too generic to be matched
+/// as prefixes.
+const RUNTIME_FRAME_NAMES: &[&str] = &[
+    "<unknown>",
+    "LLVMFuzzerTestOneInput",
+    "pthread_kill",
+    "gsignal",
+    "raise",
+    "abort",
+    "malloc",
+    "calloc",
+    "realloc",
+    "free",
+    "_start",
+    "main",
+];
+
+/// A single frame of a backtrace.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Frame {
+    /// The function name, without the symbol hash suffix.
+    pub function: String,
+    /// The source location or module offset, when known.
+    pub location: Option<String>,
+}
+
+impl Frame {
+    /// Whether this frame belongs to a runtime rather than to the user's code.
+    pub fn is_runtime(&self) -> bool {
+        RUNTIME_FRAME_NAMES.contains(&&self.function[..])
+            || RUNTIME_FRAME_PREFIXES
+                .iter()
+                .any(|prefix| self.function.starts_with(prefix))
+            || self.function.contains("__libfuzzer_sys_run")
+    }
+}
+
+/// The interesting bits of a fuzz target's output after it crashed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CrashReport {
+    /// The error reported by libFuzzer or a sanitizer, e.g.
+    /// `AddressSanitizer: heap-buffer-overflow`.
+    pub kind: Option<String>,
+    /// Where the target panicked, if it did.
+    pub panic_location: Option<String>,
+    /// The first backtrace found in the output.
+    pub frames: Vec<Frame>,
+}
+
+impl CrashReport {
+    /// Extracts a crash report from the (stderr) output of a fuzz target.
+    ///
+    /// Both sanitizer-style (`#0 0x... in foo`) and Rust-style (`0: foo`)
+    /// backtraces are understood; sanitizer backtraces take precedence.
+    pub fn parse(output: &str) -> Self {
+        let kind = output.lines().find_map(parse_error_kind);
+        let panic_location = output.lines().find_map(parse_panic_location);
+        let mut frames = parse_sanitizer_frames(output);
+        if frames.iter().all(Frame::is_runtime) {
+            let rust_frames = parse_rust_frames(output);
+            if !rust_frames.is_empty() {
+                frames = rust_frames;
+            }
+        }
+        CrashReport {
+            kind,
+            panic_location,
+            frames,
+        }
+    }
+
+    /// The frames that belong to the code under test.
+    pub fn user_frames(&self) -> impl Iterator<Item = &Frame> {
+        self.frames.iter().filter(|frame| !frame.is_runtime())
+    }
+
+    /// Renders the backtrace with runs of runtime frames folded into a single
+    /// line, and the first frame of the user's code marked with `>`. Returns
+    /// `None` when there is no user frame, as folding would then hide
+    /// everything.
+    pub fn folded_backtrace(&self) -> Option<String> {
+        let first_user_frame = self.frames.iter().position(|frame| !frame.is_runtime())?;
+        let mut backtrace = String::new();
+        let mut folded = 0;
+        for (i, frame) in self.frames.iter().enumerate() {
+            if frame.is_runtime() {
+                folded += 1;
+                continue;
+            }
+            fold_frames(&mut backtrace, folded);
+            folded = 0;
+            let marker = if i == first_user_frame { '>' } else { ' ' };
+            writeln!(backtrace, "{} #{} {}", marker, i, frame.function).unwrap();
+            if let Some(location) = &frame.location {
+                writeln!(backtrace, "        at {}", location).unwrap();
+            }
+        }
+        fold_frames(&mut backtrace, folded);
+        Some(backtrace)
+    }
+
+    /// A stable textual description of the crash, which ignores addresses,
+    /// symbol hashes and the input-dependent parts of messages.
+    pub fn signature(&self) -> String {
+        let mut signature = String::new();
+        if let Some(kind) = &self.kind {
+            signature.push_str(kind);
+        }
+        if let Some(location) = &self.panic_location {
+            signature.push_str("\npanicked at ");
+            signature.push_str(location);
+        }
+        for frame in self.user_frames().take(SIGNATURE_FRAMES) {
+            signature.push('\n');
+            signature.push_str(&frame.function);
+        }
+        signature
+    }
+
+    /// A short hash of [`CrashReport::signature`], suitable for bucketing
+    /// crashes.
+    pub fn stack_hash(&self) -> String {
+        utils::content_hash(self.signature().as_bytes())[..16].to_owned()
+    }
+}
+
+fn fold_frames(backtrace: &mut String, folded: usize) {
+    match folded {
+        0 => {}
+        1 => writeln!(backtrace, "  ... 1 runtime frame").unwrap(),
+        n => writeln!(backtrace, "  ... {} runtime frames", n).unwrap(),
+    }
+}
+
+/// Returns the part of `output` that reports the last crash in it, from the
+/// panic message or sanitizer error up to the sanitizer's `SUMMARY:` line.
+pub fn crash_excerpt(output: &str) -> Option<&str> {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in output.split_inclusive('\n') {
+        if parse_panic_location(line).is_some() {
+            starts.push((offset, true));
+        } else if parse_error_kind(line).is_some() {
+            starts.push((offset, false));
+        }
+        offset += line.len();
+    }
+
+    let &(last, last_is_panic) = starts.last()?;
+    // A Rust panic is followed by libFuzzer's `deadly signal` error, which
+    // belongs to the same crash.
+    let start = match starts.len().checked_sub(2).map(|i| starts[i]) {
+        Some((panic, true)) if !last_is_panic => panic,
+        _ => last,
+    };
+    let excerpt = &output[start..];
+    let end = excerpt.find("SUMMARY: ").map_or(excerpt.len(), |summary| {
+        excerpt[summary..]
+            .find('\n')
+            .map_or(excerpt.len(), |newline| summary + newline + 1)
+    });
+    Some(&excerpt[..end])
+}
+
+/// The errors that a sanitizer reported and recovered from, as built with
+/// `-Zsanitizer-recover`, collected from the output of a fuzz target one line
+/// at a time and deduplicated by their stack hash.
+#[derive(Debug, Default)]
+pub struct RecoveredErrors {
+    /// The report being read, from its `ERROR:` line on.
+    current: Option<String>,
+    /// The stack hash and the first report of each distinct error, and how
+    /// many times it was reported, in the order they were first reported.
+    pub errors: Vec<(String, String, usize)>,
+}
+
+impl RecoveredErrors {
+    /// Reads another line of output, with its newline.
+    pub fn observe(&mut self, line: &str) {
+        if self.current.is_none() {
+            match parse_error_kind(line) {
+                Some(kind) if kind.contains("Sanitizer") => self.current = Some(String::new()),
+                _ => return,
+            }
+        }
+        let report = self.current.as_mut().unwrap();
+        report.push_str(line);
+        if !line.contains("SUMMARY: ") {
+            return;
+        }
+        let report = self.current.take().unwrap();
+        let hash = CrashReport::parse(&report).stack_hash();
+        match self.errors.iter_mut().find(|(other, ..)| *other == hash) {
+            Some((.., count)) => *count += 1,
+            None => self.errors.push((hash, report, 1)),
+        }
+    }
+
+    /// How many errors were reported, including the duplicates.
+    pub fn total(&self) -> usize {
+        self.errors.iter().map(|(.., count)| count).sum()
+    }
+}
+
+/// Parses `==123==ERROR: AddressSanitizer: heap-buffer-overflow on address ...`
+/// into `AddressSanitizer: heap-buffer-overflow`.
+fn parse_error_kind(line: &str) -> Option<String> {
+    let (_, error) = line.split_once("ERROR: ")?;
+    let words: Vec<_> = error
+        .split_whitespace()
+        .take_while(|word| {
+            !matches!(*word, "on" | "after" | "at")
+                && !word.starts_with(|c: char| c.is_ascii_digit() || c == '(')
+        })
+        .collect();
+    if words.is_empty() {
+        None
+    } else {
+        Some(words.join(" "))
+    }
+}
+
+/// Parses both `thread '<unnamed>' panicked at src/lib.rs:1:2:` and the older
+/// `thread '<unnamed>' panicked at 'message', src/lib.rs:1:2` formats.
+fn parse_panic_location(line: &str) -> Option<String> {
+    if !line.starts_with("thread '") {
+        return None;
+    }
+    let (_, rest) = line.split_once("panicked at ")?;
+    let location = if rest.starts_with('\'') {
+        rest.rsplit_once("', ")?.1
+    } else {
+        rest.trim_end().trim_end_matches(':')
+    };
+    Some(location.to_owned())
+}
+
+/// Demangles symbols that the symbolizer left mangled, and removes the
+/// `::h0123456789abcdef` hash that rustc appends to symbols.
+fn clean_symbol(function: &str) -> String {
+    if let Ok(demangled) = rustc_demangle::try_demangle(function) {
+        // The alternate format leaves out the hash.
+        return format!("{:#}", demangled);
+    }
+    match function.rsplit_once("::h") {
+        Some((name, hash)) if hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+            name.to_owned()
+        }
+        _ => function.to_owned(),
+    }
+}
+
+/// Parses the first sanitizer-style backtrace in `output`.
+fn parse_sanitizer_frames(output: &str) -> Vec<Frame> {
+    let mut frames = Vec::new();
+    for line in output.lines() {
+        let line = line.trim();
+        let Some(rest) = line.strip_prefix('#') else {
+            if frames.is_empty() {
+                continue;
+            }
+            break;
+        };
+        let Some((index, rest)) = rest.split_once(' ') else {
+            continue;
+        };
+        if index.parse::<usize>().is_err() {
+            continue;
+        }
+        // A new `#0` starts the next backtrace (e.g. "freed by thread T0").
+        if index == "0" && !frames.is_empty() {
+            break;
+        }
+        // Skip the address.
+        let rest = rest.split_once(' ').map_or("", |(_, rest)| rest);
+        let rest = match rest.split_once(" (BuildId: ") {
+            Some((rest, _)) => rest,
+            None => rest,
+        };
+        let Some(rest) = rest.strip_prefix("in ") else {
+            // Frames without symbols only carry a module offset.
+            frames.push(Frame {
+                function: "<unknown>".to_owned(),
+                location: Some(rest.to_owned()),
+            });
+            continue;
+        };
+        let (function, location) = if rest.ends_with(')') {
+            match rest.rsplit_once(" (") {
+                Some((function, location)) => (function, Some(location.trim_end_matches(')'))),
+                None => (rest, None),
+            }
+        } else {
+            match rest.rsplit_once(' ') {
+                Some((function, location)) if location.contains(['/', '\\', ':']) => {
+                    (function, Some(location))
+                }
+                _ => (rest, None),
+            }
+        };
+        frames.push(Frame {
+            function: clean_symbol(function),
+            location: location.map(str::to_owned),
+        });
+    }
+    frames
+}
+
+/// Parses the Rust `stack backtrace:` printed when `RUST_BACKTRACE` is set.
+fn parse_rust_frames(output: &str) -> Vec<Frame> {
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut lines = output
+        .lines()
+        .skip_while(|l| l.trim() != "stack backtrace:");
+    lines.next();
+    for line in lines {
+        let line = line.trim();
+        if let Some(location) = line.strip_prefix("at ") {
+            if let Some(frame) = frames.last_mut() {
+                frame.location = Some(location.to_owned());
+            }
+            continue;
+        }
+        let Some((index, function)) = line.split_once(": ") else {
+            break;
+        };
+        if index.parse::<usize>().is_err() {
+            break;
+        }
+        frames.push(Frame {
+            function: clean_symbol(function),
+            location: None,
+        });
+    }
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASAN_OUTPUT: &str = "\
+INFO: Running with entropic power schedule (0xFF, 100).
+==1234==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602000000011 at pc 0x55d5 bp 0x7ffc sp 0x7ffc
+READ of size 1 at 0x602000000011 thread T0
+    #0 0x55d5c1 in my_crate::parse::h0123456789abcdef /home/me/my_crate/src/lib.rs:12:5
+    #1 0x55d5c2 in my_crate::entry::hfedcba9876543210 /home/me/my_crate/src/lib.rs:20:9
+    #2 0x55d5c3 in rust_fuzzer_test_input /cargo/libfuzzer-sys/src/lib.rs:224:17
+    #3 0x55d5c4 in fuzzer::Fuzzer::ExecuteCallback(unsigned char const*, unsigned long) /cargo/FuzzerLoop.cpp:612:15
+    #4 0x7f00 in __libc_start_main (/lib/x86_64-linux-gnu/libc.so.6+0x29d8f) (BuildId: abc)
+
+0x602000000011 is located 0 bytes after 1-byte region
+allocated by thread T0 here:
+    #0 0x55d5d0 in malloc
+    #1 0x55d5d1 in my_crate::alloc_it /home/me/my_crate/src/lib.rs:5:1
+";
+
+    const PANIC_OUTPUT: &str = "\
+thread '<unnamed>' panicked at src/lib.rs:9:13:
+I'm afraid of number 7
+stack backtrace:
+   0: rust_begin_unwind
+             at /rustc/abc/library/std/src/panicking.rs:645:5
+   1: core::panicking::panic_fmt
+   2: run_with_crash::fail_fuzzing
+             at ./src/lib.rs:9:13
+   3: yes_crash::_::__libfuzzer_sys_run
+note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.
+==99== ERROR: libFuzzer: deadly signal
+";
+
+    #[test]
+    fn parses_asan_report() {
+        let report = CrashReport::parse(ASAN_OUTPUT);
+        assert_eq!(
+            report.kind.as_deref(),
+            Some("AddressSanitizer: heap-buffer-overflow")
+        );
+        assert_eq!(report.frames.len(), 5);
+        assert_eq!(
+            report.frames[0],
+            Frame {
+                function: "my_crate::parse".to_owned(),
+                location: Some("/home/me/my_crate/src/lib.rs:12:5".to_owned()),
+            }
+        );
+        assert_eq!(
+            report.frames[3].function,
+            "fuzzer::Fuzzer::ExecuteCallback(unsigned char const*, unsigned long)"
+        );
+        assert_eq!(
+            report.frames[4].location.as_deref(),
+            Some("/lib/x86_64-linux-gnu/libc.so.6+0x29d8f")
+        );
+        let user: Vec<_> = report.user_frames().map(|f| &f.function[..]).collect();
+        assert_eq!(user, ["my_crate::parse", "my_crate::entry"]);
+    }
+
+    #[test]
+    fn parses_rust_panic() {
+        let report = CrashReport::parse(PANIC_OUTPUT);
+        assert_eq!(report.kind.as_deref(), Some("libFuzzer: deadly signal"));
+        assert_eq!(report.panic_location.as_deref(), Some("src/lib.rs:9:13"));
+        let user: Vec<_> = report.user_frames().collect();
+        assert_eq!(
+            user,
+            [&Frame {
+                function: "run_with_crash::fail_fuzzing".to_owned(),
+                location: Some("./src/lib.rs:9:13".to_owned()),
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_old_panic_format() {
+        assert_eq!(
+            parse_panic_location("thread 'main' panicked at 'oh no', src/main.rs:2:5").as_deref(),
+            Some("src/main.rs:2:5")
+        );
+    }
+
+    #[test]
+    fn error_kinds() {
+        assert_eq!(
+            parse_error_kind("==1== ERROR: libFuzzer: timeout after 1 seconds").as_deref(),
+            Some("libFuzzer: timeout")
+        );
+        assert_eq!(
+            parse_error_kind("==1== ERROR: libFuzzer: out-of-memory (malloc(4096))").as_deref(),
+            Some("libFuzzer: out-of-memory")
+        );
+        assert_eq!(parse_error_kind("nothing to see here"), None);
+    }
+
+    #[test]
+    fn stack_hash_ignores_addresses_and_symbol_hashes() {
+        let other = ASAN_OUTPUT
+            .replace("0x55d5c1", "0x11111")
+            .replace("h0123456789abcdef", "h1111111111111111");
+        assert_eq!(
+            CrashReport::parse(ASAN_OUTPUT).stack_hash(),
+            CrashReport::parse(&other).stack_hash()
+        );
+        assert_ne!(
+            CrashReport::parse(ASAN_OUTPUT).stack_hash(),
+            CrashReport::parse(PANIC_OUTPUT).stack_hash()
+        );
+    }
+
+    #[test]
+    fn demangles_symbols() {
+        assert_eq!(
+            clean_symbol("_ZN8my_crate5parse17h0123456789abcdefE"),
+            "my_crate::parse"
+        );
+        assert_eq!(
+            clean_symbol("_RNvCs1234_8my_crate5parse"),
+            "my_crate::parse"
+        );
+        assert_eq!(
+            clean_symbol("my_crate::parse::h0123456789abcdef"),
+            "my_crate::parse"
+        );
+        assert_eq!(
+            clean_symbol("LLVMFuzzerTestOneInput"),
+            "LLVMFuzzerTestOneInput"
+        );
+    }
+
+    #[test]
+    fn folds_runtime_frames() {
+        assert_eq!(
+            CrashReport::parse(PANIC_OUTPUT).folded_backtrace().unwrap(),
+            "  ... 2 runtime frames\n\
+             > #2 run_with_crash::fail_fuzzing\n        \
+             at ./src/lib.rs:9:13\n  \
+             ... 1 runtime frame\n"
+        );
+        let asan = CrashReport::parse(ASAN_OUTPUT).folded_backtrace().unwrap();
+        assert!(asan.starts_with("> #0 my_crate::parse\n"));
+        assert!(asan.contains("\n  #1 my_crate::entry\n"));
+        assert_eq!(CrashReport::default().folded_backtrace(), None);
+    }
+
+    #[test]
+    fn no_crash() {
+        let report = CrashReport::parse("Done 1000 runs in 1 second(s)\n");
+        assert_eq!(report, CrashReport::default());
+        assert_eq!(crash_excerpt("Done 1000 runs in 1 second(s)\n"), None);
+    }
+
+    #[test]
+    fn crash_excerpts() {
+        let output = format!(
+            "#1024 pulse cov: 12\n{}SUMMARY: libFuzzer: deadly signal\nMS: 0 ;\n",
+            PANIC_OUTPUT
+        );
+        let excerpt = crash_excerpt(&output).unwrap();
+        assert!(excerpt.starts_with("thread '<unnamed>' panicked at src/lib.rs:9:13:\n"));
+        assert!(excerpt.ends_with("SUMMARY: libFuzzer: deadly signal\n"));
+
+        let excerpt = crash_excerpt(ASAN_OUTPUT).unwrap();
+        assert!(excerpt.starts_with("==1234==ERROR: AddressSanitizer"));
+        assert!(excerpt.ends_with("lib.rs:5:1\n"));
+    }
+
+    #[test]
+    fn collects_recovered_errors() {
+        let mut recovered = RecoveredErrors::default();
+        let other = ASAN_OUTPUT.replace("my_crate::parse", "my_crate::other");
+        let summary = "SUMMARY: AddressSanitizer: heap-buffer-overflow\n";
+        for output in [ASAN_OUTPUT, PANIC_OUTPUT, &other, ASAN_OUTPUT] {
+            for line in output.split_inclusive('\n').chain([summary]) {
+                recovered.observe(line);
+            }
+        }
+        assert_eq!(recovered.total(), 3);
+        let counts: Vec<_> = recovered.errors.iter().map(|(.., count)| *count).collect();
+        assert_eq!(counts, [2, 1]);
+        let (_, report, _) = &recovered.errors[0];
+        assert!(report.starts_with("==1234==ERROR: AddressSanitizer"));
+        assert!(report.ends_with(summary));
+    }
+}
@@ -0,0 +1,389 @@
+//! Statistics of fuzzing runs, parsed from libFuzzer's status lines
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// How often the stats of a running fuzz target are written to disk. They are
+/// written while fuzzing, rather than only at the end, so that runs that are
+/// interrupted with Ctrl-C are still recorded.
+const WRITE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A status line printed by libFuzzer, such as
+/// `#1024 pulse  cov: 12 ft: 13 corp: 5/40b lim: 4 exec/s: 512 rss: 30Mb`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Status {
+    pub execs: u64,
+    pub cov: Option<u64>,
+    pub features: Option<u64>,
+    pub corpus_inputs: Option<u64>,
+    pub corpus_bytes: Option<u64>,
+    pub execs_per_sec: Option<u64>,
+    pub rss_bytes: Option<u64>,
+}
+
+impl Status {
+    /// Parses a status line, including the ones printed in `-fork` mode.
+    pub fn parse(line: &str) -> Option<Self> {
+        // Sanitizer stack frames like `    #0 0x55d5 in ...` are indented.
+        if !line.starts_with('#') {
+            return None;
+        }
+        let mut tokens = line.split_whitespace();
+        let execs = tokens
+            .next()?
+            .strip_prefix('#')?
+            .trim_end_matches(':')
+            .parse()
+            .ok()?;
+
+        let mut status = Status {
+            execs,
+            ..Status::default()
+        };
+        let mut key = None;
+        for token in tokens {
+            match key.take() {
+                Some("cov") => status.cov = token.parse().ok(),
+                Some("ft") => status.features = token.parse().ok(),
+                Some("corp") => {
+                    let (inputs, bytes) = match token.split_once('/') {
+                        Some((inputs, bytes)) => (inputs, parse_size(bytes)),
+                        None => (token, None),
+                    };
+                    status.corpus_inputs = inputs.parse().ok();
+                    status.corpus_bytes = bytes;
+                }
+                Some("exec/s") => status.execs_per_sec = token.parse().ok(),
+                Some("rss") => status.rss_bytes = parse_size(token),
+                _ => key = Some(token.trim_end_matches(':')),
+            }
+        }
+        Some(status)
+    }
+
+    /// Takes the numbers of a newer status line, keeping the ones that it
+    /// doesn't report.
+    pub fn update(&mut self, newer: Status) {
+        self.execs = newer.execs;
+        self.cov = newer.cov.or(self.cov);
+        self.features = newer.features.or(self.features);
+        self.corpus_inputs = newer.corpus_inputs.or(self.corpus_inputs);
+        self.corpus_bytes = newer.corpus_bytes.or(self.corpus_bytes);
+        self.execs_per_sec = newer.execs_per_sec.or(self.execs_per_sec);
+        self.rss_bytes = newer.rss_bytes.or(self.rss_bytes);
+    }
+}
+
+/// The status of a fuzzing session from what libFuzzer printed, with the
+/// numbers of its last status lines.
+pub fn last_status(output: &str) -> Option<Status> {
+    output
+        .lines()
+        .filter_map(Status::parse)
+        .reduce(|mut status, newer| {
+            status.update(newer);
+            status
+        })
+}
+
+/// Combines the statuses of workers fuzzing the same corpus: their executions
+/// and memory add up, and the coverage and corpus are those of the furthest
+/// along.
+pub fn combine(statuses: &[Status]) -> Status {
+    let max = |field: fn(&Status) -> Option<u64>| statuses.iter().filter_map(field).max();
+    Status {
+        execs: statuses.iter().map(|status| status.execs).sum(),
+        cov: max(|status| status.cov),
+        features: max(|status| status.features),
+        corpus_inputs: max(|status| status.corpus_inputs),
+        corpus_bytes: max(|status| status.corpus_bytes),
+        execs_per_sec: statuses
+            .iter()
+            .filter_map(|status| status.execs_per_sec)
+            .reduce(|a, b| a + b),
+        rss_bytes: statuses
+            .iter()
+            .filter_map(|status| status.rss_bytes)
+            .reduce(|a, b| a + b),
+    }
+}
+
+/// Summarizes the progress of a fuzzing session on a single line, like
+/// `progress: 2m 5s, 250000 execs (2000/s), cov 97, ft 120, corpus 41, 0 artifacts`.
+pub fn progress_line(elapsed: Duration, status: &Status, artifacts: usize) -> String {
+    let secs = elapsed.as_secs();
+    let execs_per_sec = status.execs_per_sec.unwrap_or(status.execs / secs.max(1));
+    let mut line = format!(
+        "progress: {}, {} execs ({}/s)",
+        crate::report::format_duration(secs),
+        status.execs,
+        execs_per_sec
+    );
+    if let Some(cov) = status.cov {
+        line.push_str(&format!(", cov {}", cov));
+    }
+    if let Some(features) = status.features {
+        line.push_str(&format!(", ft {}", features));
+    }
+    if let Some(corpus_inputs) = status.corpus_inputs {
+        line.push_str(&format!(", corpus {}", corpus_inputs));
+    }
+    line.push_str(&format!(
+        ", {} artifact{}",
+        artifacts,
+        if artifacts == 1 { "" } else { "s" }
+    ));
+    line
+}
+
+/// Parses sizes like `40b`, `12Kb` and `3Mb`.
+fn parse_size(size: &str) -> Option<u64> {
+    let (number, multiplier) = if let Some(number) = size.strip_suffix("Mb") {
+        (number, 1 << 20)
+    } else if let Some(number) = size.strip_suffix("Kb") {
+        (number, 1 << 10)
+    } else {
+        (size.strip_suffix('b')?, 1)
+    };
+    Some(number.parse::<u64>().ok()? * multiplier)
+}
+
+/// The stats of one `cargo fuzz run`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RunStats {
+    /// When the run started, in seconds since the Unix epoch
+    pub started: u64,
+    pub elapsed_secs: u64,
+    pub execs: u64,
+    pub cov: Option<u64>,
+    pub features: Option<u64>,
+    pub corpus_inputs: Option<u64>,
+    pub corpus_bytes: Option<u64>,
+    /// Whether the run ended with the fuzz target failing
+    #[serde(default)]
+    pub failed: bool,
+}
+
+/// Keeps the stats file of a run up to date as libFuzzer reports progress.
+pub struct Recorder {
+    path: PathBuf,
+    start: Instant,
+    last_write: Option<Instant>,
+    stats: RunStats,
+    /// The first error writing the stats file, which is reported once the run
+    /// is over rather than interrupting it.
+    error: Option<anyhow::Error>,
+}
+
+impl Recorder {
+    pub fn new(stats_dir: &Path) -> Result<Self> {
+        fs::create_dir_all(stats_dir)
+            .with_context(|| format!("failed to create directory {}", stats_dir.display()))?;
+        let started = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        Ok(Recorder {
+            path: stats_dir.join(format!("{}.json", started)),
+            start: Instant::now(),
+            last_write: None,
+            stats: RunStats {
+                started,
+                ..RunStats::default()
+            },
+            error: None,
+        })
+    }
+
+    /// Updates the stats from a line of libFuzzer output, writing them out
+    /// at most every [`WRITE_INTERVAL`].
+    pub fn observe(&mut self, line: &str) {
+        let status = match Status::parse(line) {
+            Some(status) => status,
+            None => return,
+        };
+        self.stats.execs = status.execs;
+        self.stats.cov = status.cov.or(self.stats.cov);
+        self.stats.features = status.features.or(self.stats.features);
+        self.stats.corpus_inputs = status.corpus_inputs.or(self.stats.corpus_inputs);
+        self.stats.corpus_bytes = status.corpus_bytes.or(self.stats.corpus_bytes);
+
+        match self.last_write {
+            Some(last_write) if last_write.elapsed() < WRITE_INTERVAL => {}
+            _ => self.write(),
+        }
+    }
+
+    pub fn finish(mut self, failed: bool) -> Result<()> {
+        self.stats.failed = failed;
+        self.write();
+        self.error.map_or(Ok(()), Err)
+    }
+
+    fn write(&mut self) {
+        self.stats.elapsed_secs = self.start.elapsed().as_secs();
+        self.last_write = Some(Instant::now());
+        let written = serde_json::to_vec_pretty(&self.stats)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| {
+                fs::write(&self.path, json)
+                    .with_context(|| format!("failed to write {}", self.path.display()))
+            });
+        if let Err(e) = written {
+            self.error.get_or_insert(e);
+        }
+    }
+}
+
+/// Reads the stats of every recorded run in `stats_dir`, oldest first.
+pub fn read_runs(stats_dir: &Path) -> Result<Vec<RunStats>> {
+    if !stats_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut runs = Vec::new();
+    for entry in fs::read_dir(stats_dir)
+        .with_context(|| format!("failed to read directory {}", stats_dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension() != Some("json".as_ref()) {
+            continue;
+        }
+        let json = fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        let run: RunStats = serde_json::from_slice(&json)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        runs.push(run);
+    }
+    runs.sort_by_key(|run| run.started);
+    Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_status_lines() {
+        assert_eq!(
+            Status::parse("#1024\tpulse  cov: 12 ft: 13 corp: 5/40b lim: 4 exec/s: 512 rss: 30Mb"),
+            Some(Status {
+                execs: 1024,
+                cov: Some(12),
+                features: Some(13),
+                corpus_inputs: Some(5),
+                corpus_bytes: Some(40),
+                execs_per_sec: Some(512),
+                rss_bytes: Some(30 << 20),
+            })
+        );
+        assert_eq!(
+            Status::parse("#2\tINITED cov: 3 ft: 3 corp: 1/1b exec/s: 0 rss: 29Mb"),
+            Some(Status {
+                execs: 2,
+                cov: Some(3),
+                features: Some(3),
+                corpus_inputs: Some(1),
+                corpus_bytes: Some(1),
+                execs_per_sec: Some(0),
+                rss_bytes: Some(29 << 20),
+            })
+        );
+    }
+
+    #[test]
+    fn parses_fork_mode_status_lines() {
+        assert_eq!(
+            Status::parse(
+                "#52617: cov: 97 ft: 120 corp: 41 exec/s 26308 oom/timeout/crash: 0/0/0 time: 3s"
+            ),
+            Some(Status {
+                execs: 52617,
+                cov: Some(97),
+                features: Some(120),
+                corpus_inputs: Some(41),
+                corpus_bytes: None,
+                execs_per_sec: Some(26308),
+                rss_bytes: None,
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_other_lines() {
+        assert_eq!(Status::parse("INFO: Seed: 1234"), None);
+        assert_eq!(Status::parse("#include <stdio.h>"), None);
+        assert_eq!(Status::parse("    #0 0x55d5c1 in my_crate::parse"), None);
+    }
+
+    #[test]
+    fn last_status_of_output() {
+        let output = "INFO: Seed: 1234\n\
+                      #2\tINITED cov: 3 ft: 3 corp: 1/1b exec/s: 0 rss: 29Mb\n\
+                      #52617: cov: 97 ft: 120 corp: 41 exec/s 26308 time: 3s\n\
+                      Done 52617 runs in 3 second(s)\n";
+        assert_eq!(
+            last_status(output),
+            Some(Status {
+                execs: 52617,
+                cov: Some(97),
+                features: Some(120),
+                corpus_inputs: Some(41),
+                corpus_bytes: Some(1),
+                execs_per_sec: Some(26308),
+                rss_bytes: Some(29 << 20),
+            })
+        );
+        assert_eq!(last_status("INFO: Seed: 1234\n"), None);
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("40b"), Some(40));
+        assert_eq!(parse_size("2Kb"), Some(2048));
+        assert_eq!(parse_size("1Mb"), Some(1 << 20));
+        assert_eq!(parse_size("lots"), None);
+    }
+
+    #[test]
+    fn progress_lines() {
+        let mut status =
+            Status::parse("#2\tINITED cov: 3 ft: 3 corp: 1/1b exec/s: 0 rss: 29Mb").unwrap();
+        status.update(
+            Status::parse("#52617: cov: 97 ft: 120 corp: 41 exec/s 26308 oom/timeout/crash: 0/0/0")
+                .unwrap(),
+        );
+        assert_eq!(status.corpus_bytes, Some(1));
+        assert_eq!(
+            progress_line(Duration::from_secs(125), &status, 1),
+            "progress: 2m 5s, 52617 execs (26308/s), cov 97, ft 120, corpus 41, 1 artifact"
+        );
+        assert_eq!(
+            progress_line(Duration::from_secs(10), &Status::default(), 0),
+            "progress: 10s, 0 execs (0/s), 0 artifacts"
+        );
+    }
+
+    #[test]
+    fn combines_statuses() {
+        let statuses = [
+            Status::parse("#1024\tpulse  cov: 12 ft: 13 corp: 5/40b exec/s: 512").unwrap(),
+            Status::parse("#2048\tpulse  cov: 10 ft: 15 corp: 4/30b exec/s: 256").unwrap(),
+            Status::default(),
+        ];
+        assert_eq!(
+            combine(&statuses),
+            Status {
+                execs: 3072,
+                cov: Some(12),
+                features: Some(15),
+                corpus_inputs: Some(5),
+                corpus_bytes: Some(40),
+                execs_per_sec: Some(768),
+                rss_bytes: None,
+            }
+        );
+        assert_eq!(combine(&[]), Status::default());
+    }
+}
@@ -0,0 +1,68 @@
+//! Keeping the `[[bin]]` sections of a fuzz manifest in line with the fuzz
+//! target files in `fuzz_targets/`
+
+use crate::upgrade::{key, sections};
+
+/// Returns `manifest` with a `[[bin]]` section, as `cargo fuzz add` writes
+/// them, for each of the fuzz targets `names`.
+pub fn add_bins(manifest: &str, names: &[String]) -> String {
+    let mut manifest = manifest.to_owned();
+    for name in names {
+        manifest.push_str(&toml_bin_template!(name).to_string());
+    }
+    manifest
+}
+
+/// Returns `manifest` without the `[[bin]]` sections of the fuzz targets
+/// `names`, and the blank line before each of them.
+pub fn remove_bins(manifest: &str, names: &[String]) -> String {
+    let mut lines: Vec<String> = manifest.lines().map(String::from).collect();
+    // Removals move the sections after them, so go backwards.
+    for section in sections(&lines)
+        .iter()
+        .rev()
+        .filter(|s| s.header == "[[bin]]")
+    {
+        let name = lines[section.start..section.end]
+            .iter()
+            .find(|line| key(line) == Some("name"))
+            .and_then(|line| line.split_once('='))
+            .map(|(_, value)| value.trim().trim_matches('"'));
+        if !name.is_some_and(|name| names.iter().any(|n| n == name)) {
+            continue;
+        }
+        let mut start = section.start;
+        if start > 0 && lines[start - 1].trim().is_empty() {
+            start -= 1;
+        }
+        lines.drain(start..section.content_end(&lines));
+    }
+    let mut synced = lines.join("\n");
+    if manifest.ends_with('\n') {
+        synced.push('\n');
+    }
+    synced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_and_removes_bins() {
+        let manifest = "[package]\nname = \"a-fuzz\"\n";
+        let added = add_bins(manifest, &["one".to_owned(), "two".to_owned()]);
+        assert_eq!(
+            added,
+            "[package]\nname = \"a-fuzz\"\n\n\
+             [[bin]]\nname = \"one\"\npath = \"fuzz_targets/one.rs\"\n\
+             test = false\ndoc = false\nbench = false\n\n\
+             [[bin]]\nname = \"two\"\npath = \"fuzz_targets/two.rs\"\n\
+             test = false\ndoc = false\nbench = false\n"
+        );
+
+        let removed = remove_bins(&added, &["one".to_owned()]);
+        assert_eq!(removed, add_bins(manifest, &["two".to_owned()]));
+        assert_eq!(remove_bins(&removed, &["two".to_owned()]), manifest);
+    }
+}
@@ -40,11 +40,12 @@ macro_rules! toml_bin_template {
             r#"
 [[bin]]
 name = "{0}"
-path = "fuzz_targets/{0}.rs"
+path = "fuzz_targets/{1}.rs"
 test = false
 doc = false
 bench = false
 "#,
+            $crate::utils::bin_name($name),
             $name
         )
     };
@@ -57,6 +58,9 @@ macro_rules! gitignore_template {
 corpus
 artifacts
 coverage
+stats
+profile
+.cargo-fuzz
 "##
         )
     };
@@ -80,3 +84,214 @@ fuzz_target!(|data: &[u8]| {{
         )
     };
 }
+
+macro_rules! clusterfuzzlite_dockerfile_template {
+    ($name:expr) => {
+        format_args!(
+            r##"FROM gcr.io/oss-fuzz-base/base-builder-rust:v1
+COPY . $SRC/{name}
+WORKDIR $SRC/{name}
+COPY .clusterfuzzlite/build.sh $SRC/
+"##,
+            name = $name,
+        )
+    };
+}
+
+macro_rules! oss_fuzz_build_template {
+    ($command:expr, $name:expr, $build_args:expr, $fuzz_dir:expr, $binary_dir:expr, $targets:expr, $dictionaries:expr) => {
+        format_args!(
+            r##"#!/bin/bash -eu
+# Generated by `cargo fuzz {command}`, run it again after adding or removing
+# fuzz targets.
+
+cd "$SRC/{name}"
+cargo fuzz build{build_args}
+
+for target in {targets}; do
+    cp "{binary_dir}/$target" "$OUT/"
+    if [ -d "{fuzz_dir}/corpus/$target" ]; then
+        zip -jqr "$OUT/${{target}}_seed_corpus.zip" "{fuzz_dir}/corpus/$target"
+    fi
+done
+{dictionaries}"##,
+            command = $command,
+            name = $name,
+            build_args = $build_args,
+            fuzz_dir = $fuzz_dir,
+            binary_dir = $binary_dir,
+            targets = $targets,
+            dictionaries = $dictionaries,
+        )
+    };
+}
+
+macro_rules! clusterfuzzlite_project_template {
+    () => {
+        format_args!("language: rust\n")
+    };
+}
+
+macro_rules! oss_fuzz_dockerfile_template {
+    ($name:expr, $repository:expr) => {
+        format_args!(
+            r##"FROM gcr.io/oss-fuzz-base/base-builder-rust
+RUN git clone --depth 1 {repository} {name}
+WORKDIR $SRC/{name}
+COPY build.sh $SRC/
+"##,
+            name = $name,
+            repository = $repository,
+        )
+    };
+}
+
+macro_rules! bundle_dockerfile_template {
+    ($repository:expr, $commit:expr, $package_dir:expr, $artifact:expr, $command:expr) => {
+        format_args!(
+            r##"# Generated by `cargo fuzz bundle`. Build and run it in the unpacked bundle:
+#
+#     docker build -t crash . && docker run --rm crash
+FROM rustlang/rust:nightly
+RUN cargo install cargo-fuzz --locked
+RUN git clone {repository} /src && git -C /src checkout {commit}
+WORKDIR /src/{package_dir}
+COPY {artifact} /crash/{artifact}
+CMD {command}
+"##,
+            repository = $repository,
+            commit = $commit,
+            package_dir = $package_dir,
+            artifact = $artifact,
+            command = $command,
+        )
+    };
+}
+
+macro_rules! oss_fuzz_project_template {
+    ($homepage:expr, $repository:expr, $contact:expr) => {
+        format_args!(
+            r##"homepage: "{homepage}"
+language: rust
+primary_contact: "{contact}"
+main_repo: "{repository}"
+sanitizers:
+  - address
+fuzzing_engines:
+  - libfuzzer
+"##,
+            homepage = $homepage,
+            repository = $repository,
+            contact = $contact,
+        )
+    };
+}
+
+macro_rules! github_actions_workflow_template {
+    ($schedule:expr, $targets:expr, $working_directory:expr, $fuzz_path:expr, $fuzz_args:expr, $time_budget:expr) => {
+        format_args!(
+            r##"# Generated by `cargo fuzz init-github-actions`, run it again after adding or
+# removing fuzz targets.
+name: Fuzz
+
+on:
+  schedule:
+    - cron: "{schedule}"
+  workflow_dispatch:
+
+jobs:
+  fuzz:
+    runs-on: ubuntu-latest
+    strategy:
+      fail-fast: false
+      matrix:
+        target:
+{targets}{working_directory}
+    steps:
+      - uses: actions/checkout@v4
+      - uses: dtolnay/rust-toolchain@nightly
+      - run: cargo install cargo-fuzz --locked
+
+      - name: Restore the corpus
+        uses: actions/cache/restore@v4
+        with:
+          path: {fuzz_path}/corpus/${{{{ matrix.target }}}}
+          key: fuzz-corpus-${{{{ matrix.target }}}}-${{{{ github.run_id }}}}
+          restore-keys: fuzz-corpus-${{{{ matrix.target }}}}-
+
+      - name: Fuzz
+        run: cargo fuzz ci{fuzz_args} --time-budget={time_budget} ${{{{ matrix.target }}}}
+
+      - name: Save the corpus
+        if: always()
+        uses: actions/cache/save@v4
+        with:
+          path: {fuzz_path}/corpus/${{{{ matrix.target }}}}
+          key: fuzz-corpus-${{{{ matrix.target }}}}-${{{{ github.run_id }}}}
+
+      - name: Upload the crash artifacts
+        if: failure()
+        uses: actions/upload-artifact@v4
+        with:
+          name: fuzz-artifacts-${{{{ matrix.target }}}}
+          path: {fuzz_path}/artifacts/${{{{ matrix.target }}}}
+"##,
+            schedule = $schedule,
+            targets = $targets,
+            working_directory = $working_directory,
+            fuzz_path = $fuzz_path,
+            fuzz_args = $fuzz_args,
+            time_budget = $time_budget,
+        )
+    };
+}
+
+macro_rules! mutate_toml_template {
+    ($libfuzzer_sys:expr, $features:expr) => {
+        format_args!(
+            r##"# Generated by `cargo fuzz mutate`
+[package]
+name = "cargo-fuzz-mutate"
+version = "0.0.0"
+publish = false
+edition = "2021"
+
+[dependencies]
+libfuzzer-sys = {{ path = {libfuzzer_sys:?}, default-features = false, features = {features:?} }}
+
+[workspace]
+"##,
+            libfuzzer_sys = $libfuzzer_sys,
+            features = $features,
+        )
+    };
+}
+
+macro_rules! mutate_target_template {
+    () => {
+        format_args!(
+            r##"#![no_main]
+
+// Generated by `cargo fuzz mutate`: instead of fuzzing, writes the mutations
+// that libFuzzer makes of the input it runs on to `CARGO_FUZZ_MUTANTS_DIR`.
+
+use libfuzzer_sys::{{fuzz_target, fuzzer_mutate}};
+use std::{{env, fs, path::PathBuf}};
+
+fuzz_target!(|seed: &[u8]| {{
+    let var = |name| env::var(name).expect(name);
+    let dir = PathBuf::from(var("CARGO_FUZZ_MUTANTS_DIR"));
+    let count: usize = var("CARGO_FUZZ_MUTANTS").parse().unwrap();
+    let max_len: usize = var("CARGO_FUZZ_MUTANTS_MAX_LEN").parse().unwrap();
+    for i in 0..count {{
+        let mut mutant = seed.to_vec();
+        mutant.resize(max_len, 0);
+        let len = fuzzer_mutate(&mut mutant, seed.len(), max_len);
+        mutant.truncate(len);
+        fs::write(dir.join(i.to_string()), mutant).unwrap();
+    }}
+}});
+"##
+        )
+    };
+}
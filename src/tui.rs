@@ -0,0 +1,524 @@
+//! The terminal dashboard of `cargo fuzz run --tui`
+//!
+//! The output of the fuzz target goes to a scrollable log pane instead of the
+//! terminal, and the status lines of libFuzzer feed the counters and graphs
+//! above it. In `-fork` mode libFuzzer only reports when a job ends, so the
+//! jobs get a table of their own.
+//!
+//! Without the `dashboard` feature there is only the state of the dashboard,
+//! and nothing to draw it.
+#![cfg_attr(not(feature = "dashboard"), allow(dead_code))]
+
+#[cfg(feature = "dashboard")]
+use crate::report::format_duration;
+use crate::stats::Status;
+#[cfg(feature = "dashboard")]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(feature = "dashboard")]
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Sparkline, Table},
+    DefaultTerminal, Frame,
+};
+use std::{
+    collections::VecDeque,
+    process::{Child, ExitStatus},
+    sync::mpsc::Receiver,
+    time::Instant,
+};
+#[cfg(feature = "dashboard")]
+use std::{sync::mpsc::TryRecvError, time::Duration};
+
+/// How many lines of output the log pane keeps.
+const LOG_LINES: usize = 10_000;
+
+/// How many samples the graphs keep, one per second.
+const HISTORY: usize = 1000;
+
+/// How many of the latest `-fork` mode jobs are listed.
+const JOBS: usize = 6;
+
+/// How many lines the log pane scrolls with Page Up and Page Down.
+const PAGE: usize = 20;
+
+/// The exit code of libFuzzer when it is interrupted, by default.
+const INTERRUPTED_EXIT_CODE: i32 = 72;
+
+/// A line of output of the fuzz target.
+pub enum Output {
+    Stdout(String),
+    Stderr(String),
+}
+
+/// How a session on the dashboard ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ending {
+    /// The fuzz target exited by itself.
+    Exited,
+    /// The user stopped the fuzz target.
+    Stopped,
+    /// The user stopped the fuzz target to minimize the corpus.
+    Minimize,
+}
+
+/// A `-fork` mode job, as reported when it ends.
+#[derive(Debug, PartialEq, Eq)]
+struct Job {
+    number: String,
+    status: Status,
+    /// The `oom/timeout/crash` counts of the whole session so far.
+    failures: String,
+    time: String,
+}
+
+impl Job {
+    /// Parses a status line of `-fork` mode such as `#52617: cov: 97 ft: 120
+    /// corp: 41 exec/s 26308 oom/timeout/crash: 0/0/0 time: 3s job: 4
+    /// dft_time: 0`.
+    fn parse(line: &str) -> Option<Self> {
+        let status = Status::parse(line)?;
+        let value = |key: &str| {
+            let mut tokens = line.split_whitespace();
+            tokens.find(|&token| token == key)?;
+            tokens.next().map(str::to_owned)
+        };
+        Some(Job {
+            number: value("job:")?,
+            status,
+            failures: value("oom/timeout/crash:").unwrap_or_default(),
+            time: value("time:").unwrap_or_default(),
+        })
+    }
+}
+
+/// The state of the dashboard.
+pub struct Dashboard {
+    title: String,
+    start: Instant,
+    status: Status,
+    artifacts: usize,
+    execs_per_sec: VecDeque<u64>,
+    coverage: VecDeque<u64>,
+    /// The number of processes fuzzing in `-fork` mode.
+    workers: Option<String>,
+    jobs: VecDeque<Job>,
+    log: VecDeque<String>,
+    /// How many lines the log pane is scrolled up from the newest one.
+    scroll: usize,
+    paused: bool,
+    stopping: bool,
+}
+
+impl Dashboard {
+    pub fn new(title: String) -> Self {
+        Dashboard {
+            title,
+            start: Instant::now(),
+            status: Status::default(),
+            artifacts: 0,
+            execs_per_sec: VecDeque::new(),
+            coverage: VecDeque::new(),
+            workers: None,
+            jobs: VecDeque::new(),
+            log: VecDeque::new(),
+            scroll: 0,
+            paused: false,
+            stopping: false,
+        }
+    }
+
+    /// Takes in another line of output of the fuzz target.
+    pub fn observe(&mut self, line: &str) {
+        let line = line.trim_end();
+        if let Some(status) = Status::parse(line) {
+            self.status.update(status);
+        }
+        if let Some(job) = Job::parse(line) {
+            if self.jobs.len() == JOBS {
+                self.jobs.pop_back();
+            }
+            self.jobs.push_front(job);
+        }
+        if let Some(rest) = line.strip_prefix("INFO: -fork=") {
+            self.workers = rest.split(':').next().map(str::to_owned);
+        }
+
+        if self.log.len() == LOG_LINES {
+            self.log.pop_front();
+        }
+        self.log.push_back(line.to_owned());
+        // Keep showing the same lines while scrolled up.
+        if self.scroll > 0 {
+            self.scroll = (self.scroll + 1).min(self.log.len() - 1);
+        }
+    }
+
+    /// Takes the samples of the graphs, once a second.
+    fn sample(&mut self, artifacts: usize) {
+        self.artifacts = artifacts;
+        for (history, value) in [
+            (&mut self.execs_per_sec, self.status.execs_per_sec),
+            (&mut self.coverage, self.status.cov),
+        ] {
+            if history.len() == HISTORY {
+                history.pop_front();
+            }
+            history.push_back(value.unwrap_or(0));
+        }
+    }
+
+    /// Scrolls the log pane up by `lines`, or down for negative `lines`.
+    fn scroll_by(&mut self, lines: isize) {
+        let scroll = self.scroll.saturating_add_signed(lines);
+        self.scroll = scroll.min(self.log.len().saturating_sub(1));
+    }
+
+    #[cfg(feature = "dashboard")]
+    fn draw(&self, frame: &mut Frame) {
+        let jobs_height = if self.jobs.is_empty() {
+            0
+        } else {
+            self.jobs.len() as u16 + 3
+        };
+        let [counters, graphs, jobs, log, help] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(8),
+            Constraint::Length(jobs_height),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let state = if self.stopping {
+            "stopping"
+        } else if self.paused {
+            "paused"
+        } else {
+            "fuzzing"
+        };
+        let mut numbers = vec![
+            format_duration(self.start.elapsed().as_secs()),
+            format!("{} execs", self.status.execs),
+            format!("{}/s", self.status.execs_per_sec.unwrap_or(0)),
+            format!("cov {}", self.status.cov.unwrap_or(0)),
+            format!("ft {}", self.status.features.unwrap_or(0)),
+            format!("corpus {}", self.status.corpus_inputs.unwrap_or(0)),
+            format!("artifacts {}", self.artifacts),
+        ];
+        if let Some(workers) = &self.workers {
+            numbers.push(format!("workers {}", workers));
+        }
+        frame.render_widget(
+            Paragraph::new(numbers.join("  ")).block(
+                Block::bordered().title(Line::from(format!(" {} [{}] ", self.title, state)).bold()),
+            ),
+            counters,
+        );
+
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(graphs);
+        for (area, name, history) in [
+            (left, "exec/s", &self.execs_per_sec),
+            (right, "coverage", &self.coverage),
+        ] {
+            // The newest samples that fit, one per column.
+            let width = area.width.saturating_sub(2) as usize;
+            let shown = history.iter().skip(history.len().saturating_sub(width));
+            frame.render_widget(
+                Sparkline::default()
+                    .block(Block::bordered().title(format!(" {} ", name)))
+                    .data(shown),
+                area,
+            );
+        }
+
+        if !self.jobs.is_empty() {
+            let rows = self.jobs.iter().map(|job| {
+                Row::new([
+                    job.number.clone(),
+                    job.status.cov.unwrap_or(0).to_string(),
+                    job.status.features.unwrap_or(0).to_string(),
+                    job.status.corpus_inputs.unwrap_or(0).to_string(),
+                    job.status.execs_per_sec.unwrap_or(0).to_string(),
+                    job.failures.clone(),
+                    job.time.clone(),
+                ])
+            });
+            let table = Table::new(rows, [Constraint::Fill(1); 7])
+                .header(
+                    Row::new([
+                        "job",
+                        "cov",
+                        "ft",
+                        "corpus",
+                        "exec/s",
+                        "oom/timeout/crash",
+                        "time",
+                    ])
+                    .bold(),
+                )
+                .block(Block::bordered().title(" latest jobs "));
+            frame.render_widget(table, jobs);
+        }
+
+        let height = log.height.saturating_sub(2) as usize;
+        let end = self.log.len() - self.scroll;
+        let lines: Vec<Line> = self
+            .log
+            .range(end.saturating_sub(height)..end)
+            .map(|line| Line::raw(line.as_str()))
+            .collect();
+        let title = if self.scroll > 0 {
+            format!(" output (scrolled up {} lines) ", self.scroll)
+        } else {
+            " output ".to_owned()
+        };
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            log,
+        );
+
+        frame.render_widget(
+            Paragraph::new(
+                " q stop · p pause/resume · c stop and minimize the corpus · \
+                 ↑/↓/PgUp/PgDn scroll · End follow",
+            )
+            .style(Style::new().reversed()),
+            help,
+        );
+    }
+}
+
+/// Shows `dashboard` until the fuzz target `child` exits and its `output` is
+/// over. The lines of its stderr also go to `on_line`, and `artifacts` counts
+/// the artifacts it found so far.
+///
+/// On Unix the fuzz target must be in a process group of its own, so that
+/// pausing and stopping it also pause and stop the processes of `-fork`
+/// mode.
+#[cfg(feature = "dashboard")]
+pub fn run(
+    child: &mut Child,
+    output: &Receiver<Output>,
+    dashboard: &mut Dashboard,
+    on_line: impl FnMut(&str),
+    artifacts: impl Fn() -> usize,
+) -> Result<(ExitStatus, Ending)> {
+    let mut terminal = ratatui::try_init().context("failed to set up the terminal")?;
+    let result = show(&mut terminal, child, output, dashboard, on_line, artifacts);
+    ratatui::restore();
+    if result.is_err() {
+        // Don't leave the fuzz target running without a way to stop it.
+        let _ = child.kill();
+    }
+    result
+}
+
+#[cfg(not(feature = "dashboard"))]
+pub fn run(
+    child: &mut Child,
+    _output: &Receiver<Output>,
+    _dashboard: &mut Dashboard,
+    _on_line: impl FnMut(&str),
+    _artifacts: impl Fn() -> usize,
+) -> Result<(ExitStatus, Ending)> {
+    let _ = child.kill();
+    Err(crate::utils::missing_feature("dashboard", "`--tui`"))
+}
+
+#[cfg(feature = "dashboard")]
+fn show(
+    terminal: &mut DefaultTerminal,
+    child: &mut Child,
+    output: &Receiver<Output>,
+    dashboard: &mut Dashboard,
+    mut on_line: impl FnMut(&str),
+    artifacts: impl Fn() -> usize,
+) -> Result<(ExitStatus, Ending)> {
+    let mut ending = Ending::Exited;
+    dashboard.sample(artifacts());
+    let mut last_sample = Instant::now();
+    loop {
+        // Bound the lines taken at once, so that a chatty fuzz target
+        // doesn't keep the dashboard from being drawn.
+        for _ in 0..LOG_LINES {
+            match output.try_recv() {
+                Ok(Output::Stdout(line)) => dashboard.observe(&line),
+                Ok(Output::Stderr(line)) => {
+                    on_line(&line);
+                    dashboard.observe(&line);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    let status = child.wait().context("failed to wait on the fuzz target")?;
+                    return Ok((status, ending));
+                }
+            }
+        }
+
+        if last_sample.elapsed() >= Duration::from_secs(1) {
+            dashboard.sample(artifacts());
+            last_sample = Instant::now();
+        }
+        terminal
+            .draw(|frame| dashboard.draw(frame))
+            .context("failed to draw the dashboard")?;
+
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let ctrl_c =
+            key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c');
+        match key.code {
+            _ if ctrl_c => ending = stop(child, dashboard, Ending::Stopped),
+            KeyCode::Char('q') | KeyCode::Esc => ending = stop(child, dashboard, Ending::Stopped),
+            KeyCode::Char('c') => ending = stop(child, dashboard, Ending::Minimize),
+            KeyCode::Char('p') => pause(child, dashboard),
+            KeyCode::Up => dashboard.scroll_by(1),
+            KeyCode::Down => dashboard.scroll_by(-1),
+            KeyCode::PageUp => dashboard.scroll_by(PAGE as isize),
+            KeyCode::PageDown => dashboard.scroll_by(-(PAGE as isize)),
+            KeyCode::End => dashboard.scroll = 0,
+            _ => {}
+        }
+    }
+}
+
+/// Whether the fuzz target exited because it was interrupted, by libFuzzer
+/// handling the interrupt or by the interrupt itself.
+pub fn interrupted(status: &ExitStatus) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if status.signal() == Some(libc::SIGINT) {
+            return true;
+        }
+    }
+    status.code() == Some(INTERRUPTED_EXIT_CODE)
+}
+
+/// Asks the fuzz target to stop, the way Ctrl-C would, so that libFuzzer
+/// prints its final stats. Asking again kills it.
+fn stop(child: &mut Child, dashboard: &mut Dashboard, ending: Ending) -> Ending {
+    if dashboard.stopping {
+        let _ = child.kill();
+        return ending;
+    }
+    dashboard.stopping = true;
+    #[cfg(unix)]
+    {
+        if dashboard.paused {
+            signal(child, libc::SIGCONT);
+        }
+        signal(child, libc::SIGINT);
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = child.kill();
+    }
+    ending
+}
+
+fn pause(child: &Child, dashboard: &mut Dashboard) {
+    if dashboard.stopping {
+        return;
+    }
+    #[cfg(unix)]
+    {
+        signal(
+            child,
+            if dashboard.paused {
+                libc::SIGCONT
+            } else {
+                libc::SIGSTOP
+            },
+        );
+        dashboard.paused = !dashboard.paused;
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = child;
+        dashboard.observe("cargo-fuzz: pausing isn't supported on this platform");
+    }
+}
+
+/// Sends `signal` to the process group of the fuzz target.
+#[cfg(unix)]
+fn signal(child: &Child, signal: libc::c_int) {
+    // SAFETY: `kill` only takes plain integers.
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_jobs() {
+        let job = Job::parse(
+            "#52617: cov: 97 ft: 120 corp: 41 exec/s 26308 oom/timeout/crash: 0/0/1 time: 3s job: 4 dft_time: 0",
+        )
+        .unwrap();
+        assert_eq!(job.number, "4");
+        assert_eq!(job.status.cov, Some(97));
+        assert_eq!(job.failures, "0/0/1");
+        assert_eq!(job.time, "3s");
+        assert_eq!(
+            Job::parse("#1024\tpulse  cov: 12 ft: 13 corp: 5/40b lim: 4 exec/s: 512"),
+            None
+        );
+    }
+
+    #[test]
+    fn observes_output() {
+        let mut dashboard = Dashboard::new("fuzz".to_owned());
+        dashboard.observe("INFO: -fork=4: fuzzing in separate process(s)\n");
+        dashboard.observe("#2\tINITED cov: 3 ft: 3 corp: 1/1b exec/s: 0 rss: 29Mb\n");
+        for job in 1..=JOBS + 1 {
+            dashboard.observe(&format!(
+                "#{}: cov: 5 ft: 6 corp: 2 exec/s 9 job: {}",
+                job, job
+            ));
+        }
+        assert_eq!(dashboard.workers.as_deref(), Some("4"));
+        assert_eq!(dashboard.status.cov, Some(5));
+        assert_eq!(dashboard.status.execs, JOBS as u64 + 1);
+        assert_eq!(dashboard.jobs.len(), JOBS);
+        assert_eq!(dashboard.jobs[0].number, (JOBS + 1).to_string());
+        assert_eq!(dashboard.log.len(), JOBS + 3);
+        assert_eq!(
+            dashboard.log[0],
+            "INFO: -fork=4: fuzzing in separate process(s)"
+        );
+    }
+
+    #[test]
+    fn scrolls_the_log() {
+        let mut dashboard = Dashboard::new("fuzz".to_owned());
+        for i in 0..5 {
+            dashboard.observe(&i.to_string());
+        }
+        dashboard.scroll_by(2);
+        assert_eq!(dashboard.scroll, 2);
+        // New lines don't move what is shown while scrolled up.
+        dashboard.observe("5");
+        assert_eq!(dashboard.scroll, 3);
+        dashboard.scroll_by(PAGE as isize);
+        assert_eq!(dashboard.scroll, 5);
+        dashboard.scroll_by(-(PAGE as isize));
+        assert_eq!(dashboard.scroll, 0);
+    }
+}
@@ -0,0 +1,296 @@
+//! Migrating fuzz manifests written for older versions of cargo-fuzz
+//!
+//! Manifests are edited line by line rather than re-serialized, so that the
+//! formatting and comments of the rest of the manifest are kept.
+
+use anyhow::{Context, Result};
+
+/// The `libfuzzer-sys` version that `cargo fuzz init` depends on.
+const LIBFUZZER_SYS_VERSION: &str = "0.4";
+
+/// The attributes that `cargo fuzz add` gives fuzz targets, so that `cargo
+/// test` and friends skip them.
+const BIN_ATTRIBUTES: [&str; 3] = ["test", "doc", "bench"];
+
+/// Returns the upgraded `manifest` and a description of every change.
+/// With `fix_paths`, paths of fuzz targets in `fuzzers/` are moved to
+/// `fuzz_targets/`.
+pub fn upgrade_manifest(manifest: &str, fix_paths: bool) -> Result<(String, Vec<String>)> {
+    let value: toml::Value = manifest
+        .parse()
+        .context("could not decode the fuzz manifest")?;
+    let mut lines: Vec<String> = manifest.lines().map(String::from).collect();
+    let mut changes = Vec::new();
+
+    if fix_paths {
+        let mut fixed = 0;
+        for section in sections(&lines).iter().filter(|s| s.header == "[[bin]]") {
+            for line in &mut lines[section.start..section.end] {
+                if key(line) == Some("path") && line.contains("\"fuzzers/") {
+                    *line = line.replacen("\"fuzzers/", "\"fuzz_targets/", 1);
+                    fixed += 1;
+                }
+            }
+        }
+        if fixed > 0 {
+            changes.push(format!(
+                "move the paths of {} fuzz target(s) to `fuzz_targets/`",
+                fixed
+            ));
+        }
+    }
+
+    let mut completed = 0;
+    // Insertions move the sections after them, so go backwards.
+    for section in sections(&lines)
+        .iter()
+        .rev()
+        .filter(|s| s.header == "[[bin]]")
+    {
+        let missing: Vec<_> = BIN_ATTRIBUTES
+            .iter()
+            .filter(|attribute| {
+                !lines[section.start..section.end]
+                    .iter()
+                    .any(|line| key(line) == Some(**attribute))
+            })
+            .map(|attribute| format!("{} = false", attribute))
+            .collect();
+        if !missing.is_empty() {
+            let at = section.content_end(&lines);
+            lines.splice(at..at, missing);
+            completed += 1;
+        }
+    }
+    if completed > 0 {
+        changes.push(format!(
+            "add `test = false`, `doc = false` and `bench = false` to {} fuzz target(s)",
+            completed
+        ));
+    }
+
+    let has_marker = value
+        .get("package")
+        .and_then(|package| package.get("metadata"))
+        .and_then(|metadata| metadata.get("cargo-fuzz"))
+        .is_some();
+    if !has_marker {
+        let sections = sections(&lines);
+        let inserted =
+            if let Some(metadata) = sections.iter().find(|s| s.header == "[package.metadata]") {
+                lines.insert(metadata.start + 1, "cargo-fuzz = true".to_owned());
+                true
+            } else if let Some(package) = sections.iter().find(|s| s.header == "[package]") {
+                let at = package.content_end(&lines);
+                let table = ["", "[package.metadata]", "cargo-fuzz = true"];
+                lines.splice(at..at, table.iter().map(|line| line.to_string()));
+                true
+            } else {
+                false
+            };
+        if inserted {
+            changes.push("add the `cargo-fuzz = true` package metadata".to_owned());
+        }
+    }
+
+    if value
+        .get("dependencies")
+        .and_then(|dependencies| dependencies.get("libfuzzer-sys"))
+        .is_some_and(is_outdated_libfuzzer_sys)
+    {
+        let sections = sections(&lines);
+        let requirement = format!("\"{}\"", LIBFUZZER_SYS_VERSION);
+        if let Some(table) = sections
+            .iter()
+            .find(|s| s.header == "[dependencies.libfuzzer-sys]")
+        {
+            let at = table.content_end(&lines);
+            lines.splice(table.start + 1..at, [format!("version = {}", requirement)]);
+        } else if let Some(dependencies) = sections.iter().find(|s| s.header == "[dependencies]") {
+            if let Some(line) = lines[dependencies.start..dependencies.end]
+                .iter_mut()
+                .find(|line| key(line) == Some("libfuzzer-sys"))
+            {
+                *line = format!("libfuzzer-sys = {}", requirement);
+            }
+        }
+        changes.push(format!(
+            "update the `libfuzzer-sys` dependency to version {}",
+            LIBFUZZER_SYS_VERSION
+        ));
+    }
+
+    let mut upgraded = lines.join("\n");
+    if manifest.ends_with('\n') {
+        upgraded.push('\n');
+    }
+    upgraded
+        .parse::<toml::Value>()
+        .context("upgrading the fuzz manifest made it invalid")?;
+    Ok((upgraded, changes))
+}
+
+/// Whether a `libfuzzer-sys` dependency is on a version before the current
+/// one, or on the git repository that early fuzz projects used.
+fn is_outdated_libfuzzer_sys(dependency: &toml::Value) -> bool {
+    let version = match dependency {
+        toml::Value::String(version) => Some(version.as_str()),
+        toml::Value::Table(table) => {
+            if table
+                .get("git")
+                .and_then(toml::Value::as_str)
+                .is_some_and(|git| git.contains("rust-fuzz/libfuzzer-sys"))
+            {
+                return true;
+            }
+            table.get("version").and_then(toml::Value::as_str)
+        }
+        _ => None,
+    };
+    let minor = version
+        .map(|version| version.trim_start_matches(['^', '=', '~', ' ']))
+        .and_then(|version| version.strip_prefix("0."))
+        .and_then(|rest| rest.split('.').next())
+        .and_then(|minor| minor.parse::<u32>().ok());
+    let current: u32 = LIBFUZZER_SYS_VERSION[2..].parse().unwrap();
+    minor.is_some_and(|minor| minor < current)
+}
+
+/// A table of a manifest, from its header line up to the next header.
+pub struct Section {
+    pub header: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Section {
+    /// Returns where lines added to the table go: after its last line that
+    /// isn't blank or a comment.
+    pub fn content_end(&self, lines: &[String]) -> usize {
+        (self.start + 1..self.end)
+            .rev()
+            .find(|&i| {
+                let line = lines[i].trim();
+                !line.is_empty() && !line.starts_with('#')
+            })
+            .map_or(self.start + 1, |i| i + 1)
+    }
+}
+
+pub fn sections(lines: &[String]) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.trim();
+        if !line.starts_with('[') {
+            continue;
+        }
+        if let Some(last) = sections.last_mut() {
+            last.end = i;
+        }
+        let header = match line.find('#') {
+            Some(comment) => line[..comment].trim_end(),
+            None => line,
+        };
+        sections.push(Section {
+            header: header.replace(' ', ""),
+            start: i,
+            end: lines.len(),
+        });
+    }
+    sections
+}
+
+/// Returns the key of a `key = value` line.
+pub fn key(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    if line.starts_with('#') || line.starts_with('[') {
+        return None;
+    }
+    Some(line.split_once('=')?.0.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrades_legacy_manifests() {
+        let manifest = r#"[package]
+name = "old-fuzz"
+version = "0.0.1"
+
+[dependencies.libfuzzer-sys]
+git = "https://github.com/rust-fuzz/libfuzzer-sys.git"
+
+# Keep this comment
+[[bin]]
+name = "fuzzer_script_1"
+path = "fuzzers/fuzzer_script_1.rs"
+
+[[bin]]
+name = "two"
+path = "fuzzers/two.rs"
+test = false
+doc = false
+bench = false
+"#;
+        let (upgraded, changes) = upgrade_manifest(manifest, true).unwrap();
+        assert_eq!(
+            upgraded,
+            r#"[package]
+name = "old-fuzz"
+version = "0.0.1"
+
+[package.metadata]
+cargo-fuzz = true
+
+[dependencies.libfuzzer-sys]
+version = "0.4"
+
+# Keep this comment
+[[bin]]
+name = "fuzzer_script_1"
+path = "fuzz_targets/fuzzer_script_1.rs"
+test = false
+doc = false
+bench = false
+
+[[bin]]
+name = "two"
+path = "fuzz_targets/two.rs"
+test = false
+doc = false
+bench = false
+"#
+        );
+        assert_eq!(changes.len(), 4);
+
+        let (again, changes) = upgrade_manifest(&upgraded, true).unwrap();
+        assert_eq!(again, upgraded);
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn upgrades_inline_dependencies() {
+        let manifest = "[package]\nname = \"a\"\n\n[package.metadata]\nfoo = 1\n\n\
+                        [dependencies]\nlibfuzzer-sys = \"0.3\"\n";
+        let (upgraded, _) = upgrade_manifest(manifest, false).unwrap();
+        assert_eq!(
+            upgraded,
+            "[package]\nname = \"a\"\n\n[package.metadata]\ncargo-fuzz = true\nfoo = 1\n\n\
+             [dependencies]\nlibfuzzer-sys = \"0.4\"\n"
+        );
+    }
+
+    #[test]
+    fn detects_outdated_libfuzzer_sys() {
+        let outdated = |dependency: &str| {
+            let value: toml::Value = format!("d = {}", dependency).parse().unwrap();
+            is_outdated_libfuzzer_sys(&value["d"])
+        };
+        assert!(outdated("\"0.3.2\""));
+        assert!(outdated("{ version = \"^0.1\" }"));
+        assert!(!outdated("\"0.4\""));
+        assert!(!outdated("{ path = \"../libfuzzer\" }"));
+    }
+}
@@ -1,4 +1,144 @@
+use crate::options::Sanitizer;
+use anyhow::anyhow;
+use std::fmt::Write;
+use std::path::{Component, Path, PathBuf};
+
 /// The default target to pass to cargo, to workaround issue #11.
 pub fn default_target() -> &'static str {
     current_platform::CURRENT_PLATFORM
 }
+
+/// The error for using `what` in a cargo-fuzz built without the cargo feature
+/// `feature` that it needs.
+pub fn missing_feature(feature: &str, what: &str) -> anyhow::Error {
+    anyhow!(
+        "{} needs cargo-fuzz to be built with the `{}` feature",
+        what,
+        feature
+    )
+}
+
+/// The sanitizer to build fuzz targets with by default: the address sanitizer,
+/// unless the host has no sanitizers at all, like OpenBSD.
+pub fn default_sanitizer() -> Sanitizer {
+    if cfg!(target_os = "openbsd") {
+        Sanitizer::None
+    } else {
+        Sanitizer::Address
+    }
+}
+
+/// Returns the Cargo binary name of a fuzz target. Fuzz targets in
+/// subdirectories of `fuzz_targets/` are named after their path, like
+/// `parser/http`, but Cargo doesn't allow `/` in names, so it becomes `-`.
+pub fn bin_name(target: &str) -> String {
+    target.replace('/', "-")
+}
+
+/// Returns the hex-encoded SHA-1 of `data`, which is how libFuzzer names the
+/// inputs it writes to a corpus.
+pub fn content_hash(data: &[u8]) -> String {
+    sha1_smol::Sha1::from(data).digest().to_string()
+}
+
+/// Escapes the characters of `s` that have a meaning in regular expressions.
+pub fn regex_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Quotes `s` as a single word for a POSIX shell: as it is if it's made of
+/// characters no shell treats specially, and between single quotes otherwise.
+pub fn shell_quote(s: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "_-./=:,+@%".contains(c);
+    if !s.is_empty() && s.chars().all(plain) {
+        return s.to_owned();
+    }
+    // Nothing is special between single quotes, so a single quote ends the
+    // quoted string, is escaped and starts a new one.
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Renders `data` the way `hexdump -C` does: 16 bytes per line, after their
+/// offset, followed by their printable ASCII characters.
+pub fn hexdump(data: &[u8]) -> String {
+    let mut dump = String::new();
+    for (i, chunk) in data.chunks(16).enumerate() {
+        write!(dump, "{:08x} ", i * 16).unwrap();
+        for j in 0..16 {
+            if j % 8 == 0 {
+                dump.push(' ');
+            }
+            match chunk.get(j) {
+                Some(byte) => write!(dump, "{:02x} ", byte).unwrap(),
+                None => dump.push_str("   "),
+            }
+        }
+        dump.push_str(" |");
+        dump.extend(chunk.iter().map(|&byte| match byte {
+            b' '..=b'~' => byte as char,
+            _ => '.',
+        }));
+        dump.push_str("|\n");
+    }
+    dump
+}
+
+/// Returns the path of `to` relative to the directory `from`, where both are
+/// absolute paths without `..` components.
+pub fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut relative: PathBuf = from[common..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .collect();
+    relative.extend(&to[common..]);
+    if relative.as_os_str().is_empty() {
+        relative.push(".");
+    }
+    relative
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hexdumps() {
+        assert_eq!(hexdump(b""), "");
+        assert_eq!(
+            hexdump(b"hello world\n\0\xff fuzz"),
+            "00000000  68 65 6c 6c 6f 20 77 6f  72 6c 64 0a 00 ff 20 66  |hello world... f|\n\
+             00000010  75 7a 7a                                          |uzz|\n"
+        );
+    }
+
+    #[test]
+    fn shell_quotes() {
+        assert_eq!(shell_quote("-Cdebug-assertions"), "-Cdebug-assertions");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("--cfg fuzzing"), "'--cfg fuzzing'");
+        assert_eq!(shell_quote("$HOME \"a\""), "'$HOME \"a\"'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn relative_paths() {
+        let relative = |from, to| relative_path(Path::new(from), Path::new(to));
+        assert_eq!(
+            relative("/a/b/fuzz", "/a/b/vendor/c"),
+            Path::new("../vendor/c")
+        );
+        assert_eq!(relative("/a/b", "/a/b/c"), Path::new("c"));
+        assert_eq!(relative("/a/b", "/x"), Path::new("../../x"));
+        assert_eq!(relative("/a/b", "/a/b"), Path::new("."));
+    }
+}
@@ -0,0 +1,163 @@
+//! Picking one of the fuzz projects of the packages of a workspace
+
+use crate::project::{self, FuzzProject};
+use anyhow::{bail, Result};
+use clap::CommandFactory;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Points the command line `args` at the fuzz project of the package given
+/// with `--package`, or in `<package>::<target>` fuzz target names, by adding
+/// `--fuzz-dir`. When neither is given and the current directory has no fuzz
+/// project of its own, the fuzz project of the workspace that has the fuzz
+/// targets is picked.
+///
+/// Command lines that don't parse, or that already have a `--fuzz-dir`, are
+/// returned as they are.
+pub fn select(args: Vec<OsString>) -> Result<Vec<OsString>> {
+    let matches = match crate::Command::command().try_get_matches_from(&args) {
+        Ok(matches) => matches,
+        Err(_) => return Ok(args),
+    };
+    let mut command = "";
+    let mut matches = &matches;
+    while let Some((name, sub_matches)) = matches.subcommand() {
+        command = name;
+        matches = sub_matches;
+    }
+
+    let mut package = match matches.try_get_one::<PathBuf>("fuzz_dir") {
+        Ok(None) => matches.get_one::<String>("package").cloned(),
+        Ok(Some(_)) if matches.get_one::<String>("package").is_some() => {
+            bail!("`--package` and `--fuzz-dir` can't be used together")
+        }
+        Ok(Some(_)) => return Ok(args),
+        // Commands without a fuzz project, such as `help`.
+        Err(_) => return Ok(args),
+    };
+    if command == "init" {
+        if package.is_some() {
+            bail!(
+                "`cargo fuzz init` creates the fuzz project of the package in the current \
+                 directory, run it there instead of passing `--package`"
+            );
+        }
+        return Ok(args);
+    }
+
+    let mut args = args;
+    let separator = args
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(args.len());
+    let mut targets = Vec::new();
+    for id in ["target", "targets"] {
+        if let Ok(Some(values)) = matches.try_get_many::<String>(id) {
+            targets.extend(values.cloned());
+        }
+    }
+    for target in &mut targets {
+        let Some((target_package, name)) = target.split_once("::") else {
+            continue;
+        };
+        match &package {
+            Some(package) if package != target_package => {
+                bail!(
+                    "fuzz target `{}` isn't one of package `{}`",
+                    target,
+                    package
+                )
+            }
+            _ => package = Some(target_package.to_owned()),
+        }
+        if let Some(arg) = args[..separator].iter_mut().find(|arg| **arg == **target) {
+            *arg = name.into();
+        }
+        *target = name.to_owned();
+    }
+    // The fuzz target to add isn't in any fuzz project yet.
+    if command == "add" {
+        targets.clear();
+    }
+
+    let fuzz_dir = match package {
+        Some(package) => {
+            let fuzz_dirs = project::workspace_fuzz_dirs()?;
+            match fuzz_dirs.iter().find(|(name, _)| *name == package) {
+                Some((_, fuzz_dir)) => fuzz_dir.clone(),
+                None => bail!(
+                    "package `{}` has no fuzz project, the packages of the workspace with one \
+                     are: {}",
+                    package,
+                    package_names(&fuzz_dirs)
+                ),
+            }
+        }
+        // `cargo fuzz list` lists the fuzz targets of all the fuzz projects.
+        None if command == "list" => return Ok(args),
+        None => {
+            let has_fuzz_dir = project::default_fuzz_dir()
+                .map_or(true, |fuzz_dir| fuzz_dir.join("Cargo.toml").is_file());
+            if has_fuzz_dir {
+                return Ok(args);
+            }
+            match select_by_targets(&project::workspace_fuzz_dirs()?, &targets)? {
+                Some(fuzz_dir) => fuzz_dir,
+                None => return Ok(args),
+            }
+        }
+    };
+    args.insert(
+        separator,
+        OsString::from(format!("--fuzz-dir={}", fuzz_dir.display())),
+    );
+    Ok(args)
+}
+
+/// Returns the only fuzz project that has all of `targets`.
+fn select_by_targets(
+    fuzz_dirs: &[(String, PathBuf)],
+    targets: &[String],
+) -> Result<Option<PathBuf>> {
+    if fuzz_dirs.is_empty() {
+        return Ok(None);
+    }
+    let mut candidates = Vec::new();
+    for (package, fuzz_dir) in fuzz_dirs {
+        let project = FuzzProject::new(Some(fuzz_dir.clone()))?;
+        if targets
+            .iter()
+            .all(|target| project.targets().contains(target))
+        {
+            candidates.push((package.clone(), fuzz_dir.clone()));
+        }
+    }
+    match candidates.len() {
+        0 => bail!(
+            "no fuzz project of the workspace has the fuzz target(s) {}, the packages of the \
+             workspace with a fuzz project are: {}",
+            targets.join(", "),
+            package_names(fuzz_dirs)
+        ),
+        1 => Ok(candidates.pop().map(|(_, fuzz_dir)| fuzz_dir)),
+        _ if targets.is_empty() => bail!(
+            "several packages of the workspace have a fuzz project, pick one with \
+             `--package`: {}",
+            package_names(&candidates)
+        ),
+        _ => bail!(
+            "several packages of the workspace have the fuzz target(s) {}, pick one with \
+             `--package` or `<PACKAGE>::<TARGET>`: {}",
+            targets.join(", "),
+            package_names(&candidates)
+        ),
+    }
+}
+
+fn package_names(fuzz_dirs: &[(String, PathBuf)]) -> String {
+    fuzz_dirs
+        .iter()
+        .map(|(package, _)| package.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -3,7 +3,9 @@ pub mod project;
 use self::project::*;
 use assert_cmd::prelude::*;
 use predicates::prelude::*;
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::{Read, Write};
 use std::path::Path;
 use std::process::Command;
 
@@ -87,6 +89,137 @@ fn init_with_target_and_workspace() {
         .success();
 }
 
+#[test]
+fn init_with_inherited_fields() {
+    let project = project("init_with_inherited_fields")
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["crates/*"]
+
+                [workspace.package]
+                version = "0.3.0"
+                edition = "2018"
+            "#,
+        )
+        .file(
+            Path::new("crates").join("lib").join("Cargo.toml"),
+            r#"
+                [package]
+                name = "inherited_lib"
+                version.workspace = true
+                edition.workspace = true
+            "#,
+        )
+        .file(
+            Path::new("crates").join("lib").join("src").join("lib.rs"),
+            "",
+        )
+        .build();
+    let lib = project.root().join("crates").join("lib");
+
+    project
+        .cargo_fuzz()
+        .current_dir(&lib)
+        .arg("init")
+        .arg("--fuzzing-workspace=true")
+        .assert()
+        .success();
+    let fuzz_cargo_toml = fs::read_to_string(lib.join("fuzz").join("Cargo.toml")).unwrap();
+    assert!(fuzz_cargo_toml.contains("name = \"inherited_lib-fuzz\""));
+    assert!(fuzz_cargo_toml.contains("edition = \"2018\""));
+    assert!(fuzz_cargo_toml.contains("[dependencies.inherited_lib]"));
+
+    project
+        .cargo_fuzz()
+        .current_dir(&lib)
+        .arg("list")
+        .assert()
+        .stdout("fuzz_target_1\n")
+        .success();
+}
+
+#[test]
+fn init_with_patches() {
+    let project = project("init_with_patches")
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+
+                [package]
+                name = "init_with_patches"
+                version = "1.0.0"
+
+                [patch.crates-io]
+                patched = { path = "vendor/patched" }
+            "#,
+        )
+        .file(
+            Path::new("vendor").join("patched").join("Cargo.toml"),
+            r#"
+                [package]
+                name = "patched"
+                version = "1.0.0"
+            "#,
+        )
+        .file(
+            Path::new("vendor")
+                .join("patched")
+                .join("src")
+                .join("lib.rs"),
+            "",
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("init")
+        .arg("--fuzzing-workspace=true")
+        .assert()
+        .success();
+    let fuzz_cargo_toml = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    assert!(fuzz_cargo_toml.contains("[patch.crates-io.patched]\npath = \"../vendor/patched\""));
+
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .assert()
+        .stdout("fuzz_target_1\n")
+        .success();
+}
+
+#[test]
+fn init_records_fuzz_dir() {
+    let project = project("init_records_fuzz_dir").build();
+    project
+        .cargo_fuzz()
+        .arg("init")
+        .arg("--fuzz-dir")
+        .arg("fuzzing")
+        .arg("--fuzzing-workspace=true")
+        .assert()
+        .success();
+    let cargo_toml = fs::read_to_string(project.root().join("Cargo.toml")).unwrap();
+    assert!(cargo_toml.contains("[package.metadata.cargo-fuzz]\ndir = \"fuzzing\"\n"));
+
+    // Later commands find the fuzz directory without `--fuzz-dir`.
+    project.cargo_fuzz().arg("add").arg("b").assert().success();
+    assert!(project
+        .root()
+        .join("fuzzing")
+        .join("fuzz_targets")
+        .join("b.rs")
+        .is_file());
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .assert()
+        .stdout("b\nfuzz_target_1\n")
+        .success();
+}
+
 #[test]
 fn init_twice() {
     let project = project("init_twice").build();
@@ -210,699 +343,5275 @@ fn add_twice() {
 }
 
 #[test]
-fn list() {
-    let project = project("add").with_fuzz().build();
-
-    // Create some targets.
-    project.cargo_fuzz().arg("add").arg("c").assert().success();
-    project.cargo_fuzz().arg("add").arg("b").assert().success();
-    project.cargo_fuzz().arg("add").arg("a").assert().success();
+fn add_nested() {
+    let project = project("add_nested").with_fuzz().build();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("parser/http")
+        .assert()
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("top")
+        .assert()
+        .success();
+    assert!(project.fuzz_target_path("parser/http").is_file());
+    let manifest = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    assert!(manifest.contains("name = \"parser-http\"\npath = \"fuzz_targets/parser/http.rs\""));
+    assert!(project.fuzz_dir().join("corpus/parser/http").is_dir());
+    assert!(project.fuzz_dir().join("artifacts/parser/http").is_dir());
 
-    // Make sure that we can list our targets, and that they're always sorted.
     project
         .cargo_fuzz()
         .arg("list")
         .assert()
-        .stdout("a\nb\nc\n")
+        .stdout("parser/http\ntop\n")
         .success();
-}
-
-#[test]
-fn run_no_crash() {
-    let project = project("run_no_crash")
-        .with_fuzz()
-        .fuzz_target(
-            "no_crash",
-            r#"
-                #![no_main]
-                use libfuzzer_sys::fuzz_target;
-
-                fuzz_target!(|data: &[u8]| {
-                    run_no_crash::pass_fuzzing(data);
-                });
-            "#,
-        )
-        .build();
-
     project
         .cargo_fuzz()
         .arg("run")
-        .arg("no_crash")
+        .arg("parser/http")
         .arg("--")
-        .arg("-runs=1000")
+        .arg("-runs=1")
         .assert()
-        .stderr(predicate::str::contains("Done 1000 runs"))
+        .stderr(predicate::str::contains("Done 2 runs"))
         .success();
+
+    // Its binary name is taken.
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("parser-http")
+        .assert()
+        .stderr(predicate::str::contains(
+            "fuzz target `parser/http` already has the binary name `parser-http`",
+        ))
+        .failure();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("../escape")
+        .assert()
+        .stderr(predicate::str::contains("invalid fuzz target name"))
+        .failure();
 }
 
 #[test]
-fn run_with_crash() {
-    let project = project("run_with_crash")
-        .with_fuzz()
-        .fuzz_target(
-            "yes_crash",
-            r#"
-                #![no_main]
-                use libfuzzer_sys::fuzz_target;
+fn upgrade() {
+    let project = project("upgrade")
+        .file(
+            "fuzz/Cargo.toml",
+            r#"[package]
+name = "upgrade-fuzz"
+version = "0.0.1"
 
-                fuzz_target!(|data: &[u8]| {
-                    run_with_crash::fail_fuzzing(data);
-                });
-            "#,
+[workspace]
+
+[dependencies.libfuzzer-sys]
+git = "https://github.com/rust-fuzz/libfuzzer-sys.git"
+
+[[bin]]
+name = "a"
+path = "fuzzers/a.rs"
+"#,
         )
+        .file("fuzz/fuzzers/a.rs", "")
         .build();
+    let manifest = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
 
     project
         .cargo_fuzz()
-        .arg("run")
-        .arg("yes_crash")
-        .arg("--")
-        .arg("-runs=1000")
-        .env("RUST_BACKTRACE", "1")
+        .arg("upgrade")
+        .arg("--dry-run")
         .assert()
         .stderr(
-            predicate::str::contains("thread '<unnamed>' panicked at")
-                .and(predicate::str::contains("I'm afraid of number 7"))
-                .and(predicate::str::contains("ERROR: libFuzzer: deadly signal"))
-                .and(predicate::str::contains("run_with_crash::fail_fuzzing"))
-                .and(predicate::str::contains(
-                    "────────────────────────────────────────────────────────────────────────────────\n\
-                     \n\
-                     Failing input:\n\
-                     \n\
-                     \tfuzz/artifacts/yes_crash/crash-"
-                ))
-                .and(predicate::str::contains("Output of `std::fmt::Debug`:"))
-                .and(predicate::str::contains(
-                    "Reproduce with:\n\
-                     \n\
-                     \tcargo fuzz run yes_crash fuzz/artifacts/yes_crash/crash-"
-                ))
-                .and(predicate::str::contains(
-                    "Minimize test case with:\n\
-                     \n\
-                     \tcargo fuzz tmin yes_crash fuzz/artifacts/yes_crash/crash-"
-                )),
+            predicate::str::contains("Would upgrade the fuzz project in fuzz:").and(
+                predicate::str::contains("rename `fuzzers/` to `fuzz_targets/`"),
+            ),
         )
-        .failure();
+        .success();
+    assert_eq!(
+        fs::read_to_string(project.fuzz_cargo_toml()).unwrap(),
+        manifest
+    );
+
+    project.cargo_fuzz().arg("upgrade").assert().success();
+    assert!(project.fuzz_target_path("a").is_file());
+    let manifest = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    assert!(manifest.contains("[package.metadata]\ncargo-fuzz = true\n"));
+    assert!(manifest.contains("[dependencies.libfuzzer-sys]\nversion = \"0.4\"\n"));
+    assert!(manifest
+        .contains("path = \"fuzz_targets/a.rs\"\ntest = false\ndoc = false\nbench = false\n"));
+
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .assert()
+        .stdout("a\n")
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("upgrade")
+        .assert()
+        .stderr("The fuzz project is up to date\n")
+        .success();
 }
 
 #[test]
-fn run_with_coverage() {
-    let target = "with_coverage";
-
-    let project = project("run_with_coverage")
-        .with_fuzz()
-        .fuzz_target(
-            target,
-            r#"
-                #![no_main]
-                use libfuzzer_sys::fuzz_target;
+fn list() {
+    let project = project("add").with_fuzz().build();
 
-                fuzz_target!(|data: &[u8]| {
-                    println!("{:?}", data);
-                });
-            "#,
-        )
-        .build();
+    // Create some targets.
+    project.cargo_fuzz().arg("add").arg("c").assert().success();
+    project.cargo_fuzz().arg("add").arg("b").assert().success();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
 
+    // Make sure that we can list our targets, and that they're always sorted.
     project
         .cargo_fuzz()
-        .arg("run")
-        .arg(target)
-        .arg("--")
-        .arg("-runs=100")
+        .arg("list")
         .assert()
-        .stderr(predicate::str::contains("Done 100 runs"))
+        .stdout("a\nb\nc\n")
         .success();
+}
+
+#[test]
+fn library_api() {
+    let project = project("library_api")
+        .with_fuzz()
+        .fuzz_target("b", "")
+        .fuzz_target("a", "")
+        .build();
+
+    let fuzz_project = cargo_fuzz::FuzzProject::new(Some(project.fuzz_dir().to_owned())).unwrap();
+    assert_eq!(fuzz_project.targets(), ["a", "b"]);
+}
+
+#[test]
+fn list_discovered_targets() {
+    let project = project("list_discovered_targets")
+        .with_fuzz()
+        .fuzz_target("declared", "")
+        .file("fuzz/src/bin/discovered.rs", "fn main() {}")
+        .build();
 
+    // Cargo discovers binaries in `src/bin/` without a `[[bin]]` section,
+    // also when run from inside the fuzz directory.
     project
         .cargo_fuzz()
-        .arg("coverage")
-        .arg(target)
+        .current_dir(project.fuzz_targets_dir())
+        .arg("list")
         .assert()
-        .stderr(predicate::str::contains("Coverage data merged and saved"))
+        .stdout("declared\ndiscovered\n")
         .success();
-
-    let profdata_file = project.fuzz_coverage_dir(target).join("coverage.profdata");
-    assert!(profdata_file.exists(), "Coverage data file not generated");
 }
 
 #[test]
-fn run_without_sanitizer_with_crash() {
-    let project = project("run_without_sanitizer_with_crash")
-        .with_fuzz()
-        .fuzz_target(
-            "yes_crash",
+fn workspace_with_several_fuzz_projects() {
+    let mut builder = project("workspace_with_several_fuzz_projects");
+    builder.file(
+        "Cargo.toml",
+        r#"
+            [workspace]
+            members = ["crates/foo", "crates/bar"]
+        "#,
+    );
+    for (package, targets) in [
+        ("foo", &["only_foo", "shared"][..]),
+        ("bar", &["shared"][..]),
+    ] {
+        let dir = Path::new("crates").join(package);
+        builder
+            .file(
+                dir.join("Cargo.toml"),
+                &format!(
+                    r#"
+                        [package]
+                        name = "{package}"
+                        version = "0.1.0"
+                    "#
+                ),
+            )
+            .file(dir.join("src").join("lib.rs"), "");
+        let mut fuzz_cargo_toml = format!(
             r#"
-                #![no_main]
-                use libfuzzer_sys::fuzz_target;
+                [package]
+                name = "{package}-fuzz"
+                version = "0.0.0"
+                publish = false
+                edition = "2021"
 
-                fuzz_target!(|data: &[u8]| {
-                    run_without_sanitizer_with_crash::fail_fuzzing(data);
-                });
-            "#,
-        )
-        .build();
+                [package.metadata]
+                cargo-fuzz = true
+
+                [workspace]
+
+                [dependencies]
+                libfuzzer-sys = "0.4"
+            "#
+        );
+        for target in targets {
+            fuzz_cargo_toml.push_str(&format!(
+                r#"
+                    [[bin]]
+                    name = "{target}"
+                    path = "fuzz_targets/{target}.rs"
+                    test = false
+                    doc = false
+                "#
+            ));
+            builder.file(
+                dir.join("fuzz")
+                    .join("fuzz_targets")
+                    .join(format!("{}.rs", target)),
+                r#"
+                    #![no_main]
+                    use libfuzzer_sys::fuzz_target;
+
+                    fuzz_target!(|_data: &[u8]| {});
+                "#,
+            );
+        }
+        builder.file(dir.join("fuzz").join("Cargo.toml"), &fuzz_cargo_toml);
+    }
+    let project = builder.build();
+
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .assert()
+        .stdout("bar::shared\nfoo::only_foo\nfoo::shared\n")
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .arg("-p")
+        .arg("foo")
+        .assert()
+        .stdout("only_foo\nshared\n")
+        .success();
+
+    // A fuzz target of only one of the fuzz projects picks it, otherwise the
+    // package has to be given.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("only_foo")
+        .arg("--")
+        .arg("-runs=1")
+        .assert()
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("shared")
+        .assert()
+        .stderr(predicate::str::contains(
+            "several packages of the workspace have the fuzz target(s) shared",
+        ))
+        .failure();
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("bar::shared")
+        .arg("--")
+        .arg("-runs=1")
+        .assert()
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("--package")
+        .arg("bar")
+        .arg("new_target")
+        .assert()
+        .success();
+    assert!(project
+        .root()
+        .join("crates/bar/fuzz/fuzz_targets/new_target.rs")
+        .is_file());
+}
+
+#[test]
+fn list_json() {
+    let project = project("list_json")
+        .with_fuzz()
+        .fuzz_target("a", "")
+        .fuzz_target("b", "")
+        .file("fuzz/corpus/a/1", "12")
+        .file("fuzz/corpus/a/2", "345")
+        .file("fuzz/artifacts/a/crash-1", "")
+        .file("fuzz/artifacts/a/crash-2", "")
+        .file("fuzz/artifacts/a/oom-1", "")
+        .build();
+
+    let output = project
+        .cargo_fuzz()
+        .arg("list")
+        .arg("--json")
+        .assert()
+        .success();
+    let targets: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(
+        targets,
+        serde_json::json!([
+            {
+                "name": "a",
+                "path": "fuzz/fuzz_targets/a.rs",
+                "corpus": { "inputs": 2, "bytes": 5 },
+                "artifacts": { "crash": 2, "oom": 1 },
+                "last_run": null,
+            },
+            {
+                "name": "b",
+                "path": "fuzz/fuzz_targets/b.rs",
+                "corpus": { "inputs": 0, "bytes": 0 },
+                "artifacts": {},
+                "last_run": null,
+            },
+        ])
+    );
+}
+
+#[test]
+fn status() {
+    let project = project("status")
+        .with_fuzz()
+        .fuzz_target("a", "")
+        .fuzz_target("never_built_or_run", "")
+        .file("fuzz/corpus/a/1", &"x".repeat(2048))
+        .file("fuzz/artifacts/a/crash-1", "")
+        .file(
+            "fuzz/stats/a/1.json",
+            r#"{"started": 0, "elapsed_secs": 60, "execs": 0}"#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("status")
+        .assert()
+        .stdout(
+            predicate::str::is_match(
+                "^TARGET              BUILT       CORPUS  ARTIFACTS  LAST FUZZED  COVERAGE\n\
+                 a                   no     1 \\(2\\.0 KiB\\)          1  +\\d+d ago         -\n\
+                 never_built_or_run  no         0 \\(0 B\\)          0        never         -\n$",
+            )
+            .unwrap(),
+        )
+        .success();
+}
+
+#[test]
+fn lint() {
+    let targets = Path::new("fuzz").join("fuzz_targets");
+    let project = project("lint")
+        .file(
+            Path::new("fuzz").join("Cargo.toml"),
+            r#"
+                [package]
+                name = "lint-fuzz"
+                version = "0.0.0"
+                publish = false
+                edition = "2021"
+
+                [package.metadata]
+                cargo-fuzz = true
+
+                [workspace]
+                members = ["."]
+
+                [dependencies]
+                libfuzzer-sys = "0.4"
+
+                [[bin]]
+                name = "good"
+                path = "fuzz_targets/good.rs"
+                test = false
+                doc = false
+                bench = false
+
+                [[bin]]
+                name = "typed"
+                path = "fuzz_targets/typed.rs"
+                test = false
+                doc = false
+                bench = false
+
+                [[bin]]
+                name = "no_main"
+                path = "fuzz_targets/no_main.rs"
+                test = false
+                doc = false
+
+                [[bin]]
+                name = "missing"
+                path = "fuzz_targets/missing.rs"
+                test = false
+                doc = false
+                bench = false
+            "#,
+        )
+        .file(
+            targets.join("good.rs"),
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|input: u8| {
+                    let _ = input;
+                });
+            "#,
+        )
+        .file(
+            targets.join("typed.rs"),
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                struct Input;
+
+                fuzz_target!(|input: Input| {
+                    let _ = input;
+                });
+            "#,
+        )
+        .file(
+            targets.join("no_main.rs"),
+            r#"
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(
+            targets.join("orphan.rs"),
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(targets.join("common.rs"), "pub fn helper() {}")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("lint")
+        .assert()
+        .stdout(
+            predicate::str::contains(
+                "fuzz target `no_main` is missing `bench = false`, so `cargo test`",
+            )
+            .and(predicate::str::contains(
+                "fuzz target `missing` has no source file at fuzz/fuzz_targets/missing.rs",
+            ))
+            .and(predicate::str::contains(
+                "fuzz/fuzz_targets/no_main.rs: missing `#![no_main]`",
+            ))
+            .and(predicate::str::contains(
+                "fuzz/fuzz_targets/orphan.rs: fuzz target without a `[[bin]]`",
+            ))
+            .and(predicate::str::contains(
+                "fuzz/fuzz_targets/typed.rs: input type `Input` doesn't implement `Arbitrary`",
+            ))
+            .and(predicate::str::contains("good").not())
+            .and(predicate::str::contains("common").not()),
+        )
+        .stderr(predicate::str::contains("Error: 5 problem(s) found"))
+        .failure();
+}
+
+#[test]
+fn sync() {
+    let project = project("sync")
+        .with_fuzz()
+        .fuzz_target(
+            "kept",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .fuzz_target("deleted", "")
+        .file(
+            Path::new("fuzz").join("fuzz_targets").join("copied.rs"),
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("fuzz_targets").join("common.rs"),
+            "pub fn helper() {}",
+        )
+        .build();
+    fs::remove_file(project.fuzz_target_path("deleted")).unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("sync")
+        .assert()
+        .stderr(
+            predicate::str::contains("Adding a `[[bin]]` for fuzz target `copied`").and(
+                predicate::str::contains(
+                    "warning: the source file of fuzz target `deleted` is gone",
+                ),
+            ),
+        )
+        .success();
+    let manifest = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    assert!(manifest.contains("name = \"copied\"\npath = \"fuzz_targets/copied.rs\""));
+    assert!(manifest.contains("name = \"deleted\""));
+    assert!(!manifest.contains("common"));
+
+    project
+        .cargo_fuzz()
+        .arg("sync")
+        .arg("--remove")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Removing the `[[bin]]` of fuzz target `deleted`, whose source file is gone",
+        ))
+        .success();
+    let manifest = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    assert!(!manifest.contains("deleted"));
+
+    project
+        .cargo_fuzz()
+        .arg("sync")
+        .assert()
+        .stderr(predicate::str::contains(
+            "The manifest is in sync with `fuzz_targets/`",
+        ))
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .assert()
+        .stdout("copied\nkept\n")
+        .success();
+}
+
+#[test]
+fn smoke() {
+    let corpus = Path::new("fuzz").join("corpus").join("no_crash");
+    let project = project("smoke")
+        .with_fuzz()
+        .fuzz_target(
+            "no_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    smoke::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .fuzz_target(
+            "empty_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.is_empty() {
+                        panic!("empty input");
+                    }
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "foo")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("smoke")
+        .assert()
+        .stdout(
+            predicate::str::contains("no_crash: ok in ")
+                .and(predicate::str::contains("empty_crash: failed [")),
+        )
+        .stderr(predicate::str::contains(
+            "Error: 1 of 2 fuzz target(s) failed the smoke test",
+        ))
+        .code(10);
+
+    project
+        .cargo_fuzz()
+        .arg("smoke")
+        .arg("--runs=10")
+        .arg("no_crash")
+        .assert()
+        .stdout(predicate::str::contains("no_crash: ok in "))
+        .success();
+}
+
+#[test]
+fn ci() {
+    let corpus = Path::new("fuzz").join("corpus").join("no_crash");
+    let project = project("ci")
+        .with_fuzz()
+        .fuzz_target(
+            "no_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    ci::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    ci::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "foo")
+        .build();
+    project.cargo_fuzz().arg("build").assert().success();
+
+    let junit = project.root().join("junit.xml");
+    let sarif = project.root().join("results.sarif");
+    let output = project
+        .cargo_fuzz()
+        .arg("ci")
+        .arg("--time-budget=10")
+        .arg("--junit")
+        .arg(&junit)
+        .arg("--sarif")
+        .arg(&sarif)
+        .assert()
+        .stdout(
+            predicate::str::contains("no_crash: ok, fuzzed for ")
+                .and(predicate::str::contains("yes_crash: new crash [")),
+        )
+        .stderr(predicate::str::contains("Error: 1 new crash(es) found"))
+        .code(10);
+
+    let junit = fs::read_to_string(junit).unwrap();
+    assert!(junit.contains(r#"<testsuite name="no_crash" tests="2" failures="0""#));
+    assert!(junit.contains(r#"<testsuite name="yes_crash" tests="2" failures="1""#));
+    let sarif: serde_json::Value = serde_json::from_slice(&fs::read(sarif).unwrap()).unwrap();
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(results[0]["message"]["text"]
+        .as_str()
+        .unwrap()
+        .starts_with("`yes_crash` crashed: panicked at "));
+
+    // Once the crash is suppressed, it no longer fails the run.
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout).into_owned();
+    let hash = stdout.split("new crash [").nth(1).unwrap();
+    let hash = &hash[..hash.find(']').unwrap()];
+    fs::write(
+        project.fuzz_dir().join("fuzz.toml"),
+        format!("[targets.yes_crash]\nsuppress = [\"{}\"]\n", hash),
+    )
+    .unwrap();
+    project
+        .cargo_fuzz()
+        .arg("ci")
+        .arg("--time-budget=10")
+        .arg("yes_crash")
+        .assert()
+        .stdout(predicate::str::contains(format!(
+            "yes_crash: suppressed crash [{}]",
+            hash
+        )))
+        .success();
+}
+
+#[test]
+fn init_clusterfuzzlite() {
+    let project = project("init_clusterfuzzlite")
+        .with_fuzz()
+        .fuzz_target(
+            "one",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .fuzz_target(
+            "two",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file("fuzz/fuzz.toml", "[targets.two]\ndict = \"two.dict\"\n")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("init-clusterfuzzlite")
+        .arg("--features=foo")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Wrote the ClusterFuzzLite configuration of 2 fuzz target(s)",
+        ))
+        .success();
+
+    let dir = project.root().join(".clusterfuzzlite");
+    assert_eq!(
+        fs::read_to_string(dir.join("Dockerfile")).unwrap(),
+        "FROM gcr.io/oss-fuzz-base/base-builder-rust:v1\n\
+         COPY . $SRC/init_clusterfuzzlite\n\
+         WORKDIR $SRC/init_clusterfuzzlite\n\
+         COPY .clusterfuzzlite/build.sh $SRC/\n"
+    );
+    let build_sh = fs::read_to_string(dir.join("build.sh")).unwrap();
+    assert!(build_sh.contains("\ncargo fuzz build -O --features=foo"));
+    assert!(build_sh.contains("--target-dir=fuzz/target\n"));
+    assert!(build_sh.contains(
+        "for target in one two; do\n    \
+         cp \"fuzz/target/x86_64-unknown-linux-gnu/release/$target\" \"$OUT/\"\n    \
+         if [ -d \"fuzz/corpus/$target\" ]; then\n        \
+         zip -jqr \"$OUT/${target}_seed_corpus.zip\" \"fuzz/corpus/$target\"\n    \
+         fi\n\
+         done\n\
+         \n\
+         cp \"fuzz/two.dict\" \"$OUT/two.dict\"\n"
+    ));
+    assert_eq!(
+        fs::read_to_string(dir.join("project.yaml")).unwrap(),
+        "language: rust\n"
+    );
+
+    project
+        .cargo_fuzz()
+        .arg("init-clusterfuzzlite")
+        .assert()
+        .stderr(predicate::str::contains(
+            "already exists, pass `--force` to overwrite it",
+        ))
+        .failure();
+    project
+        .cargo_fuzz()
+        .arg("init-clusterfuzzlite")
+        .arg("--force")
+        .assert()
+        .success();
+}
+
+#[test]
+fn init_github_actions() {
+    let project = project("init_github_actions")
+        .with_fuzz()
+        .fuzz_target(
+            "one",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .fuzz_target(
+            "two",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
+    // Keep the workflow out of any repository around the test project.
+    Command::new("git")
+        .arg("init")
+        .arg("--quiet")
+        .arg(project.root())
+        .status()
+        .unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("init-github-actions")
+        .arg("--time-budget=600")
+        .arg("--features=foo")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Wrote a workflow that fuzzes 2 fuzz target(s)",
+        ))
+        .success();
+
+    let workflow = fs::read_to_string(project.root().join(".github/workflows/fuzz.yml")).unwrap();
+    assert!(workflow.contains("    - cron: \"0 3 * * *\"\n"));
+    assert!(workflow.contains("        target:\n          - one\n          - two\n    steps:\n"));
+    assert!(workflow.contains(
+        "        run: cargo fuzz ci --features=foo --time-budget=600 ${{ matrix.target }}\n"
+    ));
+    assert!(workflow.contains("          path: fuzz/corpus/${{ matrix.target }}\n"));
+    assert!(workflow.contains("          path: fuzz/artifacts/${{ matrix.target }}\n"));
+}
+
+#[test]
+fn init_oss_fuzz() {
+    let project = project("init_oss_fuzz")
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                [package]
+                name = "init_oss_fuzz"
+                version = "1.0.0"
+                authors = ["Jane Doe <jane@example.com>"]
+                repository = "https://example.com/init_oss_fuzz.git"
+            "#,
+        )
+        .with_fuzz()
+        .fuzz_target(
+            "one",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("init-oss-fuzz")
+        .arg("--project=example")
+        .assert()
+        .stderr(
+            predicate::str::contains("Wrote the OSS-Fuzz project `example` with 1 fuzz target(s)")
+                .and(predicate::str::contains("warning").not()),
+        )
+        .success();
+
+    let dir = project
+        .root()
+        .join("oss-fuzz")
+        .join("projects")
+        .join("example");
+    assert_eq!(
+        fs::read_to_string(dir.join("Dockerfile")).unwrap(),
+        "FROM gcr.io/oss-fuzz-base/base-builder-rust\n\
+         RUN git clone --depth 1 https://example.com/init_oss_fuzz.git init_oss_fuzz\n\
+         WORKDIR $SRC/init_oss_fuzz\n\
+         COPY build.sh $SRC/\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("project.yaml")).unwrap(),
+        "homepage: \"https://example.com/init_oss_fuzz.git\"\n\
+         language: rust\n\
+         primary_contact: \"jane@example.com\"\n\
+         main_repo: \"https://example.com/init_oss_fuzz.git\"\n\
+         sanitizers:\n  \
+         - address\n\
+         fuzzing_engines:\n  \
+         - libfuzzer\n"
+    );
+    let build_sh = fs::read_to_string(dir.join("build.sh")).unwrap();
+    assert!(build_sh.starts_with("#!/bin/bash -eu\n# Generated by `cargo fuzz init-oss-fuzz`"));
+    assert!(build_sh.contains("cd \"$SRC/init_oss_fuzz\"\ncargo fuzz build -O"));
+}
+
+#[test]
+fn run_no_crash() {
+    let project = project("run_no_crash")
+        .with_fuzz()
+        .fuzz_target(
+            "no_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_no_crash::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("no_crash")
+        .arg("--")
+        .arg("-runs=1000")
+        .assert()
+        .stderr(predicate::str::contains("Done 1000 runs"))
+        .success();
+}
+
+#[test]
+fn run_with_config() {
+    let project = project("run_with_config")
+        .with_fuzz()
+        .fuzz_target(
+            "no_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_with_config::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .file(
+            "fuzz/fuzz.toml",
+            r#"
+                args = ["-runs=1000"]
+
+                [targets.no_crash]
+                max-len = 7
+
+                [profiles.quick]
+                args = ["-runs=100"]
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("no_crash")
+        .assert()
+        .stderr(
+            predicate::str::contains("Done 1000 runs")
+                .and(predicate::str::contains("-max_len is not provided").not()),
+        )
+        .success();
+
+    // The command line overrides the configuration.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("no_crash")
+        .arg("--")
+        .arg("-runs=10")
+        .assert()
+        .stderr(predicate::str::contains("Done 10 runs"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--fuzz-dir")
+        .arg(project.fuzz_dir())
+        .arg("no_crash")
+        .assert()
+        .stderr(predicate::str::contains("Done 1000 runs"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--with")
+        .arg("quick")
+        .arg("no_crash")
+        .assert()
+        .stderr(predicate::str::contains("Done 100 runs"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--with")
+        .arg("deep")
+        .arg("no_crash")
+        .assert()
+        .stderr(predicate::str::contains(
+            "no profile named `deep`, the configured ones are: quick",
+        ))
+        .failure();
+}
+
+#[cfg(unix)]
+#[test]
+fn run_with_hooks() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = project("run_with_hooks")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_with_hooks::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .file(
+            "fuzz/fuzz.toml",
+            r#"
+                [hooks]
+                pre-run = "hook.sh"
+                post-run = "hook.sh"
+                on-crash = "hook.sh"
+            "#,
+        )
+        .file(
+            "fuzz/hook.sh",
+            "#!/bin/sh\n\
+             echo \"$CARGO_FUZZ_TARGET ${CARGO_FUZZ_EXIT_STATUS:-none} \
+             ${CARGO_FUZZ_ARTIFACT:+artifact}\" >> \"$CARGO_FUZZ_DIR/hooks.log\"\n",
+        )
+        .build();
+    let hook = project.fuzz_dir().join("hook.sh");
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("yes_crash")
+        .arg("--")
+        .arg("-runs=1000")
+        .assert()
+        .failure();
+
+    let log = fs::read_to_string(project.fuzz_dir().join("hooks.log")).unwrap();
+    let lines: Vec<_> = log.lines().map(str::trim_end).collect();
+    assert_eq!(lines.len(), 3, "{}", log);
+    assert_eq!(lines[0], "yes_crash none");
+    assert!(lines[1].starts_with("yes_crash ") && lines[1].ends_with(" artifact"));
+    assert!(lines[2].starts_with("yes_crash ") && !lines[2].ends_with(" artifact"));
+}
+
+#[test]
+fn run_reuses_build() {
+    let project = project("run_reuses_build")
+        .with_fuzz()
+        .fuzz_target(
+            "no_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_reuses_build::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    // The fuzz target built by `cargo fuzz run` is run directly, not through
+    // another `cargo run`.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("no_crash")
+        .arg("--")
+        .arg("-runs=1")
+        .assert()
+        .stderr(
+            predicate::str::contains("Finished")
+                .and(predicate::str::contains("Running `").not())
+                .and(predicate::str::contains("Done 2 runs")),
+        )
+        .success();
+}
+
+#[test]
+fn run_feature_sets() {
+    let project = project("run_feature_sets")
+        .with_fuzz()
+        .fuzz_target(
+            "gated",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    #[cfg(feature = "extra")]
+                    if data.is_empty() {
+                        panic!("crash behind the extra feature");
+                    }
+                    let _ = data;
+                });
+            "#,
+        )
+        .build();
+    let mut manifest = fs::OpenOptions::new()
+        .append(true)
+        .open(project.fuzz_cargo_toml())
+        .unwrap();
+    writeln!(manifest, "\n[features]\nextra = []").unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("gated")
+        .arg("--feature-set=")
+        .arg("--feature-set=extra")
+        .arg("--")
+        .arg("-runs=10")
+        .assert()
+        .stderr(
+            predicate::str::contains("Fuzzing `gated` with the features: default")
+                .and(predicate::str::contains(
+                    "Fuzzing `gated` with the features: extra",
+                ))
+                .and(predicate::str::contains("crash behind the extra feature"))
+                .and(predicate::str::contains(
+                    "fuzzing with the features `extra` failed",
+                )),
+        )
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("gated")
+        .arg("--feature-set=")
+        .arg("--separate-corpora")
+        .arg("--")
+        .arg("-runs=10")
+        .assert()
+        .success();
+    assert!(project
+        .fuzz_dir()
+        .join("feature-corpora")
+        .join("gated")
+        .join("default")
+        .is_dir());
+}
+
+#[test]
+fn run_ensemble() {
+    let project = project("run_ensemble")
+        .with_fuzz()
+        .fuzz_target(
+            "no_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_ensemble::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .fuzz_target(
+            "empty_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.is_empty() {
+                        panic!("crash on the empty input");
+                    }
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("no_crash")
+        .arg("--ensemble=address,none")
+        .arg("--")
+        .arg("-runs=10")
+        .assert()
+        .stderr(
+            predicate::str::contains("[address] Done 10 runs")
+                .and(predicate::str::contains("[none] Done 10 runs")),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("empty_crash")
+        .arg("--ensemble=none,address")
+        .arg("--")
+        .arg("-runs=10")
+        .assert()
+        .stderr(
+            predicate::str::contains("crash on the empty input")
+                .and(predicate::str::contains("build of `empty_crash` crashed"))
+                .and(predicate::str::contains("Failing input:")),
+        )
+        .failure();
+}
+
+#[test]
+fn run_regression() {
+    let corpus = Path::new("fuzz").join("corpus").join("yes_crash");
+    let artifacts = Path::new("fuzz").join("artifacts").join("yes_crash");
+    let project = project("run_regression")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_regression::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .file(corpus.join("pass"), "abc")
+        .file(corpus.join("fail"), "1234567")
+        .file(artifacts.join("crash-1"), "7777777")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--regression")
+        .arg("yes_crash")
+        .assert()
+        .stderr(
+            predicate::str::contains("Replaying 3 input(s) of `yes_crash`")
+                .and(predicate::str::contains(
+                    "Failing input:\n\n\tfuzz/corpus/yes_crash/fail",
+                ))
+                .and(predicate::str::contains(
+                    "Failing input:\n\n\tfuzz/artifacts/yes_crash/crash-1",
+                ))
+                .and(predicate::str::contains("Output of `std::fmt::Debug`:"))
+                .and(predicate::str::contains(
+                    "Replayed 3 input(s): 1 passed, 2 failed",
+                ))
+                .and(predicate::str::contains("Error: 2 input(s) failed")),
+        )
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--regression")
+        .arg("yes_crash")
+        .arg(
+            project
+                .fuzz_dir()
+                .join("corpus")
+                .join("yes_crash")
+                .join("pass"),
+        )
+        .assert()
+        .stderr(predicate::str::contains(
+            "Replayed 1 input(s): 1 passed, 0 failed",
+        ))
+        .success();
+}
+
+#[test]
+fn run_regression_junit() {
+    let corpus = Path::new("fuzz").join("corpus").join("yes_crash");
+    let project = project("run_regression_junit")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_regression_junit::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .file(corpus.join("pass"), "abc")
+        .file(corpus.join("fail"), "1234567")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--junit=junit.xml")
+        .arg("yes_crash")
+        .assert()
+        .stderr(predicate::str::contains(
+            "the following required arguments were not provided:\n  --regression",
+        ))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--regression")
+        .arg("--junit=junit.xml")
+        .arg("yes_crash")
+        .assert()
+        .failure();
+
+    let junit = fs::read_to_string(project.root().join("junit.xml")).unwrap();
+    assert!(junit.contains("tests=\"2\" failures=\"1\""));
+    assert!(junit.contains("<testcase classname=\"yes_crash\" name=\"fuzz/corpus/yes_crash/fail\""));
+    assert!(junit.contains("<failure type=\"crash\""));
+}
+
+#[test]
+fn run_color() {
+    let project = project("run_color")
+        .with_fuzz()
+        .fuzz_target(
+            "color_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {
+                    panic!("crash");
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--color=always")
+        .arg("color_crash")
+        .assert()
+        .stderr(
+            predicate::str::contains("\x1b[1;31mFailing input:\x1b[0m").and(
+                predicate::str::contains(
+                    "\x1b[1mReproduce with:\x1b[0m\n\n\t\x1b[36mcargo fuzz run",
+                ),
+            ),
+        )
+        .failure();
+
+    // Without a terminal, the output stays plain.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("color_crash")
+        .assert()
+        .stderr(
+            predicate::str::contains("\nFailing input:\n")
+                .and(predicate::str::contains("\x1b[").not()),
+        )
+        .failure();
+}
+
+#[test]
+fn run_with_crash() {
+    let project = project("run_with_crash")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_with_crash::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("yes_crash")
+        .arg("--")
+        .arg("-runs=1000")
+        .env("RUST_BACKTRACE", "1")
+        .assert()
+        .stderr(
+            predicate::str::contains("thread '<unnamed>' panicked at")
+                .and(predicate::str::contains("I'm afraid of number 7"))
+                .and(predicate::str::contains("ERROR: libFuzzer: deadly signal"))
+                .and(predicate::str::contains("run_with_crash::fail_fuzzing"))
+                .and(predicate::str::contains(
+                    "Backtrace, with runtime frames folded:\n\
+                     \n\
+                     \t  ... "
+                ))
+                .and(predicate::str::is_match(r"\t> #\d+ run_with_crash::fail_fuzzing\n").unwrap())
+                .and(predicate::str::contains(
+                    "────────────────────────────────────────────────────────────────────────────────\n\
+                     \n\
+                     Failing input:\n\
+                     \n\
+                     \tfuzz/artifacts/yes_crash/crash-"
+                ))
+                .and(predicate::str::contains("Output of `std::fmt::Debug`:"))
+                .and(predicate::str::contains(
+                    "Reproduce with:\n\
+                     \n\
+                     \tcargo fuzz run yes_crash fuzz/artifacts/yes_crash/crash-"
+                ))
+                .and(predicate::str::contains(
+                    "Minimize test case with:\n\
+                     \n\
+                     \tcargo fuzz tmin yes_crash fuzz/artifacts/yes_crash/crash-"
+                )),
+        )
+        .failure();
+}
+
+#[test]
+fn run_writes_artifact_metadata() {
+    let project = project("run_writes_artifact_metadata")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_writes_artifact_metadata::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("yes_crash")
+        .arg("--")
+        .arg("-runs=1000")
+        .assert()
+        .failure();
+
+    let metadata = fs::read_dir(project.fuzz_dir().join("artifacts").join("yes_crash"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.to_str().unwrap().ends_with(".meta.json"))
+        .expect("a metadata file should be written next to the artifact");
+    let metadata = fs::read_to_string(metadata).unwrap();
+    assert!(metadata
+        .contains("\"reproduce\": \"cargo fuzz run yes_crash fuzz/artifacts/yes_crash/crash-"));
+    assert!(metadata.contains("\"seed\": "));
+    assert!(metadata.contains("I'm afraid of number 7"));
+}
+
+#[test]
+fn run_with_coverage() {
+    let target = "with_coverage";
+
+    let project = project("run_with_coverage")
+        .with_fuzz()
+        .fuzz_target(
+            target,
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    println!("{:?}", data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg(target)
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(predicate::str::contains("Done 100 runs"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg(target)
+        .assert()
+        .stderr(predicate::str::contains("Coverage data merged and saved"))
+        .success();
+
+    let profdata_file = project.fuzz_coverage_dir(target).join("coverage.profdata");
+    assert!(profdata_file.exists(), "Coverage data file not generated");
+}
+
+#[test]
+fn coverage_html() {
+    let target = "html";
+
+    let project = project("coverage_html")
+        .with_fuzz()
+        .fuzz_target(
+            target,
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    println!("{:?}", data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg(target)
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(predicate::str::contains("Done 100 runs"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("--html")
+        .arg(target)
+        .assert()
+        .stderr(predicate::str::contains("Coverage HTML report saved"))
+        .success();
+
+    let html_index = project.fuzz_coverage_dir(target).join("html/index.html");
+    assert!(html_index.exists(), "HTML coverage report not generated");
+
+    // `--html` is short for `--format=html`, so they can't be combined.
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("--html")
+        .arg("--format=lcov")
+        .arg(target)
+        .assert()
+        .stderr(predicate::str::contains("cannot be used with"))
+        .failure();
+}
+
+#[test]
+fn coverage_lcov() {
+    let target = "lcov";
+
+    let project = project("coverage_lcov")
+        .with_fuzz()
+        .fuzz_target(
+            target,
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    println!("{:?}", data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg(target)
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(predicate::str::contains("Done 100 runs"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("--format=lcov")
+        .arg("-o")
+        .arg("coverage.lcov")
+        .arg(target)
+        .assert()
+        .stderr(predicate::str::contains("Coverage LCOV tracefile saved"))
+        .success();
+
+    let lcov = fs::read_to_string(project.root().join("coverage.lcov")).unwrap();
+    assert!(lcov.contains("SF:"), "LCOV tracefile not generated");
+}
+
+#[test]
+fn cov_report() {
+    let target = "reported";
+
+    let project = project("cov_report")
+        .with_fuzz()
+        .fuzz_target(
+            target,
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    println!("{:?}", data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg(target)
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(predicate::str::contains("Done 100 runs"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg(target)
+        .assert()
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("cov")
+        .arg("report")
+        .arg(target)
+        .assert()
+        .stdout(predicate::str::contains("TOTAL"))
+        .success();
+}
+
+#[test]
+fn coverage_uncovered() {
+    let target = "uncovered";
+
+    let project = project("coverage_uncovered")
+        .with_fuzz()
+        .fuzz_target(
+            target,
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    println!("{:?}", data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg(target)
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(predicate::str::contains("Done 100 runs"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("--uncovered")
+        .arg(target)
+        .assert()
+        .stdout(predicate::str::contains("functions were"))
+        .success();
+}
+
+#[test]
+fn coverage_badge_json() {
+    let target = "badged";
+
+    let project = project("coverage_badge_json")
+        .with_fuzz()
+        .fuzz_target(
+            target,
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    println!("{:?}", data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg(target)
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(predicate::str::contains("Done 100 runs"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("--badge-json")
+        .arg("badge.json")
+        .arg(target)
+        .assert()
+        .stderr(predicate::str::contains("Coverage badge saved"))
+        .success();
+
+    let badge = fs::read_to_string(project.root().join("badge.json")).unwrap();
+    assert!(badge.contains("\"fuzz coverage\""), "Badge not generated");
+}
+
+#[test]
+fn coverage_fail_under() {
+    let target = "gated";
+
+    let project = project("coverage_fail_under")
+        .with_fuzz()
+        .fuzz_target(
+            target,
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    println!("{:?}", data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg(target)
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(predicate::str::contains("Done 100 runs"))
+        .success();
+
+    // The fuzz target doesn't call into the crate at all.
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("--fail-under=100")
+        .arg(target)
+        .assert()
+        .stderr(predicate::str::contains(
+            "is below the `--fail-under` threshold of 100%",
+        ))
+        .code(12);
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("--no-build")
+        .arg("--fail-under=0")
+        .arg(target)
+        .assert()
+        .success();
+}
+
+#[test]
+fn coverage_summary_json() {
+    let target = "summarized";
+
+    let project = project("coverage_summary_json")
+        .with_fuzz()
+        .fuzz_target(
+            target,
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    println!("{:?}", data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg(target)
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(predicate::str::contains("Done 100 runs"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("--summary-json")
+        .arg("summary.json")
+        .arg(target)
+        .assert()
+        .stderr(predicate::str::contains("Coverage summary saved"))
+        .success();
+
+    let summary = fs::read_to_string(project.root().join("summary.json")).unwrap();
+    assert!(summary.contains("\"totals\""), "JSON summary not generated");
+}
+
+#[test]
+fn coverage_no_build_without_build() {
+    let project = project("coverage_no_build_without_build")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("--no-build")
+        .arg("foo")
+        .assert()
+        .stderr(predicate::str::contains("no coverage build of `foo`"))
+        .failure();
+}
+
+#[test]
+fn coverage_history() {
+    let project = project("coverage_history")
+        .with_fuzz()
+        .fuzz_target(
+            "history",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    println!("{:?}", data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("cov")
+        .arg("history")
+        .arg("history")
+        .assert()
+        .stderr(predicate::str::contains(
+            "No recorded coverage of `history`",
+        ))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("history")
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .success();
+    // A fuzz target named `history` is covered like any other.
+    for _ in 0..2 {
+        project
+            .cargo_fuzz()
+            .arg("coverage")
+            .arg("history")
+            .assert()
+            .success();
+    }
+
+    project
+        .cargo_fuzz()
+        .arg("cov")
+        .arg("history")
+        .arg("history")
+        .assert()
+        .stdout(
+            predicate::str::is_match(r"(?m)^TIME +COMMIT +LINES +REGIONS +CHANGE\n(.*%.*\n){2}")
+                .unwrap()
+                .and(predicate::str::contains(
+                    "The line coverage of every file is unchanged",
+                )),
+        )
+        .success();
+    let history = fs::read_to_string(
+        project
+            .fuzz_dir()
+            .join("coverage")
+            .join(".history")
+            .join("history.jsonl"),
+    )
+    .unwrap();
+    assert_eq!(history.lines().count(), 2);
+}
+
+#[test]
+fn coverage_all() {
+    let project = project("coverage_all")
+        .with_fuzz()
+        .fuzz_target(
+            "covered",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    println!("{:?}", data);
+                });
+            "#,
+        )
+        .fuzz_target(
+            "not_run",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("covered")
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("--all")
+        .arg("--jobs=2")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Skipping `not_run`, its corpus is empty",
+        ))
+        .stdout(predicate::str::contains("TOTAL"))
+        .success();
+
+    let profdata_file = project
+        .fuzz_dir()
+        .join("coverage/all-targets/coverage.profdata");
+    assert!(profdata_file.exists(), "Merged coverage data not generated");
+}
+
+#[test]
+fn run_without_sanitizer_with_crash() {
+    let project = project("run_without_sanitizer_with_crash")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_without_sanitizer_with_crash::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("yes_crash")
+        .arg("--")
+        .arg("-runs=1000")
+        .arg("-sanitizer=none")
+        .env("RUST_BACKTRACE", "1")
+        .assert()
+        .stderr(
+            predicate::str::contains("thread '<unnamed>' panicked at")
+                .and(predicate::str::contains("I'm afraid of number 7"))
+                .and(predicate::str::contains("ERROR: libFuzzer: deadly signal"))
+                .and(predicate::str::contains("run_without_sanitizer_with_crash::fail_fuzzing"))
+                .and(predicate::str::contains(
+                    "────────────────────────────────────────────────────────────────────────────────\n\
+                     \n\
+                     Failing input:\n\
+                     \n\
+                     \tfuzz/artifacts/yes_crash/crash-"
+                ))
+                .and(predicate::str::contains("Output of `std::fmt::Debug`:"))
+                .and(predicate::str::contains(
+                    "Reproduce with:\n\
+                     \n\
+                     \tcargo fuzz run yes_crash fuzz/artifacts/yes_crash/crash-"
+                ))
+                .and(predicate::str::contains(
+                    "Minimize test case with:\n\
+                     \n\
+                     \tcargo fuzz tmin yes_crash fuzz/artifacts/yes_crash/crash-"
+                )),
+        )
+        .failure();
+}
+
+// TODO: these msan tests are crashing `rustc` in CI:
+// https://github.com/rust-fuzz/cargo-fuzz/issues/323
+//
+// #[test]
+// fn run_with_msan_no_crash() {
+//     let project = project("run_with_msan_no_crash")
+//         .with_fuzz()
+//         .fuzz_target(
+//             "msan_no_crash",
+//             r#"
+//                 #![no_main]
+//                 use libfuzzer_sys::fuzz_target;
+//
+//                 fuzz_target!(|data: &[u8]| {
+//                     // get data from fuzzer and print it
+//                     // to force a memory access that cannot be optimized out
+//                     if let Some(x) = data.get(0) {
+//                         dbg!(x);
+//                     }
+//                 });
+//             "#,
+//         )
+//         .build();
+//
+//     project
+//         .cargo_fuzz()
+//         .arg("run")
+//         .arg("--sanitizer=memory")
+//         .arg("msan_no_crash")
+//         .arg("--")
+//         .arg("-runs=1000")
+//         .assert()
+//         .stderr(predicate::str::contains("Done 1000 runs"))
+//         .success();
+// }
+//
+// #[test]
+// fn run_with_msan_with_crash() {
+//     let project = project("run_with_msan_with_crash")
+//         .with_fuzz()
+//         .fuzz_target(
+//             "msan_with_crash",
+//             r#"
+//                 #![no_main]
+//                 use libfuzzer_sys::fuzz_target;
+//
+//                 fuzz_target!(|data: &[u8]| {
+//                     let test_data: Vec<u8> = Vec::with_capacity(4);
+//                     let uninitialized_value = unsafe {test_data.get_unchecked(0)};
+//                     // prevent uninit read from being optimized out
+//                     println!("{}", uninitialized_value);
+//                 });
+//             "#,
+//         )
+//         .build();
+//
+//     project
+//         .cargo_fuzz()
+//         .arg("run")
+//         .arg("--sanitizer=memory")
+//         .arg("msan_with_crash")
+//         .arg("--")
+//         .arg("-runs=1000")
+//         .assert()
+//         .stderr(
+//             predicate::str::contains("MemorySanitizer: use-of-uninitialized-value")
+//                 .and(predicate::str::contains(
+//                     "Reproduce with:\n\
+//                 \n\
+//                 \tcargo fuzz run --sanitizer=memory msan_with_crash fuzz/artifacts/msan_with_crash/crash-",
+//                 ))
+//                 .and(predicate::str::contains(
+//                     "Minimize test case with:\n\
+//                 \n\
+//                 \tcargo fuzz tmin --sanitizer=memory msan_with_crash fuzz/artifacts/msan_with_crash/crash-",
+//                 )),
+//         )
+//         .failure();
+// }
+
+#[test]
+fn run_one_input() {
+    let corpus = Path::new("fuzz").join("corpus").join("run_one");
+
+    let project = project("run_one_input")
+        .with_fuzz()
+        .fuzz_target(
+            "run_one",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    assert!(data.is_empty());
+                });
+            "#,
+        )
+        .file(corpus.join("pass"), "")
+        .file(corpus.join("fail"), "not empty")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("run_one")
+        .arg(corpus.join("pass"))
+        .assert()
+        .stderr(
+            predicate::str::contains("Running 1 inputs 1 time(s) each.").and(
+                predicate::str::contains("Running: fuzz/corpus/run_one/pass"),
+            ),
+        )
+        .success();
+}
+
+#[test]
+fn run_a_few_inputs() {
+    let corpus = Path::new("fuzz").join("corpus").join("run_few");
+
+    let project = project("run_a_few_inputs")
+        .with_fuzz()
+        .fuzz_target(
+            "run_few",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    assert!(data.len() != 4);
+                });
+            "#,
+        )
+        .file(corpus.join("pass-0"), "")
+        .file(corpus.join("pass-1"), "1")
+        .file(corpus.join("pass-2"), "12")
+        .file(corpus.join("pass-3"), "123")
+        .file(corpus.join("fail"), "fail")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("run_few")
+        .arg(corpus.join("pass-0"))
+        .arg(corpus.join("pass-1"))
+        .arg(corpus.join("pass-2"))
+        .arg(corpus.join("pass-3"))
+        .assert()
+        .stderr(
+            predicate::str::contains("Running 4 inputs 1 time(s) each.").and(
+                predicate::str::contains("Running: fuzz/corpus/run_few/pass"),
+            ),
+        )
+        .success();
+}
+
+#[test]
+fn run_alt_corpus() {
+    let corpus = Path::new("fuzz").join("corpus").join("run_alt");
+    let alt_corpus = Path::new("fuzz").join("alt-corpus").join("run_alt");
+
+    let project = project("run_alt_corpus")
+        .with_fuzz()
+        .fuzz_target(
+            "run_alt",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    assert!(data.len() <= 1);
+                });
+            "#,
+        )
+        .file(corpus.join("fail"), "fail")
+        .file(alt_corpus.join("pass-0"), "0")
+        .file(alt_corpus.join("pass-1"), "1")
+        .file(alt_corpus.join("pass-2"), "2")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("run_alt")
+        .arg(&alt_corpus)
+        .arg("--")
+        .arg("-runs=0")
+        .assert()
+        .stderr(
+            predicate::str::contains("3 files found in fuzz/alt-corpus/run_alt")
+                .and(predicate::str::contains("fuzz/corpus/run_alt").not())
+                // libFuzzer will always test the empty input, so the number of
+                // runs performed is always one more than the number of files in
+                // the corpus.
+                .and(predicate::str::contains("Done 4 runs in")),
+        )
+        .success();
+}
+
+#[test]
+fn debug_fmt() {
+    let corpus = Path::new("fuzz").join("corpus").join("debugfmt");
+    let project = project("debugfmt")
+        .with_fuzz()
+        .fuzz_target(
+            "debugfmt",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+                use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured, Result};
+
+                #[derive(Debug)]
+                pub struct Rgb {
+                    r: u8,
+                    g: u8,
+                    b: u8,
+                }
+
+                impl<'a> Arbitrary<'a> for Rgb {
+                    fn arbitrary(raw: &mut Unstructured<'a>) -> Result<Self> {
+                        let mut buf = [0; 3];
+                        raw.fill_buffer(&mut buf)?;
+                        let r = buf[0];
+                        let g = buf[1];
+                        let b = buf[2];
+                        Ok(Rgb { r, g, b })
+                    }
+                }
+
+                fuzz_target!(|data: Rgb| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "111")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("fmt")
+        .arg("debugfmt")
+        .arg("fuzz/corpus/debugfmt/0")
+        .assert()
+        .stderr(predicates::str::contains(
+            "
+Rgb {
+    r: 49,
+    g: 49,
+    b: 49,
+}",
+        ))
+        .success();
+}
+
+#[test]
+fn mutate() {
+    let project = project("mutate")
+        .with_fuzz()
+        .fuzz_target(
+            "mutate_typed",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: (u8, bool)| {
+                    panic!("mutations are only decoded, not run");
+                });
+            "#,
+        )
+        .fuzz_target(
+            "mutate_bytes",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file("seed", "ab")
+        .build();
+
+    let mutate = |target: &str| {
+        project
+            .cargo_fuzz()
+            .arg("mutate")
+            .arg("-n5")
+            .arg("--seed=1")
+            .arg(target)
+            .arg("seed")
+            .assert()
+            .success()
+    };
+
+    let typed = mutate("mutate_typed");
+    let stdout = String::from_utf8_lossy(&typed.get_output().stdout).into_owned();
+    assert!(
+        stdout.starts_with(
+            "==> seed (2 bytes) <==\n\n\
+             00000000  61 62                                             |ab|\n\
+             \n\
+             (\n    97,\n    false,\n)\n"
+        ),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("\n==> mutant 1 of 5 ("), "{}", stdout);
+    assert!(stdout.contains("\n==> mutant 5 of 5 ("), "{}", stdout);
+    assert!(!stdout.contains("mutant 6"), "{}", stdout);
+    // The same seed makes the same mutations.
+    mutate("mutate_typed").stdout(stdout);
+
+    mutate("mutate_bytes").stdout(
+        predicate::str::contains("==> mutant 5 of 5 (").and(predicate::str::contains("97,").not()),
+    );
+
+    project
+        .cargo_fuzz()
+        .arg("mutate")
+        .arg("mutate_bytes")
+        .arg("no-such-input")
+        .assert()
+        .stderr(predicate::str::contains("failed to read no-such-input"))
+        .failure();
+}
+
+#[test]
+fn fmt_all_artifacts() {
+    let artifacts = Path::new("fuzz").join("artifacts").join("foo");
+    let project = project("fmt_all_artifacts")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: (u8, bool)| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(artifacts.join("crash-1"), "\x01\x01")
+        .file(artifacts.join("crash-2"), "\x02\x00")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("fmt")
+        .arg("--all-artifacts")
+        .arg("foo")
+        .assert()
+        .stderr(
+            predicate::str::contains(
+                "==> fuzz/artifacts/foo/crash-1 <==\n\n(\n    1,\n    true,\n)",
+            )
+            .and(predicate::str::contains(
+                "==> fuzz/artifacts/foo/crash-2 <==\n\n(\n    2,\n    false,\n)",
+            )),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("fmt")
+        .arg("--output=json")
+        .arg("--all-artifacts")
+        .arg("foo")
+        .assert()
+        .stdout(
+            "{\"debug\":\"(\\n    1,\\n    true,\\n)\",\"error\":null,\"len\":2,\
+             \"path\":\"fuzz/artifacts/foo/crash-1\"}\n\
+             {\"debug\":\"(\\n    2,\\n    false,\\n)\",\"error\":null,\"len\":2,\
+             \"path\":\"fuzz/artifacts/foo/crash-2\"}\n",
+        )
+        .success();
+}
+
+#[test]
+fn fmt_diff() {
+    let project = project("fmt_diff")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: (u8, bool, u8)| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file("old", "\x01\x01\x05")
+        .file("new", "\x01\x00\x05")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("fmt")
+        .arg("--diff")
+        .arg("--output=stdout")
+        .arg("foo")
+        .arg("old")
+        .arg("new")
+        .assert()
+        .stdout(
+            "--- old
++++ new
+ (
+     1,
+-    true,
++    false,
+     5,
+ )
+",
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("fmt")
+        .arg("--diff")
+        .arg("foo")
+        .arg("old")
+        .arg("old")
+        .assert()
+        .stderr(predicate::str::contains(
+            "The `std::fmt::Debug` output of both inputs is the same",
+        ))
+        .success();
+}
+
+#[test]
+fn fmt_no_build_without_build() {
+    let project = project("fmt_no_build_without_build")
+        .with_fuzz()
+        .fuzz_target(
+            "never_built",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file("input", "x")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("fmt")
+        .arg("--no-build")
+        .arg("never_built")
+        .arg("input")
+        .assert()
+        .stderr(predicate::str::contains("no build of `never_built`"))
+        .failure();
+}
+
+#[test]
+fn fmt_hexdump_fallback() {
+    let project = project("fmt_hexdump_fallback")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: u32| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file("short", "ab")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("fmt")
+        .arg("foo")
+        .arg("short")
+        .assert()
+        .stderr(
+            predicate::str::contains("Hexdump of the input (2 bytes)").and(
+                predicate::str::contains(
+                    "00000000  61 62                                             |ab|",
+                ),
+            ),
+        )
+        .success();
+}
+
+#[test]
+fn fmt_emit_test() {
+    let input = Path::new("fuzz")
+        .join("artifacts")
+        .join("foo")
+        .join("crash-1");
+    let project = project("fmt_emit_test")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data == b"x\n" {
+                        panic!();
+                    }
+                });
+            "#,
+        )
+        .file(&input, "x\n")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("fmt")
+        .arg("--emit-test")
+        .arg("foo")
+        .arg(&input)
+        .assert()
+        .stdout(predicate::str::diff(
+            "#[test]
+fn fuzz_foo_6fcf9dfb() {
+    // Regression test for fuzz target `foo`, from fuzz/artifacts/foo/crash-1
+    let data: &[u8] = b\"x\\n\";
+    if data == b\"x\\n\" {
+        panic!();
+    }
+}
+",
+        ))
+        .success();
+}
+
+#[test]
+fn cmin() {
+    let corpus = Path::new("fuzz").join("corpus").join("foo");
+    let project = project("cmin")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "")
+        .file(corpus.join("1"), "a")
+        .file(corpus.join("2"), "ab")
+        .file(corpus.join("3"), "abc")
+        .file(corpus.join("4"), "abcd")
+        .build();
+
+    let corpus_count = || {
+        fs::read_dir(project.root().join("fuzz").join("corpus").join("foo"))
+            .unwrap()
+            .count()
+    };
+    assert_eq!(corpus_count(), 5);
+
+    project
+        .cargo_fuzz()
+        .arg("cmin")
+        .arg("foo")
+        .assert()
+        .stderr(predicate::str::contains("Original corpus backed up to"))
+        .success();
+    assert_eq!(corpus_count(), 1);
+
+    // The original corpus is kept next to the minimized one.
+    let backups: Vec<_> = fs::read_dir(project.root().join("fuzz").join("corpus"))
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.starts_with("foo.bak-"))
+        .collect();
+    assert_eq!(backups.len(), 1);
+    let backup = project.root().join("fuzz").join("corpus").join(&backups[0]);
+    assert_eq!(fs::read_dir(backup).unwrap().count(), 5);
+}
+
+#[test]
+fn cmin_no_backup() {
+    let corpus = Path::new("fuzz").join("corpus").join("foo");
+    let project = project("cmin_no_backup")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "")
+        .file(corpus.join("1"), "a")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("cmin")
+        .arg("--no-backup")
+        .arg("foo")
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_dir(project.root().join("fuzz").join("corpus"))
+            .unwrap()
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn corpus_cross_pollinate() {
+    let corpus = Path::new("fuzz").join("corpus").join("from");
+    let project = project("corpus_cross_pollinate")
+        .with_fuzz()
+        .fuzz_target(
+            "from",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .fuzz_target(
+            "to",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.first() == Some(&b'a') {
+                        let _ = data.len();
+                    }
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "a")
+        .file(corpus.join("1"), "b")
+        .build();
+
+    let to_corpus = project.root().join("fuzz").join("corpus").join("to");
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("cross-pollinate")
+        .arg("from")
+        .arg("to")
+        .assert()
+        .success();
+    assert!(fs::read_dir(&to_corpus).unwrap().count() >= 1);
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("cross-pollinate")
+        .arg("to")
+        .arg("to")
+        .assert()
+        .stderr(predicate::str::contains(
+            "cannot cross-pollinate the corpus of `to` with itself",
+        ))
+        .failure();
+}
+
+#[test]
+fn corpus_dedup() {
+    let corpus = Path::new("fuzz").join("corpus").join("foo");
+    let project = project("corpus_dedup")
+        .with_fuzz()
+        .file(corpus.join("a"), "same")
+        .file(corpus.join("b"), "same")
+        .file(corpus.join("c"), "different")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("dedup")
+        .arg("foo")
+        .arg("--rename")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Removed 1 duplicate inputs (4 bytes)",
+        ))
+        .success();
+
+    let mut names: Vec<_> = fs::read_dir(project.root().join(corpus))
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            // sha1("different")
+            "517d5a7240861ec297fa07542a7bf7470bb604fe",
+            // sha1("same")
+            "ff3390557335ba88d37755e41514beb03bc499ec",
+        ]
+    );
+}
+
+#[test]
+fn corpus_import_afl() {
+    let afl = Path::new("afl_out").join("default");
+    let project = project("corpus_import_afl")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.first() == Some(&b's') {
+                        std::hint::black_box(data);
+                    }
+                });
+            "#,
+        )
+        .file(afl.join("queue").join("id:000000,orig:seed"), "a")
+        .file(
+            afl.join("queue")
+                .join(".state")
+                .join("redundant_edges")
+                .join("id:000000,orig:seed"),
+            "state",
+        )
+        .file(afl.join("crashes").join("README.txt"), "readme")
+        .file(afl.join("crashes").join("id:000000,sig:06"), "boom")
+        .file(afl.join("hangs").join("id:000000"), "slow")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("import-afl")
+        .arg("afl_out")
+        .arg("foo")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Imported the queue of 1 AFL instance(s) and 2 new crash/hang artifact(s)",
+        ))
+        .success();
+
+    // Only the inputs of the queue are merged, not AFL's bookkeeping.
+    let corpus = project.root().join("fuzz").join("corpus").join("foo");
+    let inputs: Vec<_> = fs::read_dir(corpus)
+        .unwrap()
+        .map(|e| fs::read(e.unwrap().path()).unwrap())
+        .collect();
+    assert!(!inputs.is_empty());
+    assert!(!inputs.contains(&b"state".to_vec()));
+    let mut artifacts: Vec<_> =
+        fs::read_dir(project.root().join("fuzz").join("artifacts").join("foo"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+    artifacts.sort();
+    assert_eq!(artifacts.len(), 2);
+    assert!(artifacts[0].starts_with("crash-"));
+    assert!(artifacts[1].starts_with("timeout-"));
+}
+
+#[test]
+fn triage() {
+    let artifacts = Path::new("fuzz").join("artifacts").join("foo");
+    let project = project("triage")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fn check_a(data: &[u8]) {
+                    if data.first() == Some(&b'a') {
+                        panic!("a");
+                    }
+                }
+
+                fn check_b(data: &[u8]) {
+                    if data.first() == Some(&b'b') {
+                        panic!("b");
+                    }
+                }
+
+                fuzz_target!(|data: &[u8]| {
+                    check_a(data);
+                    check_b(data);
+                });
+            "#,
+        )
+        .file(artifacts.join("crash-1"), "a")
+        .file(artifacts.join("crash-2"), "aaaa")
+        .file(artifacts.join("crash-3"), "b")
+        .file(artifacts.join("crash-4"), "c")
+        // The result of `cargo fuzz tmin` reproduces `crash-1` and isn't
+        // counted again.
+        .file(artifacts.join("minimized-crash-1"), "a")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("triage")
+        .arg("foo")
+        .assert()
+        .stdout(
+            predicate::str::contains("Found 2 unique crash(es) among 4 artifact(s)")
+                .and(predicate::str::contains("] 2 artifact(s)"))
+                .and(predicate::str::contains(
+                    "representative: fuzz/artifacts/foo/crash-1",
+                ))
+                .and(predicate::str::contains(
+                    "representative: fuzz/artifacts/foo/crash-3",
+                ))
+                .and(predicate::str::contains("1 artifact(s) did not reproduce"))
+                .and(predicate::str::contains("fuzz/artifacts/foo/crash-4")),
+        )
+        .success();
+}
+
+#[test]
+fn triage_and_verify_without_artifacts() {
+    let project = project("triage_and_verify_without_artifacts")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
+
+    // Nothing to replay isn't an error for either of them.
+    for command in ["triage", "verify"] {
+        project
+            .cargo_fuzz()
+            .arg(command)
+            .arg("foo")
+            .assert()
+            .stderr(predicate::str::contains("No artifacts found in"))
+            .success();
+    }
+}
+
+#[test]
+fn verify() {
+    let artifacts = Path::new("fuzz").join("artifacts").join("foo");
+    let project = project("verify")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data == b"crash" {
+                        panic!();
+                    }
+                });
+            "#,
+        )
+        .file(artifacts.join("crash-1"), "crash")
+        .file(artifacts.join("crash-2"), "fixed")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("verify")
+        .arg("--move-fixed")
+        .arg("--junit=junit.xml")
+        .arg("foo")
+        .assert()
+        .stdout(
+            predicate::str::contains("Fixed: 1")
+                .and(predicate::str::contains("Still crashing: 1"))
+                .and(predicate::str::contains("fuzz/artifacts/foo/crash-1")),
+        )
+        .stderr(predicate::str::contains("1 artifact(s) still crash"))
+        .code(11);
+
+    assert!(project.root().join(&artifacts).join("crash-1").is_file());
+    assert!(!project.root().join(&artifacts).join("crash-2").exists());
+
+    let junit = fs::read_to_string(project.root().join("junit.xml")).unwrap();
+    assert!(junit.contains("tests=\"2\" failures=\"1\""));
+    assert!(junit.contains("<testcase classname=\"foo\" name=\"crash-1\""));
+    assert!(junit.contains("<failure type=\"crash\""));
+    assert_eq!(
+        fs::read(
+            project
+                .root()
+                .join("fuzz")
+                .join("corpus")
+                .join("foo")
+                .join("7bc555991dbab41aa198fbd77899d49a277d4d6d")
+        )
+        .ok(),
+        Some(b"fixed".to_vec())
+    );
+}
+
+#[test]
+fn verify_corpus() {
+    let corpus = Path::new("fuzz").join("corpus").join("foo");
+    let project = project("verify_corpus")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data == b"crash" {
+                        panic!();
+                    }
+                });
+            "#,
+        )
+        .file(corpus.join("crashes"), "crash")
+        .file(corpus.join("passes"), "pass")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("verify")
+        .arg("--corpus")
+        .arg("--junit=junit.xml")
+        .arg("foo")
+        .assert()
+        .stdout(
+            predicate::str::contains("Crashing corpus inputs: 1")
+                .and(predicate::str::contains("fuzz/corpus/foo/crashes")),
+        )
+        .stderr(predicate::str::contains("1 input(s) of the corpus crash"))
+        .code(11);
+
+    let junit = fs::read_to_string(project.root().join("junit.xml")).unwrap();
+    assert!(junit.contains("tests=\"2\" failures=\"1\""));
+    assert!(junit.contains("<testcase classname=\"foo\" name=\"corpus/crashes\""));
+    assert!(junit.contains("<testcase classname=\"foo\" name=\"corpus/passes\""));
+}
+
+#[test]
+fn artifacts_list() {
+    let artifacts = Path::new("fuzz").join("artifacts").join("foo");
+    let project = project("artifacts_list")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file(artifacts.join("crash-1"), "abc")
+        .file(artifacts.join("timeout-2"), "abcdef")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("artifacts")
+        .arg("list")
+        .arg("foo")
+        .assert()
+        .stdout(
+            predicate::str::is_match(r"crash\s+3\s+\d+s\s+fuzz/artifacts/foo/crash-1")
+                .unwrap()
+                .and(
+                    predicate::str::is_match(r"timeout\s+6\s+\d+s\s+fuzz/artifacts/foo/timeout-2")
+                        .unwrap(),
+                ),
+        )
+        .success();
+}
+
+#[test]
+fn artifacts_prune() {
+    let artifacts = Path::new("fuzz").join("artifacts").join("foo");
+    let project = project("artifacts_prune")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file(artifacts.join("crash-1"), "abc")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("artifacts")
+        .arg("prune")
+        .arg("foo")
+        .assert()
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("artifacts")
+        .arg("prune")
+        .arg("--older-than=0")
+        .arg("--dry-run")
+        .arg("foo")
+        .assert()
+        .stdout(predicate::str::contains(
+            "Would delete fuzz/artifacts/foo/crash-1",
+        ))
+        .success();
+    assert!(project.root().join(&artifacts).join("crash-1").exists());
+
+    // Ages beyond what the clock can go back to keep every artifact.
+    for days in [u64::MAX, 1_000_000_000_000] {
+        project
+            .cargo_fuzz()
+            .arg("artifacts")
+            .arg("prune")
+            .arg(format!("--older-than={}", days))
+            .arg("--dry-run")
+            .arg("foo")
+            .assert()
+            .stdout(predicate::str::contains("Would delete").not())
+            .success();
+    }
+
+    project
+        .cargo_fuzz()
+        .arg("artifacts")
+        .arg("prune")
+        .arg("--older-than=0")
+        .arg("foo")
+        .assert()
+        .stderr(predicate::str::contains("Pruned 1 of 1 artifact(s)"))
+        .success();
+    assert!(!project.root().join(&artifacts).join("crash-1").exists());
+}
+
+#[test]
+fn artifacts_archive() {
+    let artifacts = Path::new("fuzz").join("artifacts").join("foo");
+    let project = project("artifacts_archive")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file(artifacts.join("crash-1"), "abc")
+        .file(artifacts.join("crash-2"), "def")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("artifacts")
+        .arg("archive")
+        .arg("--output=crashes.tar.gz")
+        .arg("foo")
+        .arg("crash-2")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Archived 1 artifact(s) to crashes.tar.gz",
+        ))
+        .success();
+    assert!(project.root().join("crashes.tar.gz").is_file());
+
+    project
+        .cargo_fuzz()
+        .arg("artifacts")
+        .arg("archive")
+        .arg("foo")
+        .arg("crash-3")
+        .assert()
+        .stderr(predicate::str::contains("no artifact named `crash-3`"))
+        .failure();
+}
+
+#[test]
+fn bundle() {
+    let artifacts = Path::new("fuzz").join("artifacts").join("foo");
+    let project = project("bundle")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data == b"crash" {
+                        panic!("boom");
+                    }
+                });
+            "#,
+        )
+        .file(artifacts.join("crash-1"), "crash")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("bundle")
+        .arg("--sanitizer=none")
+        .arg("--dockerfile")
+        .arg("--output=bundle.tar.gz")
+        .arg("foo")
+        .arg(artifacts.join("crash-1"))
+        .assert()
+        .stderr(predicate::str::contains(
+            "Bundled crash-1 with metadata.json, debug.txt, backtrace.txt, Dockerfile to \
+             bundle.tar.gz",
+        ))
+        .success();
+
+    let archive = fs::File::open(project.root().join("bundle.tar.gz")).unwrap();
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+    let mut files = BTreeMap::new();
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents).unwrap();
+        files.insert(entry.path().unwrap().display().to_string(), contents);
+    }
+    assert_eq!(
+        files.keys().collect::<Vec<_>>(),
+        [
+            "foo-crash-1/Dockerfile",
+            "foo-crash-1/backtrace.txt",
+            "foo-crash-1/crash-1",
+            "foo-crash-1/debug.txt",
+            "foo-crash-1/metadata.json",
+        ]
+    );
+    assert_eq!(files["foo-crash-1/crash-1"], "crash");
+    assert!(files["foo-crash-1/backtrace.txt"].contains("boom"));
+    let metadata: serde_json::Value =
+        serde_json::from_str(&files["foo-crash-1/metadata.json"]).unwrap();
+    assert_eq!(metadata["reproduces"], true);
+    assert_eq!(metadata["target"], "foo");
+    assert!(files["foo-crash-1/Dockerfile"]
+        .contains("CMD cargo fuzz run --sanitizer=none foo /crash/crash-1\n"));
+}
+
+#[test]
+fn repro() {
+    let artifacts = Path::new("fuzz").join("artifacts").join("foo");
+    let project = project("repro")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    #[cfg(fuzzing_repro)]
+                    eprintln!("reproducing with {} bytes", data.len());
+                    if data == b"crash" {
+                        panic!("boom");
+                    }
+                });
+            "#,
+        )
+        .file(artifacts.join("crash-1"), "crash")
+        .file(artifacts.join("crash-2"), "fixed")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("repro")
+        .arg("foo")
+        .arg(artifacts.join("crash-1"))
+        .assert()
+        .stderr(
+            predicate::str::contains("Output of `std::fmt::Debug`:")
+                .and(predicate::str::contains("reproducing with 5 bytes"))
+                .and(predicate::str::contains("panicked at"))
+                .and(predicate::str::contains("boom")),
+        )
+        .code(11);
+
+    project
+        .cargo_fuzz()
+        .arg("repro")
+        .arg("foo")
+        .arg(artifacts.join("crash-2"))
+        .assert()
+        .stderr(predicate::str::contains(
+            "fuzz/artifacts/foo/crash-2 did not crash `foo`",
+        ))
+        .success();
+}
+
+#[test]
+fn promote() {
+    let artifact = Path::new("fuzz")
+        .join("artifacts")
+        .join("foo")
+        .join("crash-1");
+    let project = project("promote")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file(&artifact, "fixed")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("promote")
+        .arg("foo")
+        .arg(&artifact)
+        .assert()
+        .stderr(predicate::str::contains(
+            "Promoted fuzz/artifacts/foo/crash-1 to \
+             fuzz/corpus/foo/7bc555991dbab41aa198fbd77899d49a277d4d6d",
+        ))
+        .success();
+
+    assert!(!project.root().join(&artifact).exists());
+    assert_eq!(
+        fs::read(
+            project
+                .root()
+                .join("fuzz")
+                .join("corpus")
+                .join("foo")
+                .join("7bc555991dbab41aa198fbd77899d49a277d4d6d")
+        )
+        .unwrap(),
+        b"fixed"
+    );
+}
+
+#[test]
+fn report() {
+    let fuzz = Path::new("fuzz");
+    let artifacts = fuzz.join("artifacts").join("foo");
+    let project = project("report")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file(fuzz.join("corpus").join("foo").join("0"), "ab")
+        .file(fuzz.join("corpus").join("foo").join("1"), "cde")
+        .file(
+            fuzz.join("stats").join("foo").join("100.json"),
+            r#"{"started": 100, "elapsed_secs": 90, "execs": 5000, "cov": 42,
+                "features": 60, "corpus_inputs": 2, "corpus_bytes": 5}"#,
+        )
+        .file(artifacts.join("crash-1"), "x")
+        .file(
+            artifacts.join("crash-1.meta.json"),
+            r#"{"reproduce": "cargo fuzz run -O foo fuzz/artifacts/foo/crash-1",
+                "backtrace": "thread '<unnamed>' panicked at src/lib.rs:3:5:\nboom\n"}"#,
+        )
+        .file(artifacts.join("crash-2"), "y")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("report")
+        .assert()
+        .stdout(
+            predicate::str::contains("## `foo`")
+                .and(predicate::str::contains("| Corpus | 2 inputs, 5 bytes |"))
+                .and(predicate::str::contains("| Fuzzing time | 1m 30s |"))
+                .and(predicate::str::contains("| Edge coverage | 42 |"))
+                .and(predicate::str::contains("| Unique crashes | 2 |"))
+                .and(predicate::str::contains(", panicked at src/lib.rs:3:5"))
+                .and(predicate::str::contains(
+                    "cargo fuzz run -O foo fuzz/artifacts/foo/crash-1",
+                ))
+                .and(predicate::str::contains("### Artifacts without metadata"))
+                .and(predicate::str::contains(
+                    "cargo fuzz run foo fuzz/artifacts/foo/crash-2",
+                )),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("report")
+        .arg("--format=html")
+        .arg("--output=report.html")
+        .assert()
+        .success();
+    let html = fs::read_to_string(project.root().join("report.html")).unwrap();
+    assert!(html.contains("<h2><code>foo</code></h2>"));
+}
+
+#[test]
+fn tmin() {
+    let corpus = Path::new("fuzz").join("corpus").join("i_hate_zed");
+    let test_case = corpus.join("test-case");
+    let project = project("tmin")
+        .with_fuzz()
+        .fuzz_target(
+            "i_hate_zed",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let s = String::from_utf8_lossy(data);
+                    if s.contains('z') {
+                        panic!("nooooooooo");
+                    }
+                });
+            "#,
+        )
+        .file(&test_case, "pack my box with five dozen liquor jugs")
+        .build();
+    let test_case = project.root().join(test_case);
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("i_hate_zed")
+        .arg("--sanitizer=none")
+        .arg(&test_case)
+        .assert()
+        .stderr(
+            predicates::str::contains("CRASH_MIN: minimizing crash input: ")
+                .and(predicate::str::contains("(1 bytes) caused a crash"))
+                .and(predicate::str::contains(
+                    "────────────────────────────────────────────────────────────────────────────────\n\
+                     \n\
+                     Minimized artifact:\n\
+                     \n\
+                     \tfuzz/artifacts/i_hate_zed/minimized-test-case\n"))
+                .and(predicate::str::contains(
+                    "Reproduce with:\n\
+                     \n\
+                     \tcargo fuzz run --sanitizer=none i_hate_zed fuzz/artifacts/i_hate_zed/minimized-test-case\n"
+                )),
+        )
+        .success();
+    assert_eq!(
+        fs::read(
+            project
+                .fuzz_dir()
+                .join("artifacts/i_hate_zed/minimized-test-case")
+        )
+        .unwrap(),
+        b"z"
+    );
+}
+
+#[test]
+fn tmin_output() {
+    let test_case = Path::new("fuzz")
+        .join("corpus")
+        .join("i_hate_zed")
+        .join("test-case");
+    let project = project("tmin_output")
+        .with_fuzz()
+        .fuzz_target(
+            "i_hate_zed",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let s = String::from_utf8_lossy(data);
+                    if s.contains('z') {
+                        panic!("nooooooooo");
+                    }
+                });
+            "#,
+        )
+        .file(&test_case, "pack my box with five dozen liquor jugs")
+        .build();
+    let test_case = project.root().join(test_case);
+
+    // An explicit output file, and a directory to write into.
+    let output = project.root().join("min.bin");
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("i_hate_zed")
+        .arg("--sanitizer=none")
+        .arg("-o")
+        .arg(&output)
+        .arg(&test_case)
+        .assert()
+        .stderr(predicate::str::contains(
+            "Minimized artifact:\n\n\tmin.bin\n",
+        ))
+        .success();
+    assert_eq!(fs::read(&output).unwrap(), b"z");
+
+    let output_dir = project.root().join("minimized");
+    fs::create_dir(&output_dir).unwrap();
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("i_hate_zed")
+        .arg("--sanitizer=none")
+        .arg("--output")
+        .arg(&output_dir)
+        .arg(&test_case)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read(output_dir.join("minimized-test-case")).unwrap(),
+        b"z"
+    );
+}
+
+#[test]
+fn tmin_timeout() {
+    let test_case = Path::new("fuzz").join("test-case");
+    let project = project("tmin_timeout")
+        .with_fuzz()
+        .fuzz_target(
+            "i_hate_zed",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.contains(&b'z') {
+                        std::thread::sleep(std::time::Duration::from_secs(5));
+                    }
+                });
+            "#,
+        )
+        .file(&test_case, "pack my box with five dozen liquor jugs")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("--kind=timeout")
+        .arg("--sanitizer=none")
+        .arg("i_hate_zed")
+        .arg(project.root().join(&test_case))
+        .arg("--")
+        .arg("-timeout=1")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Minimized artifact:\n\n\tfuzz/artifacts/i_hate_zed/timeout-minimized-test-case\n",
+        ))
+        .success();
+    assert_eq!(
+        fs::read(
+            project
+                .fuzz_dir()
+                .join("artifacts/i_hate_zed/timeout-minimized-test-case")
+        )
+        .unwrap(),
+        b"z"
+    );
+}
+
+#[test]
+fn tmin_external() {
+    let test_case = Path::new("fuzz").join("test-case");
+    let project = project("tmin_external")
+        .with_fuzz()
+        .fuzz_target(
+            "i_hate_zed",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.contains(&b'z') {
+                        panic!("nooooooooo");
+                    }
+                });
+            "#,
+        )
+        .file(&test_case, "pack my box with five dozen liquor jugs")
+        // A minimizer that only knows a few candidates, passing some of them
+        // on stdin and the last one as a file.
+        .file(
+            "minimize.sh",
+            r#"
+                printf q | "$1" && exit 1
+                printf zz | "$1" || exit 1
+                printf z > "$2.candidate"
+                "$1" "$2.candidate" && mv "$2.candidate" "$2"
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("--sanitizer=none")
+        .arg("--external")
+        .arg(format!(
+            "sh {} {{test}} {{input}}",
+            project.root().join("minimize.sh").display()
+        ))
+        .arg("i_hate_zed")
+        .arg(&test_case)
+        .assert()
+        .stderr(predicate::str::contains(
+            "Minimized artifact, from 39 to 1 bytes:\n\n\tfuzz/artifacts/i_hate_zed/minimized-test-case\n",
+        ))
+        .success();
+    assert_eq!(
+        fs::read(
+            project
+                .fuzz_dir()
+                .join("artifacts/i_hate_zed/minimized-test-case")
+        )
+        .unwrap(),
+        b"z"
+    );
+}
+
+#[test]
+fn tmin_keeps_the_crash() {
+    let test_case = Path::new("fuzz").join("test-case");
+    let project = project("tmin_keeps_the_crash")
+        .with_fuzz()
+        .fuzz_target(
+            "i_hate_zed",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.contains(&b'z') {
+                        if data.len() >= 10 {
+                            panic!("the bug we're after");
+                        }
+                        panic!("another bug");
+                    }
+                });
+            "#,
+        )
+        .file(&test_case, "pack my box with five dozen liquor jugs")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("--sanitizer=none")
+        .arg("i_hate_zed")
+        .arg(&test_case)
+        .assert()
+        .stderr(
+            predicate::str::contains("so it's likely a different bug").and(
+                predicate::str::contains("Minimizing again, keeping the stack hash"),
+            ),
+        )
+        .success();
+    let minimized = fs::read(
+        project
+            .fuzz_dir()
+            .join("artifacts/i_hate_zed/minimized-test-case"),
+    )
+    .unwrap();
+    assert!(minimized.contains(&b'z'));
+    assert!(minimized.len() >= 10 && minimized.len() < 39);
+}
+
+#[test]
+fn tmin_all_artifacts() {
+    let artifacts = Path::new("fuzz").join("artifacts").join("i_hate_zed");
+    let project = project("tmin_all_artifacts")
+        .with_fuzz()
+        .fuzz_target(
+            "i_hate_zed",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let s = String::from_utf8_lossy(data);
+                    if s.contains('z') {
+                        panic!("nooooooooo");
+                    }
+                });
+            "#,
+        )
+        .file(
+            artifacts.join("crash-1"),
+            "pack my box with five dozen liquor jugs",
+        )
+        .file(artifacts.join("crash-2"), "zzzz")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("--all-artifacts")
+        .arg("--sanitizer=none")
+        .arg("i_hate_zed")
+        .assert()
+        .stderr(
+            predicate::str::contains("fuzz/artifacts/i_hate_zed/crash-1: 39 -> 1 bytes")
+                .and(predicate::str::contains(
+                    "fuzz/artifacts/i_hate_zed/crash-2: 4 -> 1 bytes",
+                ))
+                .and(predicate::str::contains(
+                    "Minimized 2 of 2 artifact(s) from 43 to 2 bytes",
+                )),
+        )
+        .success();
+
+    let artifacts = project.root().join(artifacts);
+    assert_eq!(fs::read(artifacts.join("minimized-crash-1")).unwrap(), b"z");
+    assert_eq!(fs::read(artifacts.join("minimized-crash-2")).unwrap(), b"z");
+}
+
+#[test]
+fn tmin_structured() {
+    let test_case = Path::new("fuzz").join("test-case");
+    let project = project("tmin_structured")
+        .with_fuzz()
+        .fuzz_target(
+            "no_sevens",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|input: Vec<u8>| {
+                    if input.contains(&7) {
+                        panic!("seven");
+                    }
+                });
+            "#,
+        )
+        .file(&test_case, "abc\x07efgh")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("--structured")
+        .arg("--sanitizer=none")
+        .arg("no_sevens")
+        .arg(project.root().join(&test_case))
+        .assert()
+        .stderr(
+            predicate::str::contains(
+                "Minimized artifact, from 8 to 2 bytes:\n\
+                 \n\
+                 \tfuzz/artifacts/no_sevens/minimized-test-case",
+            )
+            .and(predicate::str::contains(
+                "Output of `std::fmt::Debug`:\n\n\t[\n\t    7,\n\t]",
+            ))
+            .and(predicate::str::contains(
+                "cargo fuzz run --sanitizer=none no_sevens \
+                 fuzz/artifacts/no_sevens/minimized-test-case",
+            )),
+        )
+        .success();
+
+    let artifacts = project
+        .root()
+        .join("fuzz")
+        .join("artifacts")
+        .join("no_sevens");
+    assert_eq!(
+        fs::read(artifacts.join("minimized-test-case")).unwrap(),
+        b"1\x07"
+    );
+    // The crashing candidates leave no artifacts of their own.
+    assert_eq!(fs::read_dir(&artifacts).unwrap().count(), 1);
+}
+
+#[test]
+fn dict_generate() {
+    let corpus = Path::new("fuzz").join("corpus").join("parse");
+    let project = project("dict_generate")
+        .with_fuzz()
+        .file(
+            Path::new("src").join("lib.rs"),
+            r#"
+                // "not in the dictionary"
+                pub fn method(line: &[u8]) -> Option<u8> {
+                    match line.split(|&c| c == b' ').next()? {
+                        b"GET" => Some(0),
+                        b"POST" => Some(1),
+                        _ => None,
+                    }
+                }
+            "#,
+        )
+        .fuzz_target(
+            "parse",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = dict_generate::method(data);
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "GET /index HTTP/1.1")
+        .file(corpus.join("1"), "PUT /index HTTP/1.0")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("dict")
+        .arg("generate")
+        .arg("parse")
+        .assert()
+        .stderr(
+            predicate::str::contains("Wrote 5 entries to fuzz/dictionaries/parse.dict").and(
+                predicate::str::contains("`cargo fuzz run`, `tmin` and `cmin` use it from now on"),
+            ),
+        )
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(project.fuzz_dir().join("dictionaries").join("parse.dict")).unwrap(),
+        "# Generated by `cargo fuzz dict generate parse`\n\
+         \" \"\n\
+         \"GET\"\n\
+         \"HTTP\"\n\
+         \"POST\"\n\
+         \"index\"\n"
+    );
+}
+
+#[test]
+fn conventional_dictionary() {
+    let project = project("conventional_dictionary")
+        .with_fuzz()
+        .fuzz_target(
+            "i_hate_zed",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.contains(&b'z') {
+                        panic!("nooooooooo");
+                    }
+                });
+            "#,
+        )
+        .file(
+            Path::new("fuzz")
+                .join("dictionaries")
+                .join("i_hate_zed.dict"),
+            "\"zed\"\n\"zebra\"\n",
+        )
+        .file("test-case", "zzz")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--sanitizer=none")
+        .arg("i_hate_zed")
+        .arg("--")
+        .arg("-runs=0")
+        .assert()
+        .stderr(predicate::str::contains("Dictionary: 2 entries"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("--sanitizer=none")
+        .arg("i_hate_zed")
+        .arg(project.root().join("test-case"))
+        .assert()
+        .stderr(
+            predicate::str::contains("Dictionary: 2 entries").and(predicate::str::contains(
+                "Reproduce with:\n\n\tcargo fuzz run --sanitizer=none i_hate_zed \
+                 fuzz/artifacts/i_hate_zed/minimized-test-case \
+                 -- -dict=fuzz/dictionaries/i_hate_zed.dict\n",
+            )),
+        )
+        .success();
+}
+
+#[test]
+fn run_stability_check() {
+    let corpus = Path::new("fuzz").join("corpus");
+    let project = project("run_stability_check")
+        .with_fuzz()
+        .fuzz_target(
+            "stable",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.first() == Some(&b'a') {
+                        std::hint::black_box(data);
+                    }
+                });
+            "#,
+        )
+        .fuzz_target(
+            "stateful",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+                use std::sync::atomic::{AtomicUsize, Ordering};
+
+                static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+                fuzz_target!(|data: &[u8]| {
+                    if data == b"abc" && RUNS.fetch_add(1, Ordering::Relaxed) == 1 {
+                        std::hint::black_box(data);
+                    }
+                });
+            "#,
+        )
+        .file(corpus.join("stable").join("0"), "abc")
+        .file(corpus.join("stable").join("1"), "xyz")
+        .file(corpus.join("stateful").join("0"), "abc")
+        .file(corpus.join("stateful").join("1"), "xyz")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--sanitizer=none")
+        .arg("--stability-check")
+        .arg("stable")
+        .assert()
+        .stdout(predicate::str::contains(
+            "All 2 sampled input(s) covered the same code in 3 runs",
+        ))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--sanitizer=none")
+        .arg("--stability-check")
+        .arg("stateful")
+        .assert()
+        .stdout(
+            predicate::str::is_match("fuzz/corpus/stateful/0: coverage/features [0-9/, ]+\n$")
+                .unwrap(),
+        )
+        .stderr(predicate::str::contains(
+            "1 of 2 sampled input(s) didn't cover the same code in every run",
+        ))
+        .failure();
+}
+
+#[test]
+fn build_deps_only() {
+    let project = project("build_deps_only")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    build_deps_only::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--deps-only")
+        .assert()
+        .stderr(
+            predicate::str::contains("Compiling build_deps_only v1.0.0")
+                .and(predicate::str::contains("build_deps_only-fuzz").not()),
+        )
+        .success();
+
+    // The dependencies are built just as the fuzz targets need them.
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .assert()
+        .stderr(
+            predicate::str::contains("Compiling build_deps_only-fuzz")
+                .and(predicate::str::contains("Compiling build_deps_only v1.0.0").not()),
+        )
+        .success();
+}
+
+#[test]
+fn build_all() {
+    let project = project("build_all").with_fuzz().build();
+
+    // Create some targets.
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("build_all_a")
+        .assert()
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("build_all_b")
+        .assert()
+        .success();
+
+    // Build to ensure that the build directory is created and
+    // `fuzz_build_dir()` won't panic.
+    project.cargo_fuzz().arg("build").assert().success();
+
+    let build_dir = project.fuzz_build_dir().join("release");
+
+    let a_bin = build_dir.join("build_all_a");
+    let b_bin = build_dir.join("build_all_b");
+
+    // Remove the files we just built.
+    fs::remove_file(&a_bin).unwrap();
+    fs::remove_file(&b_bin).unwrap();
+
+    assert!(!a_bin.is_file());
+    assert!(!b_bin.is_file());
+
+    // Test that building all fuzz targets does in fact recreate the files.
+    project.cargo_fuzz().arg("build").assert().success();
+
+    assert!(a_bin.is_file());
+    assert!(b_bin.is_file());
+}
+
+#[test]
+fn build_failure() {
+    let project = project("build_failure")
+        .with_fuzz()
+        .fuzz_target(
+            "broken",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _: u32 = data;
+                });
+            "#,
+        )
+        .build();
+
+    // Distinct from the exit code of a crash.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("broken")
+        .assert()
+        .stderr(predicate::str::contains("failed to build fuzz script"))
+        .code(3);
+}
+
+#[test]
+fn build_one() {
+    let project = project("build_one").with_fuzz().build();
+
+    // Create some targets.
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("build_one_a")
+        .assert()
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("build_one_b")
+        .assert()
+        .success();
+
+    // Build to ensure that the build directory is created and
+    // `fuzz_build_dir()` won't panic.
+    project.cargo_fuzz().arg("build").assert().success();
+
+    let build_dir = project.fuzz_build_dir().join("release");
+    let a_bin = build_dir.join("build_one_a");
+    let b_bin = build_dir.join("build_one_b");
+
+    // Remove the files we just built.
+    fs::remove_file(&a_bin).unwrap();
+    fs::remove_file(&b_bin).unwrap();
+
+    assert!(!a_bin.is_file());
+    assert!(!b_bin.is_file());
+
+    // Test that we can build one and not the other.
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("build_one_a")
+        .assert()
+        .success();
+
+    assert!(a_bin.is_file());
+    assert!(!b_bin.is_file());
+}
+
+#[test]
+fn build_dev() {
+    let project = project("build_dev").with_fuzz().build();
+
+    // Create some targets.
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("build_dev_a")
+        .assert()
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("build_dev_b")
+        .assert()
+        .success();
+
+    // Build to ensure that the build directory is created and
+    // `fuzz_build_dir()` won't panic.
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--dev")
+        .assert()
+        .success();
+
+    let build_dir = project.fuzz_build_dir().join("debug");
+
+    let a_bin = build_dir.join("build_dev_a");
+    let b_bin = build_dir.join("build_dev_b");
+
+    // Remove the files we just built.
+    fs::remove_file(&a_bin).unwrap();
+    fs::remove_file(&b_bin).unwrap();
+
+    assert!(!a_bin.is_file());
+    assert!(!b_bin.is_file());
+
+    // Test that building all fuzz targets does in fact recreate the files.
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--dev")
+        .assert()
+        .success();
+
+    assert!(a_bin.is_file());
+    assert!(b_bin.is_file());
+}
+
+#[test]
+fn build_stripping_dead_code() {
+    let project = project("build_strip").with_fuzz().build();
+
+    // Create some targets.
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("build_strip_a")
+        .assert()
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--strip-dead-code")
+        .arg("--dev")
+        .assert()
+        .success();
+
+    let build_dir = project.fuzz_build_dir().join("debug");
+
+    let a_bin = build_dir.join("build_strip_a");
+    assert!(a_bin.is_file(), "Not a file: {}", a_bin.display());
+}
+
+#[test]
+fn build_print_rustflags() {
+    let project = project("build_print_rustflags").with_fuzz().build();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("print_a")
+        .assert()
+        .success();
+
+    let output = project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--print-rustflags")
+        .arg("--sanitizer=none")
+        .env("RUSTFLAGS", "-Aunused")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let rustflags = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("RUSTFLAGS="))
+        .expect("RUSTFLAGS are printed");
+    let mut flags: Vec<String> = Vec::new();
+    let mut tokens = rustflags.trim_matches('\'').split(' ');
+    while let Some(token) = tokens.next() {
+        match token {
+            "--cfg" => flags.push(format!("--cfg {}", tokens.next().unwrap())),
+            _ => flags.push(token.to_owned()),
+        }
+    }
+    // The flags of cargo-fuzz are sorted, and followed by those of the
+    // environment.
+    assert_eq!(flags.pop().as_deref(), Some("-Aunused"));
+    assert!(flags.contains(&"-Ccodegen-units=1".to_owned()));
+    assert!(
+        flags.windows(2).all(|pair| pair[0] < pair[1]),
+        "{:?}",
+        flags
+    );
+    assert!(stdout.contains("cargo build --manifest-path"));
+}
+
+#[test]
+fn run_sanitizer_recover() {
+    let project = project("run_sanitizer_recover")
+        .with_fuzz()
+        .fuzz_target(
+            "recover_a",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.first() == Some(&b'x') {
+                        let v = vec![0u8; 1];
+                        unsafe { std::ptr::read_volatile(v.as_ptr().add(1)) };
+                    }
+                });
+            "#,
+        )
+        .file("fuzz/corpus/recover_a/crash", "xyz")
+        .build();
 
     project
         .cargo_fuzz()
         .arg("run")
-        .arg("yes_crash")
+        .arg("--sanitizer=none")
+        .arg("--sanitizer-recover")
+        .arg("recover_a")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`--sanitizer-recover` only works with `--sanitizer=address`",
+        ))
+        .failure();
+
+    // The fuzzer keeps going after the error, which is reported once at the
+    // end however often it happened.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--sanitizer-recover")
+        .arg("recover_a")
         .arg("--")
         .arg("-runs=1000")
-        .arg("-sanitizer=none")
-        .env("RUST_BACKTRACE", "1")
         .assert()
         .stderr(
-            predicate::str::contains("thread '<unnamed>' panicked at")
-                .and(predicate::str::contains("I'm afraid of number 7"))
-                .and(predicate::str::contains("ERROR: libFuzzer: deadly signal"))
-                .and(predicate::str::contains("run_without_sanitizer_with_crash::fail_fuzzing"))
-                .and(predicate::str::contains(
-                    "────────────────────────────────────────────────────────────────────────────────\n\
-                     \n\
-                     Failing input:\n\
-                     \n\
-                     \tfuzz/artifacts/yes_crash/crash-"
-                ))
-                .and(predicate::str::contains("Output of `std::fmt::Debug`:"))
+            predicate::str::contains("Done 1000 runs")
+                .and(predicate::str::contains("distinct:"))
                 .and(predicate::str::contains(
-                    "Reproduce with:\n\
-                     \n\
-                     \tcargo fuzz run yes_crash fuzz/artifacts/yes_crash/crash-"
+                    "1. AddressSanitizer: heap-buffer-overflow (reported",
                 ))
                 .and(predicate::str::contains(
-                    "Minimize test case with:\n\
-                     \n\
-                     \tcargo fuzz tmin yes_crash fuzz/artifacts/yes_crash/crash-"
+                    "Fuzz target reported 1 distinct sanitizer error(s)",
                 )),
         )
+        .code(10);
+}
+
+#[test]
+fn run_no_leak_detection() {
+    let project = project("run_no_leak_detection")
+        .with_fuzz()
+        .fuzz_target(
+            "leaks",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data == b"leak" {
+                        std::mem::forget(data.to_vec());
+                    }
+                });
+            "#,
+        )
+        .file("fuzz/corpus/leaks/0", "leak")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("leaks")
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(predicate::str::contains("LeakSanitizer"))
         .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--no-leak-detection")
+        .arg("leaks")
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(predicate::str::contains("Done 100 runs"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("fmt")
+        .arg("--no-leak-detection")
+        .arg("leaks")
+        .arg("fuzz/corpus/leaks/0")
+        .assert()
+        .stderr(predicate::str::contains("[108, 101, 97, 107]"))
+        .success();
 }
 
-// TODO: these msan tests are crashing `rustc` in CI:
-// https://github.com/rust-fuzz/cargo-fuzz/issues/323
-//
-// #[test]
-// fn run_with_msan_no_crash() {
-//     let project = project("run_with_msan_no_crash")
-//         .with_fuzz()
-//         .fuzz_target(
-//             "msan_no_crash",
-//             r#"
-//                 #![no_main]
-//                 use libfuzzer_sys::fuzz_target;
-//
-//                 fuzz_target!(|data: &[u8]| {
-//                     // get data from fuzzer and print it
-//                     // to force a memory access that cannot be optimized out
-//                     if let Some(x) = data.get(0) {
-//                         dbg!(x);
-//                     }
-//                 });
-//             "#,
-//         )
-//         .build();
-//
-//     project
-//         .cargo_fuzz()
-//         .arg("run")
-//         .arg("--sanitizer=memory")
-//         .arg("msan_no_crash")
-//         .arg("--")
-//         .arg("-runs=1000")
-//         .assert()
-//         .stderr(predicate::str::contains("Done 1000 runs"))
-//         .success();
-// }
-//
-// #[test]
-// fn run_with_msan_with_crash() {
-//     let project = project("run_with_msan_with_crash")
-//         .with_fuzz()
-//         .fuzz_target(
-//             "msan_with_crash",
-//             r#"
-//                 #![no_main]
-//                 use libfuzzer_sys::fuzz_target;
-//
-//                 fuzz_target!(|data: &[u8]| {
-//                     let test_data: Vec<u8> = Vec::with_capacity(4);
-//                     let uninitialized_value = unsafe {test_data.get_unchecked(0)};
-//                     // prevent uninit read from being optimized out
-//                     println!("{}", uninitialized_value);
-//                 });
-//             "#,
-//         )
-//         .build();
-//
-//     project
-//         .cargo_fuzz()
-//         .arg("run")
-//         .arg("--sanitizer=memory")
-//         .arg("msan_with_crash")
-//         .arg("--")
-//         .arg("-runs=1000")
-//         .assert()
-//         .stderr(
-//             predicate::str::contains("MemorySanitizer: use-of-uninitialized-value")
-//                 .and(predicate::str::contains(
-//                     "Reproduce with:\n\
-//                 \n\
-//                 \tcargo fuzz run --sanitizer=memory msan_with_crash fuzz/artifacts/msan_with_crash/crash-",
-//                 ))
-//                 .and(predicate::str::contains(
-//                     "Minimize test case with:\n\
-//                 \n\
-//                 \tcargo fuzz tmin --sanitizer=memory msan_with_crash fuzz/artifacts/msan_with_crash/crash-",
-//                 )),
-//         )
-//         .failure();
-// }
+#[test]
+fn run_progress() {
+    let project = project("run_progress")
+        .with_fuzz()
+        .fuzz_target(
+            "progress_a",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--progress=1")
+        .arg("progress_a")
+        .arg("--")
+        .arg("-max_total_time=3")
+        .assert()
+        .stderr(
+            predicate::str::is_match(r"progress: \ds, \d+ execs \(\d+/s\), cov \d+.*, 0 artifacts")
+                .unwrap(),
+        )
+        .success();
+}
+
+#[test]
+fn bench_corpus() {
+    let project = project("bench_corpus")
+        .with_fuzz()
+        .fuzz_target(
+            "bench_a",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data == b"slow" {
+                        std::thread::sleep(std::time::Duration::from_millis(100));
+                    }
+                });
+            "#,
+        )
+        .file("fuzz/corpus/bench_a/fast_1", "a")
+        .file("fuzz/corpus/bench_a/fast_2", "b")
+        .file("fuzz/corpus/bench_a/slow", "slow")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("bench-corpus")
+        .arg("-n1")
+        .arg("bench_a")
+        .assert()
+        .stdout(
+            predicate::str::contains("Replayed 3 inputs in ")
+                .and(predicate::str::is_match(r"\d{3,} ms +4  .*slow\n").unwrap())
+                .and(predicate::str::contains("fast_").not()),
+        )
+        .success();
+}
+
+#[test]
+fn bench() {
+    let project = project("bench")
+        .with_fuzz()
+        .fuzz_target(
+            "bench_b",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file("fuzz/corpus/bench_b/a", "a")
+        .file("fuzz/corpus/bench_b/b", "bb")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("bench")
+        .arg("--time=1")
+        .arg("bench_b")
+        .assert()
+        .stdout(
+            predicate::str::contains("Benchmarked `bench_b` with the 2 input(s) of ").and(
+                predicate::str::is_match(concat!(
+                    r"(?m)^MODE +EXECS +TIME +EXEC/S\n",
+                    r"replay +\d+ +\d+\.\ds +[1-9]\d*\n",
+                    r"fuzz +\d+ +1\.0s +[1-9]\d*$",
+                ))
+                .unwrap(),
+            ),
+        )
+        .success();
+
+    // Fuzzing leaves the corpus as it was.
+    assert_eq!(
+        fs::read_dir(project.fuzz_dir().join("corpus").join("bench_b"))
+            .unwrap()
+            .count(),
+        2
+    );
+
+    project
+        .cargo_fuzz()
+        .arg("bench")
+        .arg("--time=1")
+        .arg("--json")
+        .arg("bench_b")
+        .assert()
+        .stdout(
+            predicate::str::contains(r#""inputs": 2"#)
+                .and(predicate::str::contains(r#""replay": {"#))
+                .and(predicate::str::contains(r#""execs_per_sec": "#)),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("bench")
+        .arg("bench_b")
+        .arg(project.root().join("no-corpus"))
+        .assert()
+        .stderr(predicate::str::contains("no inputs found in "))
+        .failure();
+}
+
+#[cfg(feature = "flamegraphs")]
+#[cfg(unix)]
+#[test]
+fn profile() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = project("profile")
+        .with_fuzz()
+        .fuzz_target(
+            "profiled",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file("fuzz/corpus/profiled/a", "a")
+        .file("fuzz/corpus/profiled/b", "b")
+        // A stand-in for perf, which runs the fuzz target, keeps its command
+        // line as the samples, and prints a single stack for `perf script`.
+        .file(
+            "bin/perf",
+            "#!/bin/sh\n\
+             if [ \"$1\" = script ]; then\n\
+             \x20   printf 'profiled 1 1.0: 1 cycles:\\n\\t1 profile::parse+0x1 (/profiled)\\n\\n'\n\
+             \x20   exit 0\n\
+             fi\n\
+             while [ \"$1\" != -- ]; do\n\
+             \x20   [ \"$1\" = -o ] && out=\"$2\"\n\
+             \x20   shift\n\
+             done\n\
+             shift\n\
+             \"$@\" || exit\n\
+             echo \"$*\" > \"$out\"\n",
+        )
+        .build();
+    let bin = project.root().join("bin");
+    fs::set_permissions(bin.join("perf"), fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+    let samples = project.fuzz_dir().join("profile/profiled/perf.data");
+
+    project
+        .cargo_fuzz()
+        .env("PATH", &path)
+        .arg("profile")
+        .arg("--profiler=perf")
+        .arg("--time=1")
+        .arg("profiled")
+        .assert()
+        .stdout(
+            predicate::str::contains(
+                "Wrote the flamegraph of `profiled` to fuzz/profile/profiled/flamegraph.svg",
+            )
+            .and(predicate::str::contains(
+                "perf report -i fuzz/profile/profiled/perf.data",
+            )),
+        )
+        .success();
+    let svg =
+        fs::read_to_string(project.fuzz_dir().join("profile/profiled/flamegraph.svg")).unwrap();
+    assert!(svg.contains("profile::parse"), "{}", svg);
+    let command = fs::read_to_string(&samples).unwrap();
+    assert!(command.contains(" -runs="), "{}", command);
+    assert!(command.contains("corpus/profiled/b"), "{}", command);
+
+    project
+        .cargo_fuzz()
+        .env("PATH", &path)
+        .arg("profile")
+        .arg("--profiler=perf")
+        .arg("--fuzz")
+        .arg("--time=1")
+        .arg("profiled")
+        .assert()
+        .success();
+    let command = fs::read_to_string(&samples).unwrap();
+    assert!(command.contains(" -max_total_time=1 "), "{}", command);
+
+    project
+        .cargo_fuzz()
+        .arg("profile")
+        .arg("profiled")
+        .arg(project.root().join("no-corpus"))
+        .assert()
+        .stderr(predicate::str::contains("profile it with `--fuzz`"))
+        .failure();
+}
 
 #[test]
-fn run_one_input() {
-    let corpus = Path::new("fuzz").join("corpus").join("run_one");
-
-    let project = project("run_one_input")
+fn slow_units() {
+    let project = project("slow_units")
         .with_fuzz()
         .fuzz_target(
-            "run_one",
+            "slow_a",
             r#"
                 #![no_main]
                 use libfuzzer_sys::fuzz_target;
 
                 fuzz_target!(|data: &[u8]| {
-                    assert!(data.is_empty());
+                    if data == b"slow" {
+                        std::thread::sleep(std::time::Duration::from_millis(1200));
+                    }
                 });
             "#,
         )
-        .file(corpus.join("pass"), "")
-        .file(corpus.join("fail"), "not empty")
+        .file("fuzz/corpus/slow_a/0", "slow")
         .build();
 
+    // A `-timeout` below libFuzzer's default threshold of 10 seconds lowers
+    // the threshold, so that the input is reported as slow.
     project
         .cargo_fuzz()
         .arg("run")
-        .arg("run_one")
-        .arg(corpus.join("pass"))
+        .arg("slow_a")
+        .arg("--")
+        .arg("-timeout=2")
+        .arg("-runs=1")
         .assert()
-        .stderr(
-            predicate::str::contains("Running 1 inputs 1 time(s) each.").and(
-                predicate::str::contains("Running: fuzz/corpus/run_one/pass"),
-            ),
+        .stderr(predicate::str::contains(
+            "Found 1 slow unit(s), list them with `cargo fuzz slow slow_a`",
+        ))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("slow")
+        .arg("slow_a")
+        .assert()
+        .stdout(
+            predicate::str::is_match(r"\d{4} ms +4  .*slow-unit-")
+                .unwrap()
+                .and(predicate::str::contains("--perf slow_a")),
         )
         .success();
 }
 
 #[test]
-fn run_a_few_inputs() {
-    let corpus = Path::new("fuzz").join("corpus").join("run_few");
-
-    let project = project("run_a_few_inputs")
+fn run_timeout() {
+    let project = project("run_timeout")
         .with_fuzz()
         .fuzz_target(
-            "run_few",
+            "hangs",
             r#"
                 #![no_main]
                 use libfuzzer_sys::fuzz_target;
 
                 fuzz_target!(|data: &[u8]| {
-                    assert!(data.len() != 4);
+                    if data == b"hang" {
+                        std::thread::sleep(std::time::Duration::from_secs(5));
+                    }
                 });
             "#,
         )
-        .file(corpus.join("pass-0"), "")
-        .file(corpus.join("pass-1"), "1")
-        .file(corpus.join("pass-2"), "12")
-        .file(corpus.join("pass-3"), "123")
-        .file(corpus.join("fail"), "fail")
+        .file("fuzz/corpus/hangs/0", "hang")
+        // `--timeout` takes precedence over the configured timeout.
+        .file("fuzz/fuzz.toml", "timeout = 10\n")
         .build();
 
     project
         .cargo_fuzz()
         .arg("run")
-        .arg("run_few")
-        .arg(corpus.join("pass-0"))
-        .arg(corpus.join("pass-1"))
-        .arg(corpus.join("pass-2"))
-        .arg(corpus.join("pass-3"))
+        .arg("--sanitizer=none")
+        .arg("--timeout=1")
+        .arg("hangs")
         .assert()
         .stderr(
-            predicate::str::contains("Running 4 inputs 1 time(s) each.").and(
-                predicate::str::contains("Running: fuzz/corpus/run_few/pass"),
-            ),
+            predicate::str::contains("ERROR: libFuzzer: timeout after 1 seconds")
+                .and(predicate::str::contains(
+                    "The input ran for longer than the timeout of 1 seconds.",
+                ))
+                .and(predicate::str::contains(
+                    "\tcargo fuzz run --sanitizer=none --timeout=1 hangs \
+                     fuzz/artifacts/hangs/timeout-",
+                ))
+                .and(predicate::str::contains(
+                    "\tcargo fuzz tmin --sanitizer=none --timeout=1 --kind=timeout hangs \
+                     fuzz/artifacts/hangs/timeout-",
+                )),
         )
-        .success();
+        .failure();
 }
 
 #[test]
-fn run_alt_corpus() {
-    let corpus = Path::new("fuzz").join("corpus").join("run_alt");
-    let alt_corpus = Path::new("fuzz").join("alt-corpus").join("run_alt");
-
-    let project = project("run_alt_corpus")
+fn run_ignore_crashes() {
+    let project = project("run_ignore_crashes")
         .with_fuzz()
         .fuzz_target(
-            "run_alt",
+            "crashes",
             r#"
                 #![no_main]
                 use libfuzzer_sys::fuzz_target;
 
                 fuzz_target!(|data: &[u8]| {
-                    assert!(data.len() <= 1);
+                    if data.first() == Some(&b'x') {
+                        panic!("boring");
+                    }
                 });
             "#,
         )
-        .file(corpus.join("fail"), "fail")
-        .file(alt_corpus.join("pass-0"), "0")
-        .file(alt_corpus.join("pass-1"), "1")
-        .file(alt_corpus.join("pass-2"), "2")
+        .file("fuzz/corpus/crashes/0", "x")
         .build();
 
     project
         .cargo_fuzz()
         .arg("run")
-        .arg("run_alt")
-        .arg(&alt_corpus)
+        .arg("--sanitizer=none")
+        .arg("--ignore-crashes")
+        .arg("crashes")
         .arg("--")
-        .arg("-runs=0")
+        .arg("-max_total_time=3")
         .assert()
         .stderr(
-            predicate::str::contains("3 files found in fuzz/alt-corpus/run_alt")
-                .and(predicate::str::contains("fuzz/corpus/run_alt").not())
-                // libFuzzer will always test the empty input, so the number of
-                // runs performed is always one more than the number of files in
-                // the corpus.
-                .and(predicate::str::contains("Done 4 runs in")),
+            predicate::str::contains("INFO: -fork=1").and(
+                predicate::str::is_match(concat!(
+                    r"Ignoring crash: kept fuzzing after \d+ crash artifact\(s\), ",
+                    "list them with `cargo fuzz artifacts list crashes`"
+                ))
+                .unwrap(),
+            ),
         )
         .success();
 }
 
+#[cfg(feature = "notifications")]
 #[test]
-fn debug_fmt() {
-    let corpus = Path::new("fuzz").join("corpus").join("debugfmt");
-    let project = project("debugfmt")
+fn run_notify() {
+    let project = project("run_notify")
         .with_fuzz()
         .fuzz_target(
-            "debugfmt",
+            "notifies",
             r#"
                 #![no_main]
                 use libfuzzer_sys::fuzz_target;
-                use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured, Result};
 
-                #[derive(Debug)]
-                pub struct Rgb {
-                    r: u8,
-                    g: u8,
-                    b: u8,
-                }
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
 
-                impl<'a> Arbitrary<'a> for Rgb {
-                    fn arbitrary(raw: &mut Unstructured<'a>) -> Result<Self> {
-                        let mut buf = [0; 3];
-                        raw.fill_buffer(&mut buf)?;
-                        let r = buf[0];
-                        let g = buf[1];
-                        let b = buf[2];
-                        Ok(Rgb { r, g, b })
-                    }
-                }
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--notify")
+        .arg("--regression")
+        .arg("notifies")
+        .assert()
+        .stderr(predicate::str::contains(
+            "the argument '--notify' cannot be used with '--regression'",
+        ))
+        .failure();
 
-                fuzz_target!(|data: Rgb| {
-                    let _ = data;
-                });
+    // Failing to show the notifications, like without a desktop, only warns.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--notify")
+        .arg("--sanitizer=none")
+        .arg("notifies")
+        .arg("--")
+        .arg("-runs=10")
+        .env_remove("DBUS_SESSION_BUS_ADDRESS")
+        .assert()
+        .stderr(predicate::str::contains("Done 10 runs"))
+        .success();
+}
+
+#[test]
+fn run_metrics() {
+    let project = project("run_metrics")
+        .with_fuzz()
+        .fuzz_target(
+            "measured",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
+
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut fuzzing = project
+        .cargo_fuzz()
+        .arg("run")
+        .arg(format!("--metrics-addr={}", addr))
+        .arg("measured")
+        .arg("--")
+        .arg("-max_total_time=10")
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // Scrape until libFuzzer has reported its coverage, while it builds and
+    // starts.
+    let scrape = || -> std::io::Result<String> {
+        let mut stream = std::net::TcpStream::connect(addr)?;
+        stream.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        Ok(response)
+    };
+    let start = std::time::Instant::now();
+    let metrics = loop {
+        match scrape() {
+            Ok(response) if response.contains("cargo_fuzz_coverage{") => break response,
+            _ if start.elapsed() < std::time::Duration::from_secs(120) => {
+                std::thread::sleep(std::time::Duration::from_millis(500))
+            }
+            result => panic!("no coverage in the metrics: {:?}", result),
+        }
+    };
+    fuzzing.kill().unwrap();
+    fuzzing.wait().unwrap();
+
+    assert!(metrics.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(metrics.contains("# TYPE cargo_fuzz_execs_total counter\n"));
+    assert!(metrics.contains("cargo_fuzz_rss_bytes{target=\"measured\"} "));
+    assert!(
+        metrics.contains("cargo_fuzz_artifacts_total{target=\"measured\",category=\"crash\"} 0\n")
+    );
+}
+
+#[cfg(feature = "sessions")]
+#[test]
+fn run_records_sessions() {
+    let project = project("run_records_sessions")
+        .with_fuzz()
+        .fuzz_target(
+            "recorded",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
             "#,
         )
-        .file(corpus.join("0"), "111")
         .build();
 
     project
         .cargo_fuzz()
-        .arg("fmt")
-        .arg("debugfmt")
-        .arg("fuzz/corpus/debugfmt/0")
+        .arg("run")
+        .arg("recorded")
+        .arg("--")
+        .arg("-runs=10")
         .assert()
-        .stderr(predicates::str::contains(
-            "
-Rgb {
-    r: 49,
-    g: 49,
-    b: 49,
-}",
-        ))
+        .stderr(predicate::str::contains("warning: failed to record").not())
         .success();
+
+    let db = fs::read(project.fuzz_dir().join(".cargo-fuzz").join("sessions.db")).unwrap();
+    assert!(db.starts_with(b"SQLite format 3\0"));
 }
 
+#[cfg(feature = "sessions")]
 #[test]
-fn cmin() {
-    let corpus = Path::new("fuzz").join("corpus").join("foo");
-    let project = project("cmin")
+fn history() {
+    let project = project("history")
         .with_fuzz()
         .fuzz_target(
-            "foo",
+            "recorded",
             r#"
                 #![no_main]
                 use libfuzzer_sys::fuzz_target;
 
-                fuzz_target!(|data: &[u8]| {
-                    let _ = data;
-                });
+                fuzz_target!(|_data: &[u8]| {});
             "#,
         )
-        .file(corpus.join("0"), "")
-        .file(corpus.join("1"), "a")
-        .file(corpus.join("2"), "ab")
-        .file(corpus.join("3"), "abc")
-        .file(corpus.join("4"), "abcd")
         .build();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(project.root())
+            .args([
+                "-c",
+                "user.name=Fuzzer",
+                "-c",
+                "user.email=fuzzer@example.com",
+            ])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    let run = || {
+        project
+            .cargo_fuzz()
+            .arg("run")
+            .arg("recorded")
+            .arg("--")
+            .arg("-runs=10")
+            .assert()
+            .success();
+    };
 
-    let corpus_count = || {
-        fs::read_dir(project.root().join("fuzz").join("corpus").join("foo"))
-            .unwrap()
-            .count()
+    project
+        .cargo_fuzz()
+        .arg("history")
+        .arg("recorded")
+        .assert()
+        .stderr(predicate::str::contains("No recorded runs of `recorded`"))
+        .success();
+
+    git(&["init", "--quiet"]);
+    git(&["commit", "--quiet", "--allow-empty", "-m", "before"]);
+    run();
+    git(&["commit", "--quiet", "--allow-empty", "-m", "after"]);
+    run();
+
+    project
+        .cargo_fuzz()
+        .arg("history")
+        .arg("recorded")
+        .assert()
+        .stdout(
+            predicate::str::is_match(concat!(
+                r"(?m)^ID +STARTED +COMMIT +DURATION +EXECS +EXEC/S +COV +FT +CORPUS +ARTIFACTS +RESULT\n",
+                r" *\d+ +\d{4}-\d\d-\d\d \d\d:\d\d +[0-9a-f]{8} +\d+s +\d+ +\d+ +\d+ +\d+ +\d+ +0 +ok\n",
+            ))
+            .unwrap(),
+        )
+        .stderr(predicate::str::contains("2 run(s), "))
+        .success();
+
+    // Only the second run is at a commit that has the last one.
+    project
+        .cargo_fuzz()
+        .arg("history")
+        .arg("--rev=HEAD")
+        .arg("recorded")
+        .assert()
+        .stderr(predicate::str::contains("1 run(s), "))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("history")
+        .arg("--since=2000-01-01")
+        .arg("--json")
+        .arg("recorded")
+        .assert()
+        .stdout(
+            predicate::str::contains(r#""target": "recorded""#)
+                .and(predicate::str::contains(r#""failed": false"#)),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("history")
+        .arg("--until=1d")
+        .arg("recorded")
+        .assert()
+        .stderr(predicate::str::contains("No recorded runs of `recorded`"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("history")
+        .arg("--rev=no-such-branch")
+        .arg("recorded")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`no-such-branch` isn't a git revision",
+        ))
+        .failure();
+}
+
+#[cfg(feature = "sessions")]
+#[test]
+fn compare() {
+    let project = project("compare")
+        .with_fuzz()
+        .fuzz_target(
+            "compared",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
+    for _ in 0..2 {
+        project
+            .cargo_fuzz()
+            .arg("run")
+            .arg("compared")
+            .arg("--")
+            .arg("-runs=10")
+            .assert()
+            .success();
+    }
+
+    project
+        .cargo_fuzz()
+        .arg("compare")
+        .arg("1")
+        .arg("2")
+        .assert()
+        .stdout(
+            predicate::str::contains("Comparing run 1 of `compared`, ")
+                .and(predicate::str::contains("     with run 2 of `compared`, "))
+                .and(predicate::str::is_match(r"(?m)^ +BASE +NEW +CHANGE$").unwrap())
+                .and(predicate::str::is_match(r"(?m)^failures +0 +0 +\+0$").unwrap()),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("compare")
+        .arg("1")
+        .arg("99")
+        .assert()
+        .stderr(predicate::str::contains("no recorded run 99"))
+        .failure();
+
+    let stats = |name: &str, execs: u64| {
+        let path = project.root().join(name);
+        fs::write(
+            &path,
+            format!(
+                r#"{{"started": 0, "elapsed_secs": 10, "execs": {}, "cov": 50, "features": 80}}"#,
+                execs
+            ),
+        )
+        .unwrap();
+        path
     };
-    assert_eq!(corpus_count(), 5);
+    let base = stats("base.json", 10000);
+    let new = stats("new.json", 5000);
+
+    project
+        .cargo_fuzz()
+        .arg("compare")
+        .arg(&base)
+        .arg(&new)
+        .assert()
+        .stdout(predicate::str::is_match(r"(?m)^exec/s +1000 +500 +-50\.0%$").unwrap())
+        .stderr(predicate::str::contains("Regressed: exec/s"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("compare")
+        .arg("--threshold=60")
+        .arg(&base)
+        .arg(&new)
+        .assert()
+        .stderr(predicate::str::contains("No regressions"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("compare")
+        .arg("--fail-on-regression")
+        .arg(&base)
+        .arg(&new)
+        .assert()
+        .stderr(predicate::str::contains("Regressed: exec/s"))
+        .failure();
 
     project
         .cargo_fuzz()
-        .arg("cmin")
-        .arg("foo")
+        .arg("compare")
+        .arg(&base)
+        .arg(project.root().join("Cargo.toml"))
         .assert()
-        .success();
-    assert_eq!(corpus_count(), 1);
+        .stderr(predicate::str::contains(
+            "is neither a stats file nor a coverage summary",
+        ))
+        .failure();
 }
 
 #[test]
-fn tmin() {
-    let corpus = Path::new("fuzz").join("corpus").join("i_hate_zed");
-    let test_case = corpus.join("test-case");
-    let project = project("tmin")
+fn run_worker_logs() {
+    let project = project("run_worker_logs")
         .with_fuzz()
         .fuzz_target(
-            "i_hate_zed",
+            "workers_a",
             r#"
                 #![no_main]
                 use libfuzzer_sys::fuzz_target;
 
-                fuzz_target!(|data: &[u8]| {
-                    let s = String::from_utf8_lossy(data);
-                    if s.contains('z') {
-                        panic!("nooooooooo");
-                    }
-                });
+                fuzz_target!(|_data: &[u8]| {});
             "#,
         )
-        .file(&test_case, "pack my box with five dozen liquor jugs")
         .build();
-    let test_case = project.root().join(test_case);
+
+    // Sizes that don't fit in bytes are rejected rather than wrapped around.
     project
         .cargo_fuzz()
-        .arg("tmin")
-        .arg("i_hate_zed")
-        .arg("--sanitizer=none")
-        .arg(&test_case)
+        .arg("run")
+        .arg("--jobs=2")
+        .arg("--worker-logs")
+        .arg(format!("--max-log-size={}", 1u64 << 44))
+        .arg("workers_a")
+        .assert()
+        .stderr(predicate::str::contains(
+            "invalid value '17592186044416' for '--max-log-size <MIB>'",
+        ))
+        .failure();
+
+    // The console only gets the summaries, and each worker's output goes to
+    // a log of its own.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--jobs=2")
+        .arg("--worker-logs")
+        .arg("--progress=1")
+        .arg("workers_a")
+        .arg("--")
+        .arg("-max_total_time=3")
         .assert()
         .stderr(
-            predicates::str::contains("CRASH_MIN: minimizing crash input: ")
-                .and(predicate::str::contains("(1 bytes) caused a crash"))
-                .and(predicate::str::contains(
-                    "────────────────────────────────────────────────────────────────────────────────\n\
-                     \n\
-                     Minimized artifact:\n\
-                     \n\
-                     \tfuzz/artifacts/i_hate_zed/minimized-from-"))
-                .and(predicate::str::contains(
-                    "Reproduce with:\n\
-                     \n\
-                     \tcargo fuzz run --sanitizer=none i_hate_zed fuzz/artifacts/i_hate_zed/minimized-from-"
-                )),
+            predicate::str::contains("Fuzzing `workers_a` with 2 worker(s)")
+                .and(predicate::str::contains("progress: "))
+                .and(predicate::str::contains("INFO: Seed:").not()),
         )
         .success();
+
+    let logs = project.fuzz_dir().join("logs").join("workers_a");
+    for worker in ["worker-0.log", "worker-1.log"] {
+        let log = fs::read_to_string(logs.join(worker)).unwrap();
+        assert!(log.contains("INFO: Seed:"), "{}", log);
+        assert!(log.contains("Done"), "{}", log);
+    }
 }
 
 #[test]
-fn build_all() {
-    let project = project("build_all").with_fuzz().build();
+fn build_wasi() {
+    let project = project("build_wasi")
+        .with_fuzz()
+        .fuzz_target(
+            "wasi_a",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file("libFuzzer-wasi.a", "")
+        .build();
 
-    // Create some targets.
     project
         .cargo_fuzz()
-        .arg("add")
-        .arg("build_all_a")
+        .arg("build")
+        .arg("--target=wasm32-wasip1")
         .assert()
-        .success();
+        .stderr(predicate::str::contains(
+            "there are no sanitizers for `--target=wasm32-wasip1`",
+        ))
+        .failure();
+
     project
         .cargo_fuzz()
-        .arg("add")
-        .arg("build_all_b")
+        .arg("build")
+        .arg("--target=wasm32-wasip1")
+        .arg("--sanitizer=none")
         .assert()
-        .success();
-
-    // Build to ensure that the build directory is created and
-    // `fuzz_build_dir()` won't panic.
-    project.cargo_fuzz().arg("build").assert().success();
-
-    let build_dir = project.fuzz_build_dir().join("release");
-
-    let a_bin = build_dir.join("build_all_a");
-    let b_bin = build_dir.join("build_all_b");
-
-    // Remove the files we just built.
-    fs::remove_file(&a_bin).unwrap();
-    fs::remove_file(&b_bin).unwrap();
-
-    assert!(!a_bin.is_file());
-    assert!(!b_bin.is_file());
-
-    // Test that building all fuzz targets does in fact recreate the files.
-    project.cargo_fuzz().arg("build").assert().success();
+        .stderr(predicate::str::contains(
+            "libfuzzer-sys can't build libFuzzer for `--target=wasm32-wasip1`",
+        ))
+        .failure();
 
-    assert!(a_bin.is_file());
-    assert!(b_bin.is_file());
+    // Whether the build itself works depends on the toolchain having the
+    // target, but the fuzz targets are set up to run under wasmtime.
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--target=wasm32-wasip1")
+        .arg("--sanitizer=none")
+        .arg("--libfuzzer=libFuzzer-wasi.a")
+        .arg("--print-rustflags")
+        .assert()
+        .stdout(predicate::str::contains(
+            "CARGO_TARGET_WASM32_WASIP1_RUNNER='wasmtime run --dir=",
+        ));
 }
 
 #[test]
-fn build_one() {
-    let project = project("build_one").with_fuzz().build();
+fn build_ios_simulator() {
+    let project = project("build_ios_simulator")
+        .with_fuzz()
+        .fuzz_target(
+            "ios_a",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
 
-    // Create some targets.
-    project
-        .cargo_fuzz()
-        .arg("add")
-        .arg("build_one_a")
-        .assert()
-        .success();
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
+
+    // Whether the build itself works depends on the toolchain having the
+    // target, but the fuzz targets are set up to run in the simulator.
     project
         .cargo_fuzz()
-        .arg("add")
-        .arg("build_one_b")
+        .arg("build")
+        .arg("--target=aarch64-apple-ios-sim")
+        .arg("--print-rustflags")
         .assert()
-        .success();
-
-    // Build to ensure that the build directory is created and
-    // `fuzz_build_dir()` won't panic.
-    project.cargo_fuzz().arg("build").assert().success();
-
-    let build_dir = project.fuzz_build_dir().join("release");
-    let a_bin = build_dir.join("build_one_a");
-    let b_bin = build_dir.join("build_one_b");
+        .stdout(predicate::str::contains(
+            "CARGO_TARGET_AARCH64_APPLE_IOS_SIM_RUNNER='xcrun simctl spawn booted'",
+        ));
+}
 
-    // Remove the files we just built.
-    fs::remove_file(&a_bin).unwrap();
-    fs::remove_file(&b_bin).unwrap();
+#[test]
+fn build_bsd() {
+    let project = project("build_bsd")
+        .with_fuzz()
+        .fuzz_target(
+            "bsd_a",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
 
-    assert!(!a_bin.is_file());
-    assert!(!b_bin.is_file());
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
 
-    // Test that we can build one and not the other.
     project
         .cargo_fuzz()
         .arg("build")
-        .arg("build_one_a")
+        .arg("--target=x86_64-unknown-openbsd")
         .assert()
-        .success();
+        .stderr(predicate::str::contains(
+            "`--sanitizer=address` isn't available for `x86_64-unknown-openbsd`, \
+             pick one of: none",
+        ))
+        .failure();
 
-    assert!(a_bin.is_file());
-    assert!(!b_bin.is_file());
+    // Whether the build itself works depends on the toolchain having the
+    // target, but the flags are those that work on FreeBSD.
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--target=x86_64-unknown-freebsd")
+        .arg("--print-rustflags")
+        .assert()
+        .stdout(
+            predicate::str::contains("-sanitizer-coverage-stack-depth").and(
+                predicate::str::contains("detect_odr_violation=0:detect_leaks=0"),
+            ),
+        );
 }
 
 #[test]
-fn build_dev() {
-    let project = project("build_dev").with_fuzz().build();
+fn doctor() {
+    let project = project("doctor").with_fuzz().build();
 
-    // Create some targets.
     project
         .cargo_fuzz()
-        .arg("add")
-        .arg("build_dev_a")
-        .assert()
-        .success();
-    project
-        .cargo_fuzz()
-        .arg("add")
-        .arg("build_dev_b")
+        .arg("doctor")
+        .arg("--sanitizer=none")
         .assert()
+        .stdout(
+            predicate::str::is_match(r"ok    toolchain: .*\nok    target .* is installed\n")
+                .unwrap(),
+        )
         .success();
 
-    // Build to ensure that the build directory is created and
-    // `fuzz_build_dir()` won't panic.
     project
         .cargo_fuzz()
-        .arg("build")
-        .arg("--dev")
+        .arg("doctor")
+        .arg("--sanitizer=none")
+        .arg("--target=x86_64-unknown-none-such")
         .assert()
-        .success();
-
-    let build_dir = project.fuzz_build_dir().join("debug");
-
-    let a_bin = build_dir.join("build_dev_a");
-    let b_bin = build_dir.join("build_dev_b");
+        .stdout(predicate::str::contains(
+            "FAIL  target x86_64-unknown-none-such isn't installed\n      \
+             fix: rustup +nightly target add x86_64-unknown-none-such",
+        ))
+        .stderr(predicate::str::contains("1 check(s) failed"))
+        .failure();
+}
 
-    // Remove the files we just built.
-    fs::remove_file(&a_bin).unwrap();
-    fs::remove_file(&b_bin).unwrap();
+#[test]
+fn build_external_libfuzzer() {
+    let project = project("build_external_libfuzzer")
+        .with_fuzz()
+        .fuzz_target(
+            "external_a",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
 
-    assert!(!a_bin.is_file());
-    assert!(!b_bin.is_file());
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
 
-    // Test that building all fuzz targets does in fact recreate the files.
     project
         .cargo_fuzz()
         .arg("build")
-        .arg("--dev")
+        .arg("--libfuzzer=missing/libFuzzer.a")
         .assert()
-        .success();
-
-    assert!(a_bin.is_file());
-    assert!(b_bin.is_file());
-}
+        .stderr(predicate::str::contains(
+            "libFuzzer library missing/libFuzzer.a doesn't exist",
+        ))
+        .failure();
 
-#[test]
-fn build_stripping_dead_code() {
-    let project = project("build_strip").with_fuzz().build();
+    // Link against a copy of the library that libfuzzer-sys builds itself.
+    project.cargo_fuzz().arg("build").assert().success();
+    let bundled = fs::read_dir(project.fuzz_build_dir().join("release").join("build"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path().join("out").join("libfuzzer.a"))
+        .find(|path| path.is_file())
+        .expect("libfuzzer-sys builds libFuzzer");
+    fs::copy(&bundled, project.root().join("libFuzzer.a")).unwrap();
 
-    // Create some targets.
     project
         .cargo_fuzz()
-        .arg("add")
-        .arg("build_strip_a")
+        .arg("build")
+        .arg("--libfuzzer=libFuzzer.a")
+        .arg("--print-rustflags")
         .assert()
+        .stdout(predicate::str::contains("CUSTOM_LIBFUZZER_PATH="))
         .success();
-
     project
         .cargo_fuzz()
-        .arg("build")
-        .arg("--strip-dead-code")
-        .arg("--dev")
+        .arg("run")
+        .arg("--libfuzzer=libFuzzer.a")
+        .arg("external_a")
+        .arg("--")
+        .arg("-runs=1")
         .assert()
         .success();
-
-    let build_dir = project.fuzz_build_dir().join("debug");
-
-    let a_bin = build_dir.join("build_strip_a");
-    assert!(a_bin.is_file(), "Not a file: {}", a_bin.display());
 }
 
 #[test]
@@ -938,6 +5647,47 @@ fn run_with_different_fuzz_dir() {
         .success();
 }
 
+#[test]
+fn run_from_inside_fuzz_dir() {
+    let (fuzz_dir, mut project_builder) =
+        project_with_fuzz_dir("run_from_inside_fuzz_dir", Some("fuzzing"));
+    let project = project_builder
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_from_inside_fuzz_dir::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .file(Path::new("fuzzing").join("inputs").join("crash"), "1234567")
+        .build();
+
+    // The fuzz directory the command is run in is found without
+    // `--fuzz-dir`, and relative paths stay relative to it.
+    project
+        .cargo_fuzz()
+        .current_dir(&fuzz_dir)
+        .arg("run")
+        .arg("yes_crash")
+        .arg("inputs/crash")
+        .assert()
+        .stderr(predicate::str::contains("I'm afraid of number 7"))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .current_dir(Path::new(&fuzz_dir).join("fuzz_targets"))
+        .arg("list")
+        .assert()
+        .stdout("yes_crash\n")
+        .success();
+}
+
 #[test]
 fn run_diagnostic_contains_fuzz_dir() {
     let (fuzz_dir, mut project_builder) = project_with_fuzz_dir("run_with_crash", None);